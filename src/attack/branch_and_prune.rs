@@ -0,0 +1,130 @@
+use indicatif::ProgressBar;
+use rug::Integer;
+
+use crate::{
+    key::PrivateKey, partial::partial_factors, Attack, AttackContext, AttackKind, AttackSpeed,
+    Error, Parameters, PartialInteger, Progress, Solution, Vulnerability,
+};
+
+/// Largest number of candidates kept at each bit
+const MAX_CANDIDATES: u64 = 1 << 16;
+
+/// Branch and prune attack (scattered known bits of p and/or q, Heninger-Shacham)
+///
+/// p and q are rebuilt from their lowest bit: each candidate is extended with every value of the
+/// next bits of p and q that matches the known bits, and kept only if p * q = n modulo 2^(i + 1).
+/// Works when around half of the bits of p and q are known, whatever their positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchAndPruneAttack;
+
+impl Attack for BranchAndPruneAttack {
+    fn name(&self) -> &'static str {
+        "branch_and_prune"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Medium
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let (p, q) = partial_factors(n, &params.partial_p, &params.partial_q)
+            .ok_or(Error::MissingParameters)?;
+        let bits = p.bits().max(q.bits());
+        pb.set_length(bits as u64);
+
+        let max_candidates = ctx.config().scaled_memory(MAX_CANDIDATES) as usize;
+        let mut candidates = vec![(Integer::from(1), Integer::from(1))];
+        candidates.retain(|(p_low, q_low)| matches(&p, 0, p_low) && matches(&q, 0, q_low));
+        for i in 1..bits {
+            ctx.check()?;
+            pb.inc(1);
+
+            let n_low = Integer::from(n.keep_bits_ref(i + 1));
+            let mut next = vec![];
+            for (p_low, q_low) in candidates {
+                for (p_bit, q_bit) in [(false, false), (true, false), (false, true), (true, true)] {
+                    let mut p_next = p_low.clone();
+                    let mut q_next = q_low.clone();
+                    p_next.set_bit(i, p_bit);
+                    q_next.set_bit(i, q_bit);
+                    if matches(&p, i, &p_next)
+                        && matches(&q, i, &q_next)
+                        && Integer::from(&p_next * &q_next).keep_bits(i + 1) == n_low
+                    {
+                        next.push((p_next, q_next));
+                    }
+                }
+            }
+            if next.len() > max_candidates {
+                return Err(Error::NotFound);
+            }
+            candidates = next;
+        }
+
+        let (p, q) = candidates
+            .into_iter()
+            .find(|(p, q)| *p > 1 && *q > 1 && Integer::from(p * q) == *n)
+            .ok_or(Error::NotFound)?;
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p, q, &params.e)?,
+        ))
+    }
+}
+
+/// Returns true if bit i of the candidate matches the partial integer
+fn matches(partial: &PartialInteger, i: u32, candidate: &Integer) -> bool {
+    let bit = candidate.get_bit(i);
+    if i >= partial.bits() {
+        !bit
+    } else {
+        !partial.mask().get_bit(i) || partial.value().get_bit(i) == bit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{Attack, Factors, Parameters};
+
+    use super::*;
+
+    #[test]
+    fn attack() {
+        let p = Integer::from_str("9680013379709450894240896318618061284105559659947313368392185471991345885186021740399175810163138239351083996692031751120232675301366868879251239724616281").unwrap();
+        let q = Integer::from_str("8648015025408335181758712092667722045493734511644452769085082668804450076445917118092786133629868326002982629135152287590451302935304608656309477470314237").unwrap();
+        let factors = Factors::from([p.clone(), q.clone()]);
+
+        // Every other bit of p and q is known
+        let bits = p.significant_bits().max(q.significant_bits());
+        let mask = Integer::from_str_radix(&"01".repeat(bits as usize / 2 + 1), 2).unwrap();
+        let params = Parameters {
+            n: Some(factors.product()),
+            partial_p: Some(PartialInteger::new(p, mask.clone(), bits)),
+            partial_q: Some(PartialInteger::new(q, mask, bits)),
+            ..Default::default()
+        };
+
+        let solution = BranchAndPruneAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+}
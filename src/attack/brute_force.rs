@@ -0,0 +1,190 @@
+use std::{
+    collections::BTreeSet,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use indicatif::ProgressBar;
+use rayon::prelude::*;
+use rug::Integer;
+
+use crate::{AttackContext, AttackState, Error, Progress, STOP_CHECK_INTERVAL};
+
+/// Bounded brute force over a range of candidates, which can be resumed where it stopped
+///
+/// The range is split in blocks of `STOP_CHECK_INTERVAL` candidates, handed out in order to the
+/// available threads, the progress bar advancing with each block. Between blocks, the search
+/// stops as soon as a value is found or the attacks are asked to stop. All the candidates below
+/// `searched` were searched, it is saved in an attack state to resume the search later.
+/// With a shard in the configuration, only the blocks of the shard are searched, the others
+/// being left to other machines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BruteForce {
+    range: Range<u64>,
+    searched: u64,
+}
+
+impl BruteForce {
+    /// Create a brute force over `range`, from its start
+    pub fn new(range: Range<u64>) -> Self {
+        Self {
+            searched: range.start,
+            range,
+        }
+    }
+
+    /// Returns the candidates not searched yet
+    pub fn remaining(&self) -> Range<u64> {
+        self.searched..self.range.end
+    }
+
+    /// Resume from a state saved by `export`, if it is a brute force from the same start
+    ///
+    /// The end of the range may differ, e.g. with a higher effort.
+    pub fn import(&mut self, state: &AttackState) {
+        let get = |name| state.get_one(name).and_then(Integer::to_u64);
+        if get("start") == Some(self.range.start) {
+            if let Some(searched) = get("searched") {
+                self.searched = searched.clamp(self.range.start, self.range.end);
+            }
+        }
+    }
+
+    /// Save the progress in a state
+    pub fn export(&self, state: &mut AttackState) {
+        state.set("start", [Integer::from(self.range.start)]);
+        state.set("searched", [Integer::from(self.searched)]);
+    }
+
+    /// Search the remaining candidates, given to `search` by blocks
+    ///
+    /// Returns `Error::Stopped` if the attacks were asked to stop, the progress being kept.
+    pub fn run<T: Send>(
+        &mut self,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+        search: impl Fn(Range<u64>) -> Option<T> + Sync,
+    ) -> Result<Option<T>, Error> {
+        let Range { start, end } = self.remaining();
+        pb.set_length(self.range.end.saturating_sub(self.range.start));
+        pb.inc(start - self.range.start);
+
+        let pool = ctx.thread_pool()?;
+        let found = AtomicBool::new(false);
+        // First block not searched, and the blocks searched after it
+        let searched = Mutex::new((start, BTreeSet::new()));
+
+        let result = pool.install(|| {
+            (0..end.saturating_sub(start).div_ceil(STOP_CHECK_INTERVAL))
+                .par_bridge()
+                .find_map_any(|block| {
+                    if let Err(err) = ctx.check() {
+                        return Some(Err(err));
+                    }
+                    if found.load(Ordering::Relaxed) {
+                        return None;
+                    }
+
+                    let block_start = start + block * STOP_CHECK_INTERVAL;
+                    let block_end = (block_start + STOP_CHECK_INTERVAL).min(end);
+                    // Blocks are numbered from the start of the range, to shard resumed searches alike
+                    let number = (block_start - self.range.start) / STOP_CHECK_INTERVAL;
+                    let value = match ctx.config().shard {
+                        Some(shard) if !shard.contains(number) => None,
+                        _ => search(block_start..block_end),
+                    };
+                    pb.inc(block_end - block_start);
+
+                    let mut searched = searched.lock().unwrap();
+                    let (first, after) = &mut *searched;
+                    after.insert(block_start);
+                    while after.remove(first) {
+                        *first = (*first + STOP_CHECK_INTERVAL).min(end);
+                    }
+                    drop(searched);
+
+                    value.map(|value| {
+                        found.store(true, Ordering::Relaxed);
+                        Ok(value)
+                    })
+                })
+                .transpose()
+        });
+        self.searched = searched.into_inner().unwrap().0;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AttackConfig, Shard};
+
+    use super::*;
+
+    #[test]
+    fn resume() {
+        let config = AttackConfig {
+            threads: 4,
+            ..Default::default()
+        };
+
+        // Stopped before the first block
+        let ctx = AttackContext::default().with_config(config.clone());
+        ctx.stop();
+        let mut brute_force = BruteForce::new(10..1_000_000);
+        let res = brute_force.run(&ctx, None, |_| None::<u64>);
+        assert_eq!(res, Err(Error::Stopped));
+        assert_eq!(brute_force.remaining(), 10..1_000_000);
+
+        // Stopped by the search itself, the blocks searched before are kept
+        let ctx = AttackContext::default().with_config(config.clone());
+        let res = brute_force.run(&ctx, None, |range| {
+            if range.contains(&123_457) {
+                ctx.stop();
+            }
+            None::<u64>
+        });
+        assert_eq!(res, Err(Error::Stopped));
+        let searched = brute_force.remaining().start;
+        assert!(searched > 10 && searched < 1_000_000);
+
+        let mut state = AttackState::new("brute_force", &1.into());
+        brute_force.export(&mut state);
+        let state = AttackState::from_json(&state.to_json()).unwrap();
+        let mut resumed = BruteForce::new(10..2_000_000);
+        resumed.import(&state);
+        assert_eq!(resumed.remaining(), searched..2_000_000);
+        // Another start, the state is ignored
+        let mut other = BruteForce::new(0..1_000_000);
+        other.import(&state);
+        assert_eq!(other.remaining(), 0..1_000_000);
+
+        let ctx = AttackContext::default().with_config(config);
+        let res = resumed.run(&ctx, None, |range| {
+            assert!(range.start >= searched);
+            range.into_iter().find(|&i| i == 1_500_000)
+        });
+        assert_eq!(res, Ok(Some(1_500_000)));
+    }
+
+    #[test]
+    fn shard() {
+        // The value is in the second block, searched by the second shard only
+        let value = STOP_CHECK_INTERVAL + 1;
+        let search = |shard| {
+            let config = AttackConfig {
+                threads: 2,
+                shard: Some(shard),
+                ..Default::default()
+            };
+            let ctx = AttackContext::default().with_config(config);
+            BruteForce::new(0..10 * STOP_CHECK_INTERVAL)
+                .run(&ctx, None, |range| range.into_iter().find(|&i| i == value))
+        };
+        assert_eq!(search(Shard { index: 0, count: 2 }), Ok(None));
+        assert_eq!(search(Shard { index: 1, count: 2 }), Ok(Some(value)));
+    }
+}
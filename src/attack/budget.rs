@@ -0,0 +1,85 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{Attack, AttackSpeed};
+
+/// Expected duration of an attack never timed before
+fn default_duration(speed: AttackSpeed) -> Duration {
+    match speed {
+        AttackSpeed::Fast => Duration::from_secs(1),
+        AttackSpeed::Medium => Duration::from_secs(10),
+        AttackSpeed::Slow => Duration::from_secs(60),
+    }
+}
+
+/// Split a time budget between attacks, from their expected durations
+///
+/// The expected duration of an attack is given by `history` (e.g. the average of its previous
+/// runs), or guessed from its speed. Fast and medium attacks run concurrently, so they all get the
+/// time expected for the longest of them, while slow attacks run one after the other and each get
+/// their own expected time. All durations are then scaled so that the whole run fits in the budget.
+pub fn allocate_time_budget(
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+    budget: Duration,
+    history: impl Fn(&str) -> Option<Duration>,
+) -> Vec<Duration> {
+    let expected = attacks
+        .iter()
+        .map(|attack| {
+            history(attack.name())
+                .unwrap_or_else(|| default_duration(attack.speed()))
+                .max(Duration::from_millis(1))
+        })
+        .collect::<Vec<_>>();
+
+    let is_slow = |index: usize| attacks[index].speed() == AttackSpeed::Slow;
+    let concurrent = (0..attacks.len())
+        .filter(|&i| !is_slow(i))
+        .map(|i| expected[i])
+        .max()
+        .unwrap_or_default();
+    let sequential = (0..attacks.len())
+        .filter(|&i| is_slow(i))
+        .map(|i| expected[i])
+        .sum::<Duration>();
+
+    let scale = budget.as_secs_f64() / (concurrent + sequential).as_secs_f64();
+    (0..attacks.len())
+        .map(|i| {
+            if is_slow(i) {
+                expected[i].mul_f64(scale)
+            } else {
+                concurrent.mul_f64(scale)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EcmAttack, FermatAttack, SmallPrimeAttack, WienerAttack};
+
+    use super::*;
+
+    #[test]
+    fn allocate_time_budget() {
+        let budget = Duration::from_secs(60);
+        let attacks: [Arc<dyn Attack + Sync + Send>; 2] =
+            [Arc::new(WienerAttack), Arc::new(FermatAttack)];
+        // Medium attacks only, they run concurrently for the whole budget
+        assert_eq!(
+            super::allocate_time_budget(&attacks, budget, |_| None),
+            [budget, budget]
+        );
+
+        let attacks: [Arc<dyn Attack + Sync + Send>; 2] =
+            [Arc::new(SmallPrimeAttack), Arc::new(EcmAttack)];
+        let history = |name: &str| match name {
+            "ecm" => Some(Duration::from_secs(40)),
+            _ => None,
+        };
+        // 1s expected for the fast attack and 40s for ecm, scaled to 60s
+        let allocation = super::allocate_time_budget(&attacks, budget, history);
+        assert_eq!(allocation[0].as_millis(), 1463);
+        assert_eq!(allocation[1].as_millis(), 58536);
+    }
+}
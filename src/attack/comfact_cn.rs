@@ -1,7 +1,9 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, AttackKind, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackKind, AttackSpeed, Error, Parameters, Solution, Vulnerability,
+};
 
 /// Common factor shared in plaintext / ciphertext attack
 ///
@@ -22,6 +24,10 @@ impl Attack for ComfactCnAttack {
         AttackKind::KnownExtraInformation
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SharedFactor(None))
+    }
+
     fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use rug::Integer;
+
+use crate::{
+    Attack, AttackKind, AttackSpeed, Error, KeyEntry, Parameters, Solution, Vulnerability,
+};
+
+/// Common modulus attack (same message encrypted under the same modulus with several exponents)
+///
+/// Keys are grouped by modulus, so the pair may be any of the given keys. With a * e1 + b * e2 = g,
+/// m^g = c1^a * c2^b mod n, and m is its g-th root (m itself when e1 and e2 are coprime).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommonModulusAttack;
+
+impl Attack for CommonModulusAttack {
+    fn name(&self) -> &'static str {
+        "common_modulus"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Fast
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::CommonModulus(vec![]))
+    }
+
+    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        // Keys with a cipher grouped by modulus
+        let mut groups = BTreeMap::<&Integer, Vec<usize>>::new();
+        for (i, key) in params.keys.iter().enumerate() {
+            if key.c.is_some() {
+                groups.entry(&key.n).or_default().push(i);
+            }
+        }
+        let pairs = groups
+            .into_values()
+            .flat_map(|indices| indices.into_iter().tuple_combinations())
+            .filter(|&(i, j)| params.keys[i].e != params.keys[j].e)
+            .collect::<Vec<_>>();
+        if pairs.is_empty() {
+            return Err(Error::MissingParameters);
+        }
+
+        pairs
+            .into_iter()
+            .find_map(|(i, j)| {
+                let m = common_message(&params.keys[i], &params.keys[j])?;
+                Some(
+                    Solution::new_m(self.name(), m)
+                        .with_vulnerability(Vulnerability::CommonModulus(vec![i, j])),
+                )
+            })
+            .ok_or(Error::NotFound)
+    }
+}
+
+/// Message encrypted with both keys, which have the same modulus
+fn common_message(first: &KeyEntry, second: &KeyEntry) -> Option<Integer> {
+    let n = &first.n;
+    let (c1, c2) = (first.c.as_ref()?, second.c.as_ref()?);
+    let (g, a, b) = <(Integer, Integer, Integer)>::from(first.e.extended_gcd_ref(&second.e));
+    let g = g.to_u32().filter(|&g| g >= 1)?;
+
+    // Negative coefficients use the inverse of the cipher
+    let m_g = c1.clone().pow_mod(&a, n).ok()? * c2.clone().pow_mod(&b, n).ok()? % n;
+    let (m, rem) = m_g.root_rem(Integer::new(), g);
+    let encrypts_to =
+        |key: &KeyEntry, c: &Integer| m.clone().pow_mod(&key.e, n).is_ok_and(|m_e| m_e == *c);
+    (rem == 0 && encrypts_to(first, c1) && encrypts_to(second, c2)).then_some(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{bytes_to_integer, Attack, KeyEntry, Parameters};
+
+    use super::*;
+
+    fn key(n: &Integer, e: u32, m: &Integer) -> KeyEntry {
+        KeyEntry {
+            n: n.clone(),
+            e: e.into(),
+            c: Some(m.clone().pow_mod(&e.into(), n).unwrap()),
+            known: Parameters::default(),
+        }
+    }
+
+    fn params(exponents: [u32; 2], m: &Integer) -> Parameters {
+        let n = Integer::from_str("95118357989037539883272168746004652872958890562445814301889866663072352421703264985997800660075311645555799745426868343365321502734736006248007902409628540578635925559742217480797487130202747020211452620743021097565113059392504472785227154824117231077844444672393221838192941390309312484066647007469668558141").unwrap();
+        let other = Integer::from_str("98364165919251246243846667323542318022804234833677924161175733253689581393607346667895298253718184273532268982060905629399628154981918712070241451494491161470827737146176316011843738943427121602324208773653180782732999422869439588198318422451697920640563880777385577064913983202033744281727004289781821019463").unwrap();
+
+        // The main key is not one of the pair
+        let keys = vec![
+            key(&other, 65537, m),
+            key(&n, exponents[0], m),
+            key(&n, exponents[1], m),
+        ];
+        Parameters {
+            n: Some(keys[0].n.clone()),
+            c: keys[0].c.clone(),
+            keys,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn coprime_exponents() {
+        let m = bytes_to_integer(b"RsaCracker! The same message was sent twice under one modulus");
+        let params = params([65537, 17], &m);
+
+        let solution = CommonModulusAttack.run(&params, None).unwrap();
+        assert_eq!(solution.m.unwrap(), m);
+        assert_eq!(
+            solution.vulnerability,
+            Some(Vulnerability::CommonModulus(vec![1, 2]))
+        );
+    }
+
+    #[test]
+    fn shared_divisor() {
+        // gcd(6, 9) = 3, and m^3 < n
+        let m = bytes_to_integer(b"RsaCracker! Small message");
+        let params = params([6, 9], &m);
+
+        let solution = CommonModulusAttack.run(&params, None).unwrap();
+        assert_eq!(solution.m.unwrap(), m);
+    }
+}
@@ -0,0 +1,331 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use rug::Integer;
+
+use crate::{bytes_to_integer, integer_to_bytes, Cache};
+
+/// Time budget the default effort is calibrated for
+const REFERENCE_TIME_BUDGET: Duration = Duration::from_secs(60);
+/// Largest multiplier of the iteration limits given by the time budget, in percent
+const MAX_TIME_EFFORT: u64 = 1600;
+
+/// Check of the candidate plaintexts, returning true to accept one (e.g. a flag validator)
+#[derive(Clone)]
+pub struct CandidateCheck(Arc<dyn Fn(&Integer) -> bool + Send + Sync>);
+
+impl CandidateCheck {
+    /// Create a check from a callback
+    pub fn new(check: impl Fn(&Integer) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(check))
+    }
+
+    /// Returns true if the candidate plaintext is accepted
+    pub fn accepts(&self, m: &Integer) -> bool {
+        (self.0)(m)
+    }
+}
+
+impl Debug for CandidateCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CandidateCheck")
+    }
+}
+
+impl PartialEq for CandidateCheck {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CandidateCheck {}
+
+/// Candidate plaintexts of a short, low entropy message (e.g. a PIN or a word)
+///
+/// The words are tried first, then all the strings of the characters of the charset, from the
+/// shortest to the longest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageGuesses {
+    /// Candidate messages, e.g. the lines of a wordlist
+    pub words: Arc<Vec<Vec<u8>>>,
+    /// Characters of the brute forced messages, none are brute forced if empty
+    pub charset: Vec<u8>,
+    /// Largest length of the brute forced messages
+    pub max_length: u32,
+}
+
+impl MessageGuesses {
+    /// Returns the number of candidates, saturated to `u64::MAX`
+    pub fn count(&self) -> u64 {
+        let strings = (1..=self.max_length)
+            .map(|length| (self.charset.len() as u64).saturating_pow(length))
+            .fold(0u64, u64::saturating_add);
+        strings.saturating_add(self.words.len() as u64)
+    }
+
+    /// Returns the candidate of the given index
+    pub fn get(&self, index: u64) -> Option<Vec<u8>> {
+        let words = self.words.len() as u64;
+        if index < words {
+            return Some(self.words[index as usize].clone());
+        }
+
+        // Index of the string among the strings of its length
+        let base = self.charset.len() as u64;
+        let mut index = index - words;
+        for length in 1..=self.max_length {
+            let count = base.checked_pow(length)?;
+            if index < count {
+                let mut string = vec![0; length as usize];
+                for c in string.iter_mut().rev() {
+                    *c = self.charset[(index % base) as usize];
+                    index /= base;
+                }
+                return Some(string);
+            }
+            index -= count;
+        }
+        None
+    }
+}
+
+/// Known structure of the plaintext (e.g. a flag format), to filter the candidate plaintexts
+///
+/// Attacks finding many possible plaintexts (square roots, e-th roots with e not coprime with
+/// phi) only keep the matching ones, and brute forces of the plaintext skip the other candidates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlaintextConstraint {
+    /// Bytes the plaintext starts with
+    pub prefix: Vec<u8>,
+    /// Bytes the plaintext ends with
+    pub suffix: Vec<u8>,
+    /// Length of the plaintext in bytes, any if `None`
+    pub length: Option<usize>,
+}
+
+impl PlaintextConstraint {
+    /// Returns true if the constraint accepts any plaintext
+    pub fn is_empty(&self) -> bool {
+        self.prefix.is_empty() && self.suffix.is_empty() && self.length.is_none()
+    }
+
+    /// Returns true if the plaintext matches the constraint
+    pub fn accepts(&self, m: &Integer) -> bool {
+        let bytes = integer_to_bytes(m);
+        self.length.is_none_or(|length| bytes.len() == length)
+            && bytes.starts_with(&self.prefix)
+            && bytes.ends_with(&self.suffix)
+    }
+
+    /// Returns the residue of the plaintexts modulo 256^k given by the suffix of k bytes, and the
+    /// modulus, `None` without suffix
+    pub fn residue(&self) -> Option<(Integer, Integer)> {
+        (!self.suffix.is_empty()).then(|| {
+            (
+                bytes_to_integer(&self.suffix),
+                Integer::from(1) << (8 * self.suffix.len() as u32),
+            )
+        })
+    }
+
+    /// Returns the range [low, high) of the plaintexts given by the prefix and the length,
+    /// `None` if the length is unknown
+    pub fn bounds(&self) -> Option<(Integer, Integer)> {
+        let length = self.length?;
+        let unknown = 8 * length.checked_sub(self.prefix.len())? as u32;
+        let prefix = bytes_to_integer(&self.prefix);
+        let low = Integer::from(&prefix << unknown);
+        let high = if self.prefix.is_empty() {
+            Integer::from(1) << unknown
+        } else {
+            (prefix + 1u32) << unknown
+        };
+        Some((low, high))
+    }
+}
+
+/// Share of the brute force searches of a run, when several machines split them
+///
+/// The blocks of candidates of the brute forces (see `BruteForce`) are dealt round-robin to the
+/// shards: the block i goes to the shard i mod `count`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// Index of the shard, below `count`
+    pub index: u64,
+    /// Number of shards
+    pub count: u64,
+}
+
+impl Shard {
+    /// Returns true if the block of the given index belongs to the shard
+    pub fn contains(&self, block: u64) -> bool {
+        block % self.count.max(1) == self.index
+    }
+}
+
+/// Attacks tuning parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttackConfig {
+    /// Number of threads available to the attacks
+    pub threads: usize,
+    /// Upper bound of the primes searched by the small prime attack
+    pub small_prime_bound: u64,
+    /// Londahl attack bound B (phi is searched within B^2 of its approximation)
+    pub londahl_b: u64,
+    /// Size in bits of the factors m1 and m2 of the message searched by the meet-in-the-middle attack
+    pub mitm_bits: u32,
+    /// Memory budget shared by the attacks of a run, in bytes
+    pub max_memory: u64,
+    /// Multiplier of the iteration limits and search bounds of the attacks, in percent
+    pub effort: u64,
+    /// Multiplier of the iteration limits given by the time budget, in percent, set by `tuned`
+    /// and not applied to the limits allocating memory
+    pub time_effort: u64,
+    /// Time budget of a run, no limit if `None`
+    pub time_budget: Option<Duration>,
+    /// Duration of the first time slice given to each slow attack
+    pub time_slice: Duration,
+    /// Maximum number of progress bars redraws per second
+    pub progress_refresh_rate: u8,
+    /// Only show the aggregate progress bar, not one bar per attack
+    pub aggregate_progress: bool,
+    /// Check of the plaintexts found, the solutions whose plaintexts are all rejected are
+    /// dropped and the search goes on
+    pub on_candidate: Option<CandidateCheck>,
+    /// Candidate plaintexts encrypted and compared to the cipher by the guess message attack
+    pub message_guesses: MessageGuesses,
+    /// Share of the brute force searches to run, all of them if `None`
+    pub shard: Option<Shard>,
+    /// Known structure of the plaintext, filtering the candidate plaintexts
+    pub plaintext: PlaintextConstraint,
+    /// On-disk cache of the FactorDB factorizations and of the attack durations, not used if `None`
+    pub cache: Option<Cache>,
+}
+
+impl Default for AttackConfig {
+    fn default() -> Self {
+        Self {
+            threads: num_cpus::get(),
+            small_prime_bound: 1 << 24,
+            londahl_b: 10_000_000,
+            mitm_bits: 20,
+            max_memory: 1 << 30,
+            effort: 100,
+            time_effort: 100,
+            time_budget: None,
+            time_slice: Duration::from_secs(60),
+            progress_refresh_rate: 20,
+            aggregate_progress: false,
+            on_candidate: None,
+            message_guesses: MessageGuesses::default(),
+            shard: None,
+            plaintext: PlaintextConstraint::default(),
+            cache: None,
+        }
+    }
+}
+
+impl AttackConfig {
+    /// Scale an iteration limit or a search bound by the effort and the time budget
+    pub fn scaled(&self, limit: u64) -> u64 {
+        (limit as u128 * self.effort as u128 * self.time_effort as u128 / 10_000)
+            .clamp(1, u64::MAX as u128) as u64
+    }
+
+    /// Scale a limit allocating memory (e.g. a number of candidates kept) by the effort only
+    pub fn scaled_memory(&self, limit: u64) -> u64 {
+        (limit as u128 * self.effort as u128 / 100).clamp(1, u64::MAX as u128) as u64
+    }
+
+    /// Adapt the effort to the bit length of n and to the time budget
+    ///
+    /// Small moduli are searched further, since each iteration is cheap, while huge ones, beyond
+    /// the usual key sizes, spend less time on methods that are hopeless at their size. The time
+    /// budget multiplies the iteration limits by at most `MAX_TIME_EFFORT` percent.
+    pub fn tuned(mut self, n_bits: u32) -> Self {
+        let size_effort = match n_bits {
+            0..=128 => 400,
+            129..=2048 => 100,
+            2049..=4096 => 50,
+            _ => 25,
+        };
+        self.effort = (self.effort as u128 * size_effort / 100).clamp(1, u64::MAX as u128) as u64;
+        if let Some(budget) = self.time_budget {
+            let time_effort = budget.as_millis() * 100 / REFERENCE_TIME_BUDGET.as_millis();
+            self.time_effort = time_effort.clamp(1, MAX_TIME_EFFORT as u128) as u64;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tuned() {
+        let config = AttackConfig::default();
+        assert_eq!(config.scaled(1000), 1000);
+        assert_eq!(config.clone().tuned(100).scaled(1000), 4000);
+        assert_eq!(config.clone().tuned(2048).scaled(1000), 1000);
+        assert_eq!(config.clone().tuned(8192).scaled(1000), 250);
+
+        let config = AttackConfig {
+            time_budget: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+        assert_eq!(config.tuned(256).scaled(1000), 500);
+
+        let config = AttackConfig {
+            time_budget: Some(Duration::from_secs(24 * 3600)),
+            ..Default::default()
+        }
+        .tuned(256);
+        assert_eq!(config.scaled(1000), 16_000);
+        assert_eq!(config.scaled_memory(1000), 1000);
+    }
+
+    #[test]
+    fn message_guesses() {
+        let guesses = MessageGuesses {
+            words: Arc::new(vec![b"admin".to_vec()]),
+            charset: b"01".to_vec(),
+            max_length: 3,
+        };
+        assert_eq!(guesses.count(), 15);
+        let candidates = (0..guesses.count()).filter_map(|i| guesses.get(i));
+        assert_eq!(
+            candidates.collect::<Vec<_>>(),
+            [
+                "admin", "0", "1", "00", "01", "10", "11", "000", "001", "010", "011", "100",
+                "101", "110", "111"
+            ]
+            .map(|s| s.as_bytes().to_vec())
+        );
+        assert_eq!(guesses.get(15), None);
+        assert_eq!(MessageGuesses::default().count(), 0);
+    }
+
+    #[test]
+    fn plaintext_constraint() {
+        let constraint = PlaintextConstraint {
+            prefix: b"flag{".to_vec(),
+            suffix: b"}".to_vec(),
+            length: Some(8),
+        };
+        assert!(constraint.accepts(&bytes_to_integer(b"flag{ab}")));
+        assert!(!constraint.accepts(&bytes_to_integer(b"flag{abc}")));
+        assert!(!constraint.accepts(&bytes_to_integer(b"flog{ab}")));
+        assert!(!constraint.accepts(&bytes_to_integer(b"flag{ab)")));
+        assert!(PlaintextConstraint::default().accepts(&42.into()));
+        assert!(PlaintextConstraint::default().is_empty());
+
+        assert_eq!(
+            constraint.residue(),
+            Some((Integer::from(b'}'), Integer::from(256)))
+        );
+        let (low, high) = constraint.bounds().unwrap();
+        assert_eq!(low, bytes_to_integer(b"flag{\0\0\0"));
+        assert_eq!(high, bytes_to_integer(b"flag|\0\0\0"));
+        assert_eq!(PlaintextConstraint::default().bounds(), None);
+    }
+}
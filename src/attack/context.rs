@@ -0,0 +1,478 @@
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+    time::Instant,
+};
+
+use indicatif::ProgressBar;
+use primal::Primes;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use rug::{integer::IsPrime, Integer};
+
+use crate::{
+    contfrac::convergents, ntheory::is_prime, AttackConfig, BruteForce, Error, Parameters,
+};
+
+/// Number of iterations between two checks of the stop flag in long-running loops
+pub(crate) const STOP_CHECK_INTERVAL: u64 = 4096;
+
+/// Number of primes in the shared small primes table
+const SMALL_PRIMES_COUNT: usize = 1_000_000;
+
+/// Values derived from n and e, computed on first use and shared by all attacks of a same run
+#[derive(Debug, Default)]
+pub struct PrecomputedContext {
+    n: Option<Integer>,
+    e: Integer,
+    n_sqrt_rem: OnceLock<Option<(Integer, Integer)>>,
+    e_n_convergents: OnceLock<Option<Vec<(Integer, Integer)>>>,
+    n_is_prime: OnceLock<Option<IsPrime>>,
+    small_primes: OnceLock<Vec<u64>>,
+}
+
+impl PrecomputedContext {
+    /// Create a new precomputation context for the given parameters
+    pub fn new(params: &Parameters) -> Self {
+        Self {
+            n: params.n.clone(),
+            e: params.e.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns n
+    pub fn n(&self) -> Option<&Integer> {
+        self.n.as_ref()
+    }
+
+    /// Returns the bit length of n
+    pub fn n_bits(&self) -> Option<u32> {
+        self.n.as_ref().map(|n| n.significant_bits())
+    }
+
+    /// Returns the integer square root of n and its remainder
+    pub fn n_sqrt_rem(&self) -> Option<(&Integer, &Integer)> {
+        self.n_sqrt_rem
+            .get_or_init(|| self.n.as_ref().map(|n| n.sqrt_rem_ref().into()))
+            .as_ref()
+            .map(|(sqrt, rem)| (sqrt, rem))
+    }
+
+    /// Returns the integer square root of n
+    pub fn n_sqrt(&self) -> Option<&Integer> {
+        self.n_sqrt_rem().map(|(sqrt, _)| sqrt)
+    }
+
+    /// Returns the convergents of e / n, the candidates k / d of Wiener's attack
+    ///
+    /// The continued fraction of e / n has as many terms as n has bits, so it is only expanded
+    /// once for all the attacks of a run.
+    pub fn e_n_convergents(&self) -> Option<&[(Integer, Integer)]> {
+        self.e_n_convergents
+            .get_or_init(|| self.n.as_ref().map(|n| convergents(&self.e, n).collect()))
+            .as_deref()
+    }
+
+    /// Returns the result of the primality test of n
+    pub fn n_is_prime(&self) -> Option<IsPrime> {
+        *self
+            .n_is_prime
+            .get_or_init(|| self.n.as_ref().map(is_prime))
+    }
+
+    /// Returns the first `SMALL_PRIMES_COUNT` primes
+    pub fn small_primes(&self) -> &[u64] {
+        self.small_primes.get_or_init(|| {
+            Primes::all()
+                .take(SMALL_PRIMES_COUNT)
+                .map(|p| p as u64)
+                .collect()
+        })
+    }
+}
+
+/// State shared by all attacks of a same run
+#[derive(Debug, Clone, Default)]
+pub struct AttackContext {
+    stop: Arc<AtomicBool>,
+    ancestors_stop: Arc<[Arc<AtomicBool>]>,
+    precomputed: Arc<PrecomputedContext>,
+    config: Arc<AttackConfig>,
+    deadline: Option<Instant>,
+    memory_used: Arc<AtomicU64>,
+    thread_pool: Arc<OnceLock<Result<ThreadPool, String>>>,
+}
+
+/// Memory reserved by an attack from the budget of a run, given back when dropped
+#[derive(Debug)]
+pub struct MemoryReservation {
+    size: u64,
+    memory_used: Arc<AtomicU64>,
+}
+
+impl MemoryReservation {
+    /// Returns the number of bytes reserved
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.memory_used.fetch_sub(self.size, Ordering::Relaxed);
+    }
+}
+
+impl AttackContext {
+    /// Create a new context for the given parameters
+    pub fn new(params: &Parameters) -> Self {
+        Self {
+            stop: Arc::default(),
+            ancestors_stop: Arc::default(),
+            precomputed: Arc::new(PrecomputedContext::new(params)),
+            config: Arc::default(),
+            deadline: None,
+            memory_used: Arc::default(),
+            thread_pool: Arc::default(),
+        }
+    }
+
+    /// Set the attacks configuration
+    ///
+    /// The thread pool is only shared with the contexts of a same number of threads.
+    pub fn with_config(mut self, config: AttackConfig) -> Self {
+        if config.threads != self.config.threads {
+            self.thread_pool = Arc::default();
+        }
+        self.config = Arc::new(config);
+        self
+    }
+
+    /// Stop the attacks sharing this context once the deadline is reached
+    ///
+    /// The earliest deadline is kept if one was already set.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(self.deadline.map_or(deadline, |d| d.min(deadline)));
+        self
+    }
+
+    /// Returns the deadline of the attacks, if any
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Create a context sharing the same precomputed values, memory budget and thread pool, which can be
+    /// stopped on its own
+    ///
+    /// The child context is also stopped when this context or any of its ancestors is, or when its
+    /// deadline is reached.
+    pub fn child(&self) -> Self {
+        let ancestors_stop = self.ancestors_stop.iter().chain([&self.stop]).cloned();
+        Self {
+            stop: Arc::default(),
+            ancestors_stop: ancestors_stop.collect(),
+            precomputed: Arc::clone(&self.precomputed),
+            config: Arc::clone(&self.config),
+            deadline: self.deadline,
+            memory_used: Arc::clone(&self.memory_used),
+            thread_pool: Arc::clone(&self.thread_pool),
+        }
+    }
+
+    /// Create a context for other parameters, sharing the same configuration, deadline, memory
+    /// budget and thread pool, which is stopped along with this context
+    ///
+    /// Stopping either context stops the attacks of both.
+    pub fn with_params(&self, params: &Parameters) -> Self {
+        Self {
+            precomputed: Arc::new(PrecomputedContext::new(params)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the attacks configuration
+    pub fn config(&self) -> &AttackConfig {
+        &self.config
+    }
+
+    /// Returns the values precomputed from the parameters
+    pub fn precomputed(&self) -> &PrecomputedContext {
+        &self.precomputed
+    }
+
+    /// Returns the pool of `AttackConfig::threads` threads shared by the attacks, built on first use
+    pub fn thread_pool(&self) -> Result<&ThreadPool, Error> {
+        self.thread_pool
+            .get_or_init(|| {
+                ThreadPoolBuilder::new()
+                    .num_threads(self.config().threads)
+                    .build()
+                    .map_err(|err| err.to_string())
+            })
+            .as_ref()
+            .map_err(|err| Error::ThreadPool(err.clone()))
+    }
+
+    /// Ask all attacks sharing this context to stop as soon as possible
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the attacks were asked to stop, or if the deadline is reached
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+            || self
+                .ancestors_stop
+                .iter()
+                .any(|stop| stop.load(Ordering::Relaxed))
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Returns `Error::Stopped` if the attacks were asked to stop
+    pub fn check(&self) -> Result<(), Error> {
+        if self.is_stopped() {
+            Err(Error::Stopped)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reserve up to `bytes` from the memory budget shared by the attacks, `AttackConfig::max_memory`
+    ///
+    /// Less is reserved if the other attacks already hold most of the budget, down to nothing: the
+    /// caller is expected to build smaller tables rather than exceed it.
+    pub fn reserve_memory(&self, bytes: u64) -> MemoryReservation {
+        let max_memory = self.config().max_memory;
+        let mut size = 0;
+        let _ = self
+            .memory_used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                size = bytes.min(max_memory.saturating_sub(used));
+                Some(used + size)
+            });
+        MemoryReservation {
+            size,
+            memory_used: Arc::clone(&self.memory_used),
+        }
+    }
+
+    /// Search `1..max_iterations` by blocks of `STOP_CHECK_INTERVAL` iterations, spread across the
+    /// available threads
+    ///
+    /// Blocks are handed out in order, and the search stops as soon as a block returns a value.
+    /// See `BruteForce` to resume the search later.
+    pub fn par_search<T: Send>(
+        &self,
+        max_iterations: u64,
+        pb: Option<&ProgressBar>,
+        search: impl Fn(Range<u64>) -> Option<T> + Sync,
+    ) -> Result<Option<T>, Error> {
+        BruteForce::new(1..max_iterations.max(1)).run(self, pb, search)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        str::FromStr,
+        time::{Duration, Instant},
+    };
+
+    use rug::Integer;
+
+    use crate::{
+        Attack, GaaAttack, HartAttack, KraitchikAttack, Parameters, SqufofAttack, TwinPrimeAttack,
+    };
+
+    use super::*;
+
+    #[test]
+    fn precomputed() {
+        let params = Parameters {
+            n: Some(Integer::from(1779681653u64) * 1903643191u64),
+            ..Default::default()
+        };
+        let precomputed = PrecomputedContext::new(&params);
+
+        let (sqrt, rem) = params.n.as_ref().unwrap().sqrt_rem_ref().into();
+        assert_eq!(precomputed.n_sqrt_rem(), Some((&sqrt, &rem)));
+        assert_eq!(precomputed.n_bits(), Some(62));
+        assert_eq!(precomputed.n_is_prime(), Some(IsPrime::No));
+        assert_eq!(precomputed.small_primes().len(), SMALL_PRIMES_COUNT);
+        assert_eq!(precomputed.small_primes()[..5], [2, 3, 5, 7, 11]);
+        let convergents = precomputed.e_n_convergents().unwrap();
+        assert_eq!(
+            convergents.last(),
+            Some(&(Integer::from(65537), params.n.clone().unwrap()))
+        );
+        assert!(std::ptr::eq(
+            convergents,
+            precomputed.e_n_convergents().unwrap()
+        ));
+
+        let empty = PrecomputedContext::default();
+        assert_eq!(empty.n_sqrt(), None);
+        assert_eq!(empty.e_n_convergents(), None);
+        assert_eq!(empty.n_is_prime(), None);
+    }
+
+    #[test]
+    fn stop() {
+        let ctx = AttackContext::default();
+        let shared = ctx.clone();
+        assert!(ctx.check().is_ok());
+
+        let child = ctx.child();
+        child.stop();
+        assert!(child.is_stopped());
+        assert!(!ctx.is_stopped());
+
+        let child = ctx.child();
+        let grandchild = child.child();
+        shared.stop();
+        assert!(ctx.is_stopped());
+        assert!(child.is_stopped());
+        assert!(grandchild.is_stopped());
+        assert_eq!(ctx.check(), Err(Error::Stopped));
+    }
+
+    #[test]
+    fn deadline() {
+        let ctx = AttackContext::default().with_deadline(Instant::now() + Duration::from_secs(60));
+        assert!(!ctx.is_stopped());
+
+        let child = ctx.child().with_deadline(Instant::now());
+        assert!(child.is_stopped());
+        assert!(!ctx.is_stopped());
+
+        // The earliest deadline is kept
+        let child = child.with_deadline(Instant::now() + Duration::from_secs(60));
+        assert!(child.is_stopped());
+    }
+
+    #[test]
+    fn reserve_memory() {
+        let ctx = AttackContext::default().with_config(AttackConfig {
+            max_memory: 1000,
+            ..Default::default()
+        });
+        let child = ctx.child();
+
+        let first = ctx.reserve_memory(600);
+        assert_eq!(first.size(), 600);
+        // The budget is shared with the child contexts
+        let second = child.reserve_memory(600);
+        assert_eq!(second.size(), 400);
+        assert_eq!(ctx.reserve_memory(100).size(), 0);
+
+        drop(first);
+        assert_eq!(child.reserve_memory(600).size(), 600);
+        drop(second);
+    }
+
+    #[test]
+    fn thread_pool() {
+        let ctx = AttackContext::default().with_config(AttackConfig {
+            threads: 3,
+            ..Default::default()
+        });
+        let pool = ctx.thread_pool().unwrap();
+        assert_eq!(pool.current_num_threads(), 3);
+        assert!(std::ptr::eq(pool, ctx.child().thread_pool().unwrap()));
+        assert!(std::ptr::eq(
+            pool,
+            ctx.with_params(&Parameters::default())
+                .thread_pool()
+                .unwrap()
+        ));
+
+        let same_threads = ctx.clone().with_config(AttackConfig {
+            threads: 3,
+            effort: 200,
+            ..Default::default()
+        });
+        assert!(std::ptr::eq(pool, same_threads.thread_pool().unwrap()));
+        let other_threads = ctx.clone().with_config(AttackConfig {
+            threads: 2,
+            ..Default::default()
+        });
+        assert_eq!(
+            other_threads.thread_pool().unwrap().current_num_threads(),
+            2
+        );
+    }
+
+    #[test]
+    fn par_search() {
+        let ctx = AttackContext::default().with_config(AttackConfig {
+            threads: 4,
+            ..Default::default()
+        });
+
+        let res = ctx.par_search(1_000_000, None, |range| {
+            range.into_iter().find(|&i| i == 123_457)
+        });
+        assert_eq!(res, Ok(Some(123_457)));
+        assert_eq!(ctx.par_search(100_000, None, |_| None::<u64>), Ok(None));
+
+        ctx.stop();
+        assert_eq!(
+            ctx.par_search(100_000, None, |_| None::<u64>),
+            Err(Error::Stopped)
+        );
+    }
+
+    #[test]
+    fn stopped_attacks_return_promptly() {
+        // Product of two 256-bit primes, out of reach of all these attacks
+        let p = Integer::from_str(
+            "90511711160491104683260185958356471267265849470934538618804038653604797333997",
+        )
+        .unwrap();
+        let q = Integer::from_str(
+            "114596133725443196642584039352225739386449324911628952764126006973006794144319",
+        )
+        .unwrap();
+        let params = Parameters {
+            n: Some(p * q),
+            p: Some(2539.into()),
+            q: Some(3017.into()),
+            ..Default::default()
+        };
+
+        let attacks: [&dyn Attack; 5] = [
+            &GaaAttack,
+            &HartAttack,
+            &KraitchikAttack,
+            &SqufofAttack,
+            &TwinPrimeAttack,
+        ];
+        for attack in attacks {
+            let ctx = AttackContext::new(&params);
+            let killer = {
+                let ctx = ctx.clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(Duration::from_millis(100));
+                    ctx.stop();
+                })
+            };
+
+            let start = Instant::now();
+            let res = attack.run_with_context(&params, &ctx, None);
+            killer.join().unwrap();
+
+            assert_eq!(res.unwrap_err(), Error::Stopped, "{}", attack.name());
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "{} took {:?} to stop",
+                attack.name(),
+                start.elapsed()
+            );
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{Attack, AttackKind, AttackSpeed, Error, Parameters, Solution};
+use crate::{Attack, AttackKind, AttackSpeed, Error, Parameters, Solution, Vulnerability};
 
 /// Cube root attack (m^e < n and small e)
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,6 +20,10 @@ impl Attack for CubeRootAttack {
         AttackKind::KnownExtraInformation
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LowExponent)
+    }
+
     fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = match params.e.to_u32() {
             Some(e) => e,
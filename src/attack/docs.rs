@@ -0,0 +1,585 @@
+/// Structured documentation of an attack, e.g. for a front end to explain it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttackDoc {
+    /// What the attack does, in one sentence
+    pub summary: &'static str,
+    /// Typical conditions for the attack to succeed
+    pub conditions: &'static str,
+    /// Cost of the attack, in the size of its inputs
+    pub complexity: &'static str,
+    /// Papers and reference implementations
+    pub references: &'static [&'static str],
+}
+
+/// Documentation of each attack, by name
+const ATTACK_DOCS: &[(&str, AttackDoc)] = &[
+    (
+        "branch_and_prune",
+        AttackDoc {
+            summary: "Rebuilds p and q bit by bit from their lowest bit, keeping the candidates matching n modulo a power of 2",
+            conditions: "About half of the bits of p and q are known, at any positions",
+            complexity: "Polynomial in the size of n while enough bits are known, exponential in the missing ones",
+            references: &["Heninger and Shacham, Reconstructing RSA Private Keys from Random Key Bits, CRYPTO 2009"],
+        },
+    ),
+    (
+        "cipolla",
+        AttackDoc {
+            summary: "Decrypts with the exponent e / 2, then takes a square root modulo n with Cipolla's algorithm",
+            conditions: "n is prime and e is even (e.g. e = 2, Rabin-like encryption)",
+            complexity: "O(log n) multiplications in F_n^2",
+            references: &["https://en.wikipedia.org/wiki/Cipolla%27s_algorithm"],
+        },
+    ),
+    (
+        "comfact_cn",
+        AttackDoc {
+            summary: "Factors n with the gcd of the cipher and n",
+            conditions: "The message shares a factor with n, so the cipher does too",
+            complexity: "One gcd",
+            references: &["https://crypto.stackexchange.com/a/99221"],
+        },
+    ),
+    (
+        "common_modulus",
+        AttackDoc {
+            summary: "Combines the ciphers of a message under the same modulus and several exponents with Bezout coefficients",
+            conditions: "The same message encrypted under the same n with exponents e1 and e2 of small gcd",
+            complexity: "An extended gcd and two modular exponentiations",
+            references: &["Simmons, A \"weak\" privacy protocol using the RSA crypto algorithm, Cryptologia 1983"],
+        },
+    ),
+    (
+        "cube_root",
+        AttackDoc {
+            summary: "Takes the integer e-th root of c, the message being too small to be reduced modulo n",
+            conditions: "Small e and unpadded message with m^e < n",
+            complexity: "One integer root",
+            references: &[],
+        },
+    ),
+    (
+        "eth_root",
+        AttackDoc {
+            summary: "Searches m^e = c + k * n for small k, or small m, by blocks of integer roots",
+            conditions: "Unpadded message slightly larger than n^(1/e), or small for any e",
+            complexity: "One integer root per k, or one exponentiation per m",
+            references: &[],
+        },
+    ),
+    (
+        "gaa",
+        AttackDoc {
+            summary: "Recovers p and q from their known least significant bits",
+            conditions: "Primes sharing their least significant half, e.g. generated from a common value",
+            complexity: "Polynomial in the size of n",
+            references: &[
+                "Ghafar, Ariffin and Asbullah, A New LSB Attack on Special-Structured RSA Primes, Symmetry 2020",
+                "https://www.mdpi.com/2073-8994/12/5/838",
+                "https://github.com/jvdsn/crypto-attacks/blob/master/attacks/factorization/gaa.py",
+            ],
+        },
+    ),
+    (
+        "guess_message",
+        AttackDoc {
+            summary: "Encrypts candidate messages from a wordlist or a charset and compares them to the cipher",
+            conditions: "Unpadded low entropy message, e.g. a PIN or a word",
+            complexity: "One modular exponentiation per candidate",
+            references: &[],
+        },
+    ),
+    (
+        "hastad",
+        AttackDoc {
+            summary: "Combines with the CRT the ciphers of a message under e moduli, then takes the integer e-th root",
+            conditions: "The same unpadded message encrypted with the same small e under at least e moduli",
+            complexity: "A CRT and an integer root per subset of e keys",
+            references: &["Håstad, Solving Simultaneous Modular Equations of Low Degree, SIAM J. Comput. 1988"],
+        },
+    ),
+    (
+        "known_d",
+        AttackDoc {
+            summary: "Factors n from the private exponent, e * d - 1 being a multiple of phi(n)",
+            conditions: "d is known",
+            complexity: "A few modular exponentiations on average",
+            references: &[
+                "Boneh, Twenty Years of Attacks on the RSA Cryptosystem, Notices of the AMS 1999",
+                "https://github.com/jvdsn/crypto-attacks/blob/master/attacks/rsa/known_d.py",
+            ],
+        },
+    ),
+    (
+        "known_phi",
+        AttackDoc {
+            summary: "Factors n from phi(n), p and q being the roots of x^2 - (n - phi + 1) * x + n",
+            conditions: "phi(n) is known",
+            complexity: "One integer square root",
+            references: &["https://github.com/jvdsn/crypto-attacks/blob/master/attacks/factorization/known_phi.py"],
+        },
+    ),
+    (
+        "leaked_crt_coefficient",
+        AttackDoc {
+            summary: "Factors n from the CRT coefficients qinv and pinv, with phi or the candidates of phi given by d",
+            conditions: "qinv and pinv are known, with d or phi",
+            complexity: "A few gcds and modular exponentiations",
+            references: &[],
+        },
+    ),
+    (
+        "leaked_crt_exponent",
+        AttackDoc {
+            summary: "Factors n with the gcd of 2^(e * dp) - 2 and n",
+            conditions: "dp or dq is known",
+            complexity: "One modular exponentiation and one gcd",
+            references: &[],
+        },
+    ),
+    (
+        "leaked_crt_exponents",
+        AttackDoc {
+            summary: "Recovers p and q, and n, from the CRT exponents dp and dq and the coefficient qinv",
+            conditions: "dp, dq and qinv are known, n may be unknown",
+            complexity: "Linear in e",
+            references: &[],
+        },
+    ),
+    (
+        "leaked_pq",
+        AttackDoc {
+            summary: "Builds the private key from a known prime",
+            conditions: "p or q is known",
+            complexity: "One division",
+            references: &[],
+        },
+    ),
+    (
+        "non_coprime_exp",
+        AttackDoc {
+            summary: "Takes the roots of c modulo each prime when e is not coprime with phi(n), and combines them with the CRT",
+            conditions: "gcd(e, phi(n)) > 1 and the factors of n are known",
+            complexity: "Polynomial in the size of n, times the number of roots",
+            references: &["https://github.com/jvdsn/crypto-attacks/blob/master/attacks/rsa/non_coprime_exponent.py"],
+        },
+    ),
+    (
+        "p_mod",
+        AttackDoc {
+            summary: "Factors n from the residue of a prime modulo m, by brute force or Coppersmith's method",
+            conditions: "p mod m is known, e.g. its last decimal digits, with m larger than n^(1/4) for balanced primes",
+            complexity: "Polynomial in the size of n with Coppersmith's method",
+            references: &["Coppersmith, Small Solutions to Polynomial Equations, and Low Exponent RSA Vulnerabilities, J. Cryptology 1997"],
+        },
+    ),
+    (
+        "partial_crt_exponent",
+        AttackDoc {
+            summary: "Factors n from a partially known dp or dq, by brute force or Coppersmith's method",
+            conditions: "A few unknown bits of dp, or its MSBs or LSBs known with a small e",
+            complexity: "Exponential in the unknown bits by brute force, polynomial with Coppersmith's method",
+            references: &["Blömer and May, New Partial Key Exposure Attacks on RSA, CRYPTO 2003"],
+        },
+    ),
+    (
+        "partial_d",
+        AttackDoc {
+            summary: "Completes the known least significant bits of d with the most significant ones of (k * n + 1) / e",
+            conditions: "More than half of the bits of d, its least significant ones, are known and e is small",
+            complexity: "Linear in e",
+            references: &["Boneh, Durfee and Frankel, An Attack on RSA Given a Small Fraction of the Private Key Bits, ASIACRYPT 1998"],
+        },
+    ),
+    (
+        "partial_m",
+        AttackDoc {
+            summary: "Finds the unknown window of a stereotyped message with Coppersmith's method",
+            conditions: "Small e, and the unknown window of the message is smaller than n^(1/e)",
+            complexity: "Polynomial in the size of n and e",
+            references: &["Coppersmith, Small Solutions to Polynomial Equations, and Low Exponent RSA Vulnerabilities, J. Cryptology 1997"],
+        },
+    ),
+    (
+        "partial_pq",
+        AttackDoc {
+            summary: "Completes partially known p and q with p * q = n, then searches their unknown window",
+            conditions: "Some bits of p and q are known, the unknown ones forming a small window or one smaller than n^(1/4)",
+            complexity: "Exponential in the unknown window by brute force, polynomial with Coppersmith's method",
+            references: &["Coppersmith, Small Solutions to Polynomial Equations, and Low Exponent RSA Vulnerabilities, J. Cryptology 1997"],
+        },
+    ),
+    (
+        "partial_prime",
+        AttackDoc {
+            summary: "Finds the unknown window of a single partially known prime with Coppersmith's method",
+            conditions: "Some bits of p or q are known, the unknown ones forming a small window or one smaller than n^(1/4)",
+            complexity: "Exponential in the unknown window by brute force, polynomial with Coppersmith's method",
+            references: &["Coppersmith, Small Solutions to Polynomial Equations, and Low Exponent RSA Vulnerabilities, J. Cryptology 1997"],
+        },
+    ),
+    (
+        "prime_modulus",
+        AttackDoc {
+            summary: "Decrypts with phi(n) = n - 1 when n is prime",
+            conditions: "n is prime and e is odd",
+            complexity: "One primality test",
+            references: &[],
+        },
+    ),
+    (
+        "signature_dlog",
+        AttackDoc {
+            summary: "Recovers d from a textbook signature with discrete logarithms modulo p and q",
+            conditions: "A known message and its signature, the factors of n, and a smooth order of the message",
+            complexity: "Pohlig-Hellman, polynomial in the largest prime factor of the order",
+            references: &["Pohlig and Hellman, An Improved Algorithm for Computing Logarithms over GF(p), IEEE Trans. Inf. Theory 1978"],
+        },
+    ),
+    (
+        "small_e",
+        AttackDoc {
+            summary: "Searches m^e = c + k * n for small k, with integer roots",
+            conditions: "Small e and unpadded message slightly larger than n^(1/e)",
+            complexity: "One integer root per k",
+            references: &[],
+        },
+    ),
+    (
+        "small_message_mitm",
+        AttackDoc {
+            summary: "Meets in the middle on m = m1 * m2, with a table of m1^e looked up with c / m2^e",
+            conditions: "Unpadded 2k-bit message splitting into two k-bit factors",
+            complexity: "2^(k + 1) modular exponentiations and a 2^k entries table",
+            references: &["Boneh, Joux and Nguyen, Why Textbook ElGamal and RSA Encryption Are Insecure, ASIACRYPT 2000"],
+        },
+    ),
+    (
+        "sum_pq",
+        AttackDoc {
+            summary: "Factors n from p + q, p and q being the roots of x^2 - (p + q) * x + n",
+            conditions: "p + q is known",
+            complexity: "One integer square root",
+            references: &[],
+        },
+    ),
+    (
+        "symmetric_pq",
+        AttackDoc {
+            summary: "Factors n from symmetric functions of p and q, exact or modulo m, with Newton's identities",
+            conditions: "Two of p + q, |p - q|, p * q and p^2 + q^2 are known, one of them possibly modulo m",
+            complexity: "One integer square root per candidate",
+            references: &[],
+        },
+    ),
+    (
+        "wiener",
+        AttackDoc {
+            summary: "Finds d among the convergents of the continued fraction of e / n",
+            conditions: "d < n^(1/4) / 3",
+            complexity: "Logarithmic in n convergents",
+            references: &[
+                "Wiener, Cryptanalysis of Short RSA Secret Exponents, IEEE Trans. Inf. Theory 1990",
+                "https://github.com/jvdsn/crypto-attacks/blob/master/attacks/rsa/wiener_attack.py",
+            ],
+        },
+    ),
+    (
+        "xor_pq",
+        AttackDoc {
+            summary: "Rebuilds p and q from their highest bit with p ^ q, bounding n with each prefix",
+            conditions: "p ^ q is known",
+            complexity: "Polynomial in the size of n in practice",
+            references: &[],
+        },
+    ),
+    (
+        "brent",
+        AttackDoc {
+            summary: "Pollard's rho factorization with Brent's cycle detection",
+            conditions: "n has a small factor, up to about 60 bits",
+            complexity: "O(p^(1/2)) multiplications, with p the smallest factor",
+            references: &["Brent, An Improved Monte Carlo Factorization Algorithm, BIT 1980"],
+        },
+    ),
+    (
+        "cache",
+        AttackDoc {
+            summary: "Looks n, or one of its factors, up in the FactorDB factorizations of previous runs",
+            conditions: "n, or a modulus sharing a prime with it, was factored by FactorDB",
+            complexity: "One look-up",
+            references: &[],
+        },
+    ),
+    (
+        "cunningham_chain",
+        AttackDoc {
+            summary: "Divides n by the primes of famous Cunningham chains",
+            conditions: "A factor of n belongs to a known Cunningham chain",
+            complexity: "One division per prime of the chains",
+            references: &["https://en.wikipedia.org/wiki/Cunningham_chain"],
+        },
+    ),
+    (
+        "ecm",
+        AttackDoc {
+            summary: "Lenstra's elliptic curve factorization, running independent curves concurrently",
+            conditions: "n has a factor of up to about 40 digits",
+            complexity: "Subexponential in the smallest factor p, exp((2 + o(1)) * sqrt(ln p * ln ln p))",
+            references: &["Lenstra, Factoring Integers with Elliptic Curves, Annals of Mathematics 1987"],
+        },
+    ),
+    (
+        "factordb",
+        AttackDoc {
+            summary: "Looks n up on factordb.com",
+            conditions: "n was factored by someone and submitted to factordb.com",
+            complexity: "One request",
+            references: &["https://factordb.com"],
+        },
+    ),
+    (
+        "fermat",
+        AttackDoc {
+            summary: "Searches n = a^2 - b^2 with a from sqrt(n) up, sieving the candidates",
+            conditions: "p and q are close, |p - q| of about n^(1/4) or less",
+            complexity: "O((p - q)^2 / (8 * sqrt(n))) candidates",
+            references: &["https://github.com/jvdsn/crypto-attacks/blob/master/attacks/factorization/fermat.py"],
+        },
+    ),
+    (
+        "hart",
+        AttackDoc {
+            summary: "Hart's one line factorization, searching a square among ceil(sqrt(n * i))^2 mod n",
+            conditions: "p and q are close, or their ratio is close to a small fraction",
+            complexity: "O(n^(1/3)) steps at most",
+            references: &[
+                "Hart, A One Line Factoring Algorithm, J. Aust. Math. Soc. 2012",
+                "https://programmingpraxis.com/2014/01/28/harts-one-line-factoring-algorithm/",
+            ],
+        },
+    ),
+    (
+        "known_factors",
+        AttackDoc {
+            summary: "Builds the private key from given factors of n",
+            conditions: "The factors of n are known",
+            complexity: "A few divisions",
+            references: &[],
+        },
+    ),
+    (
+        "kraitchik",
+        AttackDoc {
+            summary: "Kraitchik's variant of Fermat's method, searching x^2 - k * n square",
+            conditions: "p and q are close, or their ratio is close to a small integer",
+            complexity: "Like Fermat's method for each multiplier k",
+            references: &["https://github.com/daedalus/integer_factorization_algorithms/blob/main/kraitchik.py"],
+        },
+    ),
+    (
+        "londahl",
+        AttackDoc {
+            summary: "Searches phi(n) within B^2 of its approximation with a baby-step giant-step table",
+            conditions: "p and q are close, their difference giving phi(n) within B^2 of n - 2 * sqrt(n) + 1",
+            complexity: "O(B) modular exponentiations and a B entries table",
+            references: &["https://github.com/RsaCtfTool/RsaCtfTool/blob/master/attacks/single_key/londahl.py"],
+        },
+    ),
+    (
+        "mersenne_prime",
+        AttackDoc {
+            summary: "Divides n by the known Mersenne primes",
+            conditions: "A factor of n is a Mersenne prime 2^k - 1",
+            complexity: "One division per Mersenne prime",
+            references: &["https://en.wikipedia.org/wiki/Mersenne_prime"],
+        },
+    ),
+    (
+        "pollard_pm1",
+        AttackDoc {
+            summary: "Pollard's p - 1 factorization, with the gcd of a^M - 1 and n for a smooth M",
+            conditions: "p - 1 is smooth for a factor p of n",
+            complexity: "O(B log B) multiplications for a smoothness bound B",
+            references: &["Pollard, Theorems on Factorization and Primality Testing, Proc. Cambridge Philos. Soc. 1974"],
+        },
+    ),
+    (
+        "pollard_rho",
+        AttackDoc {
+            summary: "Pollard's rho factorization, with one pseudo-random walk per thread",
+            conditions: "n has a small factor, up to about 60 bits",
+            complexity: "O(p^(1/2)) multiplications, with p the smallest factor",
+            references: &["Pollard, A Monte Carlo Method for Factorization, BIT 1975"],
+        },
+    ),
+    (
+        "power",
+        AttackDoc {
+            summary: "Takes the integer k-th roots of n, for n = p^k",
+            conditions: "n is a perfect power of a prime",
+            complexity: "One integer root per k up to log2(n)",
+            references: &[],
+        },
+    ),
+    (
+        "small_prime",
+        AttackDoc {
+            summary: "Trial division by the primes up to a bound, enumerated with a segmented wheel sieve",
+            conditions: "n has a factor below the bound",
+            complexity: "O(B / log B) divisions for a bound B",
+            references: &[],
+        },
+    ),
+    (
+        "sparse",
+        AttackDoc {
+            summary: "Searches p and q differing by a few bits",
+            conditions: "p and q only differ by a few bits",
+            complexity: "Exponential in the number of differing bits",
+            references: &[],
+        },
+    ),
+    (
+        "squfof",
+        AttackDoc {
+            summary: "Shanks' square forms factorization, with one multiplier per thread",
+            conditions: "n is small, up to about 60 bits, or has close factors",
+            complexity: "O(n^(1/4)) steps",
+            references: &[
+                "https://en.wikipedia.org/wiki/Shanks%27s_square_forms_factorization",
+                "https://github.com/daedalus/integer_factorization_algorithms/blob/main/SQUFOF.py",
+            ],
+        },
+    ),
+    (
+        "twin_prime",
+        AttackDoc {
+            summary: "Solves n = p * (p + 2) with an integer square root",
+            conditions: "p and q are twin primes",
+            complexity: "One integer square root",
+            references: &[],
+        },
+    ),
+    (
+        "weak_primes",
+        AttackDoc {
+            summary: "Divides n by the factors of the public RSA challenge moduli, with a single gcd",
+            conditions: "n shares a prime with a publicly factored modulus",
+            complexity: "One gcd",
+            references: &["https://en.wikipedia.org/wiki/RSA_numbers"],
+        },
+    ),
+    (
+        "factorial_gcd",
+        AttackDoc {
+            summary: "Takes the gcd of n and the factorials plus or minus 1",
+            conditions: "A factor of n is k! + 1 or k! - 1",
+            complexity: "One gcd per term",
+            references: &["https://en.wikipedia.org/wiki/Factorial_prime"],
+        },
+    ),
+    (
+        "fermat_gcd",
+        AttackDoc {
+            summary: "Takes the gcd of n and the Fermat numbers 2^(2^k) + 1",
+            conditions: "A factor of n divides a Fermat number",
+            complexity: "One gcd per term",
+            references: &["https://en.wikipedia.org/wiki/Fermat_number"],
+        },
+    ),
+    (
+        "fibonacci_gcd",
+        AttackDoc {
+            summary: "Takes the gcd of n and the Fibonacci numbers",
+            conditions: "A factor of n divides a Fibonacci number",
+            complexity: "One gcd per term",
+            references: &["https://en.wikipedia.org/wiki/Fibonacci_prime"],
+        },
+    ),
+    (
+        "jacobsthal_gcd",
+        AttackDoc {
+            summary: "Takes the gcd of n and the Jacobsthal numbers",
+            conditions: "A factor of n divides a Jacobsthal number",
+            complexity: "One gcd per term",
+            references: &["https://en.wikipedia.org/wiki/Jacobsthal_number"],
+        },
+    ),
+    (
+        "lucas_gcd",
+        AttackDoc {
+            summary: "Takes the gcd of n and the Lucas numbers",
+            conditions: "A factor of n divides a Lucas number",
+            complexity: "One gcd per term",
+            references: &["https://en.wikipedia.org/wiki/Lucas_number"],
+        },
+    ),
+    (
+        "lucas_index",
+        AttackDoc {
+            summary: "Pollard p-1 and Williams p+1 with Fibonacci and Lucas number exponents",
+            conditions: "p - 1 or p + 1 is a small cofactor times a Fibonacci or Lucas number",
+            complexity: "O(i) multiplications modulo n per index i",
+            references: &[
+                "https://en.wikipedia.org/wiki/Williams%27s_p_%2B_1_algorithm",
+                "https://en.wikipedia.org/wiki/Lucas_sequence",
+            ],
+        },
+    ),
+    (
+        "mersenne_gcd",
+        AttackDoc {
+            summary: "Takes the gcd of n and the Mersenne numbers plus or minus 1",
+            conditions: "A factor of n divides 2^k - 1 or 2^k + 1",
+            complexity: "One gcd per term",
+            references: &["https://en.wikipedia.org/wiki/Mersenne_prime"],
+        },
+    ),
+    (
+        "primorial_gcd",
+        AttackDoc {
+            summary: "Takes the gcd of n and the primorials plus or minus 1",
+            conditions: "A factor of n is k# + 1 or k# - 1",
+            complexity: "One gcd per term",
+            references: &["https://en.wikipedia.org/wiki/Primorial_prime"],
+        },
+    ),
+    (
+        "xy",
+        AttackDoc {
+            summary: "Takes the gcd of n and the powers x^y of small primes, plus or minus 1 when even",
+            conditions: "A factor of n divides x^y, x^y - 1 or x^y + 1 for a small prime x",
+            complexity: "One gcd per power",
+            references: &[],
+        },
+    ),
+];
+
+/// Returns the documentation of the attack with the given name
+pub fn attack_doc(name: &str) -> Option<&'static AttackDoc> {
+    ATTACK_DOCS
+        .iter()
+        .find(|(attack, _)| *attack == name)
+        .map(|(_, doc)| doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ATTACKS;
+
+    use super::*;
+
+    #[test]
+    fn documented_attacks() {
+        for attack in ATTACKS.iter() {
+            assert!(
+                attack_doc(attack.name()).is_some(),
+                "Undocumented attack: {}",
+                attack.name()
+            );
+        }
+        assert_eq!(ATTACK_DOCS.len(), ATTACKS.len());
+        assert!(attack_doc("unknown").is_none());
+    }
+}
@@ -0,0 +1,190 @@
+use indicatif::ProgressBar;
+use rug::{ops::RemRounding, Integer};
+
+use crate::{
+    Attack, AttackContext, AttackKind, AttackSpeed, Error, Parameters, Progress, Solution,
+    Vulnerability, STOP_CHECK_INTERVAL,
+};
+
+const MAX_ITERATIONS: u64 = 10_000_000;
+
+/// Integer e-th root attack (m^e = c + k * n, for any e and small k or small m)
+///
+/// The search goes by blocks of k: all m in [root(c + k0 * n), root(c + k1 * n)] are candidates.
+/// Once this bracket holds fewer integers than the block, which happens early for large e,
+/// candidates m are tested directly in increasing order instead of taking a root for each k.
+/// These candidates are then restricted to the known bounds and suffix of the plaintext, see
+/// `AttackConfig::plaintext`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthRootAttack;
+
+impl Attack for EthRootAttack {
+    fn name(&self) -> &'static str {
+        "eth_root"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Slow
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LowExponent)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let e = params.e.to_u32().ok_or(Error::NotFound)?;
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let c = params.c.as_ref().ok_or(Error::MissingParameters)?;
+        if e < 2 {
+            return Err(Error::NotFound);
+        }
+
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        pb.set_length(max_iterations);
+
+        // Candidates m are congruent to the known suffix, and within the known bounds
+        let constraint = &ctx.config().plaintext;
+        let (residue, modulus) = constraint
+            .residue()
+            .unwrap_or((Integer::ZERO, Integer::from(1)));
+        let bounds = constraint.bounds();
+
+        // Smallest m such that m^e >= c + k * n
+        let mut m_low = {
+            let (root, rem) = c.root_rem_ref(e).into();
+            if rem == 0 {
+                return Ok(Solution::new_m(self.name(), root));
+            }
+            root + 1u32
+        };
+        let mut k = 0u64;
+        let mut iterations = 0;
+        let mut enumerate_m = false;
+        while iterations < max_iterations {
+            ctx.check()?;
+            if bounds.as_ref().is_some_and(|(_, high)| m_low >= *high) {
+                break;
+            }
+
+            let block = STOP_CHECK_INTERVAL.min(max_iterations - iterations);
+            let mut m_high = Integer::from(&m_low + (block - 1));
+            if !enumerate_m {
+                let k_high = k + block;
+                let k_m_high = Integer::from(n * k_high + c).root(e);
+                if k_m_high >= m_high {
+                    // Fewer roots to take than candidates to test
+                    let found = (k + 1..=k_high).find_map(|k| {
+                        let (root, rem) = Integer::from(n * k + c).root_rem(Integer::new(), e);
+                        (rem == 0).then_some(root)
+                    });
+                    if let Some(m) = found {
+                        return Ok(Solution::new_m(self.name(), m));
+                    }
+                    m_high = k_m_high;
+                    k = k_high;
+                } else {
+                    enumerate_m = true;
+                }
+            }
+            if enumerate_m {
+                if let Some((low, _)) = bounds.as_ref().filter(|(low, _)| m_low < *low) {
+                    m_low = low.clone();
+                }
+                m_high = m_low.clone() + Integer::from(&modulus * block) - 1u32;
+                let mut m = m_low.clone() + Integer::from(&residue - &m_low).rem_euc(&modulus);
+                while m <= m_high {
+                    if &Integer::from(m.pow_mod_ref(&params.e, n).unwrap()) == c {
+                        return Ok(Solution::new_m(self.name(), m));
+                    }
+                    m += &modulus;
+                }
+            }
+
+            pb.inc(block);
+            iterations += block;
+            m_low = m_high + 1u32;
+        }
+        Err(Error::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rug::ops::Pow;
+
+    use crate::{bytes_to_integer, Attack, AttackConfig, Parameters, PlaintextConstraint};
+
+    use super::*;
+
+    fn params(m: &Integer, e: u32) -> Parameters {
+        let p = Integer::from_str("10516567718118716602791930727855199494190478912727182354857606776715779027196209315905790350010119220287035355327718513394492773485069389806205568352533083").unwrap();
+        let q = Integer::from_str("12869992910655119669765280088337996038516113996953468433685852659331554429285128301221602991903893809659317109932793836235757301811226355874891889915573169").unwrap();
+        let n = p * q;
+
+        Parameters {
+            e: e.into(),
+            c: Some(m.clone().pow(e) % &n),
+            n: Some(n),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn small_e() {
+        let m = bytes_to_integer(b"Skyf0l!");
+        let params = params(&m, 19);
+        assert!(m.clone().pow(19) > *params.n.as_ref().unwrap());
+
+        let solution = EthRootAttack.run(&params, None).unwrap();
+        assert_eq!(solution.m.unwrap(), m);
+    }
+
+    #[test]
+    fn large_e() {
+        // m^e / n is far beyond the reach of a root per k
+        let m = Integer::from(1_000_003);
+        let params = params(&m, 257);
+
+        let solution = EthRootAttack.run(&params, None).unwrap();
+        assert_eq!(solution.m.unwrap(), m);
+    }
+
+    #[test]
+    fn plaintext_constraint() {
+        // Far beyond the candidates tested with the lowest effort, but for the known structure
+        let m = bytes_to_integer(b"flag}");
+        let params = params(&m, 257);
+        let run = |plaintext| {
+            let ctx = AttackContext::new(&params).with_config(AttackConfig {
+                effort: 1,
+                plaintext,
+                ..Default::default()
+            });
+            EthRootAttack.run_with_context(&params, &ctx, None)
+        };
+
+        assert!(run(PlaintextConstraint::default()).is_err());
+        let solution = run(PlaintextConstraint {
+            prefix: b"fla".to_vec(),
+            suffix: b"}".to_vec(),
+            length: Some(5),
+        })
+        .unwrap();
+        assert_eq!(solution.m.unwrap(), m);
+    }
+}
@@ -1,83 +1,77 @@
 use indicatif::ProgressBar;
 use rug::{rand::RandState, Integer};
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, AttackSpeed, Error, Parameters, Progress, Solution,
+    Vulnerability,
+};
+
+/// Number of steps whose differences are multiplied together before taking a gcd
+const BATCH_SIZE: u64 = 128;
+/// Maximum cycle length power of two
+const MAX_POWER: u64 = 22;
+
+/// Pollard rho with Brent's cycle detection and batched gcd
+///
+/// See <https://maths-people.anu.edu.au/~brent/pd/rpb051i.pdf>
+fn brent(
+    n: &Integer,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Integer>, Error> {
+    pb.set_length(MAX_POWER + 1);
 
-fn brent(n: &Integer, pb: Option<&ProgressBar>) -> Option<Integer> {
-    if let Some(pb) = pb {
-        pb.set_length(23)
-    }
-
-    // Implementation inspired by https://gist.github.com/ssanin82/18582bf4a1849dfb8afd
     let mut rgen = RandState::new();
     let two = Integer::from(2);
-
-    let mut y: Integer = Integer::from(n - 1).random_below(&mut rgen) + 1;
     let c = Integer::from(n - 1).random_below(&mut rgen) + 1;
-    let m: Integer = Integer::from(n - 1).random_below(&mut rgen) + 1;
+    let f = |x: &Integer| (Integer::from(x.pow_mod_ref(&two, n).unwrap()) + &c) % n;
 
+    let mut y: Integer = Integer::from(n - 1).random_below(&mut rgen) + 1;
+    let mut x = y.clone();
+    let mut ys = y.clone();
     let mut g = Integer::from(1);
-    let mut r = 1u64;
     let mut q = Integer::from(1);
+    let mut r = 1u64;
 
-    let mut ys = Integer::from(1);
-
-    let mut x;
-    loop {
+    while g == 1 {
         x = y.clone();
-
-        for _ in 0..=r {
-            y = (Integer::from(y.pow_mod_ref(&two, n).unwrap()) + &c) % n;
+        for _ in 0..r {
+            y = f(&y);
         }
 
-        let mut k = Integer::ZERO;
+        // Accumulate |x - y| for a whole batch before taking a single gcd
+        let mut k = 0;
         while k < r && g == 1 {
-            ys = y.clone();
+            ctx.check()?;
 
-            let mut i = 0;
-            while i <= m && r - i > k {
-                y = (Integer::from(y.pow_mod_ref(&two, n).unwrap()) + &c) % n;
-                q = q * (x.clone() - &y).abs() % n;
-                i += 1;
+            ys = y.clone();
+            for _ in 0..BATCH_SIZE.min(r - k) {
+                y = f(&y);
+                q = q * Integer::from(&x - &y).abs() % n;
             }
             g = Integer::from(q.gcd_ref(n));
-            k += &m;
-            if n > &g && g > 1 {
-                break;
-            }
+            k += BATCH_SIZE;
         }
 
         r <<= 1;
-        if let Some(pb) = pb {
-            pb.inc(1);
-        }
-        // Limit to 22 iterations
-        if r > 1 << 22 {
-            return None;
-        }
-
-        if g != 1 {
-            break;
+        pb.inc(1);
+        if r > 1 << MAX_POWER {
+            return Ok(None);
         }
     }
 
+    // The batch overshot, replay it one step at a time
     if &g == n {
-        let start_ys = Integer::from(&ys);
         loop {
-            ys = (Integer::from(ys.pow_mod_ref(&two, n).unwrap()) + &c) % n;
-            g = Integer::from((x.clone() - &ys).abs().gcd_ref(n));
-            if n > &g && g > 1 {
+            ys = f(&ys);
+            g = Integer::from(&x - &ys).abs().gcd(n);
+            if g > 1 {
                 break;
             }
-
-            // If looped back to start, n might be prime
-            if start_ys == ys {
-                return None;
-            }
         }
     }
 
-    Some(g)
+    Ok(if &g == n { None } else { Some(g) })
 }
 
 /// Pollard rho with brent's optimization attack
@@ -93,11 +87,24 @@ impl Attack for BrentAttack {
         AttackSpeed::Slow
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SmallFactor)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        if let Some(p) = brent(n, pb) {
+        if let Some(p) = brent(n, ctx, pb)? {
             let q = Integer::from(n / &p);
             Ok(Solution::new_pk(
                 self.name(),
@@ -127,7 +134,23 @@ mod tests {
         let solution = BrentAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn big_primes() {
+        let p = Integer::from(1031374876333u64);
+        let q = Integer::from(1097247397867u64);
+
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        let solution = BrentAttack.run(&params, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
@@ -0,0 +1,151 @@
+use std::iter;
+
+use indicatif::ProgressBar;
+
+use crate::{
+    key::PrivateKey, Attack, AttackContext, AttackSpeed, Error, Parameters, Progress, Solution,
+    Vulnerability,
+};
+
+/// Cached factors attack (n, or one of its factors, was factored by FactorDB in a previous run)
+///
+/// Only runs with the cache of `AttackConfig::cache`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheAttack;
+
+impl Attack for CacheAttack {
+    fn name(&self) -> &'static str {
+        "cache"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Fast
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::KnownFactorization)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let e = &params.e;
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let cache = ctx.config().cache.as_ref().ok_or(Error::NotFound)?;
+
+        if let Some(factors) = cache.get_factordb(n) {
+            return Ok(Solution::new_pk(
+                self.name(),
+                PrivateKey::from_factors(factors, e)?,
+            ));
+        }
+
+        // Look for primes shared with moduli factored by FactorDB
+        let primes = cache.primes();
+        let tick_size = (primes.len() / 100).max(1);
+        pb.set_length(primes.len() as u64);
+        let mut factors = vec![];
+        let mut cofactor = n.clone();
+        for (i, p) in primes.into_iter().enumerate() {
+            if (i + 1).is_multiple_of(tick_size) {
+                pb.inc(tick_size as u64);
+            }
+            if 1 < p && p < *n {
+                let count = cofactor.remove_factor_mut(&p);
+                factors.extend(iter::repeat_n(p, count as usize));
+            }
+        }
+        if factors.is_empty() {
+            return Err(Error::NotFound);
+        }
+        if cofactor != 1 {
+            factors.push(cofactor);
+        }
+
+        // A composite cofactor is a partial factorization, left to the other attacks
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_factors(factors, e)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::Integer;
+
+    use crate::{AttackConfig, Cache, Factors, Parameters};
+
+    use super::*;
+
+    #[test]
+    fn attack() {
+        let dir =
+            std::env::temp_dir().join(format!("rsacracker-cache-attack-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        let cache = Cache::new(&dir);
+        let ctx = |params: &Parameters| {
+            AttackContext::new(params).with_config(AttackConfig {
+                cache: Some(cache.clone()),
+                ..Default::default()
+            })
+        };
+
+        let p = Integer::from(1779681653);
+        let q = Integer::from(1903643191);
+        let r = Integer::from(2071723);
+        cache.store_factordb(&(p.clone() * &q), &[p.clone(), q.clone()]);
+
+        // Same modulus
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        let solution = CacheAttack
+            .run_with_context(&params, &ctx(&params), None)
+            .unwrap();
+        let pk = solution.pk.unwrap();
+        assert_eq!(pk.p(), Some(p.clone()));
+        assert_eq!(pk.q(), Some(q.clone()));
+
+        // Modulus sharing a factor
+        let params = Parameters {
+            n: Some(r.clone() * &q),
+            ..Default::default()
+        };
+        let solution = CacheAttack
+            .run_with_context(&params, &ctx(&params), None)
+            .unwrap();
+        let pk = solution.pk.unwrap();
+        assert_eq!(pk.p(), Some(r.clone()));
+        assert_eq!(pk.q(), Some(q.clone()));
+
+        // Modulus sharing a factor, with a composite cofactor
+        let s = Integer::from(1_000_003);
+        let params = Parameters {
+            n: Some(Integer::from(&r * &s) * &q),
+            ..Default::default()
+        };
+        match CacheAttack.run_with_context(&params, &ctx(&params), None) {
+            Err(Error::PartialFactorization(factors)) => {
+                assert_eq!(factors, Factors::from([q, Integer::from(&r * &s)]))
+            }
+            res => panic!("Unexpected result: {res:?}"),
+        }
+
+        // Without a cache
+        assert!(matches!(
+            CacheAttack.run(&params, None),
+            Err(Error::NotFound)
+        ));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -3,7 +3,7 @@ use lazy_static::lazy_static;
 use rug::{ops::Pow, Integer};
 use std::str::FromStr;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use crate::{key::PrivateKey, Attack, Error, Parameters, Progress, Solution, Vulnerability};
 
 /// Product of all primes between 2 and x
 fn p(x: usize) -> Integer {
@@ -374,13 +374,15 @@ impl Attack for CunninghamChainAttack {
         "cunningham_chain"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        if let Some(pb) = pb {
-            pb.set_length((CC_FIRST_KIND.len() + CC_SECOND_KIND.len()) as u64);
-        }
+        pb.set_length((CC_FIRST_KIND.len() + CC_SECOND_KIND.len()) as u64);
         for ((m, length), kind) in CC_FIRST_KIND
             .iter()
             .zip([ChainKind::First].iter().cycle())
@@ -402,9 +404,7 @@ impl Attack for CunninghamChainAttack {
                     ));
                 }
             }
-            if let Some(pb) = pb {
-                pb.inc(1);
-            }
+            pb.inc(1);
         }
         Err(Error::NotFound)
     }
@@ -444,7 +444,7 @@ mod tests {
         let solution = CunninghamChainAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
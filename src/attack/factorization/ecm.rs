@@ -1,9 +1,13 @@
 use std::collections::HashMap;
 
 use indicatif::ProgressBar;
-use rug::{integer::IsPrime, Integer};
+use rayon::prelude::*;
+use rug::{integer::IsPrime, rand::RandState, Integer};
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, ntheory::is_prime, Attack, AttackContext, AttackSpeed, AttackState, Error,
+    Parameters, Progress, ResumableAttack, Solution, Vulnerability,
+};
 
 const MAX_DEEP: usize = 4;
 
@@ -22,40 +26,246 @@ const OPTIMAL_B1: [usize; 12] = [
     2900000000, // 70 digits
 ];
 
-fn ecm(
+/// Number of curves tried for each bound, scaled by the effort
+const CURVES: usize = 100;
+/// Number of curves per batch, batches being run concurrently
+const CURVES_PER_BATCH: usize = 4;
+/// Stage 2 bound, scaled by the effort
+const B2: usize = 100_000;
+/// Number of stage 1 curves run on the GPU for each bound, before the CPU curves, scaled by the
+/// effort
+#[cfg(feature = "gpu")]
+const GPU_CURVES: u64 = 1024;
+/// Number of small primes trial divided before running ECM
+const TRIAL_DIVISION_PRIMES: usize = 100_000;
+
+/// Find one non-trivial factor of n, running batches of curves on the thread pool
+///
+/// Batches are run from `batch`, one round of a batch per thread at a time. If the attack is
+/// stopped, `batch` is left at the first batch of the round which was not run. With the `gpu`
+/// feature, curves are first run on a CUDA GPU when one is available and no batch was run yet.
+fn ecm_one_factor(
     n: &Integer,
-    pb: Option<&ProgressBar>,
+    b1: usize,
     seed: usize,
-    deep: usize,
-) -> Result<HashMap<Integer, usize>, Error> {
-    if deep == MAX_DEEP {
-        return Ok(HashMap::from([(n.clone(), 1)]));
+    batch: &mut usize,
+    ctx: &AttackContext,
+    pool: &rayon::ThreadPool,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Integer>, Error> {
+    let curves = ctx.config().scaled(CURVES as u64) as usize;
+    let b2 = ctx.config().scaled(B2 as u64) as usize;
+    let batches = curves.div_ceil(CURVES_PER_BATCH);
+    let round = pool.current_num_threads().max(1);
+    #[cfg(feature = "gpu")]
+    if *batch == 0 {
+        let first = seed as u64 + 6;
+        let sigmas = first..first + ctx.config().scaled(GPU_CURVES);
+        if let Some(factor) = crate::gpu::ecm_stage1(n, b1 as u64, sigmas, ctx)? {
+            return Ok(Some(factor));
+        }
     }
+    while *batch < batches {
+        ctx.check()?;
+        let start = *batch;
+        let end = (start + round).min(batches);
+        let factor = pool.install(|| {
+            (start..end).into_par_iter().find_map_any(|batch| {
+                let mut rgen = RandState::new();
+                rgen.seed(&Integer::from(seed + batch));
+                // Note: `max_curve` is inclusive
+                let factor =
+                    ecm::ecm_one_factor(n, b1, b2, CURVES_PER_BATCH - 1, &mut rgen, None).ok();
 
-    if let Some(pb) = pb {
-        pb.set_prefix(format!("ecm ({}/{})", deep + 1, MAX_DEEP));
+                pb.inc(CURVES_PER_BATCH as u64);
+                factor.filter(|f| *f != 1 && f != n)
+            })
+        });
+        if factor.is_some() {
+            return Ok(factor);
+        }
+        *batch = end;
     }
+    Ok(None)
+}
 
-    let mut factors = HashMap::new();
-    for (factor, count) in ecm::ecm_with_params(n, OPTIMAL_B1[deep], 100_000, 100, seed, pb)
-        .or(Err(Error::NotFound))?
-    {
-        if factor.is_probably_prime(100) != IsPrime::No {
-            factors.insert(factor, count);
-        } else {
-            // Try with larger bounds
-            let sub_factors = ecm(&factor, pb, seed * 17, deep + 1)?;
+/// Number left to factor by ECM
+#[derive(Debug, Clone)]
+struct Pending {
+    n: Integer,
+    /// Multiplicity of n in the number being factored
+    count: usize,
+    /// Index of the bounds used, in `OPTIMAL_B1`
+    deep: usize,
+    seed: usize,
+    /// Next batch of curves to run
+    batch: usize,
+}
+
+/// Progress of the factorization: the factors found so far and the numbers left to factor
+#[derive(Debug, Default)]
+struct EcmProgress {
+    factors: HashMap<Integer, usize>,
+    pending: Vec<Pending>,
+}
 
-            for (sub_factor, sub_count) in sub_factors {
-                *factors.entry(sub_factor).or_insert(0) += sub_count * count;
+/// Number of fields of a pending number in an exported state
+const PENDING_FIELDS: usize = 5;
+
+impl EcmProgress {
+    /// Start the factorization of n, trial dividing it by small primes
+    fn new(n: &Integer, ctx: &AttackContext) -> Self {
+        let mut factors = HashMap::new();
+        let mut n = n.clone();
+        for &prime in ctx
+            .precomputed()
+            .small_primes()
+            .iter()
+            .take(TRIAL_DIVISION_PRIMES)
+        {
+            let prime = Integer::from(prime);
+            while n.is_divisible(&prime) {
+                n /= &prime;
+                *factors.entry(prime.clone()).or_insert(0) += 1;
             }
         }
+
+        let pending = vec![Pending {
+            n,
+            count: 1,
+            deep: 0,
+            seed: 1234,
+            batch: 0,
+        }];
+        Self { factors, pending }
     }
 
-    Ok(factors)
+    /// Progress saved in a state, factors and pending numbers being flattened in lists
+    fn import(state: &AttackState) -> Option<Self> {
+        let factors = state.get("factors");
+        let pending = state.get("pending");
+        if !factors.len().is_multiple_of(2) || !pending.len().is_multiple_of(PENDING_FIELDS) {
+            return None;
+        }
+
+        Some(Self {
+            factors: factors
+                .chunks(2)
+                .map(|pair| Some((pair[0].clone(), pair[1].to_usize()?)))
+                .collect::<Option<_>>()?,
+            pending: pending
+                .chunks(PENDING_FIELDS)
+                .map(|fields| {
+                    Some(Pending {
+                        n: fields[0].clone(),
+                        count: fields[1].to_usize()?,
+                        deep: fields[2].to_usize().filter(|&deep| deep <= MAX_DEEP)?,
+                        seed: fields[3].to_usize()?,
+                        batch: fields[4].to_usize()?,
+                    })
+                })
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// Save the progress in a state
+    fn export(&self, state: &mut AttackState) {
+        state.set(
+            "factors",
+            self.factors
+                .iter()
+                .flat_map(|(p, &count)| [p.clone(), count.into()]),
+        );
+        state.set(
+            "pending",
+            self.pending.iter().flat_map(|pending| {
+                [
+                    pending.n.clone(),
+                    pending.count.into(),
+                    pending.deep.into(),
+                    pending.seed.into(),
+                    pending.batch.into(),
+                ]
+            }),
+        );
+    }
+}
+
+/// Factor the pending numbers, with larger bounds for those whose curves all failed
+///
+/// If the attack is stopped, the interrupted number is left pending.
+fn ecm(
+    progress: &mut EcmProgress,
+    ctx: &AttackContext,
+    pool: &rayon::ThreadPool,
+    pb: Option<&ProgressBar>,
+) -> Result<(), Error> {
+    while let Some(mut pending) = progress.pending.pop() {
+        if pending.n == 1 {
+            continue;
+        }
+        if pending.deep == MAX_DEEP || is_prime(&pending.n) != IsPrime::No {
+            *progress.factors.entry(pending.n).or_insert(0) += pending.count;
+            continue;
+        }
+
+        if let Some(pb) = pb {
+            pb.set_prefix(format!("ecm ({}/{})", pending.deep + 1, MAX_DEEP));
+        }
+        pb.set_length(ctx.config().scaled(CURVES as u64));
+        let b1 = OPTIMAL_B1[pending.deep];
+        let factor = match ecm_one_factor(
+            &pending.n,
+            b1,
+            pending.seed,
+            &mut pending.batch,
+            ctx,
+            pool,
+            pb,
+        ) {
+            Ok(factor) => factor,
+            Err(err) => {
+                progress.pending.push(pending);
+                return Err(err);
+            }
+        };
+
+        let Some(factor) = factor else {
+            // Try with larger bounds
+            progress.pending.push(Pending {
+                deep: pending.deep + 1,
+                seed: pending.seed * 17,
+                batch: 0,
+                ..pending
+            });
+            continue;
+        };
+
+        let mut count = 0;
+        while pending.n.is_divisible(&factor) {
+            pending.n /= &factor;
+            count += 1;
+        }
+        // The factor is split further with larger bounds if it is not prime
+        progress.pending.push(Pending {
+            n: factor,
+            count: pending.count * count,
+            deep: pending.deep + 1,
+            seed: pending.seed * 17,
+            batch: 0,
+        });
+        progress.pending.push(Pending {
+            batch: 0,
+            ..pending
+        });
+    }
+    Ok(())
 }
 
 /// Lenstra's ECM factorization attack
+///
+/// Independent curves are run concurrently on the available threads.
+/// The factorization is resumed at the curve it stopped from an exported state.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EcmAttack;
 
@@ -68,17 +278,60 @@ impl Attack for EcmAttack {
         AttackSpeed::Slow
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SmallFactor)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        self.run_resumable(params, ctx, &mut None, pb)
+    }
+
+    fn as_resumable(&self) -> Option<&dyn ResumableAttack> {
+        Some(self)
+    }
+}
+
+impl ResumableAttack for EcmAttack {
+    fn run_resumable(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        state: &mut Option<AttackState>,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        let factors = ecm(n, pb, 1234, 0)
-            .or(Err(Error::NotFound))?
+        let pool = ctx.thread_pool()?;
+        let mut progress = state
+            .take()
+            .filter(|state| state.resumes(self.name(), n))
+            .and_then(|state| EcmProgress::import(&state))
+            .unwrap_or_else(|| EcmProgress::new(n, ctx));
+        if let Err(err) = ecm(&mut progress, ctx, pool, pb) {
+            if err == Error::Stopped {
+                let mut stopped = AttackState::new(self.name(), n);
+                progress.export(&mut stopped);
+                *state = Some(stopped);
+            }
+            return Err(err);
+        }
+
+        let factors = progress
+            .factors
             .iter()
-            .flat_map(|(p, e)| std::iter::repeat(p).take(*e))
+            .flat_map(|(p, e)| std::iter::repeat_n(p, *e))
             .cloned()
             .collect::<Vec<_>>();
-
         if factors.len() < 2 {
             return Err(Error::NotFound);
         }
@@ -92,7 +345,7 @@ impl Attack for EcmAttack {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Attack, Parameters};
+    use crate::{Attack, AttackConfig, Factors, Parameters};
 
     use super::*;
 
@@ -108,7 +361,59 @@ mod tests {
         let solution = EcmAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn many_factors() {
+        let factors = Factors::from([
+            Integer::from(1779681653),
+            Integer::from(1903643191),
+            Integer::from(1031374876333u64),
+            Integer::from(1097247397867u64),
+        ]);
+
+        let params = Parameters {
+            n: Some(factors.product()),
+            ..Default::default()
+        };
+        let ctx = AttackContext::new(&params).with_config(AttackConfig {
+            threads: 4,
+            ..Default::default()
+        });
+        let solution = EcmAttack.run_with_context(&params, &ctx, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.factors, factors);
+    }
+
+    #[test]
+    fn resume() {
+        let factors = Factors::from([
+            Integer::from(3),
+            Integer::from(1779681653),
+            Integer::from(1903643191),
+        ]);
+        let params = Parameters {
+            n: Some(factors.product()),
+            ..Default::default()
+        };
+
+        // Stopped after the trial division, before the first curve
+        let ctx = AttackContext::new(&params);
+        ctx.stop();
+        let mut state = None;
+        let res = EcmAttack.run_resumable(&params, &ctx, &mut state, None);
+        assert_eq!(res.unwrap_err(), Error::Stopped);
+        let state = AttackState::from_json(&state.unwrap().to_json()).unwrap();
+        assert_eq!(state.get("factors"), [3, 1]);
+        assert_eq!(state.get("pending")[1..], [1, 0, 1234, 0]);
+
+        let ctx = AttackContext::new(&params);
+        let solution = EcmAttack
+            .run_resumable(&params, &ctx, &mut Some(state), None)
+            .unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
     }
 }
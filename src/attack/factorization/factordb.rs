@@ -1,12 +1,86 @@
-use std::str::FromStr;
+use std::{
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
-use factordb::FactorDbBlockingClient;
+use factordb::{FactorDbBlockingClient, FactorDbError};
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, AttackSpeed, Error, Parameters, Solution, Vulnerability,
+};
+
+/// Maximum time to connect to FactorDB, kept short to give up quickly when offline
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Maximum time of a whole request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// Number of retries of a failed request
+const MAX_RETRIES: u32 = 3;
+/// Delay before the first retry, doubled on each retry
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+/// Minimum delay between two requests
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time of the last request, shared by all FactorDB queries of the process
+static LAST_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Wait until `MIN_REQUEST_INTERVAL` elapsed since the last request
+fn wait_rate_limit() {
+    let mut last_request = LAST_REQUEST.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(last_request) = *last_request {
+        std::thread::sleep(MIN_REQUEST_INTERVAL.saturating_sub(last_request.elapsed()));
+    }
+    *last_request = Some(Instant::now());
+}
+
+/// Returns true if the request may succeed when retried
+///
+/// Connection failures and timeouts are not retried, since FactorDB is most likely unreachable.
+fn is_transient(err: &FactorDbError) -> bool {
+    match err {
+        FactorDbError::RequestError(err) => !err.is_connect() && !err.is_timeout(),
+        // Error status, e.g. rate limited or server error
+        FactorDbError::InvalidNumber => true,
+    }
+}
+
+/// Query the factors of n on FactorDB
+fn query(n: &Integer) -> Result<Vec<Integer>, Error> {
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|_| Error::NotFound)?;
+    let client = FactorDbBlockingClient::with_client(client);
+
+    let mut backoff = RETRY_BACKOFF;
+    let mut retries = 0;
+    let number = loop {
+        wait_rate_limit();
+        match client.get(n) {
+            Ok(number) => break number,
+            Err(err) if retries < MAX_RETRIES && is_transient(&err) => {
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                retries += 1;
+            }
+            Err(_) => return Err(Error::NotFound),
+        }
+    };
+
+    number
+        .into_factors_flattened()
+        .iter()
+        .map(|f| Integer::from_str(&f.to_string()))
+        .collect::<Result<Vec<Integer>, _>>()
+        .map_err(|_| Error::NotFound)
+}
 
 /// Factordb attack
+///
+/// Responses are cached in `AttackConfig::cache`, if any, and requests are rate limited and retried on transient failures.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FactorDbAttack;
 
@@ -19,7 +93,20 @@ impl Attack for FactorDbAttack {
         AttackSpeed::Fast
     }
 
-    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::KnownFactorization)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        _pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         // If `NO_FACTORDB` is set, ignore the factordb attack
         // This is useful for testing
         if std::env::var("NO_FACTORDB").is_ok() {
@@ -29,13 +116,16 @@ impl Attack for FactorDbAttack {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        let factors = tokio::task::block_in_place(|| FactorDbBlockingClient::new().get(n))
-            .map_err(|_| Error::NotFound)?
-            .into_factors_flattened()
-            .iter()
-            .map(|f| Integer::from_str(&f.to_string()))
-            .collect::<Result<Vec<Integer>, _>>()
-            .map_err(|_| Error::NotFound)?;
+        let cache = ctx.config().cache.as_ref();
+        let factors = if let Some(factors) = cache.and_then(|c| c.get_factordb(n)) {
+            factors
+        } else {
+            let factors = tokio::task::block_in_place(|| query(n))?;
+            if let Some(cache) = cache {
+                cache.store_factordb(n, &factors);
+            }
+            factors
+        };
 
         if factors.len() < 2 {
             return Err(Error::NotFound);
@@ -47,3 +137,16 @@ impl Attack for FactorDbAttack {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit() {
+        wait_rate_limit();
+        let start = Instant::now();
+        wait_rate_limit();
+        assert!(start.elapsed() >= MIN_REQUEST_INTERVAL - Duration::from_millis(10));
+    }
+}
@@ -1,13 +1,63 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, Error, Parameters, Progress, Solution, Vulnerability,
+    STOP_CHECK_INTERVAL,
+};
 
 const MAX_ITERATIONS: u64 = 10_000_000;
 const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
+/// Small moduli used to sieve out the values of a for which a^2 - n can't be a square
+const SIEVE_MODULI: [u64; 7] = [64, 63, 65, 11, 17, 19, 23];
+
+/// Quadratic residue sieve modulo a small integer
+struct Sieve {
+    modulus: u64,
+    /// a mod modulus
+    a: u64,
+    /// `allowed[r]` is true if r^2 - n is a square modulo `modulus`
+    allowed: Vec<bool>,
+}
+
+impl Sieve {
+    fn new(modulus: u64, n: &Integer, a: &Integer) -> Self {
+        let mut squares = vec![false; modulus as usize];
+        for x in 0..modulus {
+            squares[(x * x % modulus) as usize] = true;
+        }
+
+        let n = n.mod_u(modulus as u32) as u64;
+        let allowed = (0..modulus)
+            .map(|r| squares[((r * r + modulus - n) % modulus) as usize])
+            .collect();
+
+        Self {
+            modulus,
+            a: a.mod_u(modulus as u32) as u64,
+            allowed,
+        }
+    }
+
+    /// Returns true if a^2 - n may be a square
+    fn is_allowed(&self) -> bool {
+        self.allowed[self.a as usize]
+    }
+
+    /// Move to a + 1
+    fn next(&mut self) {
+        self.a += 1;
+        if self.a == self.modulus {
+            self.a = 0;
+        }
+    }
+}
+
 /// Fermat factorization attack
 ///
+/// Candidates a are sieved modulo small integers, only computing a^2 - n for the ones that may be a square.
+///
 /// See <https://github.com/jvdsn/crypto-attacks/blob/master/attacks/factorization/fermat.py>
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FermatAttack;
@@ -17,7 +67,20 @@ impl Attack for FermatAttack {
         "fermat"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::ClosePrimes)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
@@ -25,37 +88,39 @@ impl Attack for FermatAttack {
             return Err(Error::NotFound);
         }
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS);
-        }
-
-        let (a, rem) = n.sqrt_rem_ref().into();
-        let mut b2 = -rem;
-        let mut c = (a << 1) + 1;
-        let mut tries = 0;
-        while !b2.is_perfect_square() {
-            b2 += &c;
-            c += 2;
-
-            tries += 1;
-            if tries % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
+        pb.set_length(MAX_ITERATIONS);
+
+        let a0 = ctx.precomputed().n_sqrt().ok_or(Error::MissingParameters)?;
+        let mut sieves = SIEVE_MODULI
+            .iter()
+            .map(|&m| Sieve::new(m, n, a0))
+            .collect::<Vec<_>>();
+
+        for i in 0..=MAX_ITERATIONS {
+            if sieves.iter().all(Sieve::is_allowed) {
+                let a = Integer::from(a0 + i);
+                let b2 = a.clone().square() - n;
+                if b2.is_perfect_square() {
+                    let b = b2.sqrt();
+                    let p = Integer::from(&a - &b);
+                    let q = a + b;
+                    return Ok(Solution::new_pk(
+                        self.name(),
+                        PrivateKey::from_p_q(p, q, e)?,
+                    ));
                 }
             }
-            if tries > MAX_ITERATIONS {
-                return Err(Error::NotFound);
+            sieves.iter_mut().for_each(Sieve::next);
+
+            if i.is_multiple_of(STOP_CHECK_INTERVAL) {
+                ctx.check()?;
+            }
+            if i.is_multiple_of(TICK_SIZE) {
+                pb.inc(TICK_SIZE);
             }
         }
 
-        let a = (c - 1) >> 1;
-        let b = Integer::from(b2.sqrt_ref());
-        let p = Integer::from(&a - &b);
-        let q = a + b;
-        Ok(Solution::new_pk(
-            self.name(),
-            PrivateKey::from_p_q(p, q, e)?,
-        ))
+        Err(Error::NotFound)
     }
 }
 
@@ -79,8 +144,8 @@ mod tests {
         let solution = FermatAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 
     #[test]
@@ -95,7 +160,24 @@ mod tests {
         let solution = FermatAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn distant_primes() {
+        // a - sqrt(n) = 2097152
+        let p = Integer::from_str("18446744073709551557").unwrap();
+        let q = Integer::from_str("18446761665895596013").unwrap();
+
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        let solution = FermatAttack.run(&params, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
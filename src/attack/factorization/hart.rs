@@ -1,14 +1,15 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use crate::{key::PrivateKey, Attack, AttackContext, Error, Parameters, Solution, Vulnerability};
 
 const MAX_ITERATIONS: u64 = 2_000_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 /// Hart factorization method attack
 ///
 /// See <https://programmingpraxis.com/2014/01/28/harts-one-line-factoring-algorithm/>
+///
+/// The search range is split in blocks spread across the available threads.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HartAttack;
 
@@ -17,44 +18,52 @@ impl Attack for HartAttack {
         "hart"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::ClosePrimes)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
         let two = Integer::from(2);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS);
-        }
-
-        for i in 1..MAX_ITERATIONS {
-            let s = Integer::from(n * i).sqrt() + 1u64;
-            let m = s.clone().pow_mod(&two, n).unwrap();
-
-            if m.is_perfect_square() {
-                let t = m.sqrt();
-                let p = (s - &t).gcd(n);
-                let q = Integer::from(n / &p);
-
-                return Ok(Solution::new_pk(
-                    self.name(),
-                    PrivateKey::from_p_q(p, q, e)?,
-                ));
-            }
-
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
+        let (p, q) = ctx
+            .par_search(max_iterations, pb, |range| {
+                for i in range {
+                    let s = Integer::from(n * i).sqrt() + 1u64;
+                    let m = s.clone().pow_mod(&two, n).unwrap();
+
+                    if m.is_perfect_square() {
+                        let t = m.sqrt();
+                        let p = (s - &t).gcd(n);
+                        let q = Integer::from(n / &p);
+                        return Some((p, q));
+                    }
                 }
-            }
-        }
-
-        Err(Error::NotFound)
+                None
+            })?
+            .ok_or(Error::NotFound)?;
+
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p, q, e)?,
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Attack, Parameters};
+    use crate::{Attack, AttackConfig, Parameters};
 
     use super::*;
 
@@ -70,7 +79,27 @@ mod tests {
         let solution = HartAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn many_threads() {
+        let p = Integer::from(1779681653);
+        let q = Integer::from(1903643191);
+
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        let ctx = AttackContext::new(&params).with_config(AttackConfig {
+            threads: 8,
+            ..Default::default()
+        });
+        let solution = HartAttack.run_with_context(&params, &ctx, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
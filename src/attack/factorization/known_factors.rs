@@ -3,7 +3,7 @@ use std::str::FromStr;
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution, Vulnerability};
 
 /// Known factors attack
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,6 +18,10 @@ impl Attack for KnownFactorsAttack {
         AttackSpeed::Fast
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::KnownFactorization)
+    }
+
     fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
@@ -106,7 +110,7 @@ mod tests {
         let solution = KnownFactorsAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
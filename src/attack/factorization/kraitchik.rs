@@ -1,14 +1,15 @@
 use indicatif::ProgressBar;
 use rug::{ops::Pow, Integer};
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use crate::{key::PrivateKey, Attack, AttackContext, Error, Parameters, Solution, Vulnerability};
 
 const MAX_ITERATIONS: u64 = 10_000_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 /// Kraitchi factorization attack
 ///
 /// See <https://github.com/daedalus/integer_factorization_algorithms/blob/main/kraitchik.py>
+///
+/// The search range is split in blocks spread across the available threads.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KraitchikAttack;
 
@@ -17,43 +18,54 @@ impl Attack for KraitchikAttack {
         "kraitchik"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::ClosePrimes)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS);
-        }
-
-        let mut x = n.clone().sqrt();
-        for i in 1..MAX_ITERATIONS {
-            let mut k = 1;
-            let mut s = x.clone().pow(2) - Integer::from(k * n);
-            while s >= 0 {
-                if s.is_perfect_square() {
-                    let y = s.sqrt();
-                    let z = x.clone() + y.clone();
-                    let w = x.clone() - y.clone();
-                    if z.clone() % n != 0 && w.clone() % n != 0 {
-                        return Ok(Solution::new_pk(
-                            self.name(),
-                            PrivateKey::from_p_q(z.gcd(n), w.gcd(n), e)?,
-                        ));
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        let sqrt = ctx.precomputed().n_sqrt().ok_or(Error::MissingParameters)?;
+        let (p, q) = ctx
+            .par_search(max_iterations, pb, |range| {
+                let mut x = Integer::from(sqrt + (range.start - 1));
+                #[allow(clippy::explicit_counter_loop)]
+                for _ in range {
+                    let mut k = 1;
+                    let mut s = x.clone().pow(2) - Integer::from(k * n);
+                    while s >= 0 {
+                        if s.is_perfect_square() {
+                            let y = s.sqrt();
+                            let z = x.clone() + y.clone();
+                            let w = x.clone() - y.clone();
+                            if z.clone() % n != 0 && w.clone() % n != 0 {
+                                return Some((z.gcd(n), w.gcd(n)));
+                            }
+                        }
+                        k += 1;
+                        s = x.clone().pow(2) - k * n;
                     }
+                    x += 1;
                 }
-                k += 1;
-                s = x.clone().pow(2) - k * n;
-            }
-            x += 1;
-
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
-            }
-        }
+                None
+            })?
+            .ok_or(Error::NotFound)?;
 
-        Err(Error::NotFound)
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p, q, e)?,
+        ))
     }
 }
 
@@ -75,7 +87,7 @@ mod tests {
         let solution = KraitchikAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
@@ -1,74 +1,179 @@
-use std::collections::hash_map::Entry;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
+};
 
 use indicatif::ProgressBar;
 use rug::Integer;
 
 use crate::{
-    key::PrivateKey, utils::solve_quadratic, Attack, AttackSpeed, Error, Parameters, Solution,
+    key::PrivateKey, utils::solve_quadratic, Attack, AttackContext, AttackSpeed, AttackState,
+    Error, Parameters, Progress, ResumableAttack, Solution, Vulnerability, STOP_CHECK_INTERVAL,
 };
 
-const LONDAHL_B: u64 = 10_000_000;
+/// Approximate memory used by a look-up table entry, including the hash map overhead
+const ENTRY_SIZE: u64 = 2 * std::mem::size_of::<(u64, LondahlState)>() as u64;
 
 enum LondahlState {
     Store(u64),
     Lookup(u64),
 }
 
-// Optimization of <https://github.com/RsaCtfTool/RsaCtfTool/blob/master/attacks/single_key/londahl.py>
-fn close_factor(n: &Integer, b: u64, pb: Option<&ProgressBar>) -> Option<(Integer, Integer)> {
-    let tick_size: u64 = b / 100;
-    if let Some(pb) = pb {
-        pb.set_length(b);
+/// Returns the memory of the look-up table for the bound B (up to 2 * B entries)
+fn table_memory(b: u64) -> u64 {
+    b.saturating_mul(2 * ENTRY_SIZE)
+}
+
+/// Returns the largest B whose look-up table fits in the memory budget
+fn bound_for_memory(b: u64, max_memory: u64) -> u64 {
+    b.min(max_memory / ENTRY_SIZE / 2)
+}
+
+/// Look-up table key: the entries are only stored as a 64-bit hash,
+/// false positives being filtered out when solving for p and q
+fn fingerprint(z: &Integer) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    z.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Progress of the search: the next step, the current powers of 2 and the look-up table
+struct LondahlSearch {
+    b: u64,
+    j: u64,
+    z: Integer,
+    mu: Integer,
+    generate_entries: bool,
+    look_up: HashMap<u64, LondahlState>,
+}
+
+impl LondahlSearch {
+    /// Start a search with the bound b, returns `None` if 2^phi_approx is not invertible
+    fn new(n: &Integer, b: u64, phi_approx: &Integer) -> Option<Self> {
+        // Create a look-up table, with a first entry
+        let mut look_up = HashMap::new();
+        look_up.reserve(b as usize * 2);
+        let z = Integer::from(1);
+        look_up.insert(fingerprint(&z), LondahlState::Store(0));
+
+        // Prepare to check the table
+        let mu = Integer::from(2)
+            .pow_mod(phi_approx, n)
+            .unwrap()
+            .invert(n)
+            .ok()?;
+        Some(Self {
+            b,
+            j: 1,
+            z: (z * 2) % n,
+            mu,
+            generate_entries: true,
+            look_up,
+        })
+    }
+
+    /// Search saved in a state, if it was run with the same bound
+    ///
+    /// The look-up table is saved as pairs of fingerprint and step, so it can only be resumed by a
+    /// build hashing integers the same way.
+    fn import(state: &AttackState, b: u64) -> Option<Self> {
+        if state.get_one("b")?.to_u64()? != b {
+            return None;
+        }
+        let (stores, lookups) = (state.get("stores"), state.get("lookups"));
+        if stores.len() % 2 != 0 || lookups.len() % 2 != 0 {
+            return None;
+        }
+        let entries = |pairs: &[Integer], entry: fn(u64) -> LondahlState| {
+            pairs
+                .chunks(2)
+                .map(|pair| Some((pair[0].to_u64()?, entry(pair[1].to_u64()?))))
+                .collect::<Option<Vec<_>>>()
+        };
+
+        let mut look_up = HashMap::new();
+        look_up.reserve(b as usize * 2);
+        look_up.extend(entries(stores, LondahlState::Store)?);
+        look_up.extend(entries(lookups, LondahlState::Lookup)?);
+        Some(Self {
+            b,
+            j: state.get_one("j")?.to_u64()?,
+            z: state.get_one("z")?.clone(),
+            mu: state.get_one("mu")?.clone(),
+            generate_entries: *state.get_one("generate_entries")? != 0,
+            look_up,
+        })
     }
 
-    // Approximate phi
-    let phi_approx = n - 2 * n.clone().sqrt() + 1;
-
-    // Create a look-up table
-    let mut look_up = std::collections::HashMap::new();
-    look_up.reserve(b as usize * 2);
-
-    // Generate first entry
-    let mut generate_entries = true;
-    let mut z = Integer::from(1);
-    look_up.insert(Integer::from(1), LondahlState::Store(0));
-    z = (z * 2) % n;
-
-    // Prepare to check the table
-    let mut mu = Integer::from(2)
-        .pow_mod(&phi_approx, n)
-        .unwrap()
-        .invert(n)
-        .ok()?;
+    /// Save the search in a state
+    fn export(&self, state: &mut AttackState) {
+        let entries = |lookup: bool| {
+            self.look_up
+                .iter()
+                .filter_map(move |(&key, entry)| match entry {
+                    LondahlState::Store(i) if !lookup => Some([key.into(), (*i).into()]),
+                    LondahlState::Lookup(j) if lookup => Some([key.into(), (*j).into()]),
+                    _ => None,
+                })
+                .flatten()
+        };
+        state.set("b", [self.b.into()]);
+        state.set("j", [self.j.into()]);
+        state.set("z", [self.z.clone()]);
+        state.set("mu", [self.mu.clone()]);
+        state.set(
+            "generate_entries",
+            [u32::from(self.generate_entries).into()],
+        );
+        state.set("stores", entries(false));
+        state.set("lookups", entries(true));
+    }
+}
+
+// Optimization of <https://github.com/RsaCtfTool/RsaCtfTool/blob/master/attacks/single_key/londahl.py>
+fn close_factor(
+    n: &Integer,
+    phi_approx: &Integer,
+    search: &mut LondahlSearch,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<(Integer, Integer)>, Error> {
+    let b = search.b;
+    let tick_size: u64 = (b / 100).max(1);
+    pb.set_length(b);
     let fac = Integer::from(2).pow_mod(&b.into(), n).unwrap();
 
-    // Start computing
-    for j in 1..=b {
-        if j % tick_size == 0 {
-            if let Some(pb) = pb {
-                pb.inc(tick_size);
+    // Start computing, or resume where the search stopped
+    for j in search.j..=b {
+        if j.is_multiple_of(STOP_CHECK_INTERVAL) {
+            if let Err(err) = ctx.check() {
+                search.j = j;
+                return Err(err);
             }
         }
+        if j.is_multiple_of(tick_size) {
+            pb.inc(tick_size);
+        }
 
         // Store new stored key
-        if generate_entries {
-            match look_up.entry(z.clone()) {
+        if search.generate_entries {
+            match search.look_up.entry(fingerprint(&search.z)) {
                 Entry::Occupied(value) => {
                     match value.get() {
                         LondahlState::Lookup(lookup_j) => {
                             // Found a lookup key, try to factor the modulus
                             let i = j;
-                            let phi = &phi_approx + (i - Integer::from(*lookup_j) * b);
+                            let phi = phi_approx + (i - Integer::from(*lookup_j) * b);
                             let b = -(n - phi + 1u64);
                             let roots = solve_quadratic(&Integer::from(1), &b, n);
 
                             if roots.len() == 2 && roots.iter().all(|r| *r != 1) {
-                                return Some((roots[0].clone(), roots[1].clone()));
+                                return Ok(Some((roots[0].clone(), roots[1].clone())));
                             }
                         }
                         LondahlState::Store(_) => {
                             // Stored key already exists, so key generation is looping
-                            generate_entries = false;
+                            search.generate_entries = false;
                         }
                     }
                 }
@@ -79,19 +184,19 @@ fn close_factor(n: &Integer, b: u64, pb: Option<&ProgressBar>) -> Option<(Intege
             }
         }
 
-        z = (z * 2) % n;
-        mu = (mu * &fac) % n;
+        search.z = (std::mem::take(&mut search.z) * 2) % n;
+        search.mu = (std::mem::take(&mut search.mu) * &fac) % n;
 
         // Check if mu is in the table
-        match look_up.get(&mu) {
+        match search.look_up.get(&fingerprint(&search.mu)) {
             Some(LondahlState::Store(i)) => {
                 // Found a stored key, try to factor the modulus
-                let phi = &phi_approx + (i - Integer::from(j) * b);
+                let phi = phi_approx + (i - Integer::from(j) * b);
                 let b = -(n - phi + 1u64);
                 let roots = solve_quadratic(&Integer::from(1), &b, n);
 
                 if roots.len() == 2 && roots.iter().all(|r| *r != 1) {
-                    return Some((roots[0].clone(), roots[1].clone()));
+                    return Ok(Some((roots[0].clone(), roots[1].clone())));
                 }
             }
             Some(LondahlState::Lookup(_)) => {
@@ -100,16 +205,23 @@ fn close_factor(n: &Integer, b: u64, pb: Option<&ProgressBar>) -> Option<(Intege
             }
             None => {
                 // Insert new lookup key
-                look_up.insert(mu.clone(), LondahlState::Lookup(j));
+                search
+                    .look_up
+                    .insert(fingerprint(&search.mu), LondahlState::Lookup(j));
             }
         }
     }
 
-    None
+    search.j = b + 1;
+    Ok(None)
 }
 
 /// Londahl close-prime factorization attack
 ///
+/// B is set by `AttackConfig::londahl_b`, lowered if the look-up table does not fit in the memory
+/// left by the other attacks of `AttackConfig::max_memory`.
+/// The search is resumed at the step it stopped, with its look-up table, from an exported state.
+///
 /// See <https://github.com/RsaCtfTool/RsaCtfTool/blob/master/attacks/single_key/londahl.py>
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LondahlAttack;
@@ -123,22 +235,64 @@ impl Attack for LondahlAttack {
         AttackSpeed::Slow
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::ClosePrimes)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
-        self.run_with_b(params, pb, LONDAHL_B)
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        self.run_resumable(params, ctx, &mut None, pb)
+    }
+
+    fn as_resumable(&self) -> Option<&dyn ResumableAttack> {
+        Some(self)
     }
 }
 
-impl LondahlAttack {
-    fn run_with_b(
+impl ResumableAttack for LondahlAttack {
+    fn run_resumable(
         &self,
         params: &Parameters,
+        ctx: &AttackContext,
+        state: &mut Option<AttackState>,
         pb: Option<&ProgressBar>,
-        b: u64,
     ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        let (p, q) = close_factor(n, b, pb).ok_or(Error::NotFound)?;
+        // Approximate phi
+        let n_sqrt = ctx.precomputed().n_sqrt().ok_or(Error::MissingParameters)?;
+        let phi_approx = n - Integer::from(2 * n_sqrt) + 1;
+
+        let londahl_b = ctx.config().londahl_b;
+        let memory = ctx.reserve_memory(table_memory(londahl_b));
+        let b = bound_for_memory(londahl_b, memory.size());
+        let mut search = match state
+            .take()
+            .filter(|state| state.resumes(self.name(), n))
+            .and_then(|state| LondahlSearch::import(&state, b))
+        {
+            Some(search) => search,
+            None => LondahlSearch::new(n, b, &phi_approx).ok_or(Error::NotFound)?,
+        };
+        let (p, q) = match close_factor(n, &phi_approx, &mut search, ctx, pb) {
+            Ok(factors) => factors.ok_or(Error::NotFound)?,
+            Err(Error::Stopped) => {
+                let mut stopped = AttackState::new(self.name(), n);
+                search.export(&mut stopped);
+                *state = Some(stopped);
+                return Err(Error::Stopped);
+            }
+            Err(err) => return Err(err),
+        };
         Ok(Solution::new_pk(
             self.name(),
             PrivateKey::from_p_q(p, q, e)?,
@@ -150,7 +304,7 @@ impl LondahlAttack {
 mod tests {
     use std::str::FromStr;
 
-    use crate::Parameters;
+    use crate::{AttackConfig, Parameters};
 
     use super::*;
 
@@ -164,10 +318,57 @@ mod tests {
             ..Default::default()
         };
 
-        let solution = LondahlAttack.run_with_b(&params, None, 1_000).unwrap();
+        let ctx = AttackContext::new(&params).with_config(AttackConfig {
+            londahl_b: 1_000,
+            ..Default::default()
+        });
+        let solution = LondahlAttack.run_with_context(&params, &ctx, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn resume() {
+        let p = Integer::from_str("18446714073709551629").unwrap();
+        let q = Integer::from_str("18446774073709551671").unwrap();
+
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        let config = AttackConfig {
+            londahl_b: 10_000,
+            ..Default::default()
+        };
+
+        // The factors are found at step 4879, after the first check
+        let ctx = AttackContext::new(&params).with_config(config.clone());
+        ctx.stop();
+        let mut state = None;
+        let res = LondahlAttack.run_resumable(&params, &ctx, &mut state, None);
+        assert_eq!(res.unwrap_err(), Error::Stopped);
+        let state = AttackState::from_json(&state.unwrap().to_json()).unwrap();
+        assert_eq!(state.get("j"), [STOP_CHECK_INTERVAL]);
+
+        let ctx = AttackContext::new(&params).with_config(config);
+        let solution = LondahlAttack
+            .run_resumable(&params, &ctx, &mut Some(state), None)
+            .unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn memory_budget() {
+        assert_eq!(bound_for_memory(10_000_000, u64::MAX), 10_000_000);
+        assert_eq!(
+            bound_for_memory(10_000_000, 1 << 28),
+            (1 << 28) / ENTRY_SIZE / 2
+        );
+        assert_eq!(bound_for_memory(10_000_000, 0), 0);
     }
 }
@@ -1,7 +1,9 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Progress, Solution, Vulnerability,
+};
 
 /// Mersenne prime factorization attack
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,11 +25,16 @@ impl Attack for MersennePrimeAttack {
         AttackSpeed::Fast
     }
 
-    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
         let mut mersenne = Integer::from(1);
+        pb.set_length(MERSENNE_PRIMES.len() as u64);
         for (from, to) in [0]
             .iter()
             .chain(MERSENNE_PRIMES.iter())
@@ -35,6 +42,7 @@ impl Attack for MersennePrimeAttack {
         {
             mersenne <<= to - from;
             let p = mersenne.clone() - 1;
+            pb.inc(1);
 
             if p > *n {
                 break;
@@ -72,7 +80,7 @@ mod tests {
         let solution = MersennePrimeAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
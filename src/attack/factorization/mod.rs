@@ -3,6 +3,7 @@ use std::sync::Arc;
 use lazy_static::lazy_static;
 
 mod brent;
+mod cache;
 mod cunningham_chain;
 mod ecm;
 mod factordb;
@@ -20,9 +21,11 @@ mod small_prime;
 mod sparse;
 mod squfof;
 mod twin_prime;
+mod weak_primes;
 
 pub use self::ecm::EcmAttack;
 pub use brent::BrentAttack;
+pub use cache::CacheAttack;
 pub use cunningham_chain::CunninghamChainAttack;
 pub use factordb::FactorDbAttack;
 pub use fermat::FermatAttack;
@@ -39,12 +42,15 @@ pub use small_prime::SmallPrimeAttack;
 pub use sparse::SparseAttack;
 pub use squfof::SqufofAttack;
 pub use twin_prime::TwinPrimeAttack;
+pub(crate) use weak_primes::find_weak_prime;
+pub use weak_primes::WeakPrimesAttack;
 
 use crate::Attack;
 
 lazy_static! {
     static ref _ATTACKS: Vec<Arc<dyn Attack + Sync + Send>> = vec![
         Arc::new(BrentAttack),
+        Arc::new(CacheAttack),
         Arc::new(CunninghamChainAttack),
         Arc::new(EcmAttack),
         Arc::new(FactorDbAttack),
@@ -61,6 +67,7 @@ lazy_static! {
         Arc::new(SparseAttack),
         Arc::new(SqufofAttack),
         Arc::new(TwinPrimeAttack),
+        Arc::new(WeakPrimesAttack),
     ];
 
     /// List of attacks
@@ -1,53 +1,65 @@
 use indicatif::ProgressBar;
 use rug::{integer::IsPrime, Complete, Integer};
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, ntheory::is_prime, Attack, AttackContext, AttackSpeed, Error, Parameters,
+    Progress, Solution, Vulnerability, STOP_CHECK_INTERVAL,
+};
 
 const MAX_ITERATIONS: u64 = 1_000_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
-fn pollard_p_1(n: &Integer, pb: Option<&ProgressBar>) -> Option<Vec<Integer>> {
+fn pollard_p_1(
+    n: &Integer,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Vec<Integer>>, Error> {
+    let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+    let tick_size = (max_iterations / 100).max(1);
     let mut a = Integer::from(2);
-    let mut b = 2;
+    let mut b = 2u64;
 
-    if let Some(pb) = pb {
-        pb.set_position(0);
-        pb.set_length(MAX_ITERATIONS);
-    }
+    pb.set_length(max_iterations);
     loop {
         a = a.pow_mod(&b.into(), n).unwrap();
         let p = Integer::from(&a - 1).gcd(n);
         if p > 1 && &p < n {
             let (q, rem) = n.div_rem_ref(&p).complete();
             if rem != Integer::ZERO {
-                return None;
+                return Ok(None);
             }
 
             let mut res = vec![];
-            if p.is_probably_prime(100) == IsPrime::No {
-                res.extend(pollard_p_1(&p, pb)?);
+            if is_prime(&p) == IsPrime::No {
+                let Some(factors) = pollard_p_1(&p, ctx, pb)? else {
+                    return Ok(None);
+                };
+                res.extend(factors);
             } else {
                 res.push(p);
             }
-            if q.is_probably_prime(100) == IsPrime::No {
-                res.extend(pollard_p_1(&q, pb)?);
+            if is_prime(&q) == IsPrime::No {
+                let Some(factors) = pollard_p_1(&q, ctx, pb)? else {
+                    return Ok(None);
+                };
+                res.extend(factors);
             } else {
                 res.push(q);
             }
-            return Some(res);
+            return Ok(Some(res));
         }
         b += 1;
 
-        if b % TICK_SIZE == 0 {
-            if let Some(pb) = pb {
-                pb.inc(TICK_SIZE);
-            }
+        if b.is_multiple_of(STOP_CHECK_INTERVAL) {
+            ctx.check()?;
         }
-        if b > MAX_ITERATIONS {
+        if b.is_multiple_of(tick_size) {
+            pb.inc(tick_size);
+        }
+        if b > max_iterations {
             break;
         }
     }
-    None
+    Ok(None)
 }
 
 /// Pollard p-1 factorization attack
@@ -63,11 +75,24 @@ impl Attack for PollardPM1Attack {
         AttackSpeed::Slow
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SmoothPMinus1)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        let factors = pollard_p_1(n, pb).ok_or(Error::NotFound)?;
+        let factors = pollard_p_1(n, ctx, pb)?.ok_or(Error::NotFound)?;
         Ok(Solution::new_pk(
             self.name(),
             PrivateKey::from_factors(factors, e)?,
@@ -93,7 +118,7 @@ mod tests {
         let solution = PollardPM1Attack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
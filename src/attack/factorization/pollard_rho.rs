@@ -1,12 +1,114 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use indicatif::ProgressBar;
+use rayon::prelude::*;
 use rug::{ops::Pow, Complete, Integer};
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, AttackSpeed, AttackState, Error, Parameters, Progress,
+    ResumableAttack, Solution, Vulnerability, STOP_CHECK_INTERVAL,
+};
 
 const MAX_ITERATIONS: u64 = 1_000_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
+
+/// Position of a Pollard rho walk using x^2 + c, with Floyd's cycle detection
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Walk {
+    c: Integer,
+    x: Integer,
+    y: Integer,
+    iterations: u64,
+}
+
+impl Walk {
+    fn new(c: u64, x0: u64) -> Self {
+        Self {
+            c: c.into(),
+            x: x0.into(),
+            y: x0.into(),
+            iterations: 0,
+        }
+    }
+
+    /// Walks saved in a state, one per index of its lists
+    fn import(state: &AttackState) -> Option<Vec<Self>> {
+        let (c, x, y) = (state.get("c"), state.get("x"), state.get("y"));
+        let iterations = state.get("iterations");
+        if c.is_empty() || [x.len(), y.len(), iterations.len()] != [c.len(); 3] {
+            return None;
+        }
+        (0..c.len())
+            .map(|i| {
+                Some(Self {
+                    c: c[i].clone(),
+                    x: x[i].clone(),
+                    y: y[i].clone(),
+                    iterations: iterations[i].to_u64()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Save walks in a state
+    fn export(walks: &[Self], state: &mut AttackState) {
+        state.set("c", walks.iter().map(|walk| walk.c.clone()));
+        state.set("x", walks.iter().map(|walk| walk.x.clone()));
+        state.set("y", walks.iter().map(|walk| walk.y.clone()));
+        state.set(
+            "iterations",
+            walks.iter().map(|walk| walk.iterations.into()),
+        );
+    }
+}
+
+/// Continue a Pollard rho walk
+///
+/// Returns early if another walk found a factor. The walk is left where it stopped.
+fn rho(
+    n: &Integer,
+    walk: &mut Walk,
+    ctx: &AttackContext,
+    found: &AtomicBool,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Integer>, Error> {
+    let mut p = Integer::from(1);
+    let c = walk.c.clone();
+    let g = |x: Integer| (x.pow(2) + &c) % n;
+    let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+    let tick_size = (max_iterations / 100).max(1);
+
+    while p == 1 {
+        walk.x = g(std::mem::take(&mut walk.x));
+        walk.y = g(g(std::mem::take(&mut walk.y)));
+        p = Integer::from(&walk.x - &walk.y).abs().gcd(n);
+
+        walk.iterations += 1;
+        if walk.iterations.is_multiple_of(STOP_CHECK_INTERVAL) {
+            ctx.check()?;
+            if found.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+        }
+        if walk.iterations.is_multiple_of(tick_size) {
+            pb.inc(tick_size);
+        }
+        if walk.iterations >= max_iterations {
+            return Ok(None);
+        }
+    }
+
+    // The walk cycled without splitting n
+    if p == *n {
+        return Ok(None);
+    }
+    found.store(true, Ordering::Relaxed);
+    Ok(Some(p))
+}
 
 /// Pollard rho factorization attack
+///
+/// Runs one walk per available thread, each with its own polynomial and seed.
+/// The walks are resumed where they stopped from an exported state.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PollardRhoAttack;
 
@@ -19,35 +121,70 @@ impl Attack for PollardRhoAttack {
         AttackSpeed::Slow
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SmallFactor)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        self.run_resumable(params, ctx, &mut None, pb)
+    }
+
+    fn as_resumable(&self) -> Option<&dyn ResumableAttack> {
+        Some(self)
+    }
+}
+
+impl ResumableAttack for PollardRhoAttack {
+    fn run_resumable(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        state: &mut Option<AttackState>,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS);
-        }
+        let threads = ctx.config().threads.max(1);
+        let mut walks = state
+            .take()
+            .filter(|state| state.resumes(self.name(), n))
+            .and_then(|state| Walk::import(&state))
+            .unwrap_or_else(|| {
+                (0..threads as u64)
+                    .map(|walk| Walk::new(walk + 1, walk + 2))
+                    .collect()
+            });
+        pb.set_length(ctx.config().scaled(MAX_ITERATIONS) * walks.len() as u64);
 
-        let mut x = Integer::from(2);
-        let mut y = Integer::from(2);
-        let mut p = Integer::from(1);
-        let g = |x: Integer| (x.pow(2) + 1) % n;
-
-        let mut i = 0;
-        while p == 1 {
-            x = g(x);
-            y = g(g(y));
-            p = Integer::from(&x - &y).abs().gcd(n);
-
-            i += 1;
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
-                if i == MAX_ITERATIONS {
-                    return Err(Error::NotFound);
-                }
+        let pool = ctx.thread_pool()?;
+        let found = AtomicBool::new(false);
+        let p = pool.install(|| {
+            walks
+                .par_iter_mut()
+                .find_map_any(|walk| rho(n, walk, ctx, &found, pb).transpose())
+        });
+        let p = match p {
+            Some(Ok(p)) => p,
+            Some(Err(Error::Stopped)) => {
+                let mut stopped = AttackState::new(self.name(), n);
+                Walk::export(&walks, &mut stopped);
+                *state = Some(stopped);
+                return Err(Error::Stopped);
             }
-        }
+            Some(Err(err)) => return Err(err),
+            None => return Err(Error::NotFound),
+        };
+
         let q = match n.div_rem_ref(&p).complete() {
             (q, rem) if (rem) == Integer::ZERO => q,
             _ => return Err(Error::NotFound),
@@ -61,7 +198,7 @@ impl Attack for PollardRhoAttack {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Attack, Parameters};
+    use crate::{Attack, AttackConfig, Parameters};
 
     use super::*;
 
@@ -77,7 +214,59 @@ mod tests {
         let solution = PollardRhoAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn many_walks() {
+        let p = Integer::from(1779681653);
+        let q = Integer::from(1903643191);
+
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        let ctx = AttackContext::new(&params).with_config(AttackConfig {
+            threads: 4,
+            ..Default::default()
+        });
+        let solution = PollardRhoAttack
+            .run_with_context(&params, &ctx, None)
+            .unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn resume() {
+        let p = Integer::from(1779681653);
+        let q = Integer::from(1903643191);
+
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        let config = AttackConfig {
+            threads: 1,
+            ..Default::default()
+        };
+
+        // The walk stops at its first check, before finding p
+        let ctx = AttackContext::new(&params).with_config(config.clone());
+        ctx.stop();
+        let mut state = None;
+        let res = PollardRhoAttack.run_resumable(&params, &ctx, &mut state, None);
+        assert_eq!(res.unwrap_err(), Error::Stopped);
+        let state = AttackState::from_json(&state.unwrap().to_json()).unwrap();
+        assert_eq!(state.get("iterations"), [STOP_CHECK_INTERVAL]);
+
+        let ctx = AttackContext::new(&params).with_config(config);
+        let solution = PollardRhoAttack
+            .run_resumable(&params, &ctx, &mut Some(state), None)
+            .unwrap();
+        assert_eq!(solution.pk.unwrap().p(), Some(p));
     }
 }
@@ -4,7 +4,8 @@ use indicatif::ProgressBar;
 use rug::Integer;
 
 use crate::{
-    key::PrivateKey, utils::log_base_ceil, Attack, AttackSpeed, Error, Parameters, Solution,
+    key::PrivateKey, utils::log_base_ceil, Attack, AttackSpeed, Error, Parameters, Progress,
+    Solution, Vulnerability,
 };
 
 /// Factorize n when n = p^k
@@ -20,12 +21,19 @@ impl Attack for PowerAttack {
         AttackSpeed::Fast
     }
 
-    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::PrimePower)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        for power in (2..log_base_ceil(n, 2) as u32).rev() {
+        let max_power = log_base_ceil(n, 2) as u32;
+        pb.set_length(max_power.saturating_sub(2) as u64);
+        for power in (2..max_power).rev() {
             let (root, rem) = n.root_rem_ref(power).into();
+            pb.inc(1);
 
             if rem != Integer::ZERO {
                 continue;
@@ -0,0 +1,87 @@
+use rug::Integer;
+
+/// Number of candidates accumulated before taking a gcd
+const BATCH_SIZE: usize = 64;
+
+/// Batched gcd of candidates against n
+///
+/// Candidates are multiplied together modulo n, and a single gcd is taken per batch.
+/// If a batch contains all factors of n, its candidates are checked one by one.
+pub(super) struct BatchGcd<'a> {
+    n: &'a Integer,
+    product: Integer,
+    batch: Vec<Integer>,
+}
+
+impl<'a> BatchGcd<'a> {
+    pub(super) fn new(n: &'a Integer) -> Self {
+        Self {
+            n,
+            product: Integer::from(1),
+            batch: Vec::with_capacity(BATCH_SIZE),
+        }
+    }
+
+    /// Add a candidate, returns a non-trivial factor of n once found
+    pub(super) fn push(&mut self, candidate: Integer) -> Option<Integer> {
+        let candidate = candidate % self.n;
+        self.product *= &candidate;
+        self.product %= self.n;
+        self.batch.push(candidate);
+
+        if self.batch.len() == BATCH_SIZE {
+            self.flush()
+        } else {
+            None
+        }
+    }
+
+    /// Check the pending candidates, returns a non-trivial factor of n if found
+    pub(super) fn flush(&mut self) -> Option<Integer> {
+        let g = Integer::from(self.product.gcd_ref(self.n));
+        let factor = if &g == self.n {
+            // Too many factors in this batch, check candidates separately
+            self.batch
+                .iter()
+                .map(|c| Integer::from(c.gcd_ref(self.n)))
+                .find(|g| 1 < *g && g < self.n)
+        } else {
+            Some(g).filter(|g| *g != 1)
+        };
+
+        self.product = Integer::from(1);
+        self.batch.clear();
+        factor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_gcd() {
+        let p = Integer::from(97);
+        let q = Integer::from(257);
+        let n = p.clone() * &q;
+
+        // Factor found when the batch is flushed
+        let mut batch = BatchGcd::new(&n);
+        assert_eq!(batch.push(Integer::from(2)), None);
+        assert_eq!(batch.push(p.clone() * 3), None);
+        assert_eq!(batch.flush(), Some(p.clone()));
+
+        // Both factors in the same batch
+        let mut batch = BatchGcd::new(&n);
+        assert_eq!(batch.push(q.clone() * 5), None);
+        assert_eq!(batch.push(p.clone() * 7), None);
+        assert_eq!(batch.flush(), Some(q.clone()));
+
+        // Full batch
+        let mut batch = BatchGcd::new(&n);
+        for i in 1..BATCH_SIZE {
+            assert_eq!(batch.push(Integer::from(i)), None);
+        }
+        assert_eq!(batch.push(p.clone()), Some(p));
+    }
+}
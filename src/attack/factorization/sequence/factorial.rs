@@ -1,10 +1,12 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use super::batch_gcd::BatchGcd;
+use crate::{
+    key::PrivateKey, Attack, AttackContext, Error, Parameters, Progress, Solution, Vulnerability,
+};
 
 const MAX_ITERATIONS: u64 = 25_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 /// Factorial GCD attack (try to find a common factor with Factorial (+ or - 1) numbers)
 /// E.g. 1, 1, 2, 6, 24, 120, 720, 5040, 40320, 362880, 3628800, ...
@@ -16,21 +18,39 @@ impl Attack for FactorialGcdAttack {
         "factorial_gcd"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        let tick_size = (max_iterations / 100).max(1);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS)
-        }
+        pb.set_length(max_iterations);
 
+        // Factorial modulo n
         let mut f = Integer::from(1);
-        for i in 2..MAX_ITERATIONS {
+        let mut batch = BatchGcd::new(n);
+        for i in 2..max_iterations {
             f *= i;
+            f %= n;
 
-            // Factorial - 1
-            let p = Integer::from(&f - 1).gcd(n);
-            if 1 < p && &p < n {
+            // Factorial - 1 and factorial + 1
+            if let Some(p) = batch
+                .push(Integer::from(&f - 1))
+                .or_else(|| batch.push(Integer::from(&f + 1)))
+            {
                 let q = Integer::from(n / &p);
                 return Ok(Solution::new_pk(
                     self.name(),
@@ -38,22 +58,17 @@ impl Attack for FactorialGcdAttack {
                 ));
             }
 
-            // Factorial + 1
-            let p = Integer::from(&f + 1).gcd(n);
-            if 1 < p && &p < n {
-                let q = Integer::from(n / &p);
-                return Ok(Solution::new_pk(
-                    self.name(),
-                    PrivateKey::from_p_q(p, q, e)?,
-                ));
-            }
-
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if i.is_multiple_of(tick_size) {
+                pb.inc(tick_size);
             }
         }
+        if let Some(p) = batch.flush() {
+            let q = Integer::from(n / &p);
+            return Ok(Solution::new_pk(
+                self.name(),
+                PrivateKey::from_p_q(p, q, e)?,
+            ));
+        }
         Err(Error::NotFound)
     }
 }
@@ -1,7 +1,9 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Progress, Solution, Vulnerability,
+};
 
 /// Fermat GCD attack (try to find a common factor with Fermat numbers)
 /// E.g. 3, 5, 17, 257, 65537, 4294967297, 18446744073709551617, ...
@@ -17,13 +19,15 @@ impl Attack for FermatGcdAttack {
         AttackSpeed::Fast
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        if let Some(pb) = pb {
-            pb.set_length(30)
-        }
+        pb.set_length(30);
 
         for i in 0..30 {
             let f = (Integer::from(1) << (1u32 << i)) + 1u64; // (1 << (1 << x)) + 1
@@ -36,9 +40,7 @@ impl Attack for FermatGcdAttack {
                 ));
             }
 
-            if let Some(pb) = pb {
-                pb.inc(1);
-            }
+            pb.inc(1);
         }
         Err(Error::NotFound)
     }
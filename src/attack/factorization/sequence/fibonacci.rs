@@ -1,10 +1,12 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use super::batch_gcd::BatchGcd;
+use crate::{
+    key::PrivateKey, Attack, AttackContext, Error, Parameters, Progress, Solution, Vulnerability,
+};
 
 const MAX_ITERATIONS: u64 = 100_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 /// Fibonacci GCD attack (try to find a common factor with Fibonacci numbers)
 /// E.g. 1, 1, 2, 3, 5, 8, 13, 21, 34, 55, ...
@@ -16,60 +18,66 @@ impl Attack for FibonacciGcdAttack {
         "fibonacci_gcd"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        let tick_size = (max_iterations / 100).max(1);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS)
-        }
+        pb.set_length(max_iterations);
 
+        // Fibonacci numbers modulo 2n, to keep their parity
+        let two_n = Integer::from(n * 2);
         let mut n1 = Integer::from(1);
         let mut n2 = Integer::from(2);
-        for i in 1..MAX_ITERATIONS {
-            let f = Integer::from(&n1 + &n2);
+        let mut batch = BatchGcd::new(n);
+        for i in 1..max_iterations {
+            let f = Integer::from(&n1 + &n2) % &two_n;
 
-            if f.is_odd() {
+            let p = if f.is_odd() {
                 // Fibonacci
-                let p = Integer::from(f.gcd_ref(n));
-                if 1 < p && &p < n {
-                    let q = Integer::from(n / &p);
-                    return Ok(Solution::new_pk(
-                        self.name(),
-                        PrivateKey::from_p_q(p, q, e)?,
-                    ));
-                }
+                batch.push(f.clone())
             } else {
-                // Fibonacci - 1
-                let p = Integer::from(&f - 1).gcd(n);
-                if 1 < p && &p < n {
-                    let q = Integer::from(n / &p);
-                    return Ok(Solution::new_pk(
-                        self.name(),
-                        PrivateKey::from_p_q(p, q, e)?,
-                    ));
-                }
-
-                // Fibonacci + 1
-                let p = Integer::from(&f + 1).gcd(n);
-                if 1 < p && &p < n {
-                    let q = Integer::from(n / &p);
-                    return Ok(Solution::new_pk(
-                        self.name(),
-                        PrivateKey::from_p_q(p, q, e)?,
-                    ));
-                }
+                // Fibonacci - 1 and Fibonacci + 1
+                batch
+                    .push(Integer::from(&f - 1))
+                    .or_else(|| batch.push(Integer::from(&f + 1)))
+            };
+            if let Some(p) = p {
+                let q = Integer::from(n / &p);
+                return Ok(Solution::new_pk(
+                    self.name(),
+                    PrivateKey::from_p_q(p, q, e)?,
+                ));
             }
 
             n1 = n2;
             n2 = f;
 
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if i.is_multiple_of(tick_size) {
+                pb.inc(tick_size);
             }
         }
+        if let Some(p) = batch.flush() {
+            let q = Integer::from(n / &p);
+            return Ok(Solution::new_pk(
+                self.name(),
+                PrivateKey::from_p_q(p, q, e)?,
+            ));
+        }
         Err(Error::NotFound)
     }
 }
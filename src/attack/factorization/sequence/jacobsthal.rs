@@ -1,10 +1,11 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, Error, Parameters, Progress, Solution, Vulnerability,
+};
 
 const MAX_ITERATIONS: u64 = 100_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 /// Jacobsthal GCD attack (try to find a common factor with Jacobsthal numbers)
 /// E.g. 0, 1, 1, 3, 5, 11, 21, 43, 85, 171, 341, 683, 1365, 2731, 5461, ...
@@ -16,17 +17,30 @@ impl Attack for JacobsthalGcdAttack {
         "jacobsthal_gcd"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        let tick_size = (max_iterations / 100).max(1);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS)
-        }
+        pb.set_length(max_iterations);
 
         let mut n1 = Integer::from(0);
         let mut n2 = Integer::from(1);
-        for i in 1..MAX_ITERATIONS {
+        for i in 1..max_iterations {
             let n3 = Integer::from(&n1 * 2) + &n2;
             let p = Integer::from(n3.gcd_ref(n));
             if 1 < p && &p < n {
@@ -40,10 +54,8 @@ impl Attack for JacobsthalGcdAttack {
             n1 = n2;
             n2 = n3;
 
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if i.is_multiple_of(tick_size) {
+                pb.inc(tick_size);
             }
         }
         Err(Error::NotFound)
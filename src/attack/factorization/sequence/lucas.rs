@@ -1,10 +1,11 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, Error, Parameters, Progress, Solution, Vulnerability,
+};
 
 const MAX_ITERATIONS: u64 = 100_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 /// Lucas GCD attack (try to find a common factor with Lucas numbers)
 /// E.g. 1, 3, 4, 7, 11, 18, 29, 47, 76, 123, 199, 322, 521, 843, 1364, ...
@@ -16,17 +17,30 @@ impl Attack for LucasGcdAttack {
         "lucas_gcd"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        let tick_size = (max_iterations / 100).max(1);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS)
-        }
+        pb.set_length(max_iterations);
 
         let mut n1 = Integer::from(1);
         let mut n2 = Integer::from(3);
-        for i in 1..MAX_ITERATIONS {
+        for i in 1..max_iterations {
             let f = Integer::from(&n1 + &n2);
 
             if f.is_odd() {
@@ -64,10 +78,8 @@ impl Attack for LucasGcdAttack {
             n1 = n2;
             n2 = f;
 
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if i.is_multiple_of(tick_size) {
+                pb.inc(tick_size);
             }
         }
         Err(Error::NotFound)
@@ -0,0 +1,183 @@
+use std::iter;
+
+use indicatif::ProgressBar;
+use rug::Integer;
+
+use super::batch_gcd::BatchGcd;
+use crate::{
+    key::PrivateKey, Attack, AttackContext, AttackSpeed, Error, Parameters, Progress, Solution,
+    Vulnerability, STOP_CHECK_INTERVAL,
+};
+
+const MAX_INDEX: u64 = 1_000;
+
+/// Bound of the prime powers of the small cofactor
+const COFACTOR_BOUND: u32 = 1_000;
+
+/// Seeds of the Lucas sequences of the p+1 search, whose discriminants (A^2 - 4) have the
+/// independent square classes 5, 3 and 2
+const SEEDS: [u32; 3] = [3, 4, 6];
+
+/// Least common multiple of the integers up to `bound`
+fn cofactor_exponent(bound: u32) -> Integer {
+    let mut exponent = Integer::from(1);
+    let mut r = Integer::from(2);
+    while r <= bound {
+        let mut power = r.clone();
+        while Integer::from(&power * &r) <= bound {
+            power *= &r;
+        }
+        exponent *= power;
+        r = r.next_prime();
+    }
+    exponent
+}
+
+/// V_m(x) mod n, of the Lucas sequence V_0 = 2, V_1 = x, V_k+1 = x V_k - V_k-1
+fn lucas_v(x: &Integer, m: &Integer, n: &Integer) -> Integer {
+    let x = Integer::from(x % n);
+    let (mut v0, mut v1) = (Integer::from(2), x.clone());
+    for bit in (0..m.significant_bits()).rev() {
+        let product = (Integer::from(&v0 * &v1) - &x).modulo(n);
+        if m.get_bit(bit) {
+            v0 = product;
+            v1 = (v1.square() - 2u32).modulo(n);
+        } else {
+            v1 = product;
+            v0 = (v0.square() - 2u32).modulo(n);
+        }
+    }
+    v0
+}
+
+/// Fibonacci and Lucas index attack (p - 1 or p + 1 is a small cofactor times a Fibonacci or
+/// Lucas number)
+///
+/// For each index i, 2^(K F_i) - 1 and 2^(K L_i) - 1 reveal p if p - 1 divides K F_i or K L_i,
+/// K being the least common multiple of the integers up to the cofactor bound, as in Pollard p-1.
+/// Likewise V_(K F_i)(A) - 2 and V_(K L_i)(A) - 2 reveal p if p + 1 divides the index of the
+/// Lucas sequence, and A^2 - 4 is not a square modulo p, as in Williams p+1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LucasIndexAttack;
+
+impl Attack for LucasIndexAttack {
+    fn name(&self) -> &'static str {
+        "lucas_index"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Slow
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let e = &params.e;
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_index = ctx.config().scaled(MAX_INDEX);
+        let tick_size = (max_index / 100).max(1);
+
+        pb.set_length(max_index);
+
+        // Powers of the cofactor exponent, shared by all the indexes
+        let k = cofactor_exponent(COFACTOR_BOUND);
+        let base = Integer::from(2).pow_mod(&k, n).unwrap();
+        let seeds = SEEDS.map(|a| lucas_v(&a.into(), &k, n));
+
+        let mut batch = BatchGcd::new(n);
+        let (mut f0, mut f1) = (Integer::from(0), Integer::from(1));
+        let (mut l0, mut l1) = (Integer::from(2), Integer::from(1));
+        for i in 1..=max_index {
+            // Fibonacci and Lucas numbers, for p - 1 then for p + 1
+            let found = [&f1, &l1]
+                .into_iter()
+                .flat_map(|index| {
+                    let power = base.clone().pow_mod(index, n).unwrap() - 1;
+                    iter::once(power).chain(seeds.iter().map(|seed| lucas_v(seed, index, n) - 2))
+                })
+                .find_map(|c| batch.push(c));
+            if let Some(p) = found {
+                let q = Integer::from(n / &p);
+                return Ok(Solution::new_pk(
+                    self.name(),
+                    PrivateKey::from_p_q(p, q, e)?,
+                ));
+            }
+
+            let f = Integer::from(&f0 + &f1);
+            f0 = std::mem::replace(&mut f1, f);
+            let l = Integer::from(&l0 + &l1);
+            l0 = std::mem::replace(&mut l1, l);
+
+            if i.is_multiple_of(STOP_CHECK_INTERVAL) {
+                ctx.check()?;
+            }
+            if i.is_multiple_of(tick_size) {
+                pb.inc(tick_size);
+            }
+        }
+        if let Some(p) = batch.flush() {
+            let q = Integer::from(n / &p);
+            return Ok(Solution::new_pk(
+                self.name(),
+                PrivateKey::from_p_q(p, q, e)?,
+            ));
+        }
+        Err(Error::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{Attack, Parameters};
+
+    use super::*;
+
+    fn factor(p: &str) {
+        let p = Integer::from_str(p).unwrap();
+        let q = Integer::from_str("304492656810178217310291611588755895363").unwrap();
+
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        let solution = LucasIndexAttack.run(&params, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn fibonacci_p_minus_1() {
+        // p - 1 = 30 F_131
+        factor("31990212524751317874437165071");
+    }
+
+    #[test]
+    fn lucas_p_plus_1() {
+        // p + 1 = 2 L_113
+        factor("825340855689842074941541");
+    }
+
+    #[test]
+    fn lucas_v() {
+        // V_n(3) = L_2n, V_n(1) is periodic
+        assert_eq!(super::lucas_v(&3.into(), &5.into(), &1000.into()), 123);
+        assert_eq!(super::lucas_v(&3.into(), &0.into(), &1000.into()), 2);
+        assert_eq!(super::lucas_v(&1.into(), &6.into(), &1000.into()), 2);
+    }
+}
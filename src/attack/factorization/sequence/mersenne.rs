@@ -1,10 +1,11 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, Error, Parameters, Progress, Solution, Vulnerability,
+};
 
 const MAX_ITERATIONS: u64 = 50_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 /// Mersenne GCD attack (try to find a common factor with Mersenne (+ or - 1) numbers)
 /// E.g. 3, 5, 7, 9, 15, 17, 31, 33, 63, 65, 127, 129, 255, 257, 511, 513, ...
@@ -16,15 +17,28 @@ impl Attack for MersenneGcdAttack {
         "mersenne_gcd"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        let tick_size = (max_iterations / 100).max(1);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS)
-        }
+        pb.set_length(max_iterations);
 
-        for i in 2..MAX_ITERATIONS {
+        for i in 2..max_iterations {
             let f = Integer::from(1) << i as u32;
 
             // Mersenne - 1
@@ -47,10 +61,8 @@ impl Attack for MersenneGcdAttack {
                 ));
             }
 
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if i.is_multiple_of(tick_size) {
+                pb.inc(tick_size);
             }
         }
         Err(Error::NotFound)
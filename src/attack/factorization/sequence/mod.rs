@@ -2,11 +2,13 @@ use std::sync::Arc;
 
 use lazy_static::lazy_static;
 
+mod batch_gcd;
 mod factorial;
 mod fermat;
 mod fibonacci;
 mod jacobsthal;
 mod lucas;
+mod lucas_index;
 mod mersenne;
 mod primorial;
 mod xy;
@@ -16,6 +18,7 @@ pub use fermat::*;
 pub use fibonacci::*;
 pub use jacobsthal::*;
 pub use lucas::*;
+pub use lucas_index::*;
 pub use mersenne::*;
 pub use primorial::*;
 pub use xy::*;
@@ -30,6 +33,7 @@ lazy_static! {
         Arc::new(FibonacciGcdAttack),
         Arc::new(JacobsthalGcdAttack),
         Arc::new(LucasGcdAttack),
+        Arc::new(LucasIndexAttack),
         Arc::new(MersenneGcdAttack),
         Arc::new(PrimorialGcdAttack),
         Arc::new(XYGcdAttack),
@@ -58,8 +62,8 @@ mod tests {
                 let solution = $name::$attack.run(&params, None).unwrap();
                 let pk = solution.pk.unwrap();
 
-                assert_eq!(pk.p(), p);
-                assert_eq!(pk.q(), q);
+                assert_eq!(pk.p(), Some(p));
+                assert_eq!(pk.q(), Some(q));
             }
         };
     }
@@ -1,10 +1,12 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use super::batch_gcd::BatchGcd;
+use crate::{
+    key::PrivateKey, Attack, AttackContext, Error, Parameters, Progress, Solution, Vulnerability,
+};
 
 const MAX_ITERATIONS: u64 = 25_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 /// Primorial GCD attack (try to find a common factor with Primorial (+ or - 1) numbers)
 /// E.g 1, 3, 5, 7, 29, 31, 209, 211, 2309, 2311, ...
@@ -16,24 +18,42 @@ impl Attack for PrimorialGcdAttack {
         "primorial_gcd"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        let tick_size = (max_iterations / 100).max(1);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS)
-        }
+        pb.set_length(max_iterations);
 
+        // Primorial modulo n
         let mut primorial = Integer::from(1);
+        let mut batch = BatchGcd::new(n);
         for (i, prime) in primal::Primes::all()
-            .take(MAX_ITERATIONS as usize)
+            .take(max_iterations as usize)
             .enumerate()
         {
             primorial *= prime;
+            primorial %= n;
 
-            // Primorial - 1
-            let p = Integer::from(&primorial - 1).gcd(n);
-            if 1 < p && &p < n {
+            // Primorial - 1 and primorial + 1
+            if let Some(p) = batch
+                .push(Integer::from(&primorial - 1))
+                .or_else(|| batch.push(Integer::from(&primorial + 1)))
+            {
                 let q = Integer::from(n / &p);
                 return Ok(Solution::new_pk(
                     self.name(),
@@ -41,22 +61,17 @@ impl Attack for PrimorialGcdAttack {
                 ));
             }
 
-            // Primorial + 1
-            let p = Integer::from(&primorial + 1).gcd(n);
-            if 1 < p && &p < n {
-                let q = Integer::from(n / &p);
-                return Ok(Solution::new_pk(
-                    self.name(),
-                    PrivateKey::from_p_q(p, q, e)?,
-                ));
-            }
-
-            if i as u64 % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if (i as u64).is_multiple_of(tick_size) {
+                pb.inc(tick_size);
             }
         }
+        if let Some(p) = batch.flush() {
+            let q = Integer::from(n / &p);
+            return Ok(Solution::new_pk(
+                self.name(),
+                PrivateKey::from_p_q(p, q, e)?,
+            ));
+        }
         Err(Error::NotFound)
     }
 }
@@ -2,10 +2,12 @@ use indicatif::ProgressBar;
 use primal::Primes;
 use rug::{ops::Pow, Integer};
 
-use crate::{key::PrivateKey, utils::log_base_ceil, Attack, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, utils::log_base_ceil, Attack, AttackContext, Error, Parameters, Progress,
+    Solution, Vulnerability,
+};
 
 const MAX_ITERATIONS: u64 = 100_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 fn factor_xy(n: &Integer, base: usize) -> Option<(Integer, Integer)> {
     let max_power = log_base_ceil(n, base) >> 1;
@@ -49,15 +51,28 @@ impl Attack for XYGcdAttack {
         "xy"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SpecialFormPrime)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        let tick_size = (max_iterations / 100).max(1);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS);
-        }
+        pb.set_length(max_iterations);
 
-        for (i, base) in Primes::all().take(MAX_ITERATIONS as usize).enumerate() {
+        for (i, base) in Primes::all().take(max_iterations as usize).enumerate() {
             if let Some((p, q)) = factor_xy(n, base) {
                 return Ok(Solution::new_pk(
                     self.name(),
@@ -65,10 +80,8 @@ impl Attack for XYGcdAttack {
                 ));
             }
 
-            if i as u64 % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if (i as u64).is_multiple_of(tick_size) {
+                pb.inc(tick_size);
             }
         }
 
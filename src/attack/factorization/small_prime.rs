@@ -1,15 +1,29 @@
 use std::collections::HashMap;
 
 use indicatif::ProgressBar;
-use primal::Primes;
+use rayon::{prelude::*, ThreadPool};
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, AttackSpeed, Error, Parameters, Progress, Solution,
+    Vulnerability,
+};
 
-const MAX_ITERATIONS: u64 = 1_000_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
+/// Primes pre-sieved by the wheel
+const WHEEL_PRIMES: [u64; 4] = [2, 3, 5, 7];
+/// Wheel circumference (product of `WHEEL_PRIMES`)
+const WHEEL: u64 = 2 * 3 * 5 * 7;
+/// Number of integers covered by a sieve segment (multiple of `WHEEL`)
+const SEGMENT_SIZE: u64 = WHEEL * 2048;
+/// Number of sieve segments whose primes are checked by a single GPU launch
+#[cfg(feature = "gpu")]
+const GPU_BATCH_SEGMENTS: u64 = 256;
 
 /// Small prime attack
+///
+/// Trial division by all primes up to `AttackConfig::small_prime_bound` (scaled by the effort), enumerated with a segmented
+/// wheel sieve whose segments are spread across the available threads. With the `gpu` feature, the sieved primes are
+/// checked by batches on a CUDA GPU when one is available.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SmallPrimeAttack;
 
@@ -22,29 +36,61 @@ impl Attack for SmallPrimeAttack {
         AttackSpeed::Fast
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SmallFactor)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
+        // No need to search beyond sqrt(n) or the square of the largest base prime
+        let base_primes = ctx.precomputed().small_primes();
+        let max_base_prime = *base_primes.last().unwrap();
+        let bound = ctx
+            .config()
+            .scaled(ctx.config().small_prime_bound)
+            .min(max_base_prime * max_base_prime);
+        let bound = match ctx.precomputed().n_sqrt().and_then(|s| s.to_u64()) {
+            Some(n_sqrt) => bound.min(n_sqrt + 1),
+            None => bound,
+        };
+
+        let segments = bound.div_ceil(SEGMENT_SIZE);
+        pb.set_length(segments);
+
+        let pool = ctx.thread_pool()?;
+        let sieve = |segment: u64| {
+            ctx.check()?;
+
+            let low = segment * SEGMENT_SIZE;
+            let high = (low + SEGMENT_SIZE).min(bound);
+            Ok(sieve_segment(low, high, base_primes))
+        };
+        #[cfg(feature = "gpu")]
+        let primes = match gpu_divisors(n, segments, &sieve, pool, pb)? {
+            Some(primes) => primes,
+            None => cpu_divisors(n, segments, &sieve, pool, pb)?,
+        };
+        #[cfg(not(feature = "gpu"))]
+        let primes = cpu_divisors(n, segments, &sieve, pool, pb)?;
+
         let mut factors: HashMap<Integer, usize> = HashMap::new();
         let mut tmp_n: Integer = n.clone();
-        for (i, prime) in Primes::all().take(MAX_ITERATIONS as usize).enumerate() {
-            if prime > tmp_n {
-                break;
-            }
-
+        for prime in primes {
             let prime = Integer::from(prime);
-            if tmp_n.clone().div_rem(prime.clone()).1 == 0 {
-                while tmp_n.clone().div_rem(prime.clone()).1 == 0 {
-                    tmp_n /= &prime;
-                    *factors.entry(prime.clone()).or_insert(0) += 1;
-                }
-            }
-
-            if i % TICK_SIZE as usize == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            while tmp_n.is_divisible(&prime) {
+                tmp_n /= &prime;
+                *factors.entry(prime.clone()).or_insert(0) += 1;
             }
         }
 
@@ -63,9 +109,142 @@ impl Attack for SmallPrimeAttack {
     }
 }
 
+/// Returns the primes of the sieve segments dividing n, the segments being spread across the
+/// threads of the pool
+fn cpu_divisors(
+    n: &Integer,
+    segments: u64,
+    sieve: &(impl Fn(u64) -> Result<Vec<u64>, Error> + Sync),
+    pool: &ThreadPool,
+    pb: Option<&ProgressBar>,
+) -> Result<Vec<u64>, Error> {
+    let primes = pool.install(|| {
+        (0..segments)
+            .into_par_iter()
+            .map(|segment| {
+                let primes = sieve(segment)?
+                    .into_iter()
+                    .filter(|&p| is_divisible(n, p))
+                    .collect::<Vec<_>>();
+
+                pb.inc(1);
+                Ok(primes)
+            })
+            .collect::<Result<Vec<_>, Error>>()
+    })?;
+    Ok(primes.into_iter().flatten().collect())
+}
+
+/// Returns the primes of the sieve segments dividing n, checked on the GPU, or `None` if no GPU is
+/// available
+///
+/// Batches of `GPU_BATCH_SEGMENTS` segments are sieved by the threads of the pool, then their
+/// primes are checked by a single launch from this thread. The batches of primes which don't fit
+/// in 32 bits are checked by the threads of the pool.
+#[cfg(feature = "gpu")]
+fn gpu_divisors(
+    n: &Integer,
+    segments: u64,
+    sieve: &(impl Fn(u64) -> Result<Vec<u64>, Error> + Sync),
+    pool: &ThreadPool,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Vec<u64>>, Error> {
+    if !crate::gpu::is_available() {
+        return Ok(None);
+    }
+
+    let mut divisors = vec![];
+    for first in (0..segments).step_by(GPU_BATCH_SEGMENTS as usize) {
+        let batch = first..(first + GPU_BATCH_SEGMENTS).min(segments);
+        let primes = pool
+            .install(|| {
+                batch
+                    .clone()
+                    .into_par_iter()
+                    .map(sieve)
+                    .collect::<Result<Vec<_>, Error>>()
+            })?
+            .concat();
+
+        let found = primes
+            .iter()
+            .map(|&p| u32::try_from(p))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .and_then(|primes| crate::gpu::divisors(n, &primes));
+        match found {
+            Some(found) => divisors.extend(found.into_iter().map(u64::from)),
+            None => divisors.extend(pool.install(|| {
+                primes
+                    .into_par_iter()
+                    .filter(|&p| is_divisible(n, p))
+                    .collect::<Vec<_>>()
+            })),
+        }
+        pb.inc(batch.end - batch.start);
+    }
+    Ok(Some(divisors))
+}
+
+/// Returns true if p divides n
+fn is_divisible(n: &Integer, p: u64) -> bool {
+    match u32::try_from(p) {
+        Ok(p) => n.is_divisible_u(p),
+        Err(_) => n.is_divisible(&Integer::from(p)),
+    }
+}
+
+/// Returns the primes in [low, high), low being a multiple of `WHEEL`
+///
+/// Base primes must contain all primes up to sqrt(high).
+fn sieve_segment(low: u64, high: u64, base_primes: &[u64]) -> Vec<u64> {
+    debug_assert!(low.is_multiple_of(WHEEL));
+
+    // Pre-sieve with the wheel: is_candidate[i] <=> low + i is coprime with WHEEL
+    let wheel = (0..WHEEL)
+        .map(|i| WHEEL_PRIMES.iter().all(|&p| !i.is_multiple_of(p)))
+        .collect::<Vec<_>>();
+    let mut is_candidate = wheel
+        .iter()
+        .copied()
+        .cycle()
+        .take((high - low) as usize)
+        .collect::<Vec<_>>();
+
+    for &p in base_primes.iter().skip(WHEEL_PRIMES.len()) {
+        if p * p >= high {
+            break;
+        }
+
+        // Only odd multiples of p are wheel candidates
+        let mut m = (p * p).max(low.div_ceil(p) * p);
+        if m.is_multiple_of(2) {
+            m += p;
+        }
+        while m < high {
+            is_candidate[(m - low) as usize] = false;
+            m += 2 * p;
+        }
+    }
+
+    let mut primes = if low == 0 {
+        WHEEL_PRIMES.iter().copied().filter(|&p| p < high).collect()
+    } else {
+        vec![]
+    };
+    primes.extend(
+        is_candidate
+            .iter()
+            .enumerate()
+            .filter(|&(i, &candidate)| candidate && low + i as u64 > 1)
+            .map(|(i, _)| low + i as u64),
+    );
+    primes
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{Attack, Factors, Parameters};
+    use crate::{Attack, AttackConfig, Factors, Parameters};
 
     use super::*;
 
@@ -81,8 +260,8 @@ mod tests {
         let solution = SmallPrimeAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 
     #[test]
@@ -102,4 +281,45 @@ mod tests {
 
         assert_eq!(pk.factors, factors);
     }
+
+    #[test]
+    fn bound() {
+        // 16777259 is the first prime above the default bound
+        let p = Integer::from(16777259);
+        let q = Integer::from_str_radix("ffffffffffffffffffffffffffffff61", 16).unwrap();
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        assert!(SmallPrimeAttack.run(&params, None).is_err());
+
+        let ctx = AttackContext::new(&params).with_config(AttackConfig {
+            small_prime_bound: 1 << 25,
+            ..Default::default()
+        });
+        let solution = SmallPrimeAttack
+            .run_with_context(&params, &ctx, None)
+            .unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn sieve() {
+        let expected = primal::Primes::all()
+            .take_while(|&p| p < 3 * SEGMENT_SIZE as usize)
+            .map(|p| p as u64)
+            .collect::<Vec<_>>();
+        let base_primes = primal::Primes::all()
+            .take(1000)
+            .map(|p| p as u64)
+            .collect::<Vec<_>>();
+
+        let primes = (0..3)
+            .flat_map(|i| sieve_segment(i * SEGMENT_SIZE, (i + 1) * SEGMENT_SIZE, &base_primes))
+            .collect::<Vec<_>>();
+        assert_eq!(primes, expected);
+    }
 }
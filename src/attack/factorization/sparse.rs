@@ -5,7 +5,7 @@ use rug::Integer;
 use crate::{
     key::PrivateKey,
     utils::{log_base_ceil, solve_quadratic},
-    Attack, Error, Parameters, Solution,
+    Attack, Error, Parameters, Progress, Solution, Vulnerability,
 };
 
 const MAX_DIFFER_BITS: usize = 2; // Bigger than 2 is way too slow
@@ -19,6 +19,10 @@ impl Attack for SparseAttack {
         "sparse"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::ClosePrimes)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
@@ -26,9 +30,7 @@ impl Attack for SparseAttack {
         let one = Integer::from(1);
         let max_bits = log_base_ceil(n, 2) >> 1;
         let c = -n.clone();
-        if let Some(pb) = pb {
-            pb.set_length(MAX_DIFFER_BITS as u64);
-        }
+        pb.set_length(MAX_DIFFER_BITS as u64);
 
         // Precalculate powers of 2
         let calculated_powers = (3..=max_bits).map(|p| Integer::from(2) << p).collect_vec();
@@ -51,9 +53,7 @@ impl Attack for SparseAttack {
                     }
                 }
             }
-            if let Some(pb) = pb {
-                pb.inc(1);
-            }
+            pb.inc(1);
         }
 
         Err(Error::NotFound)
@@ -84,7 +84,7 @@ mod tests {
         let solution = SparseAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
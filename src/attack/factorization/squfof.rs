@@ -1,9 +1,15 @@
 #![allow(non_snake_case)]
 
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use indicatif::ProgressBar;
+use rayon::prelude::*;
 use rug::{ops::Pow, Integer};
 
-use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, AttackSpeed, Error, Parameters, Progress, Solution,
+    Vulnerability, STOP_CHECK_INTERVAL,
+};
 
 const MAX_ITERATIONS: u64 = 500_000;
 const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
@@ -43,8 +49,103 @@ const MULTIPLIER: [usize; 32] = [
     3 * 5 * 7 * 11 * 13,
 ];
 
+/// Run SQUFOF on n with multiplier m
+///
+/// Returns early if another multiplier found a factor.
+fn squfof(
+    n: &Integer,
+    m: usize,
+    ctx: &AttackContext,
+    found: &AtomicBool,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Integer>, Error> {
+    let check = |i: u64| -> Result<bool, Error> {
+        if i.is_multiple_of(STOP_CHECK_INTERVAL) {
+            ctx.check()?;
+            if found.load(Ordering::Relaxed) {
+                return Ok(true);
+            }
+        }
+        if i.is_multiple_of(TICK_SIZE) {
+            pb.inc(TICK_SIZE);
+        }
+        Ok(false)
+    };
+
+    let D = n.clone() * m;
+    let Po = Integer::from(D.sqrt_ref());
+    let mut Pprev = Po.clone();
+    let mut P = Po.clone();
+    let mut Qprev = Integer::from(1);
+    let mut Q = &D - (Po.clone() * &Po);
+
+    let mut r = Integer::from(Q.sqrt_ref());
+    for i in 2..MAX_ITERATIONS {
+        // Prevent division by zero
+        if Q == 0 {
+            return Ok(None);
+        }
+
+        let b = (Po.clone() + &P) / &Q;
+        P = b.clone() * &Q - &P;
+        let q = Q.clone();
+        Q = Qprev + b * (Pprev - &P);
+        r = Integer::from(Q.sqrt_ref());
+
+        if i & 1 == 0 && r.clone().pow(2) == Q {
+            break;
+        }
+        Pprev = P.clone();
+        Qprev = q;
+
+        if check(i)? {
+            return Ok(None);
+        }
+    }
+    // Check if we found a solution
+    if r.clone().pow(2) != Q {
+        return Ok(None);
+    }
+
+    Pprev = (Po.clone() - &P) / &r * &r + &P;
+    P = Pprev.clone();
+    Qprev = r.clone();
+    Q = (D - (Pprev.clone().pow(2))) / &Qprev;
+
+    for i in 2..MAX_ITERATIONS {
+        // Prevent division by zero
+        if Q == 0 {
+            return Ok(None);
+        }
+
+        let b = (Po.clone() + &P) / &Q;
+        Pprev = P.clone();
+        P = b.clone() * &Q - &P;
+        let q = Q.clone();
+        Q = Qprev + b * (Pprev.clone() - &P);
+        Qprev = q;
+
+        if P == Pprev {
+            // Solution found
+            let r = Integer::from(n.gcd_ref(&Qprev));
+            if 1 < r && r < *n {
+                found.store(true, Ordering::Relaxed);
+                return Ok(Some(r));
+            }
+        }
+
+        if check(i)? {
+            return Ok(None);
+        }
+    }
+
+    Ok(None)
+}
+
 /// Shank's square forms factorization attack
 ///
+/// The multipliers are tried concurrently on the available threads.
+///
 /// See <https://en.wikipedia.org/wiki/Shanks%27s_square_forms_factorization>
 /// See <https://github.com/daedalus/integer_factorization_algorithms/blob/main/SQUFOF.py>
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -59,7 +160,20 @@ impl Attack for SqufofAttack {
         AttackSpeed::Slow
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SmallModulus)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
@@ -67,95 +181,28 @@ impl Attack for SqufofAttack {
             return Err(Error::NotFound);
         }
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS * 2 * MULTIPLIER.len() as u64);
-        }
-        for (index, m) in MULTIPLIER.iter().enumerate() {
-            if let Some(pb) = pb {
-                pb.set_position(MAX_ITERATIONS * 2 * index as u64);
-            }
-
-            let D = n.clone() * m;
-            let Po = Integer::from(D.sqrt_ref());
-            let mut Pprev = Po.clone();
-            let mut P = Po.clone();
-            let mut Qprev = Integer::from(1);
-            let mut Q = &D - (Po.clone() * &Po);
-
-            let mut r = Integer::from(Q.sqrt_ref());
-            for i in 2..MAX_ITERATIONS {
-                // Prevent division by zero
-                if Q == 0 {
-                    return Err(Error::NotFound);
-                }
-
-                let b = (Po.clone() + &P) / &Q;
-                P = b.clone() * &Q - &P;
-                let q = Q.clone();
-                Q = Qprev + b * (Pprev - &P);
-                r = Integer::from(Q.sqrt_ref());
-
-                if i & 1 == 0 && r.clone().pow(2) == Q {
-                    break;
-                }
-                Pprev = P.clone();
-                Qprev = q;
-
-                if i % TICK_SIZE == 0 {
-                    if let Some(pb) = pb {
-                        pb.inc(TICK_SIZE);
-                    }
-                }
-            }
-            // Check if we found a solution
-            if r.clone().pow(2) != Q {
-                continue;
-            }
-
-            Pprev = (Po.clone() - &P) / &r * &r + &P;
-            P = Pprev.clone();
-            Qprev = r.clone();
-            Q = (D - (Pprev.clone().pow(2))) / &Qprev;
-
-            for i in 2..MAX_ITERATIONS {
-                // Prevent division by zero
-                if Q == 0 {
-                    return Err(Error::NotFound);
-                }
-
-                let b = (Po.clone() + &P) / &Q;
-                Pprev = P.clone();
-                P = b.clone() * &Q - &P;
-                let q = Q.clone();
-                Q = Qprev + b * (Pprev.clone() - &P);
-                Qprev = q;
-
-                if P == Pprev {
-                    // Solution found
-                    let r = Integer::from(n.gcd_ref(&Qprev));
-                    if 1 < r && r < *n {
-                        return Ok(Solution::new_pk(
-                            self.name(),
-                            PrivateKey::from_p_q(&r, n / &r, e)?,
-                        ));
-                    }
-                }
-
-                if i % TICK_SIZE == 0 {
-                    if let Some(pb) = pb {
-                        pb.inc(TICK_SIZE);
-                    }
-                }
-            }
-        }
-
-        Err(Error::NotFound)
+        pb.set_length(MAX_ITERATIONS * 2 * MULTIPLIER.len() as u64);
+
+        let pool = ctx.thread_pool()?;
+        let found = AtomicBool::new(false);
+        let p = pool
+            .install(|| {
+                MULTIPLIER
+                    .par_iter()
+                    .find_map_any(|&m| squfof(n, m, ctx, &found, pb).transpose())
+            })
+            .ok_or(Error::NotFound)??;
+
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(&p, n / &p, e)?,
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Attack, Parameters};
+    use crate::{Attack, AttackConfig, Parameters};
 
     use super::*;
 
@@ -171,7 +218,27 @@ mod tests {
         let solution = SqufofAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn many_threads() {
+        let p = Integer::from(1779681653);
+        let q = Integer::from(1903643191);
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+
+        let ctx = AttackContext::new(&params).with_config(AttackConfig {
+            threads: 8,
+            ..Default::default()
+        });
+        let solution = SqufofAttack.run_with_context(&params, &ctx, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
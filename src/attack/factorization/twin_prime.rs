@@ -1,10 +1,12 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{key::PrivateKey, Attack, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackContext, Error, Parameters, Progress, Solution, Vulnerability,
+    STOP_CHECK_INTERVAL,
+};
 
 const MAX_ITERATIONS: u64 = 1_000_000;
-const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
 
 /// Twin prime factorization attack
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -15,17 +17,30 @@ impl Attack for TwinPrimeAttack {
         "twin_prime"
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::ClosePrimes)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+        let tick_size = (max_iterations / 100).max(1);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS);
-        }
+        pb.set_length(max_iterations);
 
         let base = Integer::from(n + 1).sqrt();
 
-        for i in 1..MAX_ITERATIONS {
+        for i in 1..max_iterations {
             // TODO: Remove useless tries when p or q is even
             let p = base.clone() + i - Integer::from(1);
             let q = base.clone() - i - Integer::from(1);
@@ -54,10 +69,11 @@ impl Attack for TwinPrimeAttack {
                 ));
             }
 
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if i.is_multiple_of(STOP_CHECK_INTERVAL) {
+                ctx.check()?;
+            }
+            if i.is_multiple_of(tick_size) {
+                pb.inc(tick_size);
             }
         }
 
@@ -86,8 +102,8 @@ mod tests {
         let solution = TwinPrimeAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 
     #[test]
@@ -103,7 +119,7 @@ mod tests {
         let solution = TwinPrimeAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
@@ -0,0 +1,97 @@
+use indicatif::ProgressBar;
+use lazy_static::lazy_static;
+use rug::Integer;
+
+use crate::{key::PrivateKey, Attack, AttackSpeed, Error, Parameters, Solution, Vulnerability};
+
+lazy_static! {
+    /// Factors of the public RSA challenge moduli, reused in CTF challenges
+    static ref WEAK_PRIMES: Vec<Integer> = include_str!("weak_primes.txt")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| Integer::from_str_radix(line, 16).unwrap())
+        .collect();
+
+    /// Product of all weak primes, to test them all with a single gcd
+    static ref WEAK_PRIMES_PRODUCT: Integer = Integer::product(WEAK_PRIMES.iter()).into();
+}
+
+/// Known weak primes attack (n shares a prime with a publicly factored modulus)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeakPrimesAttack;
+
+impl Attack for WeakPrimesAttack {
+    fn name(&self) -> &'static str {
+        "weak_primes"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Fast
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::KnownWeakPrime)
+    }
+
+    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        let e = &params.e;
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+
+        let p = find_weak_prime(n).ok_or(Error::NotFound)?;
+        let q = Integer::from(n / p);
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p, q, e)?,
+        ))
+    }
+}
+
+/// Returns the known weak prime dividing n, if any
+pub(crate) fn find_weak_prime(n: &Integer) -> Option<&'static Integer> {
+    if Integer::from(WEAK_PRIMES_PRODUCT.gcd_ref(n)) == 1 {
+        return None;
+    }
+    WEAK_PRIMES.iter().find(|p| *p < n && n.is_divisible(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{Attack, Parameters};
+
+    use super::*;
+
+    #[test]
+    fn attack() {
+        // RSA-100
+        let params = Parameters {
+            n: Some(Integer::from_str("1522605027922533360535618378132637429718068114961380688657908494580122963258952897654000350692006139").unwrap()),
+            ..Default::default()
+        };
+
+        let solution = WeakPrimesAttack.run(&params, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p().unwrap() * pk.q().unwrap(), params.n.unwrap());
+        assert!(WEAK_PRIMES.contains(&pk.p().unwrap()));
+    }
+
+    #[test]
+    fn reused_prime() {
+        let p = WEAK_PRIMES[0].clone();
+        let q = Integer::from(1903643191);
+
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+
+        let solution = WeakPrimesAttack.run(&params, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.p(), Some(q));
+        assert_eq!(pk.q(), Some(p));
+    }
+}
@@ -2,7 +2,8 @@ use indicatif::ProgressBar;
 use rug::{ops::Pow, Integer};
 
 use crate::{
-    key::PrivateKey, utils::solve_quadratic, Attack, AttackKind, Error, Parameters, Solution,
+    key::PrivateKey, utils::solve_quadratic, Attack, AttackContext, AttackKind, Error, Parameters,
+    Progress, Solution, Vulnerability, STOP_CHECK_INTERVAL,
 };
 
 const MAX_ITERATIONS: u64 = 1_000_000;
@@ -24,26 +25,37 @@ impl Attack for GaaAttack {
         AttackKind::KnownExtraInformation
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
         let rp = params.p.as_ref().ok_or(Error::MissingParameters)?;
         let rq = params.q.as_ref().ok_or(Error::MissingParameters)?;
         let one = Integer::from(1);
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS);
-        }
+        pb.set_length(MAX_ITERATIONS);
 
         // k = ceil(sqrt(rp * rq))
         let mut k = match Integer::from(rp * rq).sqrt_rem(Integer::ZERO) {
             (root, rem) if rem == Integer::ZERO => root,
             (root, _) => root + 1,
         };
-        let n_sqrt = Integer::from(n.sqrt_ref());
+        let n_sqrt = ctx.precomputed().n_sqrt().ok_or(Error::MissingParameters)?;
 
         for i in 0..MAX_ITERATIONS {
-            let sigma = (n_sqrt.clone() - &k).pow(2);
+            let sigma = Integer::from(n_sqrt - &k).pow(2);
             let z = (n - Integer::from(rp * rq)) % &sigma;
 
             // Solve: x^2 - z * x + sigma * rp * rq = 0
@@ -74,10 +86,11 @@ impl Attack for GaaAttack {
                 }
             }
 
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if i.is_multiple_of(STOP_CHECK_INTERVAL) {
+                ctx.check()?;
+            }
+            if i.is_multiple_of(TICK_SIZE) {
+                pb.inc(TICK_SIZE);
             }
 
             k += 1;
@@ -111,8 +124,8 @@ mod tests {
         let solution = GaaAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 
     #[test]
@@ -134,7 +147,7 @@ mod tests {
         let solution = GaaAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
@@ -0,0 +1,119 @@
+use indicatif::ProgressBar;
+
+use crate::{
+    bytes_to_integer, Attack, AttackContext, AttackKind, AttackSpeed, BruteForce, Error,
+    Parameters, Solution, Vulnerability,
+};
+
+/// Guess message attack (the message is short and low entropy, e.g. a PIN or a word)
+///
+/// Without padding, encryption is deterministic: each candidate of the message guesses is
+/// encrypted under the public key and compared to the cipher, whatever the size of n.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuessMessageAttack;
+
+impl Attack for GuessMessageAttack {
+    fn name(&self) -> &'static str {
+        "guess_message"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Medium
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::GuessableMessage)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let c = params.c.as_ref().ok_or(Error::MissingParameters)?;
+        let guesses = &ctx.config().message_guesses;
+        if guesses.count() == 0 {
+            return Err(Error::MissingParameters);
+        }
+
+        BruteForce::new(0..guesses.count())
+            .run(ctx, pb, |range| {
+                range.into_iter().find_map(|i| {
+                    let m = bytes_to_integer(&guesses.get(i)?);
+                    m.clone()
+                        .pow_mod(&params.e, n)
+                        .is_ok_and(|m_e| m_e == *c)
+                        .then_some(m)
+                })
+            })?
+            .map(|m| Solution::new_m(self.name(), m))
+            .ok_or(Error::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, sync::Arc};
+
+    use rug::Integer;
+
+    use crate::{AttackConfig, MessageGuesses};
+
+    use super::*;
+
+    #[test]
+    fn attack() {
+        let n = Integer::from_str(
+            "95118357989037539883272168746004652872958890562445814301889866663072352421703264985997800660075311645555799745426868343365321502734736006248007902409628540578635925559742217480797487130202747020211452620743021097565113059392504472785227154824117231077844444672393221838192941390309312484066647007469668558141",
+        )
+        .unwrap();
+        let guesses = |words: &[&str]| {
+            let config = AttackConfig {
+                message_guesses: MessageGuesses {
+                    words: Arc::new(words.iter().map(|w| w.as_bytes().to_vec()).collect()),
+                    charset: b"0123456789".to_vec(),
+                    max_length: 4,
+                },
+                ..Default::default()
+            };
+            AttackContext::default().with_config(config)
+        };
+        let encrypt = |m: &[u8]| bytes_to_integer(m).pow_mod(&65537.into(), &n).unwrap();
+
+        // A PIN
+        let params = Parameters {
+            n: Some(n.clone()),
+            c: Some(encrypt(b"4821")),
+            ..Default::default()
+        };
+        let solution = GuessMessageAttack
+            .run_with_context(&params, &guesses(&[]), None)
+            .unwrap();
+        assert_eq!(solution.m.unwrap(), bytes_to_integer(b"4821"));
+
+        // A word
+        let params = Parameters {
+            n: Some(n.clone()),
+            c: Some(encrypt(b"hunter2")),
+            ..Default::default()
+        };
+        let solution = GuessMessageAttack
+            .run_with_context(&params, &guesses(&["password", "hunter2"]), None)
+            .unwrap();
+        assert_eq!(solution.m.unwrap(), bytes_to_integer(b"hunter2"));
+        assert_eq!(
+            GuessMessageAttack.run(&params, None).unwrap_err(),
+            Error::MissingParameters
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use rug::Integer;
+
+use crate::{
+    ntheory::crt, Attack, AttackKind, AttackSpeed, Error, Parameters, Solution, Vulnerability,
+};
+
+/// Largest number of key subsets tried per exponent
+const MAX_SUBSETS: usize = 10_000;
+
+/// Håstad's broadcast attack (same message encrypted with the same small e under several moduli)
+///
+/// Keys are grouped by exponent. When a group has more than e keys, each subset of e keys is tried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HastadAttack;
+
+impl Attack for HastadAttack {
+    fn name(&self) -> &'static str {
+        "hastad"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Fast
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LowExponent)
+    }
+
+    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        // Keys with a cipher grouped by exponent, the main exponent first
+        let mut groups = BTreeMap::<u32, Vec<usize>>::new();
+        for (i, key) in params.keys.iter().enumerate() {
+            if let (Some(e), Some(_)) = (key.e.to_u32(), &key.c) {
+                if e >= 2 {
+                    groups.entry(e).or_default().push(i);
+                }
+            }
+        }
+        let mut groups = groups
+            .into_iter()
+            .filter(|(_, indices)| indices.len() >= 2)
+            .collect::<Vec<_>>();
+        if groups.is_empty() {
+            return Err(Error::MissingParameters);
+        }
+        groups.sort_by_key(|(e, _)| params.e != *e);
+
+        for (e, indices) in groups {
+            let mut subsets = vec![indices.clone()];
+            // Only e keys are needed, the others may hold other messages
+            if indices.len() > e as usize {
+                subsets.extend(
+                    indices
+                        .into_iter()
+                        .combinations(e as usize)
+                        .take(MAX_SUBSETS),
+                );
+            }
+            for subset in subsets {
+                if let Some(m) = broadcast_message(params, &subset, e) {
+                    return Ok(Solution::new_m(self.name(), m)
+                        .with_vulnerability(Vulnerability::BroadcastMessage(subset)));
+                }
+            }
+        }
+        Err(Error::NotFound)
+    }
+}
+
+/// Message whose e-th power is the CRT of the ciphers of the keys, given by index
+fn broadcast_message(params: &Parameters, indices: &[usize], e: u32) -> Option<Integer> {
+    let (ciphers, moduli): (Vec<_>, Vec<_>) = indices
+        .iter()
+        .map(|&i| &params.keys[i])
+        .filter_map(|key| Some((key.c.clone()?, key.n.clone())))
+        .unzip();
+
+    // m^e < lcm(n1, n2, ..., nk), so it is the CRT of the ciphers (the same key may be repeated)
+    let me = crt(&ciphers, &moduli)?;
+    let (m, rem) = me.root_rem(Integer::new(), e);
+    (rem == 0).then_some(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rug::ops::Pow;
+
+    use crate::{bytes_to_integer, Attack, KeyEntry, Parameters};
+
+    use super::*;
+
+    fn moduli() -> [Integer; 3] {
+        [
+            "95118357989037539883272168746004652872958890562445814301889866663072352421703264985997800660075311645555799745426868343365321502734736006248007902409628540578635925559742217480797487130202747020211452620743021097565113059392504472785227154824117231077844444672393221838192941390309312484066647007469668558141",
+            "98364165919251246243846667323542318022804234833677924161175733253689581393607346667895298253718184273532268982060905629399628154981918712070241451494491161470827737146176316011843738943427121602324208773653180782732999422869439588198318422451697920640563880777385577064913983202033744281727004289781821019463",
+            "68827940939353189613090392226898155021742772897822438483545021944215812146809318686510375724064888705296373853398955093076663323001380047857809774866390083434272781362447147441422207967577323769812896038816586757242130224524828935043187315579523412439309138816335569845470021720847405857361000537204746060031",
+        ]
+        .map(|n| Integer::from_str(n).unwrap())
+    }
+
+    fn key(n: &Integer, e: u32, m: &Integer) -> KeyEntry {
+        KeyEntry {
+            n: n.clone(),
+            e: e.into(),
+            c: Some(m.clone().pow_mod(&e.into(), n).unwrap()),
+            known: Parameters::default(),
+        }
+    }
+
+    #[test]
+    fn attack() {
+        let m = bytes_to_integer(
+            b"RsaCracker! The same message was sent to several recipients, with e = 3 each time",
+        );
+        let moduli = moduli();
+
+        let mut keys = moduli.iter().map(|n| key(n, 3, &m)).collect::<Vec<_>>();
+        // The same key listed twice, the congruences still agree
+        keys.push(keys[0].clone());
+        let params = Parameters {
+            n: Some(keys[0].n.clone()),
+            e: 3.into(),
+            c: keys[0].c.clone(),
+            keys,
+            ..Default::default()
+        };
+
+        assert!(m.clone().pow(3) > moduli[0]);
+        let solution = HastadAttack.run(&params, None).unwrap();
+        assert_eq!(solution.m.unwrap(), m);
+    }
+
+    #[test]
+    fn exponent_groups() {
+        let m = bytes_to_integer(
+            b"RsaCracker! The same message was sent to several recipients, with e = 3 each time",
+        );
+        let other_m = bytes_to_integer(b"RsaCracker! Another message");
+        let [n1, n2, n3] = moduli();
+        let prime = n1.clone().next_prime();
+
+        // Only the keys 1, 3 and 4 share the message and e
+        let keys = vec![
+            key(&n1, 65537, &m),
+            key(&n1, 3, &m),
+            key(&prime, 3, &other_m),
+            key(&n2, 3, &m),
+            key(&n3, 3, &m),
+        ];
+        let params = Parameters {
+            n: Some(n1),
+            c: keys[0].c.clone(),
+            keys,
+            ..Default::default()
+        };
+
+        let solution = HastadAttack.run(&params, None).unwrap();
+        assert_eq!(solution.m.unwrap(), m);
+        assert_eq!(
+            solution.vulnerability,
+            Some(Vulnerability::BroadcastMessage(vec![1, 3, 4]))
+        );
+    }
+}
@@ -3,7 +3,7 @@ use rug::{ops::Pow, rand::RandState, Integer};
 
 use crate::{
     key::PrivateKey, utils::log_base_ceil, Attack, AttackKind, AttackSpeed, Error, Parameters,
-    Solution,
+    Progress, Solution, Vulnerability,
 };
 
 /// Known phi attack
@@ -25,7 +25,11 @@ impl Attack for KnownDAttack {
         AttackKind::KnownExtraInformation
     }
 
-    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
         let d = params.d.as_ref().ok_or(Error::MissingParameters)?;
@@ -36,6 +40,7 @@ impl Attack for KnownDAttack {
         let mut rgen = RandState::new();
         loop {
             let g = (n.clone() - Integer::from(1)).random_below(&mut rgen) + Integer::from(1);
+            pb.count(1, "witnesses");
             for s in 1..=bits as u32 {
                 let x = Integer::from(g.pow_mod_ref(&(&k / Integer::from(2).pow(s)), n).unwrap());
                 let p = Integer::from(n.gcd_ref(&(x - Integer::from(1))));
@@ -75,8 +80,8 @@ mod tests {
         let solution = KnownDAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 
     #[test]
@@ -95,7 +100,7 @@ mod tests {
         let solution = KnownDAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
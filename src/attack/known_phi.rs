@@ -1,7 +1,10 @@
 use indicatif::ProgressBar;
 use rug::{integer::IsPrime, ops::Pow, rand::RandState, Integer};
 
-use crate::{key::PrivateKey, Attack, AttackKind, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, ntheory::is_prime, Attack, AttackKind, AttackSpeed, Error, Parameters,
+    Solution, Vulnerability,
+};
 
 /// See https://github.com/jvdsn/crypto-attacks/blob/master/attacks/factorization/known_phi.py
 pub fn factorize(n: &Integer, phi: &Integer) -> Option<(Integer, Integer)> {
@@ -21,7 +24,7 @@ pub fn factorize(n: &Integer, phi: &Integer) -> Option<(Integer, Integer)> {
     }
 
     // Check if p and q are prime
-    if p.is_probably_prime(100) == IsPrime::No || q.is_probably_prime(100) == IsPrime::No {
+    if is_prime(&p) == IsPrime::No || is_prime(&q) == IsPrime::No {
         return None;
     }
 
@@ -48,13 +51,13 @@ fn factorize_multi_factors(n: &Integer, phi: &Integer) -> Option<Vec<Integer>> {
                 let p = Integer::from(n.gcd_ref(&(sqrt_1 + 1)));
                 let q = n.clone() / &p;
 
-                if p.is_probably_prime(100) != IsPrime::No {
+                if is_prime(&p) != IsPrime::No {
                     prime_factors.insert(p);
                 } else if p > 1 {
                     factors.push(p);
                 }
 
-                if q.is_probably_prime(100) != IsPrime::No {
+                if is_prime(&q) != IsPrime::No {
                     prime_factors.insert(q);
                 } else if q > 1 {
                     factors.push(q);
@@ -87,6 +90,10 @@ impl Attack for KnownPhiAttack {
         AttackKind::KnownExtraInformation
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
     fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = params.e.clone();
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
@@ -131,8 +138,8 @@ mod tests {
         let solution = KnownPhiAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 
     #[test]
@@ -149,8 +156,8 @@ mod tests {
         let solution = KnownPhiAttack.run(&params, None).unwrap();
         let pk = solution.pk.unwrap();
 
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 
     #[test]
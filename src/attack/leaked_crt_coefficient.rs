@@ -2,7 +2,8 @@ use indicatif::ProgressBar;
 use rug::{integer::IsPrime, Integer};
 
 use crate::{
-    key::PrivateKey, utils::solve_quadratic, Attack, AttackKind, Error, Parameters, Solution,
+    key::PrivateKey, ntheory::is_prime, utils::solve_quadratic, Attack, AttackKind, Error,
+    Parameters, Progress, Solution, Vulnerability,
 };
 
 fn find_phi(e: &Integer, d: &Integer) -> impl Iterator<Item = Integer> {
@@ -29,7 +30,7 @@ fn find_p_q_from_phi(phi: &Integer, qinv: &Integer, pinv: &Integer) -> Option<(I
     let c: Integer = pinv.clone() * phi - phi;
 
     for x in solve_quadratic(&a, &b, &c) {
-        if Integer::from(&x + 1).is_probably_prime(100) != IsPrime::No {
+        if is_prime(&Integer::from(&x + 1)) != IsPrime::No {
             let q = x.clone() + 1;
             let p = phi / x + 1;
             return Some((p, q));
@@ -40,6 +41,8 @@ fn find_p_q_from_phi(phi: &Integer, qinv: &Integer, pinv: &Integer) -> Option<(I
 }
 
 /// Leaked CRT coefficient attack
+///
+/// The phi recovered from d is also passed on to the attacks using it.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LeakedCrtCoefficientAttack;
 
@@ -52,7 +55,11 @@ impl Attack for LeakedCrtCoefficientAttack {
         AttackKind::KnownExtraInformation
     }
 
-    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let qinv = params.qinv.as_ref().ok_or(Error::MissingParameters)?;
         let pinv = params.pinv.as_ref().ok_or(Error::MissingParameters)?;
@@ -65,11 +72,16 @@ impl Attack for LeakedCrtCoefficientAttack {
             ));
         } else if let Some(d) = params.d.as_ref() {
             for phi in find_phi(e, d) {
+                pb.count(1, "phi candidates");
                 if let Some((p, q)) = find_p_q_from_phi(&phi, qinv, pinv) {
-                    return Ok(Solution::new_pk(
-                        self.name(),
-                        PrivateKey::from_p_q(p, q, e)?,
-                    ));
+                    let discovered = Parameters {
+                        phi: Some(phi),
+                        ..Default::default()
+                    };
+                    return Ok(
+                        Solution::new_pk(self.name(), PrivateKey::from_p_q(p, q, e)?)
+                            .with_discovered(discovered),
+                    );
                 }
             }
         }
@@ -104,6 +116,7 @@ mod tests {
         };
 
         let solution = LeakedCrtCoefficientAttack.run(&params, None).unwrap();
+        assert_eq!(solution.discovered.unwrap().phi, Some(factors.phi()));
         let pk = solution.pk.unwrap();
 
         assert_eq!(pk.factors, factors);
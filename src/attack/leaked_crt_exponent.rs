@@ -1,7 +1,9 @@
 use indicatif::ProgressBar;
 use rug::{Complete, Integer};
 
-use crate::{key::PrivateKey, Attack, AttackKind, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, Attack, AttackKind, AttackSpeed, Error, Parameters, Solution, Vulnerability,
+};
 
 /// Leaked CRT exponent attack
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,6 +22,10 @@ impl Attack for LeakedCrtExponentAttack {
         AttackKind::KnownExtraInformation
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
     fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
@@ -1,7 +1,10 @@
 use indicatif::ProgressBar;
 use rug::{integer::IsPrime, Integer};
 
-use crate::{key::PrivateKey, Attack, AttackKind, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    key::PrivateKey, ntheory::is_prime, Attack, AttackContext, AttackKind, AttackSpeed, BruteForce,
+    Error, Parameters, Solution, Vulnerability,
+};
 
 /// Recover modulus and primes from CRT exponents dP, dQ and qInv
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,7 +23,20 @@ impl Attack for LeakedCrtExponentsAttack {
         AttackKind::KnownExtraInformation
     }
 
-    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = match params.e.to_u32() {
             Some(e) => e,
             None => return Err(Error::NotFound),
@@ -42,40 +58,44 @@ impl Attack for LeakedCrtExponentsAttack {
 
         let one = Integer::from(1);
         let d1p = dp.clone() * e - &one;
+        let d1q = dq.clone() * e - &one;
 
         // Brute force p
-        for k in 3..e {
-            if d1p.clone() % k == 0 {
-                let p = d1p.clone() / k + &one;
+        let (p, q) = BruteForce::new(3..e.max(3) as u64)
+            .run(ctx, pb, |range| {
+                range.into_iter().find_map(|k| {
+                    if !d1p.is_divisible_u(k as u32) {
+                        return None;
+                    }
+                    let p = d1p.clone() / k + &one;
 
-                // If p is prime, p may be the modulus
-                if p.is_probably_prime(100) != IsPrime::No {
-                    let d1q = dq.clone() * e - &one;
+                    // If p is prime, p may be the modulus
+                    if is_prime(&p) == IsPrime::No {
+                        return None;
+                    }
 
                     // Brute force q
-                    for m in 3..e {
-                        if d1q.clone() % m == 0 {
-                            let q = d1q.clone() / m + &one;
-
-                            // If q is prime, q may be the modulus
-                            if q.is_probably_prime(100) != IsPrime::No {
-                                // If p and q satisfy the CRT, we have found the modulus
-                                if (qinv * q.clone()) % p.clone() == 1
-                                    || (qinv * p.clone()) % q.clone() == 1
-                                {
-                                    return Ok(Solution::new_pk(
-                                        self.name(),
-                                        PrivateKey::from_p_q(p, q, e)?,
-                                    ));
-                                }
-                            }
+                    (3..e).find_map(|m| {
+                        if !d1q.is_divisible_u(m) {
+                            return None;
                         }
-                    }
-                }
-            }
-        }
-
-        Err(Error::NotFound)
+                        let q = d1q.clone() / m + &one;
+
+                        // If q is prime, q may be the modulus, and if p and q satisfy the CRT,
+                        // we have found the modulus
+                        (is_prime(&q) != IsPrime::No
+                            && ((qinv * q.clone()) % p.clone() == 1
+                                || (qinv * p.clone()) % q.clone() == 1))
+                            .then(|| (p.clone(), q))
+                    })
+                })
+            })?
+            .ok_or(Error::NotFound)?;
+
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p, q, e)?,
+        ))
     }
 }
 
@@ -2,11 +2,21 @@ use indicatif::ProgressBar;
 use lazy_static::lazy_static;
 use std::sync::Arc;
 
+mod branch_and_prune;
+mod brute_force;
+mod budget;
 mod cipolla;
 mod comfact_cn;
+mod common_modulus;
+mod config;
+mod context;
 mod cube_root;
+mod docs;
+mod eth_root;
 mod factorization;
 mod gaa;
+mod guess_message;
+mod hastad;
 mod known_d;
 mod known_phi;
 mod leaked_crt_coefficient;
@@ -14,20 +24,43 @@ mod leaked_crt_exponent;
 mod leaked_crt_exponents;
 mod leaked_pq;
 mod non_coprime_exp;
+mod p_mod;
+mod partial_crt_exponent;
 mod partial_d;
+mod partial_m;
+mod partial_pq;
+mod partial_prime;
 mod prime_modulus;
+mod profile;
+mod progress;
+mod resume;
+mod signature_dlog;
 mod small_e;
+mod small_message_mitm;
 mod sum_pq;
+mod symmetric_pq;
 mod wiener;
+mod xor_pq;
 
 use crate::Factors;
-use crate::{Parameters, Solution};
+use crate::{Parameters, Solution, Vulnerability};
 
+pub use branch_and_prune::BranchAndPruneAttack;
+pub use brute_force::BruteForce;
+pub use budget::allocate_time_budget;
 pub use cipolla::CipollaAttack;
 pub use comfact_cn::ComfactCnAttack;
+pub use common_modulus::CommonModulusAttack;
+pub use config::{AttackConfig, CandidateCheck, MessageGuesses, PlaintextConstraint, Shard};
+pub(crate) use context::STOP_CHECK_INTERVAL;
+pub use context::{AttackContext, MemoryReservation, PrecomputedContext};
 pub use cube_root::CubeRootAttack;
+pub use docs::{attack_doc, AttackDoc};
+pub use eth_root::EthRootAttack;
 pub use factorization::*;
 pub use gaa::GaaAttack;
+pub use guess_message::GuessMessageAttack;
+pub use hastad::HastadAttack;
 pub use known_d::KnownDAttack;
 pub use known_phi::KnownPhiAttack;
 pub use leaked_crt_coefficient::LeakedCrtCoefficientAttack;
@@ -35,11 +68,23 @@ pub use leaked_crt_exponent::LeakedCrtExponentAttack;
 pub use leaked_crt_exponents::LeakedCrtExponentsAttack;
 pub use leaked_pq::LeakedPQAttack;
 pub use non_coprime_exp::NonCoprimeExpAttack;
+pub use p_mod::PModAttack;
+pub use partial_crt_exponent::PartialCrtExponentAttack;
 pub use partial_d::PartialDAttack;
+pub use partial_m::PartialMessageAttack;
+pub use partial_pq::PartialPQAttack;
+pub use partial_prime::PartialPrimeAttack;
 pub use prime_modulus::PrimeModulusAttack;
+pub use profile::AttackProfile;
+pub use progress::Progress;
+pub use resume::{AttackState, ResumableAttack};
+pub use signature_dlog::SignatureDlogAttack;
 pub use small_e::SmallEAttack;
+pub use small_message_mitm::SmallMessageMitmAttack;
 pub use sum_pq::SumPQAttack;
+pub use symmetric_pq::SymmetricPQAttack;
 pub use wiener::WienerAttack;
+pub use xor_pq::XorPQAttack;
 
 /// Attack error
 #[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
@@ -56,6 +101,18 @@ pub enum Error {
     /// Partial factorization
     #[error("Partial factorization: {0:?}")]
     PartialFactorization(Factors),
+    /// Parameters derived by the attack, without a solution
+    #[error("Discovered parameters")]
+    Discovered(Box<Parameters>),
+    /// Attack stopped before completion
+    #[error("Attack stopped")]
+    Stopped,
+    /// No attack of this name
+    #[error("Unknown attack: {0}")]
+    UnknownAttack(String),
+    /// The thread pool of the attacks could not be built
+    #[error("Thread pool: {0}")]
+    ThreadPool(String),
 }
 
 impl From<crate::key::KeyError> for Error {
@@ -146,16 +203,47 @@ pub trait Attack: std::fmt::Debug {
         AttackKind::Factorization
     }
 
+    /// Returns the weakness of the key exploited by the attack, if it is specific to one
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        None
+    }
+
     /// Runs the attack
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error>;
+
+    /// Runs the attack, stopping early if asked to by the context
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        ctx.check()?;
+        self.run(params, pb)
+    }
+
+    /// Returns the attack as a resumable attack, if it can export its state when it is stopped
+    fn as_resumable(&self) -> Option<&dyn ResumableAttack> {
+        None
+    }
+
+    /// Returns the documentation of the attack: references, complexity and success conditions
+    fn doc(&self) -> Option<&'static AttackDoc> {
+        attack_doc(self.name())
+    }
 }
 
 lazy_static! {
     static ref _ATTACKS: Vec<Arc<dyn Attack + Sync + Send>> = vec![
+        Arc::new(BranchAndPruneAttack),
         Arc::new(CipollaAttack),
         Arc::new(ComfactCnAttack),
+        Arc::new(CommonModulusAttack),
         Arc::new(CubeRootAttack),
+        Arc::new(EthRootAttack),
         Arc::new(GaaAttack),
+        Arc::new(GuessMessageAttack),
+        Arc::new(HastadAttack),
         Arc::new(KnownDAttack),
         Arc::new(KnownPhiAttack),
         Arc::new(LeakedCrtCoefficientAttack),
@@ -163,11 +251,20 @@ lazy_static! {
         Arc::new(LeakedCrtExponentsAttack),
         Arc::new(LeakedPQAttack),
         Arc::new(NonCoprimeExpAttack),
+        Arc::new(PModAttack),
+        Arc::new(PartialCrtExponentAttack),
         Arc::new(PartialDAttack),
+        Arc::new(PartialMessageAttack),
+        Arc::new(PartialPQAttack),
+        Arc::new(PartialPrimeAttack),
         Arc::new(PrimeModulusAttack),
+        Arc::new(SignatureDlogAttack),
         Arc::new(SmallEAttack),
+        Arc::new(SmallMessageMitmAttack),
         Arc::new(SumPQAttack),
+        Arc::new(SymmetricPQAttack),
         Arc::new(WienerAttack),
+        Arc::new(XorPQAttack),
     ];
 
     /// List of attacks
@@ -1,7 +1,10 @@
 use indicatif::ProgressBar;
 use rug::{integer::IsPrime, ops::Pow, Integer};
 
-use crate::{ntheory::crt, Attack, AttackKind, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    ntheory::{crt, is_prime},
+    Attack, AttackKind, AttackSpeed, Error, Parameters, Solution, Vulnerability,
+};
 
 use super::known_phi::factorize as factorize_from_phi;
 
@@ -24,6 +27,10 @@ impl Attack for NonCoprimeExpAttack {
         AttackKind::KnownExtraInformation
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::NonCoprimeExponent)
+    }
+
     fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let e_u32 = match e.to_u32() {
@@ -41,7 +48,7 @@ impl Attack for NonCoprimeExpAttack {
 
         if (phi.clone() / e).gcd(e) == 1 {
             // E must be prime
-            if e.is_probably_prime(100) == IsPrime::No {
+            if is_prime(e) == IsPrime::No {
                 return Err(Error::NotFound);
             }
 
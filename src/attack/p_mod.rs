@@ -0,0 +1,158 @@
+use indicatif::ProgressBar;
+use rug::Integer;
+
+use crate::{
+    key::PrivateKey, lattice::small_roots_mod_factor, Attack, AttackContext, AttackKind,
+    AttackSpeed, BruteForce, Error, Parameters, Solution, Vulnerability,
+};
+
+/// Largest number of candidates tried by brute force for each prime
+const MAX_BRUTE_FORCE: u64 = 1 << 24;
+
+/// Known residue of a prime attack (p mod m, e.g. the last decimal digits of p)
+///
+/// The residue of the other prime is n / p modulo m. The smaller prime is below sqrt(n), so its
+/// candidates r + m * x are tried when there are few of them. Otherwise, for balanced primes,
+/// x is a small root of x + r / m modulo p, found with Coppersmith's method when m is larger than
+/// about n^(1/4).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PModAttack;
+
+impl Attack for PModAttack {
+    fn name(&self) -> &'static str {
+        "p_mod"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Medium
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let (residue, m) = params.p_mod.as_ref().ok_or(Error::MissingParameters)?;
+        if *m <= 1 {
+            return Err(Error::NotFound);
+        }
+
+        // Residues of both primes
+        let r = Integer::from(residue.modulo_ref(m));
+        let s = r
+            .clone()
+            .invert(m)
+            .map(|inverse| (n * inverse).modulo(m))
+            .map_err(|_| Error::NotFound)?;
+
+        let p = match Integer::from(ctx.precomputed().n_sqrt().unwrap_or(n) / m)
+            .to_u64()
+            .filter(|&count| count <= ctx.config().scaled(MAX_BRUTE_FORCE))
+        {
+            Some(count) => brute_force(n, m, [&r, &s], count + 1, ctx, pb)?,
+            None => [&r, &s].into_iter().find_map(|r| coppersmith(n, m, r)),
+        }
+        .ok_or(Error::NotFound)?;
+
+        let q = Integer::from(n / &p);
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p, q, &params.e)?,
+        ))
+    }
+}
+
+/// Returns the non trivial factor of n, if the candidate is one
+fn factor_of(candidate: &Integer, n: &Integer) -> Option<Integer> {
+    (*candidate > 1 && candidate < n && n.is_divisible(candidate)).then(|| candidate.clone())
+}
+
+/// Try the `count` first candidates congruent to each residue
+fn brute_force(
+    n: &Integer,
+    m: &Integer,
+    residues: [&Integer; 2],
+    count: u64,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Integer>, Error> {
+    BruteForce::new(0..count).run(ctx, pb, |range| {
+        residues.iter().find_map(|&r| {
+            let mut candidate = Integer::from(m * range.start) + r;
+            for _ in range.clone() {
+                if let Some(p) = factor_of(&candidate, n) {
+                    return Some(p);
+                }
+                candidate += m;
+            }
+            None
+        })
+    })
+}
+
+/// p = r + m * x, with p of half the bits of n: x is a small root of x + r / m modulo p
+fn coppersmith(n: &Integer, m: &Integer, r: &Integer) -> Option<Integer> {
+    let p_bits = n.significant_bits().div_ceil(2);
+    let x_bits = (p_bits + 1).checked_sub(m.significant_bits())?;
+    let inverse = m.clone().invert(n).ok()?;
+    let f = [Integer::from(r * &inverse) % n, Integer::from(1)];
+    small_roots_mod_factor(&f, n, x_bits)?
+        .into_iter()
+        .find_map(|x| factor_of(&(x * m + r), n))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rug::ops::Pow;
+
+    use crate::{Attack, Factors, Parameters};
+
+    use super::*;
+
+    #[test]
+    fn last_digits() {
+        // The last 10 digits of q
+        let factors = Factors::from([202178360937239u64, 173961102589777]);
+        let params = Parameters {
+            n: Some(factors.product()),
+            p_mod: Some((1102589777.into(), Integer::from(10).pow(10))),
+            ..Default::default()
+        };
+
+        let solution = PModAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+
+    #[test]
+    fn coppersmith() {
+        // The last 100 digits of p, 180 bits are unknown
+        let p = Integer::from_str("9680013379709450894240896318618061284105559659947313368392185471991345885186021740399175810163138239351083996692031751120232675301366868879251239724616281").unwrap();
+        let q = Integer::from_str("8648015025408335181758712092667722045493734511644452769085082668804450076445917118092786133629868326002982629135152287590451302935304608656309477470314237").unwrap();
+        let m = Integer::from(10).pow(100);
+        let factors = Factors::from([p.clone(), q]);
+        let params = Parameters {
+            n: Some(factors.product()),
+            p_mod: Some((p % &m, m)),
+            ..Default::default()
+        };
+
+        let solution = PModAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+}
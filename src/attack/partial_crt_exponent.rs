@@ -0,0 +1,249 @@
+use indicatif::ProgressBar;
+use rug::Integer;
+
+use crate::{
+    key::PrivateKey, lattice::small_roots_mod_factor, Attack, AttackContext, AttackKind,
+    AttackSpeed, BruteForce, Error, Parameters, PartialInteger, Progress, Solution, Vulnerability,
+    STOP_CHECK_INTERVAL,
+};
+
+/// Largest number of candidates tried by brute force over the unknown bits
+const MAX_BRUTE_FORCE: u64 = 1 << 22;
+
+/// Partially known CRT exponent attack (some bits of dp or dq are unknown)
+///
+/// A few unknown bits, wherever they are, are brute forced: p divides 2^(e * dp) - 2.
+/// Otherwise, e * dp - 1 + k = k * p with k < e gives a linear polynomial with a small root
+/// modulo p when the MSBs or the LSBs of dp are known, solved with Coppersmith's method.
+/// With the LSBs, k is searched, so e must be small.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialCrtExponentAttack;
+
+impl Attack for PartialCrtExponentAttack {
+    fn name(&self) -> &'static str {
+        "partial_crt_exponent"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Medium
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let e = &params.e;
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let partial = params
+            .partial_dp
+            .as_ref()
+            .or(params.partial_dq.as_ref())
+            .ok_or(Error::MissingParameters)?;
+
+        let max_brute_force = ctx.config().scaled(MAX_BRUTE_FORCE);
+        let p = if partial.unknown_count() < u64::BITS
+            && 1u64 << partial.unknown_count() <= max_brute_force
+        {
+            brute_force(n, e, partial, ctx, pb)?
+        } else if let Some(unknown) = partial.unknown_lsb() {
+            known_msb(n, e, partial, unknown)
+        } else if let Some(known) = partial.known_lsb() {
+            known_lsb(n, e, partial, known, ctx, pb)?
+        } else {
+            None
+        }
+        .ok_or(Error::NotFound)?;
+
+        let q = Integer::from(n / &p);
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p, q, e)?,
+        ))
+    }
+}
+
+/// Returns the non trivial factor of n dividing x, if any
+fn factor_of(x: &Integer, n: &Integer) -> Option<Integer> {
+    let p = Integer::from(x.gcd_ref(n));
+    (p != 1 && p != *n).then_some(p)
+}
+
+/// Try all values of the unknown bits, in Gray code order so that each step flips a single bit
+fn brute_force(
+    n: &Integer,
+    e: &Integer,
+    partial: &PartialInteger,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Integer>, Error> {
+    let a = Integer::from(2);
+    let bits = partial.unknown_bits();
+    let steps = bits
+        .iter()
+        .map(|&i| {
+            let step = a.clone().pow_mod(&(Integer::from(e << i)), n).unwrap();
+            let inverse = step.invert_ref(n).map(Integer::from);
+            inverse.map(|inverse| (step, inverse))
+        })
+        .collect::<Option<Vec<_>>>();
+    let Some(steps) = steps else {
+        return Ok(None);
+    };
+
+    BruteForce::new(0..1u64 << steps.len()).run(ctx, pb, |range| {
+        // a^(e * dp) for the candidate dp of the first step of the block
+        let gray = range.start ^ (range.start >> 1);
+        let mut dp = partial.value().clone();
+        for (j, &i) in bits.iter().enumerate() {
+            dp.set_bit(i, gray >> j & 1 == 1);
+        }
+        let mut current = a.clone().pow_mod(&(e * dp), n).unwrap();
+
+        let mut block = Vec::with_capacity(STOP_CHECK_INTERVAL as usize);
+        let mut product = Integer::from(1);
+        for i in range.clone() {
+            if i > range.start {
+                let bit = i.trailing_zeros() as usize;
+                let (step, inverse) = &steps[bit];
+                if (i ^ (i >> 1)) >> bit & 1 == 1 {
+                    current *= step;
+                } else {
+                    current *= inverse;
+                }
+                current %= n;
+            }
+
+            let candidate = Integer::from(&current - &a);
+            product = (product * &candidate) % n;
+            block.push(candidate);
+        }
+
+        // Several candidates may share the factor, check them one by one
+        (Integer::from(product.gcd_ref(n)) != 1)
+            .then(|| block.iter().find_map(|candidate| factor_of(candidate, n)))
+            .flatten()
+    })
+}
+
+/// dp = h + x: e * x + k is a small root of z + e * h - 1 modulo p
+fn known_msb(n: &Integer, e: &Integer, partial: &PartialInteger, unknown: u32) -> Option<Integer> {
+    let constant: Integer = Integer::from(e * partial.value()) - 1;
+    let x_bits = unknown + e.significant_bits() + 1;
+    small_roots_mod_factor(&[constant.clone(), Integer::from(1)], n, x_bits)?
+        .into_iter()
+        .find_map(|z| factor_of(&(z + &constant), n))
+}
+
+/// dp = x * 2^known + l: for each k < e, x is a small root of
+/// x + (e * l - 1 + k) / (e * 2^known) modulo p
+fn known_lsb(
+    n: &Integer,
+    e: &Integer,
+    partial: &PartialInteger,
+    known: u32,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Integer>, Error> {
+    let max_k = e.to_u64().ok_or(Error::NotFound)?;
+    let x_bits = partial.bits() - known;
+    let a = Integer::from(e << known);
+    let Ok(a_inv) = a.clone().invert(n) else {
+        return Ok(factor_of(&a, n));
+    };
+    pb.set_length(max_k);
+
+    let base: Integer = Integer::from(e * partial.value()) - 1;
+    for k in 1..max_k {
+        ctx.check()?;
+        pb.inc(1);
+
+        let constant = Integer::from(&base + k);
+        let f = [Integer::from(&constant * &a_inv) % n, Integer::from(1)];
+        let Some(roots) = small_roots_mod_factor(&f, n, x_bits) else {
+            return Ok(None);
+        };
+        if let Some(p) = roots
+            .into_iter()
+            .find_map(|x| factor_of(&(x * &a + &constant), n))
+        {
+            return Ok(Some(p));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{Attack, Factors, Parameters};
+
+    use super::*;
+
+    fn params(e: u32, dp_mask: &Integer, dp_bits: u32) -> (Parameters, Factors) {
+        let p = Integer::from_str(
+            "90511711160491104683260185958356471267265849470934538618804038653604797333997",
+        )
+        .unwrap();
+        let q = Integer::from_str(
+            "114596133725443196642584039352225739386449324911628952764126006973006794144319",
+        )
+        .unwrap();
+        let factors = Factors::from([p.clone(), q]);
+        let d = Integer::from(e).invert(&factors.phi()).unwrap();
+        let dp = d % (p - 1);
+
+        let params = Parameters {
+            n: Some(factors.product()),
+            e: e.into(),
+            partial_dp: Some(PartialInteger::new(dp, dp_mask.clone(), dp_bits)),
+            ..Default::default()
+        };
+        (params, factors)
+    }
+
+    #[test]
+    fn brute_force() {
+        // 16 unknown bits, spread across dp
+        let mask = !(Integer::from(0x5555_5555u64) << 100u32);
+        let (params, factors) = params(65537, &mask, 256);
+
+        let solution = PartialCrtExponentAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+
+    #[test]
+    fn known_msb() {
+        // 80 unknown low bits
+        let mask = Integer::from(1) << 256u32;
+        let mask = mask - (Integer::from(1) << 80u32);
+        let (params, factors) = params(65537, &mask, 256);
+
+        let solution = PartialCrtExponentAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+
+    #[test]
+    fn known_lsb() {
+        // 80 unknown high bits
+        let mask = (Integer::from(1) << 176u32) - 1;
+        let (params, factors) = params(17, &mask, 256);
+
+        let solution = PartialCrtExponentAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+}
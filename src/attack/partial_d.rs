@@ -1,9 +1,14 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{Attack, AttackKind, AttackSpeed, Error, KnownDAttack, Parameters, Solution};
+use crate::{
+    Attack, AttackKind, AttackSpeed, Error, KnownDAttack, Parameters, Progress, Solution,
+    Vulnerability,
+};
 
 /// Partial d leaked attack (more that half of the bits of d are known)
+///
+/// The whole of d is discovered, and also passed on to the attacks using it (`known_d`, ...).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PartialDAttack;
 
@@ -20,7 +25,11 @@ impl Attack for PartialDAttack {
         AttackKind::KnownExtraInformation
     }
 
-    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let e_u32 = match params.e.to_u32() {
             Some(e) => e,
@@ -31,7 +40,12 @@ impl Attack for PartialDAttack {
 
         let known_bits = d_lsb.significant_bits();
 
+        let tick_size = (e_u32 / 100).max(1);
+        pb.set_length(e_u32.saturating_sub(1) as u64);
         for k in 1..e_u32 {
+            if k.is_multiple_of(tick_size) {
+                pb.inc(tick_size as u64);
+            }
             let d_candidate = (n.clone() * k + 1u64) / e;
             let d_msb = (d_candidate >> known_bits) << known_bits;
             let d = d_msb | d_lsb;
@@ -46,19 +60,19 @@ impl Attack for PartialDAttack {
                     .unwrap()
                     == 2
                 {
+                    let discovered = Parameters {
+                        d: Some(d),
+                        ..Default::default()
+                    };
+
                     // Compute p and q
-                    return KnownDAttack
-                        .run(
-                            &(Parameters {
-                                d: Some(d),
-                                ..Default::default()
-                            } + params),
-                            _pb,
-                        )
-                        .map(|mut s| {
-                            s.attack = self.name();
-                            s
-                        });
+                    return match KnownDAttack.run(&(discovered.clone() + params), pb) {
+                        Ok(mut solution) => {
+                            solution.attack = self.name();
+                            Ok(solution.with_discovered(discovered))
+                        }
+                        Err(_) => Err(Error::Discovered(Box::new(discovered))),
+                    };
                 }
             }
         }
@@ -92,9 +106,10 @@ mod tests {
         };
 
         let solution = PartialDAttack.run(&params, None).unwrap();
+        assert_eq!(solution.attack, "partial_d");
+        assert_eq!(solution.discovered.unwrap().d, Some(d));
         let pk = solution.pk.unwrap();
-
-        assert_eq!(pk.p(), p);
-        assert_eq!(pk.q(), q);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
     }
 }
@@ -0,0 +1,104 @@
+use indicatif::ProgressBar;
+use rug::{ops::Pow, Integer};
+
+use crate::{
+    lattice::small_roots_mod_n, Attack, AttackKind, AttackSpeed, Error, Parameters, Solution,
+    Vulnerability,
+};
+
+/// Largest e for which the lattice is built
+const MAX_E: u32 = 17;
+
+/// Stereotyped message attack (the message is known but a small window, and e is small)
+///
+/// With m = a + 2^k * x, the unknown window x is a small root of (a + 2^k * x)^e - c mod n,
+/// found by Coppersmith's method if x < n^(1/e).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialMessageAttack;
+
+impl Attack for PartialMessageAttack {
+    fn name(&self) -> &'static str {
+        "partial_m"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Medium
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::StereotypedMessage)
+    }
+
+    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        let partial_m = params.partial_m.as_ref().ok_or(Error::MissingParameters)?;
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let c = params.c.as_ref().ok_or(Error::MissingParameters)?;
+        let e = params
+            .e
+            .to_u32()
+            .filter(|e| (2..=MAX_E).contains(e))
+            .ok_or(Error::NotFound)?;
+        let (shift, bits) = partial_m.unknown_window().ok_or(Error::NotFound)?;
+
+        // (a + 2^k * x)^e - c, made monic by the inverse of 2^(k * e)
+        let a = partial_m.value();
+        let lead = Integer::from(1) << shift;
+        let mut f = (0..=e)
+            .map(|i| {
+                let binomial = Integer::from(Integer::binomial_u(e, i));
+                binomial * a.clone().pow(e - i) * lead.clone().pow(i) % n
+            })
+            .collect::<Vec<_>>();
+        f[0] -= c;
+        let inverse = lead.pow(e).invert(n).map_err(|_| Error::NotFound)?;
+        for coef in f.iter_mut() {
+            *coef = Integer::from(&*coef * &inverse).modulo(n);
+        }
+
+        let roots = small_roots_mod_n(&f, n, bits).ok_or(Error::NotFound)?;
+        roots
+            .into_iter()
+            .map(|x| a.clone() + (x << shift))
+            .find(|m| {
+                partial_m.matches(m) && m.clone().pow_mod(&params.e, n).is_ok_and(|m_e| m_e == *c)
+            })
+            .map(|m| Solution::new_m(self.name(), m))
+            .ok_or(Error::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{bytes_to_integer, PartialInteger};
+
+    use super::*;
+
+    #[test]
+    fn attack() {
+        let n = Integer::from_str(
+            "95118357989037539883272168746004652872958890562445814301889866663072352421703264985997800660075311645555799745426868343365321502734736006248007902409628540578635925559742217480797487130202747020211452620743021097565113059392504472785227154824117231077844444672393221838192941390309312484066647007469668558141",
+        )
+        .unwrap();
+        let m =
+            bytes_to_integer(b"{\"user\":\"admin\",\"role\":\"superuser\",\"token\":\"s3cr3t!!\"}");
+        // The 8 bytes of the token are unknown, and m^3 > n
+        let mask = !(Integer::from(u64::MAX) << 16u32);
+        let partial_m = PartialInteger::new(m.clone(), mask, m.significant_bits());
+
+        let params = Parameters {
+            n: Some(n.clone()),
+            e: 3.into(),
+            c: Some(m.clone().pow_mod(&3.into(), &n).unwrap()),
+            partial_m: Some(partial_m),
+            ..Default::default()
+        };
+        let solution = PartialMessageAttack.run(&params, None).unwrap();
+        assert_eq!(solution.m.unwrap(), m);
+    }
+}
@@ -0,0 +1,232 @@
+use indicatif::ProgressBar;
+use rug::Integer;
+
+use crate::{
+    key::PrivateKey, lattice::small_roots_mod_factor, Attack, AttackContext, AttackKind,
+    AttackSpeed, BruteForce, Error, Parameters, PartialInteger, Solution, Vulnerability,
+};
+
+/// Largest number of candidates tried by brute force over the unknown window
+const MAX_BRUTE_FORCE: u64 = 1 << 20;
+
+/// Simultaneous partial p and q attack (some bits of both primes are unknown)
+///
+/// The known bits of each prime are completed with p * q = n: the known LSBs of q give the same
+/// LSBs of p, as p = n / q modulo 2^k, and the known MSBs of q bound p between n / q_max and
+/// n / q_min, whose common MSBs are then those of p. This is repeated while new bits are found.
+/// Once the unknown bits of a prime are a single window, it is brute forced if it is small, or
+/// found with Coppersmith's method if it is smaller than n^(1/4).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialPQAttack;
+
+impl Attack for PartialPQAttack {
+    fn name(&self) -> &'static str {
+        "partial_pq"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Medium
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let (Some(partial_p), Some(partial_q)) = (&params.partial_p, &params.partial_q) else {
+            return Err(Error::MissingParameters);
+        };
+
+        // Each round adds known bits, or stops
+        let mut p = partial_p.clone();
+        let mut q = partial_q.clone();
+        loop {
+            ctx.check()?;
+            let p_next = complete(n, &p, &q).ok_or(Error::NotFound)?;
+            let q_next = complete(n, &q, &p_next).ok_or(Error::NotFound)?;
+            if p_next == p && q_next == q {
+                break;
+            }
+            (p, q) = (p_next, q_next);
+        }
+
+        let max_brute_force = ctx.config().scaled(MAX_BRUTE_FORCE);
+        for (partial, other) in [(&p, &q), (&q, &p)] {
+            if let Some(p) = solve(n, partial, other, max_brute_force, ctx, pb)? {
+                let q = Integer::from(n / &p);
+                return Ok(Solution::new_pk(
+                    self.name(),
+                    PrivateKey::from_p_q(p, q, &params.e)?,
+                ));
+            }
+        }
+        Err(Error::NotFound)
+    }
+}
+
+/// Add to `partial` the bits given by the known bits of the other factor of n
+///
+/// Returns `None` if they contradict the known bits of `partial`.
+fn complete(
+    n: &Integer,
+    partial: &PartialInteger,
+    other: &PartialInteger,
+) -> Option<PartialInteger> {
+    let mut value = partial.value().clone();
+    let mut mask = partial.mask().clone();
+    let mut add = |implied: Integer, implied_mask: Integer| {
+        let conflict = Integer::from(&implied ^ &value) & &implied_mask & &mask;
+        value |= implied & &implied_mask;
+        mask |= implied_mask;
+        conflict == 0
+    };
+
+    // LSBs: partial = n / other modulo 2^k
+    let known_low = other
+        .mask()
+        .find_zero(0)
+        .unwrap_or(other.bits())
+        .min(other.bits());
+    if known_low > 0 && other.value().is_odd() {
+        let modulo = Integer::from(1) << known_low;
+        let inverse = Integer::from(other.value().keep_bits_ref(known_low)).invert(&modulo);
+        let implied = (n * inverse.ok()?).keep_bits(known_low);
+        if !add(implied, modulo - 1) {
+            return None;
+        }
+    }
+
+    // MSBs: n / other_max <= partial <= n / other_min
+    if *other.value() != 0 {
+        let unknown = Integer::from(!other.mask()).keep_bits(other.bits());
+        let lo = n / Integer::from(other.value() | &unknown);
+        let hi = Integer::from(n / other.value());
+        if lo.significant_bits() > partial.bits() {
+            return None;
+        }
+        let known_high = Integer::from(&lo ^ &hi).significant_bits();
+        if known_high < partial.bits() {
+            let implied_mask =
+                (Integer::from(1) << partial.bits()) - (Integer::from(1) << known_high);
+            if !add(lo, implied_mask) {
+                return None;
+            }
+        }
+    }
+
+    Some(PartialInteger::new(value, mask, partial.bits()))
+}
+
+/// Recover the prime whose unknown bits are a single window, its cofactor matching `other`
+pub(super) fn solve(
+    n: &Integer,
+    partial: &PartialInteger,
+    other: &PartialInteger,
+    max_brute_force: u64,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+) -> Result<Option<Integer>, Error> {
+    let is_factor =
+        |p: &Integer| *p > 1 && p < n && n.is_divisible(p) && other.matches(&Integer::from(n / p));
+    if let Some(p) = partial.known() {
+        return Ok(is_factor(p).then(|| p.clone()));
+    }
+    let Some((shift, count)) = partial.unknown_window() else {
+        return Ok(None);
+    };
+    let step = Integer::from(1) << shift;
+
+    if count < u64::BITS && 1u64 << count <= max_brute_force {
+        return BruteForce::new(0..1u64 << count).run(ctx, pb, |range| {
+            let mut p = Integer::from(&step * range.start) + partial.value();
+            for _ in range {
+                if is_factor(&p) {
+                    return Some(p);
+                }
+                p += &step;
+            }
+            None
+        });
+    }
+
+    // p = a + 2^shift * x: x is a small root of x + a / 2^shift modulo p
+    let Ok(inverse) = step.clone().invert(n) else {
+        return Ok(None);
+    };
+    let f = [
+        Integer::from(partial.value() * &inverse) % n,
+        Integer::from(1),
+    ];
+    Ok(small_roots_mod_factor(&f, n, count)
+        .into_iter()
+        .flatten()
+        .map(|x| x * &step + partial.value())
+        .find(is_factor))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{Attack, Factors, Parameters};
+
+    use super::*;
+
+    fn factors() -> (Integer, Integer) {
+        let p = Integer::from_str("7677829186595210500928985174306392672408471811171209180875762805297434776914176459213038791488648198534627000248842689040078406891986742600086055337310117").unwrap();
+        let q = Integer::from_str("9143902697032342763942505516451137564641740832900385270255437253485132932252185661813754368889519423521770060618363712016586556866278606141125038904304113").unwrap();
+        (p, q)
+    }
+
+    /// Partial integer of 512 bits, known outside of the bits in `unknown`
+    fn partial(value: &Integer, unknown: std::ops::Range<u32>) -> PartialInteger {
+        let window = (Integer::from(1) << unknown.end) - (Integer::from(1) << unknown.start);
+        PartialInteger::new(value.clone(), !window, 512)
+    }
+
+    #[test]
+    fn coppersmith() {
+        // 312 unknown bits in each prime: the LSBs of p and the MSBs of q
+        let (p, q) = factors();
+        let factors = Factors::from([p.clone(), q.clone()]);
+        let params = Parameters {
+            n: Some(factors.product()),
+            partial_p: Some(partial(&p, 0..312)),
+            partial_q: Some(partial(&q, 200..512)),
+            ..Default::default()
+        };
+
+        let solution = PartialPQAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+
+    #[test]
+    fn brute_force() {
+        // 200 unknown bits in p, 222 unknown MSBs in q
+        let (p, q) = factors();
+        let factors = Factors::from([p.clone(), q.clone()]);
+        let params = Parameters {
+            n: Some(factors.product()),
+            partial_p: Some(partial(&p, 100..300)),
+            partial_q: Some(partial(&q, 290..512)),
+            ..Default::default()
+        };
+
+        let solution = PartialPQAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+}
@@ -0,0 +1,136 @@
+use indicatif::ProgressBar;
+use rug::Integer;
+
+use crate::{
+    key::PrivateKey, partial::partial_factors, Attack, AttackContext, AttackKind, AttackSpeed,
+    Error, Parameters, Solution, Vulnerability,
+};
+
+use super::partial_pq::solve;
+
+/// Largest number of candidates tried by brute force over the unknown window
+const MAX_BRUTE_FORCE: u64 = 1 << 20;
+
+/// Single partial prime attack (some bits of p or of q are unknown, the other prime is not known)
+///
+/// The unknown bits of the prime must be a single window, brute forced if it is small, or found
+/// with Coppersmith's method if it is smaller than n^(1/4), e.g. for a prime of which a bit more
+/// than half of the MSBs or of the LSBs are known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialPrimeAttack;
+
+impl Attack for PartialPrimeAttack {
+    fn name(&self) -> &'static str {
+        "partial_prime"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Medium
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        if params.partial_p.is_some() && params.partial_q.is_some() {
+            // Both partial primes are handled by the partial_pq attack
+            return Err(Error::MissingParameters);
+        }
+        let (p, q) = partial_factors(n, &params.partial_p, &params.partial_q)
+            .ok_or(Error::MissingParameters)?;
+        let (partial, other) = match params.partial_p {
+            Some(_) => (p, q),
+            None => (q, p),
+        };
+
+        let max_brute_force = ctx.config().scaled(MAX_BRUTE_FORCE);
+        let p = solve(n, &partial, &other, max_brute_force, ctx, pb)?.ok_or(Error::NotFound)?;
+        let q = Integer::from(n / &p);
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p, q, &params.e)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{Attack, Factors, Parameters, PartialInteger};
+
+    use super::*;
+
+    fn factors() -> (Integer, Integer) {
+        let p = Integer::from_str("7677829186595210500928985174306392672408471811171209180875762805297434776914176459213038791488648198534627000248842689040078406891986742600086055337310117").unwrap();
+        let q = Integer::from_str("9143902697032342763942505516451137564641740832900385270255437253485132932252185661813754368889519423521770060618363712016586556866278606141125038904304113").unwrap();
+        (p, q)
+    }
+
+    /// Partial integer of 512 bits, known outside of the bits in `unknown`
+    fn partial(value: &Integer, unknown: std::ops::Range<u32>) -> PartialInteger {
+        let window = (Integer::from(1) << unknown.end) - (Integer::from(1) << unknown.start);
+        PartialInteger::new(value.clone(), !window, 512)
+    }
+
+    #[test]
+    fn partial_p_only() {
+        // 200 unknown MSBs of p, nothing known of q
+        let (p, q) = factors();
+        let factors = Factors::from([p.clone(), q]);
+        let params = Parameters {
+            n: Some(factors.product()),
+            partial_p: Some(partial(&p, 312..512)),
+            ..Default::default()
+        };
+
+        let solution = PartialPrimeAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+
+    #[test]
+    fn partial_q_lsb() {
+        // 200 unknown LSBs of q
+        let (p, q) = factors();
+        let factors = Factors::from([p, q.clone()]);
+        let params = Parameters {
+            n: Some(factors.product()),
+            partial_q: Some(partial(&q, 0..200)),
+            ..Default::default()
+        };
+
+        let solution = PartialPrimeAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+
+    #[test]
+    fn both_partial() {
+        let (p, q) = factors();
+        let params = Parameters {
+            n: Some(Integer::from(&p * &q)),
+            partial_p: Some(partial(&p, 312..512)),
+            partial_q: Some(partial(&q, 0..200)),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            PartialPrimeAttack.run(&params, None),
+            Err(Error::MissingParameters)
+        ));
+    }
+}
@@ -1,7 +1,10 @@
 use indicatif::ProgressBar;
 use rug::{integer::IsPrime, Integer};
 
-use crate::{Attack, AttackKind, AttackSpeed, Error, KeyError, Parameters, Solution};
+use crate::{
+    Attack, AttackContext, AttackKind, AttackSpeed, Error, KeyError, Parameters, Solution,
+    Vulnerability,
+};
 
 /// N is a prime modulus attack
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,9 +23,22 @@ impl Attack for PrimeModulusAttack {
         AttackKind::KnownExtraInformation
     }
 
-    fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::PrimeModulus)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        _pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         if let (Some(n), Some(c)) = (&params.n, &params.c) {
-            if n.is_probably_prime(100) == IsPrime::No {
+            if ctx.precomputed().n_is_prime() == Some(IsPrime::No) {
                 return Err(Error::NotFound);
             }
 
@@ -0,0 +1,138 @@
+use std::{fmt::Display, sync::Arc};
+
+use crate::{Attack, AttackConfig, AttackSpeed, ATTACKS};
+
+/// Attacks querying an online service
+const ONLINE_ATTACKS: [&str; 1] = ["factordb"];
+
+/// Attacks worth running on a set of keys: the ones using all keys, and the cheap lookups
+const MULTI_KEY_ATTACKS: [&str; 6] = [
+    "hastad",
+    "cache",
+    "known_factors",
+    "factordb",
+    "cube_root",
+    "small_e",
+];
+
+/// Named set of attacks, with tuned bounds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AttackProfile {
+    /// Fast attacks only, with a quarter of the effort
+    Quick,
+    /// All attacks
+    #[default]
+    Default,
+    /// All attacks, with larger bounds and longer time slices
+    Thorough,
+    /// All attacks but the ones querying an online service
+    Offline,
+    /// Attacks exploiting several keys, and cheap lookups of their factors
+    MultiKey,
+}
+
+impl std::str::FromStr for AttackProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "quick" => Ok(Self::Quick),
+            "default" => Ok(Self::Default),
+            "thorough" => Ok(Self::Thorough),
+            "offline" => Ok(Self::Offline),
+            "multi-key" | "multikey" => Ok(Self::MultiKey),
+            _ => Err(format!("Unknown profile: {s}")),
+        }
+    }
+}
+
+impl Display for AttackProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Quick => write!(f, "quick"),
+            Self::Default => write!(f, "default"),
+            Self::Thorough => write!(f, "thorough"),
+            Self::Offline => write!(f, "offline"),
+            Self::MultiKey => write!(f, "multi-key"),
+        }
+    }
+}
+
+impl AttackProfile {
+    /// Returns the attacks of the profile
+    pub fn attacks(&self) -> Vec<Arc<dyn Attack + Sync + Send>> {
+        ATTACKS
+            .iter()
+            .filter(|attack| match self {
+                Self::Quick => attack.speed() == AttackSpeed::Fast,
+                Self::Default | Self::Thorough => true,
+                Self::Offline => !ONLINE_ATTACKS.contains(&attack.name()),
+                Self::MultiKey => MULTI_KEY_ATTACKS.contains(&attack.name()),
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Tune the bounds of a configuration for the profile
+    pub fn tuned(&self, mut config: AttackConfig) -> AttackConfig {
+        match self {
+            Self::Quick => {
+                config.effort = (config.effort / 4).max(1);
+                config.time_slice /= 4;
+            }
+            Self::Thorough => {
+                config.effort = config.effort.saturating_mul(4);
+                config.small_prime_bound = config.small_prime_bound.saturating_mul(16);
+                config.londahl_b = config.londahl_b.saturating_mul(10);
+                config.time_slice *= 4;
+            }
+            Self::Default | Self::Offline | Self::MultiKey => {}
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attacks() {
+        assert_eq!(AttackProfile::Default.attacks().len(), ATTACKS.len());
+        assert!(AttackProfile::Quick
+            .attacks()
+            .iter()
+            .all(|attack| attack.speed() == AttackSpeed::Fast));
+        assert!(AttackProfile::Offline
+            .attacks()
+            .iter()
+            .all(|attack| attack.name() != "factordb"));
+        // Every curated attack exists
+        assert_eq!(
+            AttackProfile::MultiKey.attacks().len(),
+            MULTI_KEY_ATTACKS.len()
+        );
+    }
+
+    #[test]
+    fn tuned() {
+        let config = AttackConfig::default();
+        assert_eq!(AttackProfile::Default.tuned(config.clone()), config);
+        assert_eq!(AttackProfile::Quick.tuned(config.clone()).effort, 25);
+        assert_eq!(AttackProfile::Thorough.tuned(config).effort, 400);
+    }
+
+    #[test]
+    fn from_str() {
+        for profile in [
+            AttackProfile::Quick,
+            AttackProfile::Default,
+            AttackProfile::Thorough,
+            AttackProfile::Offline,
+            AttackProfile::MultiKey,
+        ] {
+            assert_eq!(profile.to_string().parse::<AttackProfile>(), Ok(profile));
+        }
+        assert!("slow".parse::<AttackProfile>().is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Progress report of an attack, on its optional progress bar
+///
+/// Bounded attacks set their number of steps with `set_length`, then `inc` it as they go.
+/// Unbounded attacks `count` what they tried instead (curves, candidates, etc.).
+pub trait Progress {
+    /// Set the number of steps of a bounded attack, and restart from the first one
+    fn set_length(&self, length: u64);
+
+    /// Advance a bounded attack by `delta` steps
+    fn inc(&self, delta: u64);
+
+    /// Count `delta` more things tried by an unbounded attack, displayed with their `unit`
+    fn count(&self, delta: u64, unit: &str);
+}
+
+impl Progress for Option<&ProgressBar> {
+    fn set_length(&self, length: u64) {
+        if let Some(pb) = self {
+            pb.set_position(0);
+            pb.set_length(length);
+        }
+    }
+
+    fn inc(&self, delta: u64) {
+        if let Some(pb) = self {
+            pb.inc(delta);
+        }
+    }
+
+    fn count(&self, delta: u64, unit: &str) {
+        let Some(pb) = self else {
+            return;
+        };
+
+        // A counter has no length, nor percentage and ETA
+        if pb.length().is_some() {
+            pb.set_style(
+                ProgressStyle::with_template(
+                    "{prefix:>12.bold} [{elapsed_precise}] {spinner} {human_pos} {msg}",
+                )
+                .unwrap(),
+            );
+            pb.unset_length();
+            pb.set_position(0);
+        }
+        pb.inc(delta);
+        pb.set_message(unit.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress() {
+        let pb = ProgressBar::hidden();
+        Some(&pb).set_length(10);
+        Some(&pb).inc(3);
+        assert_eq!((pb.position(), pb.length()), (3, Some(10)));
+
+        // Restart from the first step
+        Some(&pb).set_length(20);
+        assert_eq!((pb.position(), pb.length()), (0, Some(20)));
+
+        Some(&pb).count(2, "curves");
+        Some(&pb).count(5, "curves");
+        assert_eq!((pb.position(), pb.length()), (7, None));
+        assert_eq!(pb.message(), "curves");
+
+        // Nothing to report on
+        let none: Option<&ProgressBar> = None;
+        none.set_length(10);
+        none.count(1, "curves");
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use indicatif::ProgressBar;
+use rug::Integer;
+
+use crate::{Attack, AttackContext, Error, Parameters, Solution};
+
+/// Internal state of an interrupted attack (walk positions, curve index, table progress, etc.)
+///
+/// The state is a set of named lists of integers, tied to the attack and to the modulus it was
+/// computed for. It can be saved as JSON, to resume the attack in another run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttackState {
+    /// Name of the attack which exported the state
+    pub attack: String,
+    /// Modulus the state was computed for
+    pub n: Integer,
+    /// Named values of the state
+    pub values: BTreeMap<String, Vec<Integer>>,
+}
+
+impl AttackState {
+    /// Create an empty state of an attack on n
+    pub fn new(attack: &str, n: &Integer) -> Self {
+        Self {
+            attack: attack.to_string(),
+            n: n.clone(),
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Returns true if the state was exported by the given attack on n
+    pub fn resumes(&self, attack: &str, n: &Integer) -> bool {
+        self.attack == attack && self.n == *n
+    }
+
+    /// Set the values of a name
+    pub fn set(&mut self, name: &str, values: impl IntoIterator<Item = Integer>) {
+        self.values
+            .insert(name.to_string(), values.into_iter().collect());
+    }
+
+    /// Returns the values of a name, empty if it is not set
+    pub fn get(&self, name: &str) -> &[Integer] {
+        self.values.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the single value of a name
+    pub fn get_one(&self, name: &str) -> Option<&Integer> {
+        match self.get(name) {
+            [value] => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Serialize the state to JSON, integers being written as decimal strings
+    pub fn to_json(&self) -> String {
+        let values = self
+            .values
+            .iter()
+            .map(|(name, values)| {
+                let values = values
+                    .iter()
+                    .map(|value| value.to_string().into())
+                    .collect();
+                (name.clone(), serde_json::Value::Array(values))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::json!({
+            "attack": self.attack,
+            "n": self.n.to_string(),
+            "values": values,
+        })
+        .to_string()
+    }
+
+    /// Parse a state serialized by `to_json`
+    pub fn from_json(json: &str) -> Option<Self> {
+        let json: serde_json::Value = serde_json::from_str(json).ok()?;
+        let integer = |value: &serde_json::Value| value.as_str()?.parse::<Integer>().ok();
+
+        let mut values = BTreeMap::new();
+        for (name, list) in json["values"].as_object()? {
+            let list = list
+                .as_array()?
+                .iter()
+                .map(integer)
+                .collect::<Option<Vec<_>>>()?;
+            values.insert(name.clone(), list);
+        }
+        Some(Self {
+            attack: json["attack"].as_str()?.to_string(),
+            n: integer(&json["n"])?,
+            values,
+        })
+    }
+}
+
+/// Attack which can export its internal state when it is stopped, and resume from it later
+///
+/// Resuming from a state continues the computation exactly where it stopped, e.g. in the next
+/// time slice of a run, or in another run once the state is saved with `AttackState::to_json`.
+pub trait ResumableAttack: Attack {
+    /// Runs the attack, resuming from `state` if it was exported by this attack on the same n
+    ///
+    /// If the attack is stopped, `state` is set to the state of the attack at that point.
+    fn run_resumable(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        state: &mut Option<AttackState>,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json() {
+        let mut state = AttackState::new("pollard_rho", &3233.into());
+        state.set("x", [Integer::from(42), Integer::from(-7)]);
+        state.set("iterations", [Integer::from(4096)]);
+
+        let json = state.to_json();
+        let parsed = AttackState::from_json(&json).unwrap();
+        assert_eq!(parsed, state);
+        assert!(parsed.resumes("pollard_rho", &3233.into()));
+        assert!(!parsed.resumes("ecm", &3233.into()));
+        assert_eq!(parsed.get("x"), [42, -7]);
+        assert_eq!(parsed.get_one("iterations"), Some(&Integer::from(4096)));
+        assert_eq!(parsed.get_one("x"), None);
+        assert!(parsed.get("y").is_empty());
+
+        assert_eq!(AttackState::from_json("{\"attack\": \"ecm\"}"), None);
+    }
+}
@@ -0,0 +1,157 @@
+use discrete_logarithm::discrete_log_with_order;
+use indicatif::ProgressBar;
+use rug::{Complete, Integer};
+
+use crate::{
+    key::PrivateKey, ntheory::crt_lcm, Attack, AttackContext, AttackKind, Error, Parameters,
+    Solution, Vulnerability,
+};
+
+/// Largest number of private exponents tried, when the order of m is smaller than lambda(n)
+const MAX_CANDIDATES: u64 = 1 << 16;
+
+/// Order of m modulo the prime p, if it is smooth over the given primes
+///
+/// The order divides the smooth part of p - 1 if m raised to it is 1, it is then reduced by each
+/// of its prime factors as long as m raised to the quotient is still 1.
+fn smooth_order(m: &Integer, p: &Integer, primes: &[u64]) -> Option<Integer> {
+    let mut cofactor = Integer::from(p - 1);
+    let mut factors = vec![];
+    for &prime in primes {
+        if cofactor == 1 {
+            break;
+        }
+        let prime = Integer::from(prime);
+        if cofactor.is_divisible(&prime) {
+            cofactor.remove_factor_mut(&prime);
+            factors.push(prime);
+        }
+    }
+
+    let mut order = Integer::from(p - 1) / cofactor;
+    if m.clone().pow_mod(&order, p).ok()? != 1 {
+        return None;
+    }
+    for factor in factors {
+        while order.is_divisible(&factor) {
+            let reduced = Integer::from(&order / &factor);
+            if m.clone().pow_mod(&reduced, p).ok()? != 1 {
+                break;
+            }
+            order = reduced;
+        }
+    }
+    Some(order)
+}
+
+/// Textbook signature attack, recovering d from a signature s = m^d mod n with a discrete logarithm
+///
+/// With p and q known, d mod ord_p(m) is the discrete logarithm of s in base m modulo p, computed
+/// with Pohlig-Hellman when the order of m is smooth, and likewise modulo q. Combined with the CRT,
+/// they give d modulo the order of m modulo n, and e is its inverse modulo lambda(n). When the order
+/// is smaller than lambda(n), the given e is kept if it is one of the candidates, else the smallest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureDlogAttack;
+
+impl Attack for SignatureDlogAttack {
+    fn name(&self) -> &'static str {
+        "signature_dlog"
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SmoothPMinus1)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        _pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let m = params.signed_m.as_ref().ok_or(Error::MissingParameters)?;
+        let s = params.signature.as_ref().ok_or(Error::MissingParameters)?;
+        let p = params
+            .p
+            .as_ref()
+            .or(params.q.as_ref())
+            .ok_or(Error::MissingParameters)?;
+        let (q, rem) = n.div_rem_ref(p).complete();
+        if rem != 0 || *p == 1 || q == 1 || *p == q {
+            return Err(Error::NotFound);
+        }
+
+        // d modulo the order of m modulo each prime
+        let primes = ctx.precomputed().small_primes();
+        let mut residues = vec![];
+        let mut orders = vec![];
+        for prime in [p, &q] {
+            ctx.check()?;
+            let (m, s) = (Integer::from(m % prime), Integer::from(s % prime));
+            let order = smooth_order(&m, prime, primes).ok_or(Error::NotFound)?;
+            let d = discrete_log_with_order(prime, &s, &m, &order).map_err(|_| Error::NotFound)?;
+            residues.push(d);
+            orders.push(order);
+        }
+        let (d, order) = crt_lcm(&residues, &orders).ok_or(Error::NotFound)?;
+
+        // Candidates for e, one for each d modulo lambda(n)
+        let lambda = Integer::from(p - 1).lcm(&Integer::from(&q - 1));
+        let count = Integer::from(&lambda / &order)
+            .to_u64()
+            .filter(|&count| count <= MAX_CANDIDATES)
+            .ok_or(Error::NotFound)?;
+        let candidates = (0..count)
+            .filter_map(|k| (Integer::from(&order * k) + &d).invert(&lambda).ok())
+            .collect::<Vec<_>>();
+        let e = if candidates.contains(&params.e) {
+            params.e.clone()
+        } else {
+            candidates.into_iter().min().ok_or(Error::NotFound)?
+        };
+
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p.clone(), q, &e)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn attack() {
+        // p - 1 and q - 1 are smooth, and 10 generates both groups
+        let p = Integer::from_str("88746927878387586779").unwrap();
+        let q = Integer::from_str("1590641444559295235687").unwrap();
+
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            e: 3.into(),
+            p: Some(p.clone()),
+            signed_m: Some(10.into()),
+            signature: Some(
+                Integer::from_str("53629493980110499085674712055141500963743").unwrap(),
+            ),
+            ..Default::default()
+        };
+        let solution = SignatureDlogAttack.run(&params, None).unwrap();
+        let pk = solution.pk.unwrap();
+
+        assert_eq!(pk.e, 65537);
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+}
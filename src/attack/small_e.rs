@@ -1,7 +1,9 @@
 use indicatif::ProgressBar;
 use rug::Integer;
 
-use crate::{Attack, AttackKind, AttackSpeed, Error, Parameters, Solution};
+use crate::{
+    Attack, AttackKind, AttackSpeed, Error, Parameters, Progress, Solution, Vulnerability,
+};
 
 const MAX_ITERATIONS: u64 = 1_000_000;
 const TICK_SIZE: u64 = MAX_ITERATIONS / 100;
@@ -23,6 +25,10 @@ impl Attack for SmallEAttack {
         AttackKind::KnownExtraInformation
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LowExponent)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = match params.e.to_u32() {
             Some(e) => e,
@@ -31,9 +37,7 @@ impl Attack for SmallEAttack {
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
         let c = params.c.as_ref().ok_or(Error::MissingParameters)?;
 
-        if let Some(pb) = pb {
-            pb.set_length(MAX_ITERATIONS);
-        }
+        pb.set_length(MAX_ITERATIONS);
         for i in 1..MAX_ITERATIONS {
             let enc = Integer::from(n) * Integer::from(i) + c.clone();
             let (root, rem) = enc.root_rem_ref(e).into();
@@ -43,10 +47,8 @@ impl Attack for SmallEAttack {
                 return Ok(Solution::new_m(self.name(), root));
             }
 
-            if i % TICK_SIZE == 0 {
-                if let Some(pb) = pb {
-                    pb.inc(TICK_SIZE);
-                }
+            if i.is_multiple_of(TICK_SIZE) {
+                pb.inc(TICK_SIZE);
             }
         }
         Err(Error::NotFound)
@@ -0,0 +1,179 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use indicatif::ProgressBar;
+use rayon::prelude::*;
+use rug::Integer;
+
+use crate::{
+    Attack, AttackContext, AttackKind, AttackSpeed, BruteForce, Error, Parameters, Progress,
+    Solution, Vulnerability, STOP_CHECK_INTERVAL,
+};
+
+/// Approximate memory used by a look-up table entry, including the hash map overhead
+const ENTRY_SIZE: u64 = 2 * std::mem::size_of::<(u64, u64)>() as u64;
+
+/// Returns the largest number of bits of m1 whose look-up table fits in the memory budget
+fn bits_for_memory(bits: u32, max_memory: u64) -> u32 {
+    let entries = max_memory / ENTRY_SIZE;
+    bits.min(entries.checked_ilog2().unwrap_or(0))
+}
+
+/// Look-up table key: the entries are only stored as a 64-bit hash,
+/// false positives being filtered out by encrypting the candidate message
+fn fingerprint(z: &Integer) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    z.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Meet-in-the-middle attack on a small unpadded message (m = m1 * m2, with m1, m2 < 2^k)
+///
+/// c = m1^e * m2^e mod n: a table of m1^e is built for all m1 < 2^k, then c / m2^e is looked up
+/// for each m2 < 2^k. About 2^(k + 1) exponentiations instead of 2^(2k) to recover a 2k-bit
+/// message, if it has such a split (a random one does with a fair probability).
+/// The table is smaller if it doesn't fit in the memory left by the other attacks of
+/// `AttackConfig::max_memory`, m1 being searched below a smaller bound.
+///
+/// See Boneh, Joux and Nguyen, Why Textbook ElGamal and RSA Encryption Are Insecure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmallMessageMitmAttack;
+
+impl Attack for SmallMessageMitmAttack {
+    fn name(&self) -> &'static str {
+        "small_message_mitm"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Slow
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SmallMessage)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let e = &params.e;
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let c = params.c.as_ref().ok_or(Error::MissingParameters)?;
+        let bits = ctx.config().mitm_bits.min(u64::BITS - 1);
+
+        let memory = ctx.reserve_memory((1u64 << bits).saturating_mul(ENTRY_SIZE));
+        let table_bits = bits_for_memory(bits, memory.size());
+        let table = table(n, e, 1u64 << table_bits, ctx, pb)?;
+        if table.is_empty() {
+            return Err(Error::NotFound);
+        }
+
+        BruteForce::new(1..1u64 << bits)
+            .run(ctx, pb, |range| {
+                range.into_iter().find_map(|m2| {
+                    // c / m2^e
+                    let m2_e = Integer::from(m2).pow_mod(e, n).ok()?;
+                    let z = m2_e.invert(n).ok()? * c % n;
+                    let m1 = *table.get(&fingerprint(&z))?;
+                    let m = Integer::from(m1) * m2;
+                    (m.clone().pow_mod(e, n).ok()? == *c).then_some(m)
+                })
+            })?
+            .map(|m| Solution::new_m(self.name(), m))
+            .ok_or(Error::NotFound)
+    }
+}
+
+/// Look-up table of m1^e mod n for all 0 < m1 < `size`, by fingerprint
+fn table(
+    n: &Integer,
+    e: &Integer,
+    size: u64,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+) -> Result<HashMap<u64, u64>, Error> {
+    pb.set_length(size);
+    let pool = ctx.thread_pool()?;
+
+    // Blocks of a few checks intervals, one per thread
+    let block = STOP_CHECK_INTERVAL * ctx.config().threads.max(1) as u64;
+    let mut table = HashMap::with_capacity(size as usize);
+    for start in (1..size).step_by(block as usize) {
+        ctx.check()?;
+        let end = (start + block).min(size);
+        let entries = pool.install(|| {
+            (start..end)
+                .into_par_iter()
+                .filter_map(|m1| {
+                    let m1_e = Integer::from(m1).pow_mod(e, n).ok()?;
+                    Some((fingerprint(&m1_e), m1))
+                })
+                .collect::<Vec<_>>()
+        });
+        table.extend(entries);
+        pb.inc(end - start);
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::AttackConfig;
+
+    use super::*;
+
+    #[test]
+    fn attack() {
+        let n = Integer::from_str(
+            "95118357989037539883272168746004652872958890562445814301889866663072352421703264985997800660075311645555799745426868343365321502734736006248007902409628540578635925559742217480797487130202747020211452620743021097565113059392504472785227154824117231077844444672393221838192941390309312484066647007469668558141",
+        )
+        .unwrap();
+        // A 24-bit message, the product of two 12-bit numbers
+        let m = Integer::from(3001 * 4093);
+        let params = Parameters {
+            n: Some(n.clone()),
+            c: Some(m.clone().pow_mod(&65537.into(), &n).unwrap()),
+            ..Default::default()
+        };
+
+        let ctx = AttackContext::new(&params).with_config(AttackConfig {
+            mitm_bits: 12,
+            ..Default::default()
+        });
+        let solution = SmallMessageMitmAttack
+            .run_with_context(&params, &ctx, None)
+            .unwrap();
+        assert_eq!(solution.m.unwrap(), m);
+
+        // Not enough memory for the table
+        let ctx = AttackContext::new(&params).with_config(AttackConfig {
+            mitm_bits: 12,
+            max_memory: 0,
+            ..Default::default()
+        });
+        assert!(SmallMessageMitmAttack
+            .run_with_context(&params, &ctx, None)
+            .is_err());
+    }
+
+    #[test]
+    fn bits_for_memory() {
+        assert_eq!(super::bits_for_memory(20, u64::MAX), 20);
+        assert_eq!(super::bits_for_memory(20, ENTRY_SIZE << 10), 10);
+        assert_eq!(super::bits_for_memory(20, 0), 0);
+    }
+}
@@ -3,7 +3,7 @@ use rug::Integer;
 
 use crate::{
     key::PrivateKey, utils::solve_quadratic, Attack, AttackKind, AttackSpeed, Error, Parameters,
-    Solution,
+    Solution, Vulnerability,
 };
 
 /// Leaked sum of p and q attack (0 = x^2 - xsum + n)
@@ -23,6 +23,10 @@ impl Attack for SumPQAttack {
         AttackSpeed::Fast
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
     fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
@@ -0,0 +1,254 @@
+use indicatif::ProgressBar;
+use rug::Integer;
+
+use crate::{
+    key::PrivateKey, Attack, AttackContext, AttackKind, AttackSpeed, Error, Parameters, Progress,
+    Solution, Vulnerability, STOP_CHECK_INTERVAL,
+};
+
+const MAX_ITERATIONS: u64 = 10_000_000;
+
+/// Symmetric functions of p and q attack (p + q, |p - q|, p * q and p^2 + q^2, exact or modulo m)
+///
+/// With s = p + q, Newton's identity p^2 + q^2 = s^2 - 2n gives any of s, n and p^2 + q^2 from
+/// the two others, and s is also given by |p - q| and n, as (p - q)^2 = s^2 - 4n. A value only
+/// known modulo m is searched by steps of m in its possible range. p and q are then the roots of
+/// x^2 - s * x + n. The found s is also passed on to the attacks using it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymmetricPQAttack;
+
+impl Attack for SymmetricPQAttack {
+    fn name(&self) -> &'static str {
+        "symmetric_pq"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Medium
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        if params.sum_squares_pq.is_none()
+            && params.diff_pq.is_none()
+            && params.sum_pq_mod.is_none()
+            && params.pq_mod.is_none()
+        {
+            // p + q and n alone are handled by the sum_pq attack
+            return Err(Error::MissingParameters);
+        }
+
+        let n = params.n.as_ref();
+        let sum_squares = params.sum_squares_pq.as_ref();
+        let sum = params.sum_pq.clone().or_else(|| {
+            // s^2 = p^2 + q^2 + 2n, or (p - q)^2 + 4n
+            let s2 = match (sum_squares, &params.diff_pq) {
+                (Some(sum_squares), _) => sum_squares + Integer::from(n? * 2u32),
+                (None, Some(diff)) => Integer::from(diff.square_ref()) + Integer::from(n? * 4u32),
+                (None, None) => return None,
+            };
+            let (s, rem) = s2.sqrt_rem(Integer::new());
+            (rem == 0).then_some(s)
+        });
+        let max_iterations = ctx.config().scaled(MAX_ITERATIONS);
+
+        let (p, q) = match (sum, n, sum_squares, &params.sum_pq_mod, &params.pq_mod) {
+            (Some(sum), Some(n), ..) => factor(&sum, n),
+            // n = (s^2 - (p^2 + q^2)) / 2
+            (Some(sum), None, Some(sum_squares), ..) => {
+                let n: Integer = (Integer::from(sum.square_ref()) - sum_squares) / 2u32;
+                factor(&sum, &n)
+            }
+            // n <= s^2 / 4
+            (Some(sum), None, None, _, Some((n_mod, m))) => {
+                let high = Integer::from(sum.square_ref()) >> 2;
+                search(n_mod, m, &high, true, max_iterations, ctx, pb, |n| {
+                    factor(&sum, n)
+                })?
+            }
+            // 2 * sqrt(n) <= s
+            (None, Some(n), _, Some((sum_mod, m)), _) => {
+                let low = Integer::from(n.sqrt_ref()) * 2u32;
+                search(sum_mod, m, &low, false, max_iterations, ctx, pb, |sum| {
+                    factor(sum, n)
+                })?
+            }
+            // p^2 + q^2 <= s^2 <= 2 * (p^2 + q^2)
+            (None, None, Some(sum_squares), Some((sum_mod, m)), _) => {
+                let low = Integer::from(sum_squares.sqrt_ref());
+                let high = Integer::from(sum_squares * 2u32).sqrt();
+                let iterations = Integer::from(&high - &low) / m + 2u32;
+                let iterations = iterations.to_u64().unwrap_or(u64::MAX).min(max_iterations);
+                search(sum_mod, m, &low, false, iterations, ctx, pb, |sum| {
+                    let n: Integer = (Integer::from(sum.square_ref()) - sum_squares) / 2u32;
+                    factor(sum, &n)
+                })?
+            }
+            _ => return Err(Error::MissingParameters),
+        }
+        .ok_or(Error::NotFound)?;
+
+        let discovered = Parameters {
+            sum_pq: Some(Integer::from(&p + &q)),
+            ..Default::default()
+        };
+        Ok(
+            Solution::new_pk(self.name(), PrivateKey::from_p_q(p, q, &params.e)?)
+                .with_discovered(discovered),
+        )
+    }
+}
+
+/// Returns p and q from their sum and product
+fn factor(sum: &Integer, n: &Integer) -> Option<(Integer, Integer)> {
+    let delta = Integer::from(sum.square_ref()) - Integer::from(n * 4u32);
+    if delta < 0 {
+        return None;
+    }
+    let (root, rem) = delta.sqrt_rem(Integer::new());
+    if rem != 0 || sum.is_odd() != root.is_odd() {
+        return None;
+    }
+    let p = Integer::from(sum + &root) >> 1;
+    let q = Integer::from(sum - &root) >> 1;
+    (q > 1 && Integer::from(&p * &q) == *n).then_some((p, q))
+}
+
+/// Check the values congruent to `residue` modulo `m`, from `start` upwards or downwards
+#[allow(clippy::too_many_arguments)]
+fn search(
+    residue: &Integer,
+    m: &Integer,
+    start: &Integer,
+    downwards: bool,
+    max_iterations: u64,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+    check: impl Fn(&Integer) -> Option<(Integer, Integer)>,
+) -> Result<Option<(Integer, Integer)>, Error> {
+    if *m <= 0 {
+        return Ok(None);
+    }
+    pb.set_length(max_iterations);
+
+    // Closest value to start, on the searched side
+    let offset = Integer::from(residue - start).modulo(m);
+    let mut value = if downwards {
+        Integer::from(start + &offset) - m
+    } else {
+        Integer::from(start + &offset)
+    };
+    if downwards && offset == 0 {
+        value += m;
+    }
+
+    for i in 0..max_iterations {
+        if downwards && value < 0 {
+            break;
+        }
+        if let Some(pq) = check(&value) {
+            return Ok(Some(pq));
+        }
+        if downwards {
+            value -= m;
+        } else {
+            value += m;
+        }
+
+        if i % STOP_CHECK_INTERVAL == 0 {
+            ctx.check()?;
+            pb.inc(STOP_CHECK_INTERVAL.min(max_iterations - i));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{Attack, Factors, Parameters};
+
+    use super::*;
+
+    fn factors() -> (Integer, Integer, Factors) {
+        let p = Integer::from_str("9680013379709450894240896318618061284105559659947313368392185471991345885186021740399175810163138239351083996692031751120232675301366868879251239724616281").unwrap();
+        let q = Integer::from_str("8648015025408335181758712092667722045493734511644452769085082668804450076445917118092786133629868326002982629135152287590451302935304608656309477470314237").unwrap();
+        (p.clone(), q.clone(), Factors::from([p, q]))
+    }
+
+    #[test]
+    fn difference() {
+        let (p, q, factors) = factors();
+
+        let params = Parameters {
+            n: Some(factors.product()),
+            diff_pq: Some(Integer::from(&p - &q)),
+            ..Default::default()
+        };
+        let solution = SymmetricPQAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+
+    #[test]
+    fn sum_squares() {
+        let (p, q, factors) = factors();
+        let sum_squares = Integer::from(p.square_ref()) + Integer::from(q.square_ref());
+
+        let params = Parameters {
+            n: Some(factors.product()),
+            sum_squares_pq: Some(sum_squares.clone()),
+            ..Default::default()
+        };
+        let solution = SymmetricPQAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+
+        // Without n
+        let params = Parameters {
+            sum_squares_pq: Some(sum_squares),
+            sum_pq: Some(Integer::from(&p + &q)),
+            ..Default::default()
+        };
+        let solution = SymmetricPQAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+
+    #[test]
+    fn modular_hints() {
+        let (p, q, factors) = factors();
+        let m = Integer::from(1) << 500u32;
+        let sum = Integer::from(&p + &q);
+
+        let params = Parameters {
+            n: Some(factors.product()),
+            sum_pq_mod: Some((Integer::from(&sum % &m), m.clone())),
+            ..Default::default()
+        };
+        let solution = SymmetricPQAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+
+        let m = Integer::from(1) << 1016u32;
+        let params = Parameters {
+            sum_pq: Some(sum),
+            pq_mod: Some((factors.product() % &m, m)),
+            ..Default::default()
+        };
+        let solution = SymmetricPQAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+}
@@ -2,13 +2,15 @@ use indicatif::ProgressBar;
 use rug::{ops::Pow, Integer};
 
 use crate::{
-    key::PrivateKey,
-    ntheory::{contfrac_to_rational, rational_to_contfrac, trivial_factorization_with_n_phi},
-    Attack, AttackKind, Error, Parameters, Solution,
+    key::PrivateKey, ntheory::trivial_factorization_with_n_phi, Attack, AttackContext, AttackKind,
+    Error, Parameters, Progress, Solution, Vulnerability,
 };
 
 /// Wiener's attack (too small d)
 ///
+/// The recovered d and phi are also passed on to the attacks using them, which factor n when it has
+/// more than two prime factors.
+///
 /// See <https://github.com/jvdsn/crypto-attacks/blob/master/attacks/rsa/wiener_attack.py>
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WienerAttack;
@@ -22,24 +24,36 @@ impl Attack for WienerAttack {
         AttackKind::KnownExtraInformation
     }
 
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::SmallPrivateExponent)
+    }
+
     fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
         let e = &params.e;
         let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
 
-        let frac = rational_to_contfrac(e, n);
-        if let Some(pb) = pb {
-            pb.set_length(frac.len() as u64);
-        }
-        let mut convergents = Vec::new();
-        for i in 0..frac.len() {
-            convergents.push(contfrac_to_rational(&frac[0..i]));
-            if let Some(pb) = pb {
-                pb.inc(1);
+        // k / d is one of the convergents of e / n, shared with the other attacks of the run
+        let convergents = ctx
+            .precomputed()
+            .e_n_convergents()
+            .ok_or(Error::MissingParameters)?;
+        let tick_size = (convergents.len() / 100).max(1);
+        pb.set_length(convergents.len() as u64);
+        for (i, (k, d)) in convergents.iter().enumerate() {
+            if (i + 1).is_multiple_of(tick_size) {
+                pb.inc(tick_size as u64);
             }
-        }
-        for (k, d) in convergents {
-            if k != 0 {
-                let (phi, q) = (e.clone() * &d - Integer::from(1)).div_rem_floor(k);
+            if *k != 0 {
+                let (phi, q) = (e.clone() * d - Integer::from(1)).div_rem_floor(k.clone());
                 if phi.is_even() && q == 0 {
                     let s = Integer::from(1) + n - &phi;
                     let discr = s.clone().pow(2) - n * Integer::from(4);
@@ -50,11 +64,25 @@ impl Attack for WienerAttack {
                     };
 
                     if (s + t).is_even() {
+                        let discovered = Parameters {
+                            d: Some(d.clone()),
+                            phi: Some(phi.clone()),
+                            ..Default::default()
+                        };
                         if let Some((p, q)) = trivial_factorization_with_n_phi(n, &phi) {
                             return Ok(Solution::new_pk(
                                 self.name(),
                                 PrivateKey::from_p_q(p, q, e)?,
-                            ));
+                            )
+                            .with_discovered(discovered));
+                        }
+
+                        // More than two factors, left to the attacks using d and phi
+                        if Integer::from(2)
+                            .pow_mod(&(e.clone() * d), n)
+                            .is_ok_and(|m| m == 2)
+                        {
+                            return Err(Error::Discovered(Box::new(discovered)));
                         }
                     }
                 }
@@ -95,4 +123,33 @@ mod tests {
 
         assert_eq!(pk.factors, factors);
     }
+
+    #[test]
+    fn three_factors() {
+        let factors = Factors::from([
+            Integer::from(2).pow(170).next_prime(),
+            Integer::from(3).pow(110).next_prime(),
+            Integer::from(5).pow(75).next_prime(),
+        ]);
+        let d = Integer::from(2).pow(40).next_prime();
+        let phi = factors.phi();
+        let e = d.clone().invert(&phi).unwrap();
+
+        let params = Parameters {
+            e,
+            n: Some(factors.product()),
+            ..Default::default()
+        };
+
+        // d and phi are left to the attacks factoring n with them
+        let discovered = Parameters {
+            d: Some(d),
+            phi: Some(phi),
+            ..Default::default()
+        };
+        assert_eq!(
+            WienerAttack.run(&params, None).unwrap_err(),
+            Error::Discovered(Box::new(discovered))
+        );
+    }
 }
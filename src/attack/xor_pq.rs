@@ -0,0 +1,123 @@
+use indicatif::ProgressBar;
+use rug::Integer;
+
+use crate::{
+    key::PrivateKey, Attack, AttackContext, AttackKind, AttackSpeed, Error, Parameters, Progress,
+    Solution, Vulnerability,
+};
+
+/// Largest number of candidates kept at each bit
+const MAX_CANDIDATES: u64 = 1 << 16;
+
+/// Leaked xor of p and q attack (p ^ q)
+///
+/// p and q are rebuilt from their highest bit: each bit of p gives the same bit of q with p ^ q,
+/// and a candidate is kept only if n is between the products of its prefixes completed with 0s
+/// and with 1s. p > q is assumed, so the first bit where they differ is set in p.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XorPQAttack;
+
+impl Attack for XorPQAttack {
+    fn name(&self) -> &'static str {
+        "xor_pq"
+    }
+
+    fn speed(&self) -> AttackSpeed {
+        AttackSpeed::Medium
+    }
+
+    fn kind(&self) -> AttackKind {
+        AttackKind::KnownExtraInformation
+    }
+
+    fn vulnerability(&self) -> Option<Vulnerability> {
+        Some(Vulnerability::LeakedSecret)
+    }
+
+    fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+        self.run_with_context(params, &AttackContext::new(params), pb)
+    }
+
+    fn run_with_context(
+        &self,
+        params: &Parameters,
+        ctx: &AttackContext,
+        pb: Option<&ProgressBar>,
+    ) -> Result<Solution, Error> {
+        let n = params.n.as_ref().ok_or(Error::MissingParameters)?;
+        let xor = params.xor_pq.as_ref().ok_or(Error::MissingParameters)?;
+        if *xor < 0 {
+            return Err(Error::NotFound);
+        }
+
+        // Primes of the same length have a shorter xor
+        let bits = xor.significant_bits().max(n.significant_bits().div_ceil(2));
+        pb.set_length(bits as u64);
+
+        let max_candidates = ctx.config().scaled_memory(MAX_CANDIDATES) as usize;
+        let mut candidates = vec![(Integer::new(), Integer::new())];
+        for i in (0..bits).rev() {
+            ctx.check()?;
+            pb.inc(1);
+
+            let xor_bit = xor.get_bit(i);
+            let low = (Integer::from(1) << i) - 1u32;
+            let mut next = vec![];
+            for (p_high, q_high) in candidates {
+                for p_bit in [false, true] {
+                    if xor_bit && !p_bit && p_high == q_high {
+                        continue;
+                    }
+                    let mut p_next = p_high.clone();
+                    let mut q_next = q_high.clone();
+                    p_next.set_bit(i, p_bit);
+                    q_next.set_bit(i, p_bit ^ xor_bit);
+                    if Integer::from(&p_next * &q_next) <= *n
+                        && Integer::from(&p_next | &low) * Integer::from(&q_next | &low) >= *n
+                    {
+                        next.push((p_next, q_next));
+                    }
+                }
+            }
+            if next.len() > max_candidates {
+                return Err(Error::NotFound);
+            }
+            candidates = next;
+        }
+
+        let (p, q) = candidates
+            .into_iter()
+            .find(|(p, q)| *q > 1 && Integer::from(p * q) == *n)
+            .ok_or(Error::NotFound)?;
+        Ok(Solution::new_pk(
+            self.name(),
+            PrivateKey::from_p_q(p, q, &params.e)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{Attack, Factors, Parameters};
+
+    use super::*;
+
+    #[test]
+    fn attack() {
+        let p = Integer::from_str("9680013379709450894240896318618061284105559659947313368392185471991345885186021740399175810163138239351083996692031751120232675301366868879251239724616281").unwrap();
+        let q = Integer::from_str("8648015025408335181758712092667722045493734511644452769085082668804450076445917118092786133629868326002982629135152287590451302935304608656309477470314237").unwrap();
+        let xor_pq = Integer::from(&p ^ &q);
+        let factors = Factors::from([p, q]);
+
+        let params = Parameters {
+            n: Some(factors.product()),
+            xor_pq: Some(xor_pq),
+            ..Default::default()
+        };
+
+        let solution = XorPQAttack.run(&params, None).unwrap();
+        assert_eq!(solution.pk.unwrap().factors, factors);
+    }
+}
@@ -0,0 +1,591 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    time::Instant,
+};
+
+use lazy_static::lazy_static;
+use rug::Integer;
+
+use crate::{find_weak_prime, AttackConfig, KeyEntry, Parameters, PrivateKey, Vulnerability};
+
+/// Largest number of Fermat iterations per modulus
+const FERMAT_ITERATIONS: u32 = 10_000;
+
+/// Bound of the small factors searched in each modulus
+const SMALL_FACTOR_BOUND: u32 = 1 << 16;
+
+/// Largest e considered low
+const LOW_EXPONENT: u32 = 17;
+
+lazy_static! {
+    /// Product of the primes below `SMALL_FACTOR_BOUND`, to find them all with a single gcd
+    static ref SMALL_PRIMES_PRODUCT: Integer = {
+        let mut product = Integer::from(1);
+        let mut p = Integer::from(2);
+        while p < SMALL_FACTOR_BOUND {
+            product *= &p;
+            p.next_prime_mut();
+        }
+        product
+    };
+}
+
+/// Small primes of the ROCA fingerprint, whose product divides the order of 65537 in the
+/// Infineon key generator
+const ROCA_PRIMES: [u32; 38] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167,
+];
+
+/// Weakness of a modulus of a corpus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Weakness {
+    /// The modulus shares a prime with another modulus of the corpus, given by index
+    SharedFactor {
+        /// Index of the other modulus
+        other: usize,
+        /// Shared prime
+        factor: Integer,
+    },
+    /// The modulus is also the modulus of another key of the corpus, given by index, with
+    /// another exponent
+    CommonModulus(usize),
+    /// The primes are close, found with a few Fermat iterations
+    ClosePrimes(Integer),
+    /// The modulus has the fingerprint of a ROCA key (CVE-2017-15361)
+    RocaFingerprint,
+    /// The modulus has a small factor
+    SmallFactor(Integer),
+    /// The modulus has a prime reused across public CTF challenges
+    WeakPrime(Integer),
+    /// The public exponent is low
+    LowExponent(Integer),
+}
+
+impl Display for Weakness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SharedFactor { other, factor } => {
+                write!(f, "shares the factor {factor} with modulus #{other}")
+            }
+            Self::CommonModulus(other) => {
+                write!(f, "same modulus as #{other}, with another exponent")
+            }
+            Self::ClosePrimes(p) => write!(f, "close primes, factor {p}"),
+            Self::RocaFingerprint => write!(f, "ROCA fingerprint (CVE-2017-15361)"),
+            Self::SmallFactor(p) => write!(f, "small factor {p}"),
+            Self::WeakPrime(p) => write!(f, "known weak prime {p}"),
+            Self::LowExponent(e) => write!(f, "low public exponent {e}"),
+        }
+    }
+}
+
+impl Weakness {
+    /// Returns the prime factor of the modulus revealed by the weakness, if any
+    pub fn factor(&self) -> Option<&Integer> {
+        match self {
+            Self::SharedFactor { factor, .. } => Some(factor),
+            Self::ClosePrimes(p) | Self::SmallFactor(p) | Self::WeakPrime(p) => Some(p),
+            Self::CommonModulus(_) | Self::RocaFingerprint | Self::LowExponent(_) => None,
+        }
+    }
+
+    /// Returns the classification of the weakness
+    pub fn vulnerability(&self) -> Vulnerability {
+        match self {
+            Self::SharedFactor { other, .. } => Vulnerability::SharedFactor(Some(*other)),
+            Self::CommonModulus(other) => Vulnerability::CommonModulus(vec![*other]),
+            Self::ClosePrimes(_) => Vulnerability::ClosePrimes,
+            Self::RocaFingerprint => Vulnerability::RocaFingerprint,
+            Self::SmallFactor(_) => Vulnerability::SmallFactor,
+            Self::WeakPrime(_) => Vulnerability::KnownWeakPrime,
+            Self::LowExponent(_) => Vulnerability::LowExponent,
+        }
+    }
+}
+
+/// Weakness of the modulus at `index` in the audited corpus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Index of the weak modulus
+    pub index: usize,
+    /// Weakness found
+    pub weakness: Weakness,
+}
+
+/// Product tree of the moduli, from the leaves to the root
+fn product_tree(moduli: &[Integer]) -> Vec<Vec<Integer>> {
+    let mut tree = vec![moduli.to_vec()];
+    while tree.last().unwrap().len() > 1 {
+        let level = tree
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| pair.iter().product())
+            .collect();
+        tree.push(level);
+    }
+    tree
+}
+
+/// Approximate memory of the product and remainder trees of `count` moduli of `bits` bits
+///
+/// Each level of the product tree holds all the moduli, and the remainders are twice as large.
+fn tree_memory(count: usize, bits: u64) -> u64 {
+    (count as u64 * bits / 8).saturating_mul(count.next_power_of_two().ilog2() as u64 + 3)
+}
+
+/// Returns the product of the other moduli modulo each modulus
+///
+/// The product of all moduli is reduced modulo each n_i^2 along a remainder tree.
+fn cofactor_residues(moduli: &[Integer]) -> Vec<Integer> {
+    let tree = product_tree(moduli);
+    let mut remainders = tree.last().unwrap().clone();
+    for level in tree.iter().rev().skip(1) {
+        remainders = level
+            .iter()
+            .enumerate()
+            .map(|(i, node)| remainders[i / 2].clone() % Integer::from(node.square_ref()))
+            .collect();
+    }
+    remainders
+        .into_iter()
+        .zip(moduli)
+        .map(|(remainder, n)| remainder / n)
+        .collect()
+}
+
+/// Returns gcd(n_i, product of the other moduli) for each modulus, with Bernstein's batch GCD
+///
+/// If the trees do not fit in `max_memory` bytes, the moduli are split in chunks whose trees fit,
+/// and the product of each chunk is then reduced modulo the moduli of the other chunks, which
+/// takes quadratic time in the number of chunks.
+pub fn batch_gcd(moduli: &[Integer], max_memory: u64) -> Vec<Integer> {
+    let bits = moduli
+        .iter()
+        .map(|n| n.significant_bits() as u64)
+        .max()
+        .unwrap_or_default();
+    let mut chunk_size = moduli.len().max(1);
+    while chunk_size > 1 && tree_memory(chunk_size, bits) > max_memory {
+        chunk_size = chunk_size.div_ceil(2);
+    }
+
+    let chunks = moduli.chunks(chunk_size).collect::<Vec<_>>();
+    let mut residues = chunks
+        .iter()
+        .flat_map(|chunk| cofactor_residues(chunk))
+        .collect::<Vec<_>>();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let product = chunk.iter().product::<Integer>();
+        for (j, other) in chunks.iter().enumerate().filter(|(j, _)| *j != i) {
+            for (k, n) in other.iter().enumerate() {
+                let residue = &mut residues[j * chunk_size + k];
+                *residue *= Integer::from(&product % n);
+                *residue %= n;
+            }
+        }
+    }
+    residues
+        .into_iter()
+        .zip(moduli)
+        .map(|(residue, n)| residue.gcd(n))
+        .collect()
+}
+
+/// Returns a factor of n if its primes are close enough for a few Fermat iterations
+pub fn fermat_factor(n: &Integer) -> Option<Integer> {
+    if *n <= 1 || n.is_even() {
+        return None;
+    }
+    let (mut a, rem) = n.clone().sqrt_rem(Integer::new());
+    if rem != 0 {
+        a += 1;
+    }
+    for _ in 0..FERMAT_ITERATIONS {
+        let b2 = Integer::from(a.square_ref()) - n;
+        if b2.is_perfect_square() {
+            let p = &a - b2.sqrt();
+            return (p > 1 && p < *n).then_some(p);
+        }
+        a += 1;
+    }
+    None
+}
+
+/// Returns true if n has the ROCA fingerprint: n mod r is a power of 65537 for each prime r
+pub fn has_roca_fingerprint(n: &Integer) -> bool {
+    *n > 1
+        && ROCA_PRIMES.iter().all(|&r| {
+            let residue = n.mod_u(r);
+            let generator = 65537 % r;
+            let mut power = 1;
+            (0..r).any(|_| {
+                let found = power == residue;
+                power = power * generator % r;
+                found
+            })
+        })
+}
+
+/// Findings of an audit, and number of keys audited before the deadline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Weaknesses found, sorted by key
+    pub findings: Vec<Finding>,
+    /// Number of keys fully audited, the next ones were only checked for shared factors
+    pub audited: usize,
+}
+
+impl AuditReport {
+    /// Returns the number of weak keys per class of vulnerability
+    pub fn statistics(&self) -> BTreeMap<&'static str, usize> {
+        let keys = self
+            .findings
+            .iter()
+            .map(|finding| (finding.weakness.vulnerability().class(), finding.index))
+            .collect::<BTreeSet<_>>();
+        let mut statistics = BTreeMap::new();
+        for (class, _) in keys {
+            *statistics.entry(class).or_default() += 1;
+        }
+        statistics
+    }
+}
+
+/// Private key of an audited key, rebuilt from a factor revealed by the audit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredKey {
+    /// Index of the key
+    pub index: usize,
+    /// Private key
+    pub pk: PrivateKey,
+    /// Message of the cipher paired with the key, if any
+    pub m: Option<Integer>,
+}
+
+/// Rebuild the private keys of the moduli factored by the audit (e.g. by a factor shared with
+/// another modulus), and decrypt the cipher of each key
+pub fn recover_keys(keys: &[KeyEntry], report: &AuditReport) -> Vec<RecoveredKey> {
+    let mut recovered = Vec::<RecoveredKey>::new();
+    for finding in &report.findings {
+        let (index, key) = (finding.index, &keys[finding.index]);
+        if recovered.last().is_some_and(|last| last.index == index) {
+            continue;
+        }
+        let Some(p) = finding.weakness.factor() else {
+            continue;
+        };
+        let q = Integer::from(&key.n / p);
+        let Ok(pk) = PrivateKey::from_p_q(p.clone(), q, &key.e) else {
+            continue;
+        };
+        if pk.is_valid() {
+            let m = key.c.as_ref().map(|c| pk.decrypt(c));
+            recovered.push(RecoveredKey { index, pk, m });
+        }
+    }
+    recovered
+}
+
+/// Returns the smallest prime factor of n below `SMALL_FACTOR_BOUND`, if any
+pub(crate) fn small_factor(n: &Integer) -> Option<Integer> {
+    let g = Integer::from(SMALL_PRIMES_PRODUCT.gcd_ref(n));
+    if g == 1 || g == *n {
+        return None;
+    }
+    let mut p = Integer::from(2);
+    while !g.is_divisible(&p) {
+        p.next_prime_mut();
+    }
+    Some(p)
+}
+
+/// Weaknesses of a single key, without the other keys of the corpus
+fn key_weaknesses(key: &KeyEntry) -> Vec<Weakness> {
+    let n = &key.n;
+    let mut weaknesses = vec![];
+    if let Some(p) = small_factor(n) {
+        weaknesses.push(Weakness::SmallFactor(p));
+    }
+    if let Some(p) = find_weak_prime(n) {
+        weaknesses.push(Weakness::WeakPrime(p.clone()));
+    }
+    if let Some(p) = fermat_factor(n) {
+        weaknesses.push(Weakness::ClosePrimes(p));
+    }
+    if has_roca_fingerprint(n) {
+        weaknesses.push(Weakness::RocaFingerprint);
+    }
+    if key.e <= LOW_EXPONENT {
+        weaknesses.push(Weakness::LowExponent(key.e.clone()));
+    }
+    weaknesses
+}
+
+/// Check a corpus of keys for shared factors and common moduli, within `max_memory` bytes, then
+/// each key for small factors, known weak primes, close primes, ROCA fingerprints and low
+/// exponents until the deadline
+pub fn audit_keys(keys: &[KeyEntry], deadline: Option<Instant>, max_memory: u64) -> AuditReport {
+    let moduli = keys.iter().map(|key| key.n.clone()).collect::<Vec<_>>();
+    let gcds = batch_gcd(&moduli, max_memory);
+
+    let mut findings = vec![];
+    for (index, n) in moduli.iter().enumerate() {
+        if gcds[index] == 1 {
+            continue;
+        }
+        // Pairwise gcd, only for the few moduli with a shared factor
+        findings.extend(
+            moduli
+                .iter()
+                .enumerate()
+                .filter(|(other, m)| *other != index && gcds[*other] != 1 && *m != n)
+                .filter_map(|(other, m)| {
+                    let factor = Integer::from(n.gcd_ref(m));
+                    (factor != 1).then_some(Weakness::SharedFactor { other, factor })
+                })
+                .map(|weakness| Finding { index, weakness }),
+        );
+        // Identical moduli, whose gcd with the other moduli is the modulus itself
+        if gcds[index] == *n {
+            findings.extend(
+                keys.iter()
+                    .enumerate()
+                    .filter(|(other, key)| key.n == *n && key.e != keys[index].e && *other != index)
+                    .map(|(other, _)| Finding {
+                        index,
+                        weakness: Weakness::CommonModulus(other),
+                    }),
+            );
+        }
+    }
+
+    let mut audited = 0;
+    for (index, key) in keys.iter().enumerate() {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break;
+        }
+        findings.extend(
+            key_weaknesses(key)
+                .into_iter()
+                .map(|weakness| Finding { index, weakness }),
+        );
+        audited += 1;
+    }
+    findings.sort_by_key(|finding| finding.index);
+    AuditReport { findings, audited }
+}
+
+/// Check a corpus of moduli, see `audit_keys`, with the default public exponent and memory budget
+pub fn audit_moduli(moduli: &[Integer]) -> Vec<Finding> {
+    let keys = moduli
+        .iter()
+        .map(|n| KeyEntry {
+            n: n.clone(),
+            e: Parameters::default().e,
+            c: None,
+            known: Parameters::default(),
+        })
+        .collect::<Vec<_>>();
+    audit_keys(&keys, None, AttackConfig::default().max_memory).findings
+}
+
+/// Parse a modulus in decimal, or in hexadecimal with or without `0x` prefix
+fn parse_modulus(n: &str) -> Option<Integer> {
+    let n = if let Some(hex) = n.strip_prefix("0x").or_else(|| n.strip_prefix("0X")) {
+        Integer::from_str_radix(hex, 16)
+    } else if n.bytes().all(|b| b.is_ascii_digit()) {
+        Integer::from_str_radix(n, 10)
+    } else {
+        Integer::from_str_radix(n, 16)
+    };
+    n.ok().filter(|n| *n > 1)
+}
+
+/// Parse a list of moduli, one per line in decimal or hexadecimal, skipping empty lines and `#`
+/// comments
+pub fn parse_moduli(text: &str) -> Result<Vec<Integer>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.split('#').next().unwrap_or_default().trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| parse_modulus(line).ok_or(format!("Invalid modulus at line {}", i + 1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rug::ops::Pow;
+
+    use super::*;
+
+    /// Returns a ROCA-like prime k * M + 65537^a mod M, with M the product of the fingerprint primes
+    fn roca_prime(k: u32, a: u32) -> Integer {
+        let m: Integer = ROCA_PRIMES.iter().map(|&r| Integer::from(r)).product();
+        let mut p = Integer::from(k) * &m + Integer::from(65537).pow(a) % &m;
+        while p.is_probably_prime(30) == rug::integer::IsPrime::No {
+            p += &m;
+        }
+        p
+    }
+
+    #[test]
+    fn audit() {
+        let p = Integer::from_str("9680013379709450894240896318618061284105559659947313368392185471991345885186021740399175810163138239351083996692031751120232675301366868879251239724616281").unwrap();
+        let q = Integer::from_str("8648015025408335181758712092667722045493734511644452769085082668804450076445917118092786133629868326002982629135152287590451302935304608656309477470314237").unwrap();
+        let r = p.clone().next_prime();
+        let s = roca_prime(1 << 20, 12345);
+        let t = roca_prime(1 << 21, 54321);
+
+        let moduli = [
+            Integer::from(&p * &q),
+            Integer::from(&r * &q),
+            Integer::from(&p * &r),
+            Integer::from(&s * &t),
+        ];
+        let findings = audit_moduli(&moduli);
+        assert!(findings.contains(&Finding {
+            index: 0,
+            weakness: Weakness::SharedFactor {
+                other: 1,
+                factor: q.clone()
+            }
+        }));
+        assert!(findings.contains(&Finding {
+            index: 1,
+            weakness: Weakness::SharedFactor {
+                other: 2,
+                factor: r.clone()
+            }
+        }));
+        assert!(findings.contains(&Finding {
+            index: 2,
+            weakness: Weakness::ClosePrimes(p)
+        }));
+        assert!(findings.contains(&Finding {
+            index: 3,
+            weakness: Weakness::RocaFingerprint
+        }));
+        assert_eq!(
+            findings
+                .iter()
+                .filter(|finding| finding.weakness == Weakness::RocaFingerprint)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn audit_keys() {
+        // RSA-100
+        let weak_prime = Integer::from_str("1522605027922533360535618378132637429718068114961380688657908494580122963258952897654000350692006139").unwrap();
+        let keys = [
+            KeyEntry {
+                n: Integer::from(65521) * Integer::from(1903643191),
+                e: 3.into(),
+                c: None,
+                known: Parameters::default(),
+            },
+            KeyEntry {
+                n: weak_prime,
+                e: 65537.into(),
+                c: None,
+                known: Parameters::default(),
+            },
+        ];
+        let report = super::audit_keys(&keys, None, u64::MAX);
+        assert_eq!(report.audited, 2);
+        assert!(report.findings.contains(&Finding {
+            index: 0,
+            weakness: Weakness::SmallFactor(65521.into())
+        }));
+        assert!(report.findings.contains(&Finding {
+            index: 0,
+            weakness: Weakness::LowExponent(3.into())
+        }));
+        assert!(report.findings.iter().any(
+            |finding| finding.index == 1 && matches!(finding.weakness, Weakness::WeakPrime(_))
+        ));
+
+        let statistics = report.statistics();
+        assert_eq!(statistics["small factor"], 1);
+        assert_eq!(statistics["low e"], 1);
+        assert_eq!(statistics["known weak prime"], 1);
+
+        // The same modulus with another exponent
+        let mut keys = keys.to_vec();
+        keys.push(KeyEntry {
+            e: 65537.into(),
+            ..keys[0].clone()
+        });
+        let report = super::audit_keys(&keys, None, u64::MAX);
+        assert!(report.findings.contains(&Finding {
+            index: 0,
+            weakness: Weakness::CommonModulus(2)
+        }));
+        assert!(report.findings.contains(&Finding {
+            index: 2,
+            weakness: Weakness::CommonModulus(0)
+        }));
+
+        // No time left, only shared factors and common moduli are searched
+        let report = super::audit_keys(&keys, Some(Instant::now()), u64::MAX);
+        assert_eq!(report.audited, 0);
+        assert_eq!(report.findings.len(), 2);
+    }
+
+    #[test]
+    fn recover_keys() {
+        let p = Integer::from_str("9680013379709450894240896318618061284105559659947313368392185471991345885186021740399175810163138239351083996692031751120232675301366868879251239724616281").unwrap();
+        let q = Integer::from_str("8648015025408335181758712092667722045493734511644452769085082668804450076445917118092786133629868326002982629135152287590451302935304608656309477470314237").unwrap();
+        let r = q.clone().next_prime();
+        let t = r.clone().next_prime();
+        let u = t.clone().next_prime();
+        let m = Integer::from(0x1337);
+
+        // The first two keys share p, the last one shares no factor
+        let keys = [(&p, &q), (&p, &r), (&t, &u)].map(|(p, q)| {
+            let n = Integer::from(p * q);
+            KeyEntry {
+                c: Some(m.clone().pow_mod(&65537.into(), &n).unwrap()),
+                n,
+                e: 65537.into(),
+                known: Parameters::default(),
+            }
+        });
+        let report = super::audit_keys(&keys, Some(Instant::now()), u64::MAX);
+        let recovered = super::recover_keys(&keys, &report);
+        assert_eq!(recovered.len(), 2);
+        for (index, key) in recovered.iter().enumerate() {
+            assert_eq!(key.index, index);
+            assert_eq!(key.pk.n, keys[index].n);
+            assert_eq!(key.m, Some(m.clone()));
+        }
+    }
+
+    #[test]
+    fn batch_gcd() {
+        let moduli = [15, 77, 35, 11 * 13, 221, 15].map(Integer::from);
+        let gcds = [15, 77, 35, 143, 13, 15];
+        assert_eq!(super::batch_gcd(&moduli, u64::MAX), gcds);
+        // Chunks of three, two and one moduli
+        assert_eq!(super::batch_gcd(&moduli, 20), gcds);
+        assert_eq!(super::batch_gcd(&moduli, 10), gcds);
+        assert_eq!(super::batch_gcd(&moduli, 0), gcds);
+        assert!(super::batch_gcd(&[], 0).is_empty());
+    }
+
+    #[test]
+    fn parse_moduli() {
+        let moduli = super::parse_moduli("# moduli\n3233\n\n0xca1\nCA1 # hex\n").unwrap();
+        assert_eq!(moduli, [3233, 3233, 3233]);
+        assert_eq!(
+            super::parse_moduli("3233\nn = 3233\n"),
+            Err("Invalid modulus at line 2".to_string())
+        );
+    }
+}
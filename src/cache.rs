@@ -0,0 +1,222 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use rug::{integer::IsPrime, Complete, Integer};
+
+use crate::ntheory::is_prime;
+
+/// Number of completed runs of an attack averaged to estimate its duration
+const TIMING_RUNS: usize = 16;
+
+/// On-disk cache of FactorDB factorizations and of the durations of the attacks, keyed by modulus.
+///
+/// Factorizations are stored as plain text files (one factor per line) named after the sha256 of the modulus, and their
+/// primes are appended to an index searched for the factors shared with other moduli.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// Create a cache stored in the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Create a cache stored in the default directory (`~/.cache/rsacracker`).
+    ///
+    /// The directory can be overridden with `RSACRACKER_CACHE_DIR`.
+    /// Returns `None` if `RSACRACKER_NO_CACHE` is set or if no cache directory can be found.
+    pub fn from_env() -> Option<Self> {
+        if std::env::var_os("RSACRACKER_NO_CACHE").is_some() {
+            return None;
+        }
+
+        if let Some(dir) = std::env::var_os("RSACRACKER_CACHE_DIR").filter(|d| !d.is_empty()) {
+            return Some(Self::new(dir));
+        }
+        std::env::var_os("XDG_CACHE_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .filter(|d| !d.is_empty())
+                    .map(|home| Path::new(&home).join(".cache"))
+            })
+            .map(|dir| Self::new(dir.join("rsacracker")))
+    }
+
+    /// Returns the cache directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn key(n: &Integer) -> String {
+        openssl::sha::sha256(n.to_string_radix(16).as_bytes())
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    fn read_integers(path: &Path) -> Option<Vec<Integer>> {
+        fs::read_to_string(path)
+            .ok()?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Integer::from_str(line.trim()).ok())
+            .collect()
+    }
+
+    fn lines<'a>(integers: impl IntoIterator<Item = &'a Integer>) -> String {
+        integers.into_iter().map(|i| format!("{i}\n")).collect()
+    }
+
+    /// Replace the file, through a temporary file renamed over it, so that readers never see
+    /// a partial write
+    fn write(path: &Path, content: &str) -> io::Result<()> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension(format!(
+            "tmp-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, path).inspect_err(|_| {
+            fs::remove_file(&tmp).ok();
+        })
+    }
+
+    /// Append to the file with a single write, so that concurrent appends are not interleaved
+    fn append(path: &Path, content: &str) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(content.as_bytes())
+    }
+
+    /// Returns the cached FactorDB factorization of n, if any.
+    pub fn get_factordb(&self, n: &Integer) -> Option<Vec<Integer>> {
+        let factors = Self::read_integers(&self.dir.join("factordb").join(Self::key(n)))?;
+
+        // Ignore corrupted entries
+        if factors.len() < 2 || Integer::product(factors.iter()).complete() != *n {
+            return None;
+        }
+        Some(factors)
+    }
+
+    /// Store a FactorDB response for n.
+    ///
+    /// Responses without a factorization are not stored, since FactorDB may factor n later.
+    pub fn store_factordb(&self, n: &Integer, factors: &[Integer]) {
+        if factors.len() < 2 {
+            return;
+        }
+
+        // Caching is best effort, errors are ignored
+        let dir = self.dir.join("factordb");
+        Self::write(&dir.join(Self::key(n)), &Self::lines(factors)).ok();
+        let primes = factors.iter().filter(|p| is_prime(p) != IsPrime::No);
+        Self::append(&dir.join("primes"), &Self::lines(primes)).ok();
+    }
+
+    /// Returns the primes of the cached FactorDB factorizations.
+    pub fn primes(&self) -> Vec<Integer> {
+        let mut primes =
+            Self::read_integers(&self.dir.join("factordb").join("primes")).unwrap_or_default();
+        primes.sort();
+        primes.dedup();
+        primes
+    }
+
+    fn timing_path(&self, attack: &str, n_bits: u32) -> PathBuf {
+        // Moduli of close sizes share their timings
+        let bucket = n_bits.div_ceil(256) * 256;
+        self.dir.join("timings").join(format!("{attack}-{bucket}"))
+    }
+
+    /// Returns the average duration of the last `TIMING_RUNS` completed runs of an attack on moduli of this size, if any.
+    pub fn get_timing(&self, attack: &str, n_bits: u32) -> Option<Duration> {
+        let millis = Self::read_integers(&self.timing_path(attack, n_bits))?;
+        let last = &millis[millis.len().saturating_sub(TIMING_RUNS)..];
+        if last.is_empty() {
+            return None;
+        }
+        let average = Integer::sum(last.iter()).complete() / last.len() as u64;
+        Some(Duration::from_millis(average.to_u64()?))
+    }
+
+    /// Record the duration of a completed run of an attack.
+    pub fn store_timing(&self, attack: &str, n_bits: u32, duration: Duration) {
+        let millis = Integer::from(duration.as_millis() as u64);
+        Self::append(&self.timing_path(attack, n_bits), &Self::lines([&millis])).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str) -> Cache {
+        let dir =
+            std::env::temp_dir().join(format!("rsacracker-cache-{name}-{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        Cache::new(dir)
+    }
+
+    #[test]
+    fn store_and_get_factordb() {
+        let cache = temp_cache("factordb");
+        let n = Integer::from(1779681653u64) * 1903643191u64;
+        let factors = vec![Integer::from(1779681653u64), Integer::from(1903643191u64)];
+
+        assert_eq!(cache.get_factordb(&n), None);
+        cache.store_factordb(&n, std::slice::from_ref(&n));
+        assert_eq!(cache.get_factordb(&n), None);
+        cache.store_factordb(&n, &factors);
+        assert_eq!(cache.get_factordb(&n), Some(factors.clone()));
+        assert_eq!(cache.primes(), factors);
+
+        fs::remove_dir_all(cache.dir()).ok();
+    }
+
+    #[test]
+    fn store_and_get_timing() {
+        let cache = temp_cache("timings");
+
+        assert_eq!(cache.get_timing("fermat", 512), None);
+        cache.store_timing("fermat", 512, Duration::from_millis(100));
+        cache.store_timing("fermat", 500, Duration::from_millis(300));
+        cache.store_timing("fermat", 510, Duration::from_millis(500));
+        assert_eq!(
+            cache.get_timing("fermat", 512),
+            Some(Duration::from_millis(300))
+        );
+
+        // Only the last runs are averaged
+        for _ in 0..TIMING_RUNS {
+            cache.store_timing("fermat", 512, Duration::from_millis(1000));
+        }
+        assert_eq!(
+            cache.get_timing("fermat", 512),
+            Some(Duration::from_millis(1000))
+        );
+        assert_eq!(cache.get_timing("fermat", 2048), None);
+
+        fs::remove_dir_all(cache.dir()).ok();
+    }
+}
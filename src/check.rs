@@ -0,0 +1,160 @@
+use std::fmt::Display;
+
+use rug::{integer::IsPrime, Integer};
+
+use crate::{ntheory::is_prime, Parameters};
+
+/// Mismatch between a private key and a public key, or inconsistency within a private key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyMismatch {
+    /// A value of the private key (n or e) differs from the public key's
+    PublicKeyMismatch(&'static str),
+    /// A value required to check the private key (n, p, q or d) is missing
+    MissingValue(&'static str),
+    /// A factor (p or q) is not prime
+    FactorNotPrime(&'static str),
+    /// n is not p * q
+    ModulusNotProduct,
+    /// d is not the inverse of e modulo lambda(n)
+    WrongPrivateExponent,
+    /// A CRT value (dp, dq or qinv) does not match p, q and d
+    WrongCrtValue(&'static str),
+}
+
+impl Display for KeyMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PublicKeyMismatch(name) => write!(f, "{name} differs from the public key"),
+            Self::MissingValue(name) => write!(f, "{name} is missing from the private key"),
+            Self::FactorNotPrime(name) => write!(f, "{name} is not prime"),
+            Self::ModulusNotProduct => write!(f, "n is not p * q"),
+            Self::WrongPrivateExponent => write!(f, "d is not the inverse of e modulo lambda(n)"),
+            Self::WrongCrtValue(name) => write!(f, "{name} does not match p, q and d"),
+        }
+    }
+}
+
+/// Check that a private key matches a public key, and that it is consistent
+///
+/// n and e must be the public key's, n must be the product of the primes p and q, and d, dp, dq
+/// and qinv must be derived from them. CRT values absent from the private key are not checked.
+/// Returns the mismatches found, none if the keys match.
+pub fn check_key(private: &Parameters, public: Option<&Parameters>) -> Vec<KeyMismatch> {
+    let mut mismatches = vec![];
+    if let Some(public) = public {
+        if public.n.is_some() && private.n != public.n {
+            mismatches.push(KeyMismatch::PublicKeyMismatch("n"));
+        }
+        if private.e != public.e {
+            mismatches.push(KeyMismatch::PublicKeyMismatch("e"));
+        }
+    }
+
+    let (Some(n), Some(p), Some(q), Some(d)) = (&private.n, &private.p, &private.q, &private.d)
+    else {
+        for (name, value) in [
+            ("n", &private.n),
+            ("p", &private.p),
+            ("q", &private.q),
+            ("d", &private.d),
+        ] {
+            if value.is_none() {
+                mismatches.push(KeyMismatch::MissingValue(name));
+            }
+        }
+        return mismatches;
+    };
+
+    for (name, factor) in [("p", p), ("q", q)] {
+        if is_prime(factor) == IsPrime::No {
+            mismatches.push(KeyMismatch::FactorNotPrime(name));
+        }
+    }
+    if Integer::from(p * q) != *n {
+        mismatches.push(KeyMismatch::ModulusNotProduct);
+    }
+
+    let (p_minus_one, q_minus_one) = (Integer::from(p - 1), Integer::from(q - 1));
+    let ed_minus_one: Integer = Integer::from(&private.e * d) - 1;
+    if !ed_minus_one.is_divisible(&p_minus_one) || !ed_minus_one.is_divisible(&q_minus_one) {
+        mismatches.push(KeyMismatch::WrongPrivateExponent);
+    }
+    for (name, value, modulus) in [
+        ("dp", &private.dp, &p_minus_one),
+        ("dq", &private.dq, &q_minus_one),
+    ] {
+        if value
+            .as_ref()
+            .is_some_and(|value| *value != Integer::from(d % modulus))
+        {
+            mismatches.push(KeyMismatch::WrongCrtValue(name));
+        }
+    }
+    if private
+        .qinv
+        .as_ref()
+        .is_some_and(|qinv| Integer::from(qinv * q) % p != 1)
+    {
+        mismatches.push(KeyMismatch::WrongCrtValue("qinv"));
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn private_key() -> Parameters {
+        // p = 61, q = 53, e = 17
+        Parameters {
+            n: Some(3233.into()),
+            e: 17.into(),
+            p: Some(61.into()),
+            q: Some(53.into()),
+            d: Some(413.into()),
+            dp: Some(53.into()),
+            dq: Some(49.into()),
+            qinv: Some(38.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn consistent_key() {
+        let public = Parameters {
+            n: Some(3233.into()),
+            e: 17.into(),
+            ..Default::default()
+        };
+        assert!(check_key(&private_key(), Some(&public)).is_empty());
+    }
+
+    #[test]
+    fn mismatches() {
+        let public = Parameters {
+            n: Some(3233.into()),
+            e: 65537.into(),
+            ..Default::default()
+        };
+        let private = Parameters {
+            d: Some(414.into()),
+            dq: Some(48.into()),
+            ..private_key()
+        };
+        assert_eq!(
+            check_key(&private, Some(&public)),
+            [
+                KeyMismatch::PublicKeyMismatch("e"),
+                KeyMismatch::WrongPrivateExponent,
+                KeyMismatch::WrongCrtValue("dp"),
+                KeyMismatch::WrongCrtValue("dq"),
+            ]
+        );
+
+        let private = Parameters {
+            q: None,
+            ..private_key()
+        };
+        assert_eq!(check_key(&private, None), [KeyMismatch::MissingValue("q")]);
+    }
+}
@@ -0,0 +1,174 @@
+//! Continued fractions of rationals, their convergents and best rational approximations.
+//!
+//! The convergents of e / n are the candidates k / d of Wiener's attack.
+
+use rug::{ops::DivRounding, Integer};
+
+/// Returns the partial quotients of the continued fraction of x / y
+///
+/// Returns no quotients if y is 0.
+pub fn expansion(x: &Integer, y: &Integer) -> Vec<Integer> {
+    let mut quotients = vec![];
+    let (mut x, mut y) = (x.clone(), y.clone());
+    while y != 0 {
+        let a = x.clone().div_floor(&y);
+        let r = x - &a * &y;
+        quotients.push(a);
+        (x, y) = (y, r);
+    }
+    quotients
+}
+
+/// Returns the numerator and denominator of a continued fraction, given by its partial quotients
+pub fn to_rational(quotients: &[Integer]) -> (Integer, Integer) {
+    Convergents::new(quotients.iter().cloned())
+        .last()
+        .unwrap_or((Integer::ZERO, Integer::from(1)))
+}
+
+/// Iterator over the convergents (numerator, denominator) of a continued fraction
+///
+/// Each convergent is computed from the two previous ones, with h_i = a_i * h_(i-1) + h_(i-2).
+#[derive(Debug, Clone)]
+pub struct Convergents<I> {
+    quotients: I,
+    /// Two previous numerators
+    h: (Integer, Integer),
+    /// Two previous denominators
+    k: (Integer, Integer),
+}
+
+impl<I: Iterator<Item = Integer>> Convergents<I> {
+    /// Create an iterator over the convergents of the continued fraction given by its partial quotients
+    pub fn new(quotients: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            quotients: quotients.into_iter(),
+            h: (Integer::ZERO, Integer::from(1)),
+            k: (Integer::from(1), Integer::ZERO),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Integer>> Iterator for Convergents<I> {
+    type Item = (Integer, Integer);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let a = self.quotients.next()?;
+        let h = Integer::from(&a * &self.h.1) + &self.h.0;
+        let k = a * &self.k.1 + &self.k.0;
+        self.h = (std::mem::take(&mut self.h.1), h.clone());
+        self.k = (std::mem::take(&mut self.k.1), k.clone());
+        Some((h, k))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.quotients.size_hint()
+    }
+}
+
+impl<I: ExactSizeIterator<Item = Integer>> ExactSizeIterator for Convergents<I> {}
+
+/// Returns the convergents of x / y, the last one being x / y in lowest terms
+pub fn convergents(x: &Integer, y: &Integer) -> Convergents<std::vec::IntoIter<Integer>> {
+    Convergents::new(expansion(x, y))
+}
+
+/// Returns the closest rational to x / y with a denominator of at most `max_denominator` (at least 1)
+///
+/// It is either a convergent or a semiconvergent of x / y.
+pub fn best_approximation(
+    x: &Integer,
+    y: &Integer,
+    max_denominator: &Integer,
+) -> (Integer, Integer) {
+    let g = Integer::from(x.gcd_ref(y));
+    let (x, y) = (Integer::from(x / &g), Integer::from(y / &g));
+    let (x, y) = if y < 0 { (-x, -y) } else { (x, y) };
+    if y <= *max_denominator {
+        return (x, y);
+    }
+
+    // Last two convergents whose denominators are within the bound
+    let (mut p0, mut q0, mut p1, mut q1) = (
+        Integer::ZERO,
+        Integer::from(1),
+        Integer::from(1),
+        Integer::ZERO,
+    );
+    let (mut n, mut d) = (x.clone(), y.clone());
+    loop {
+        let a = n.clone().div_floor(&d);
+        let q2 = Integer::from(&a * &q1) + &q0;
+        if q2 > *max_denominator {
+            break;
+        }
+        let p2 = Integer::from(&a * &p1) + &p0;
+        (p0, q0, p1, q1) = (p1, q1, p2, q2);
+        let r = n - a * &d;
+        (n, d) = (d, r);
+    }
+
+    // Largest semiconvergent within the bound, compared to the last convergent
+    // (|p / q - x / y| is compared through |p * y - x * q| / q, y being common)
+    let s = Integer::from(max_denominator - &q0) / &q1;
+    let semi = (p0 + Integer::from(&s * &p1), q0 + s * &q1);
+    let distance =
+        |(p, q): &(Integer, Integer)| (Integer::from(p * &y) - Integer::from(&x * q)).abs();
+    let convergent = (p1, q1);
+    if distance(&convergent) * &semi.1 <= distance(&semi) * &convergent.1 {
+        convergent
+    } else {
+        semi
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn expansion_and_rational() {
+        let quotients = expansion(&415.into(), &93.into());
+        assert_eq!(quotients, [4, 2, 6, 7]);
+        assert_eq!(to_rational(&quotients), (415.into(), 93.into()));
+        assert_eq!(to_rational(&[]), (0.into(), 1.into()));
+        assert!(expansion(&1.into(), &0.into()).is_empty());
+    }
+
+    #[test]
+    fn convergents() {
+        assert_eq!(
+            super::convergents(&415.into(), &93.into()).collect::<Vec<_>>(),
+            [(4, 1), (9, 2), (58, 13), (415, 93)]
+                .map(|(h, k)| (Integer::from(h), Integer::from(k)))
+        );
+    }
+
+    #[test]
+    fn best_approximation() {
+        let x = Integer::from_str("314159265358979").unwrap();
+        let y = Integer::from_str("100000000000000").unwrap();
+        assert_eq!(
+            super::best_approximation(&x, &y, &1000.into()),
+            (355.into(), 113.into())
+        );
+        assert_eq!(
+            super::best_approximation(&x, &y, &100.into()),
+            (311.into(), 99.into())
+        );
+        assert_eq!(
+            super::best_approximation(&415.into(), &93.into(), &10.into()),
+            (40.into(), 9.into())
+        );
+        assert_eq!(
+            super::best_approximation(&(-7).into(), &3.into(), &2.into()),
+            ((-5).into(), 2.into())
+        );
+        assert_eq!(
+            super::best_approximation(&830.into(), &186.into(), &100.into()),
+            (415.into(), 93.into())
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use crate::Parameters;
+
+/// Certificate Transparency search engine
+const CRT_SH: &str = "https://crt.sh/";
+
+/// Timeout of each request, the searches of large domains are slow
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// RSA key of a certificate logged in Certificate Transparency
+#[derive(Debug, Clone)]
+pub struct CtCertificate {
+    /// crt.sh identifier of the certificate
+    pub id: u64,
+    /// Common name of the certificate subject
+    pub common_name: String,
+    /// Public key of the certificate
+    pub params: Parameters,
+}
+
+/// Search the certificates of a crt.sh query (e.g. `example.com` or `%.example.com`), and download
+/// at most `limit` of them
+///
+/// Certificates without RSA key are skipped.
+pub fn fetch_ct_certificates(query: &str, limit: usize) -> Result<Vec<CtCertificate>, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let entries: serde_json::Value = client
+        .get(CRT_SH)
+        .query(&[("q", query), ("output", "json")])
+        .send()
+        .and_then(|response| response.json())
+        .map_err(|err| format!("Failed to search the certificates of {query}: {err}"))?;
+
+    let mut certificates = vec![];
+    for (id, common_name) in unique_entries(&entries, limit) {
+        let cert = client
+            .get(CRT_SH)
+            .query(&[("d", id)])
+            .send()
+            .and_then(|response| response.bytes())
+            .map_err(|err| format!("Failed to download the certificate {id}: {err}"))?;
+        if let Some(params) = Parameters::from_x509_cert(&cert) {
+            certificates.push(CtCertificate {
+                id,
+                common_name: common_name.to_string(),
+                params,
+            });
+        }
+    }
+    Ok(certificates)
+}
+
+/// Returns the ids and common names of the first `limit` distinct certificates of a crt.sh search
+///
+/// Certificates are logged once per precertificate and final certificate, under different ids but
+/// with the same issuer and serial number.
+fn unique_entries(entries: &serde_json::Value, limit: usize) -> Vec<(u64, &str)> {
+    let mut seen = BTreeSet::new();
+    entries
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|entry| {
+            let key = (
+                entry["issuer_name"].as_str(),
+                entry["serial_number"].as_str(),
+            );
+            key == (None, None) || seen.insert(key)
+        })
+        .filter_map(|entry| Some((entry["id"].as_u64()?, entry["common_name"].as_str()?)))
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn unique_entries() {
+        let entries = serde_json::json!([
+            {"id": 1, "common_name": "example.com", "issuer_name": "CA", "serial_number": "01"},
+            {"id": 2, "common_name": "example.com", "issuer_name": "CA", "serial_number": "01"},
+            {"id": 3, "common_name": "example.com", "issuer_name": "Other CA", "serial_number": "01"},
+            {"id": 4, "common_name": "www.example.com", "issuer_name": "CA", "serial_number": "02"},
+        ]);
+
+        // The precertificate and final certificate of a same serial number and issuer are one
+        assert_eq!(
+            super::unique_entries(&entries, 10),
+            [
+                (1, "example.com"),
+                (3, "example.com"),
+                (4, "www.example.com")
+            ]
+        );
+        assert_eq!(super::unique_entries(&entries, 1), [(1, "example.com")]);
+    }
+}
@@ -0,0 +1,454 @@
+use std::fmt::Display;
+
+use rug::{integer::IsPrime, Integer};
+
+use crate::{ntheory::is_prime, reverse_bytes, Parameters, PlaintextConstraint, Solution};
+
+/// Bit length under which n is considered tiny
+const TINY_MODULUS_BITS: u32 = 256;
+
+/// Largest e considered small
+const SMALL_EXPONENT: u32 = 17;
+
+/// Public exponents commonly used, whose inverse may be given as e
+const COMMON_EXPONENTS: [u32; 5] = [65537, 3, 5, 17, 257];
+
+/// Largest e tried to encrypt the known plaintext to the known cipher
+const MAX_KNOWN_PAIR_EXPONENT: u32 = 1 << 17;
+
+/// Suspicious input, detected before running the attacks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// A given factor (p or q) is not prime
+    FactorNotPrime(&'static str),
+    /// A given factor (p or q) does not divide n
+    FactorDoesNotDivideN(&'static str),
+    /// e is not coprime with phi, so d does not exist
+    ExponentNotCoprimeWithPhi,
+    /// d is not the inverse of e
+    InconsistentPrivateExponent,
+    /// n has a tiny bit length
+    TinyModulus(u32),
+    /// n is even
+    EvenModulus,
+    /// c is a multiple of n
+    CipherMultipleOfN,
+    /// c is 0 or 1, which are their own e-th powers
+    TrivialCipher,
+    /// e is 1
+    UnitExponent,
+    /// e is small
+    SmallExponent,
+    /// e is almost as large as n, d may be small
+    LargeExponent,
+    /// e is the inverse of a common public exponent (given), it is likely the private exponent d
+    PrivateExponentAsE(u32),
+    /// e is as large as a cipher and c is small, they may be swapped (e^c mod n)
+    SwappedExponentAndCipher,
+    /// The known plaintext encrypts to the known cipher with another e (given), not with e
+    KnownPairExponent(u32),
+    /// The known plaintext does not encrypt to the known cipher with e, nor with a small e
+    KnownPairMismatch,
+    /// n is even while its byte reversal is odd, or the known plaintext only encrypts to the
+    /// byte-reversed known cipher: the dump stores the values little-endian
+    ByteSwapped,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FactorNotPrime(name) => write!(f, "{name} is not prime"),
+            Self::FactorDoesNotDivideN(name) => write!(f, "{name} does not divide n"),
+            Self::ExponentNotCoprimeWithPhi => write!(f, "e is not coprime with phi"),
+            Self::InconsistentPrivateExponent => write!(f, "d is not the inverse of e"),
+            Self::TinyModulus(bits) => write!(f, "n is only {bits} bits long"),
+            Self::EvenModulus => write!(f, "n is even"),
+            Self::CipherMultipleOfN => write!(f, "c is a multiple of n"),
+            Self::TrivialCipher => write!(f, "c is 0 or 1, and so is the message"),
+            Self::UnitExponent => write!(f, "e is 1, c is not encrypted"),
+            Self::SmallExponent => write!(f, "e is small"),
+            Self::LargeExponent => write!(f, "e is almost as large as n"),
+            Self::PrivateExponentAsE(e) => {
+                write!(f, "e looks like the private exponent d of e = {e}")
+            }
+            Self::SwappedExponentAndCipher => write!(f, "e looks like a cipher and c is small"),
+            Self::KnownPairExponent(e) => {
+                write!(
+                    f,
+                    "the known plaintext encrypts to the known cipher with e = {e}"
+                )
+            }
+            Self::KnownPairMismatch => {
+                write!(
+                    f,
+                    "the known plaintext does not encrypt to the known cipher"
+                )
+            }
+            Self::ByteSwapped => write!(f, "the values look byte-swapped (little-endian)"),
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Returns the attacks enabled by this condition
+    pub fn attacks(&self) -> &'static [&'static str] {
+        match self {
+            Self::ExponentNotCoprimeWithPhi => &["non_coprime_exp", "cipolla"],
+            Self::TinyModulus(_) => &["small_prime", "pollard_rho", "squfof", "ecm"],
+            Self::EvenModulus => &["small_prime"],
+            Self::SmallExponent => &["cube_root", "small_e", "eth_root"],
+            Self::LargeExponent => &["wiener", "partial_d"],
+            Self::PrivateExponentAsE(_) => &["known_d"],
+            _ => &[],
+        }
+    }
+}
+
+/// Check the parameters for suspicious values
+pub fn diagnose(params: &Parameters) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let e = &params.e;
+    let n = params.n.as_ref();
+
+    for (name, factor) in [("p", &params.p), ("q", &params.q)] {
+        let Some(factor) = factor else {
+            continue;
+        };
+        if is_prime(factor) == IsPrime::No {
+            diagnostics.push(Diagnostic::FactorNotPrime(name));
+        }
+        if n.is_some_and(|n| *factor <= 1 || !n.is_divisible(factor)) {
+            diagnostics.push(Diagnostic::FactorDoesNotDivideN(name));
+        }
+    }
+
+    let derived = params.derived();
+    let coprime = derived
+        .phi
+        .as_ref()
+        .is_none_or(|phi| Integer::from(e.gcd_ref(phi)) == 1);
+    if !coprime {
+        diagnostics.push(Diagnostic::ExponentNotCoprimeWithPhi);
+    } else if let Some(d) = &params.d {
+        let ed = Integer::from(e * d);
+        let consistent = match (&derived.p, &derived.q, n) {
+            // d may be the inverse of e modulo lcm(p - 1, q - 1), not phi
+            (Some(p), Some(q), _) => {
+                let ed_minus_one: Integer = ed - 1u32;
+                ed_minus_one.is_divisible(&Integer::from(p - 1u32))
+                    && ed_minus_one.is_divisible(&Integer::from(q - 1u32))
+            }
+            // 2^(e * d) = 2 mod n if d is the inverse of e
+            (_, _, Some(n)) => *n <= 2 || Integer::from(2).pow_mod(&ed, n).is_ok_and(|x| x == 2),
+            _ => true,
+        };
+        if !consistent {
+            diagnostics.push(Diagnostic::InconsistentPrivateExponent);
+        }
+    }
+
+    if let Some(n) = n {
+        if n.significant_bits() < TINY_MODULUS_BITS {
+            diagnostics.push(Diagnostic::TinyModulus(n.significant_bits()));
+        }
+        if n.is_even() {
+            diagnostics.push(Diagnostic::EvenModulus);
+            if reverse_bytes(n).is_odd() {
+                diagnostics.push(Diagnostic::ByteSwapped);
+            }
+        }
+        if e.significant_bits() * 4 > n.significant_bits() * 3 {
+            diagnostics.push(Diagnostic::LargeExponent);
+            // 2^(e * e') = 2 mod n if e is the inverse of e'
+            if let Some(&public) = COMMON_EXPONENTS.iter().find(|&&public| {
+                let ed = Integer::from(e * public);
+                *n > 2 && Integer::from(2).pow_mod(&ed, n).is_ok_and(|x| x == 2)
+            }) {
+                diagnostics.push(Diagnostic::PrivateExponentAsE(public));
+            }
+        }
+    }
+
+    if let (Some(n), Some(c)) = (n, &params.c) {
+        // A cipher is about as large as n, a small value is more likely the exponent
+        let e_like_cipher = *e < *n && e.significant_bits() + 8 >= n.significant_bits();
+        if e_like_cipher && *c > 1 && c.significant_bits() * 2 <= n.significant_bits() {
+            diagnostics.push(Diagnostic::SwappedExponentAndCipher);
+        }
+    }
+
+    if let (Some(n), Some(m), Some(c)) = (n, &params.known_m, &params.known_c) {
+        if *n > 1
+            && m.clone()
+                .pow_mod(e, n)
+                .is_ok_and(|m_e| m_e != c.clone().modulo(n))
+        {
+            // The cipher, and maybe n, are stored little-endian
+            let reversed = reverse_bytes(c);
+            let swapped = [n.clone(), reverse_bytes(n)].iter().any(|n| {
+                *n > 1
+                    && m.clone()
+                        .pow_mod(e, n)
+                        .is_ok_and(|m_e| m_e == reversed.clone().modulo(n))
+            });
+            if !swapped {
+                diagnostics.push(match known_pair_exponent(m, c, n) {
+                    Some(e) => Diagnostic::KnownPairExponent(e),
+                    None => Diagnostic::KnownPairMismatch,
+                });
+            } else if !diagnostics.contains(&Diagnostic::ByteSwapped) {
+                diagnostics.push(Diagnostic::ByteSwapped);
+            }
+        }
+    }
+
+    if let Some(c) = &params.c {
+        if *c == 0 || *c == 1 {
+            diagnostics.push(Diagnostic::TrivialCipher);
+        } else if n.is_some_and(|n| c.is_divisible(n)) {
+            diagnostics.push(Diagnostic::CipherMultipleOfN);
+        }
+    }
+
+    if *e == 1 {
+        diagnostics.push(Diagnostic::UnitExponent);
+    } else if *e <= SMALL_EXPONENT {
+        diagnostics.push(Diagnostic::SmallExponent);
+    }
+    diagnostics
+}
+
+/// Solution of a degenerate input, found without running the attacks
+///
+/// c = 0 or 1 is its own message (`trivial_cipher`), e = 1 leaves c unencrypted
+/// (`unit_exponent`), and a c which is an exact e-th power was never reduced modulo n
+/// (`unreduced_message`). Only the message is recovered, not the private key.
+/// Messages rejected by the plaintext constraint are left to the attacks.
+pub fn trivial_solution(params: &Parameters, constraint: &PlaintextConstraint) -> Option<Solution> {
+    let c = params.c.as_ref()?;
+    let (attack, m) = if *c == 0 || *c == 1 {
+        ("trivial_cipher", c.clone())
+    } else if params.e == 1 {
+        ("unit_exponent", c.clone())
+    } else {
+        let e = params.e.to_u32().filter(|&e| e > 1)?;
+        let (root, rem) = c.root_rem_ref(e).into();
+        if rem != 0 || root <= 1 {
+            return None;
+        }
+        ("unreduced_message", root)
+    };
+    if !constraint.accepts(&m) {
+        return None;
+    }
+
+    let mut solution = Solution::new_m(attack, m);
+    solution.verify(params).then_some(solution)
+}
+
+/// Smallest e such that m^e = c mod n, up to `MAX_KNOWN_PAIR_EXPONENT`
+fn known_pair_exponent(m: &Integer, c: &Integer, n: &Integer) -> Option<u32> {
+    let m = m.clone().modulo(n);
+    let c = c.clone().modulo(n);
+    let mut m_e = Integer::from(1);
+    (1..=MAX_KNOWN_PAIR_EXPONENT).find(|_| {
+        m_e *= &m;
+        m_e %= n;
+        m_e == c
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::ops::Pow;
+
+    use super::*;
+
+    #[test]
+    fn diagnose() {
+        let p = Integer::from(1779681653u64);
+        let n = p.clone() * 1903643191u64;
+
+        let params = Parameters {
+            n: Some(n.clone()),
+            p: Some(p),
+            e: 3.into(),
+            c: Some(1.into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            super::diagnose(&params),
+            [
+                Diagnostic::ExponentNotCoprimeWithPhi,
+                Diagnostic::TinyModulus(62),
+                Diagnostic::TrivialCipher,
+                Diagnostic::SmallExponent,
+            ]
+        );
+
+        let params = Parameters {
+            n: Some(n),
+            q: Some(15.into()),
+            d: Some(12345.into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            super::diagnose(&params),
+            [
+                Diagnostic::FactorNotPrime("q"),
+                Diagnostic::FactorDoesNotDivideN("q"),
+                Diagnostic::InconsistentPrivateExponent,
+                Diagnostic::TinyModulus(62),
+            ]
+        );
+
+        // d is the inverse of e modulo lcm(p - 1, q - 1), not phi
+        let (p, q) = (Integer::from(1779681653u64), Integer::from(1903643191u64));
+        let lambda = Integer::from(&p - 1u32).lcm(&Integer::from(&q - 1u32));
+        let d = Integer::from(65537).invert(&lambda).unwrap();
+        let mut params = Parameters {
+            n: Some(Integer::from(&p * &q)),
+            p: Some(p),
+            d: Some(d),
+            ..Default::default()
+        };
+        assert_eq!(super::diagnose(&params), [Diagnostic::TinyModulus(62)]);
+        params.d = Some(12345.into());
+        assert_eq!(
+            super::diagnose(&params),
+            [
+                Diagnostic::InconsistentPrivateExponent,
+                Diagnostic::TinyModulus(62),
+            ]
+        );
+
+        let params = Parameters {
+            n: Some(Integer::from(1779681653u64) * 1903643191u64),
+            e: Integer::from(1779681653u64) * 1903643189u64,
+            c: Some(65537.into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            super::diagnose(&params),
+            [
+                Diagnostic::TinyModulus(62),
+                Diagnostic::LargeExponent,
+                Diagnostic::SwappedExponentAndCipher,
+            ]
+        );
+
+        // d = 65537^-1 mod phi given as e
+        let params = Parameters {
+            n: Some(Integer::from(1779681653u64) * 1903643191u64),
+            e: Integer::from(1793631577707101753u64),
+            ..Default::default()
+        };
+        assert_eq!(
+            super::diagnose(&params),
+            [
+                Diagnostic::TinyModulus(62),
+                Diagnostic::LargeExponent,
+                Diagnostic::PrivateExponentAsE(65537),
+            ]
+        );
+
+        // The known pair was encrypted with e = 3, not the default 65537
+        let n = Integer::from(1779681653u64) * 1903643191u64;
+        let mut params = Parameters {
+            n: Some(n.clone()),
+            known_m: Some(42.into()),
+            known_c: Some(Integer::from(42).pow_mod(&3.into(), &n).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            super::diagnose(&params),
+            [
+                Diagnostic::TinyModulus(62),
+                Diagnostic::KnownPairExponent(3)
+            ]
+        );
+        params.e = 3.into();
+        assert_eq!(
+            super::diagnose(&params),
+            [Diagnostic::TinyModulus(62), Diagnostic::SmallExponent]
+        );
+        params.known_c = Some(1.into());
+        assert_eq!(
+            super::diagnose(&params),
+            [
+                Diagnostic::TinyModulus(62),
+                Diagnostic::KnownPairMismatch,
+                Diagnostic::SmallExponent
+            ]
+        );
+
+        // The known cipher was dumped little-endian
+        let c = Integer::from(42).pow_mod(&65537.into(), &n).unwrap();
+        let params = Parameters {
+            n: Some(n),
+            known_m: Some(42.into()),
+            known_c: Some(reverse_bytes(&c)),
+            ..Default::default()
+        };
+        assert_eq!(
+            super::diagnose(&params),
+            [Diagnostic::TinyModulus(62), Diagnostic::ByteSwapped]
+        );
+
+        // n was dumped little-endian, its least significant byte is odd
+        let params = Parameters {
+            n: Some(Integer::from(0x0300_0000_0000_0002u64)),
+            ..Default::default()
+        };
+        assert_eq!(
+            super::diagnose(&params),
+            [
+                Diagnostic::TinyModulus(58),
+                Diagnostic::EvenModulus,
+                Diagnostic::ByteSwapped
+            ]
+        );
+
+        assert!(super::diagnose(&Parameters::default()).is_empty());
+    }
+
+    #[test]
+    fn trivial_solution() {
+        let n = Integer::from(1779681653u64) * 1903643191u64;
+        let constraint = PlaintextConstraint::default();
+        let solve = |e: u32, c: Integer| {
+            let params = Parameters {
+                n: Some(n.clone()),
+                e: e.into(),
+                c: Some(c),
+                ..Default::default()
+            };
+            super::trivial_solution(&params, &constraint).map(|solution| {
+                assert!(solution.verified);
+                (solution.attack, solution.m.unwrap())
+            })
+        };
+
+        assert_eq!(solve(65537, 1.into()), Some(("trivial_cipher", 1.into())));
+        assert_eq!(solve(3, 0.into()), Some(("trivial_cipher", 0.into())));
+        assert_eq!(solve(1, 4242.into()), Some(("unit_exponent", 4242.into())));
+        assert_eq!(
+            solve(3, Integer::from(4242).pow(3)),
+            Some(("unreduced_message", 4242.into()))
+        );
+        let c = Integer::from(4242).pow_mod(&65537.into(), &n).unwrap();
+        assert_eq!(solve(65537, c), None);
+
+        // The message does not match the known plaintext structure
+        let params = Parameters {
+            e: 1.into(),
+            c: Some(crate::bytes_to_integer(b"hello")),
+            ..Default::default()
+        };
+        let constraint = PlaintextConstraint {
+            prefix: b"flag{".to_vec(),
+            ..Default::default()
+        };
+        assert!(super::trivial_solution(&params, &constraint).is_none());
+    }
+}
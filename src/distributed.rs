@@ -0,0 +1,420 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    str::FromStr,
+    sync::{mpsc::RecvTimeoutError, Arc, Mutex},
+    time::Duration,
+};
+
+use rug::Integer;
+use serde_json::{json, Value};
+
+use crate::{
+    run_specific_attacks_with_config, schedule_attacks, Attack, AttackConfig, Parameters,
+    PrivateKey, Shard, Solution, ATTACKS,
+};
+
+/// Delay between two polls of the coordinator, for new workers or for a shard to claim
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Delay between two heartbeats of a worker searching its shard
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Time without a line from the other end, or to send one, after which a connection is dropped
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Attacks whose brute force searches are split between the shards, the other attacks only run
+/// on the first shard
+const SHARDED_ATTACKS: [&str; 9] = [
+    "guess_message",
+    "hart",
+    "kraitchik",
+    "leaked_crt_exponents",
+    "p_mod",
+    "partial_crt_exponent",
+    "partial_pq",
+    "partial_prime",
+    "small_message_mitm",
+];
+
+/// Shards left to the workers, and what they reported
+#[derive(Debug, Default)]
+struct Shards {
+    /// Shards not claimed yet, or given back by a failed worker
+    pending: VecDeque<u64>,
+    /// Number of shards searched
+    reported: u64,
+    /// Verified solution found by a worker
+    solution: Option<Solution>,
+}
+
+/// Deal `count` shards of the brute force searches on the parameters to the workers connecting to
+/// the listener, and return the first solution they found
+///
+/// Each worker claims a shard, runs the attacks on it and reports its result, over a connection
+/// speaking JSON lines: the worker sends `{"token": ...}`, the coordinator replies
+/// `{"params": ..., "shard": i, "shards": count}`, or `{"retry": true}` when all the shards are
+/// claimed, and the worker replies `{"shard": i, "attack": ..., "factors": [...], "m": ..., "ms": [...]}`,
+/// the solution being optional. While searching, the worker sends `{"alive": true}` every
+/// `HEARTBEAT_INTERVAL`: the shard of a worker silent for `IO_TIMEOUT`, or disconnecting without
+/// reporting, is dealt again.
+/// With a `token`, the workers sending another one get `{"error": ...}` instead of a shard. The
+/// connections are neither authenticated otherwise nor encrypted: anyone reaching the listener can
+/// read the parameters and report solutions, which are only kept once verified.
+/// Returns when a verified solution is reported, or all the shards are searched.
+pub fn coordinate(
+    listener: TcpListener,
+    params: &Parameters,
+    count: u64,
+    token: Option<&str>,
+) -> io::Result<Option<Solution>> {
+    let shards = Arc::new(Mutex::new(Shards {
+        pending: (0..count).collect(),
+        ..Default::default()
+    }));
+    listener.set_nonblocking(true)?;
+    loop {
+        {
+            let mut shards = shards.lock().unwrap();
+            if shards.solution.is_some() || shards.reported >= count {
+                return Ok(shards.solution.take());
+            }
+        }
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                let shards = Arc::clone(&shards);
+                let params = params.clone();
+                let token = token.map(str::to_string);
+                std::thread::spawn(move || {
+                    serve_worker(stream, &params, count, token.as_deref(), &shards)
+                });
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                std::thread::sleep(POLL_INTERVAL)
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Give a shard to the worker if it sent the token, and record its report
+fn serve_worker(
+    mut stream: TcpStream,
+    params: &Parameters,
+    count: u64,
+    token: Option<&str>,
+    shards: &Mutex<Shards>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut hello = String::new();
+    reader.read_line(&mut hello)?;
+    let sent = serde_json::from_str::<Value>(&hello).unwrap_or_default();
+    if token.is_some_and(|token| !same_token(sent["token"].as_str(), token)) {
+        return writeln!(stream, "{}", json!({ "error": "wrong token" }));
+    }
+
+    let Some(index) = shards.lock().unwrap().pending.pop_front() else {
+        return writeln!(stream, "{}", json!({ "retry": true }));
+    };
+    let task = json!({ "params": params.to_string(), "shard": index, "shards": count });
+    let report = writeln!(stream, "{task}")
+        .ok()
+        .and_then(|_| read_report(&mut reader));
+    let Some(report) = report else {
+        // Deal the shard again
+        shards.lock().unwrap().pending.push_back(index);
+        return Ok(());
+    };
+
+    let mut shards = shards.lock().unwrap();
+    shards.reported += 1;
+    if let Some(mut solution) = parse_solution(&report, params) {
+        if solution.verify(params) && shards.solution.is_none() {
+            shards.solution = Some(solution);
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if the worker sent the token, compared in constant time not to leak it
+fn same_token(sent: Option<&str>, token: &str) -> bool {
+    let sent = openssl::sha::sha256(sent.unwrap_or_default().as_bytes());
+    openssl::memcmp::eq(&sent, &openssl::sha::sha256(token.as_bytes()))
+}
+
+/// Read the report of a worker, skipping its heartbeats, or `None` if it timed out, disconnected
+/// or sent an invalid line
+fn read_report(reader: &mut impl BufRead) -> Option<Value> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let report = serde_json::from_str::<Value>(&line).ok()?;
+        if report["alive"].as_bool() != Some(true) {
+            return Some(report);
+        }
+    }
+}
+
+/// Solution reported by a worker, if any
+fn parse_solution(report: &Value, params: &Parameters) -> Option<Solution> {
+    let name = report["attack"].as_str()?;
+    let attack = ATTACKS
+        .iter()
+        .find(|attack| attack.name() == name)
+        .map_or("distributed", |attack| attack.name());
+    let integer = |value: &Value| value.as_str().and_then(|s| Integer::from_str(s).ok());
+    let integers = |value: &Value| {
+        value
+            .as_array()
+            .map(|values| values.iter().filter_map(integer).collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+
+    let factors = integers(&report["factors"]);
+    let pk = (!factors.is_empty())
+        .then(|| {
+            let e = integer(&report["e"]).unwrap_or_else(|| params.e.clone());
+            PrivateKey::from_factors(factors, e).ok()
+        })
+        .flatten();
+    let mut solution = match (pk, integer(&report["m"])) {
+        (Some(pk), Some(m)) => Solution::new(attack, pk, m),
+        (Some(pk), None) => Solution::new_pk(attack, pk),
+        (None, Some(m)) => Solution::new_m(attack, m),
+        (None, None) => Solution::new_ms(attack, vec![]),
+    };
+    solution.ms = integers(&report["ms"]);
+    (solution.pk.is_some() || solution.m.is_some() || !solution.ms.is_empty()).then_some(solution)
+}
+
+/// Claim shards from the coordinator at the address, and run the attacks on each of them with
+/// the configuration
+///
+/// The `token` is sent to the coordinator, which may require it, see `coordinate`.
+/// Only the brute force searches of `SHARDED_ATTACKS` are split between the shards, the other
+/// attacks only run on the first shard. Returns the number of shards searched, when the
+/// coordinator is done.
+pub fn work(
+    addr: impl ToSocketAddrs,
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+    config: AttackConfig,
+    token: Option<&str>,
+) -> io::Result<u64> {
+    let (mut searched, mut reached) = (0, false);
+    loop {
+        // The coordinator stops listening once done
+        let mut task = String::new();
+        let read = TcpStream::connect(&addr).and_then(|stream| {
+            stream.set_read_timeout(Some(IO_TIMEOUT))?;
+            stream.set_write_timeout(Some(IO_TIMEOUT))?;
+            writeln!(&stream, "{}", json!({ "token": token }))?;
+            BufReader::new(&stream).read_line(&mut task)?;
+            Ok(stream)
+        });
+        let stream = match read {
+            Ok(_) if task.is_empty() => return Ok(searched),
+            Ok(stream) => stream,
+            Err(_) if reached => return Ok(searched),
+            Err(err) => return Err(err),
+        };
+        reached = true;
+        let task = serde_json::from_str::<Value>(&task)?;
+        if let Some(error) = task["error"].as_str() {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, error));
+        }
+        if task["retry"].as_bool() == Some(true) {
+            std::thread::sleep(POLL_INTERVAL * 10);
+            continue;
+        }
+        let (Some(raw), Some(index), Some(count)) = (
+            task["params"].as_str(),
+            task["shard"].as_u64(),
+            task["shards"].as_u64(),
+        ) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid task"));
+        };
+
+        let params = Parameters::from_raw(raw);
+        let mut attacks = attacks
+            .iter()
+            .filter(|attack| index == 0 || SHARDED_ATTACKS.contains(&attack.name()))
+            .cloned()
+            .collect::<Vec<_>>();
+        schedule_attacks(&params, &mut attacks);
+        let config = AttackConfig {
+            shard: Some(Shard { index, count }),
+            ..config.clone()
+        };
+
+        // Tell the coordinator the worker is still searching, until it reports
+        let (done, heartbeat) = {
+            let stream = stream.try_clone()?;
+            let (done, stopped) = std::sync::mpsc::channel::<()>();
+            let heartbeat = std::thread::spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(HEARTBEAT_INTERVAL)
+                {
+                    if writeln!(&stream, "{}", json!({ "alive": true })).is_err() {
+                        break;
+                    }
+                }
+            });
+            (done, heartbeat)
+        };
+        let report = match run_specific_attacks_with_config(&params, &attacks, config) {
+            Ok(solution) => shard_report(index, &solution),
+            Err(_) => json!({ "shard": index }),
+        };
+        drop(done);
+        heartbeat.join().ok();
+        writeln!(&stream, "{report}")?;
+        searched += 1;
+    }
+}
+
+/// Report of a worker on its shard, with the solution found
+fn shard_report(index: u64, solution: &Solution) -> Value {
+    let strings = |values: &[Integer]| values.iter().map(Integer::to_string).collect::<Vec<_>>();
+    json!({
+        "shard": index,
+        "attack": solution.attack,
+        "factors": solution.pk.as_ref().map(|pk| strings(&pk.factors.to_vec())),
+        "e": solution.pk.as_ref().map(|pk| pk.e.to_string()),
+        "m": solution.m.as_ref().map(Integer::to_string),
+        "ms": strings(&solution.ms),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bytes_to_integer;
+
+    use super::*;
+
+    #[test]
+    fn coordinate_workers() {
+        let n = Integer::from_str(
+            "95118357989037539883272168746004652872958890562445814301889866663072352421703264985997800660075311645555799745426868343365321502734736006248007902409628540578635925559742217480797487130202747020211452620743021097565113059392504472785227154824117231077844444672393221838192941390309312484066647007469668558141",
+        )
+        .unwrap();
+        let m = bytes_to_integer(b"7351");
+        let params = Parameters {
+            n: Some(n.clone()),
+            c: Some(m.clone().pow_mod(&65537.into(), &n).unwrap()),
+            ..Default::default()
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let attacks = ATTACKS
+            .iter()
+            .filter(|attack| attack.name() == "guess_message")
+            .cloned()
+            .collect::<Vec<_>>();
+        let config = AttackConfig {
+            threads: 1,
+            message_guesses: crate::MessageGuesses {
+                charset: b"0123456789".to_vec(),
+                max_length: 4,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let workers = (0..2)
+            .map(|_| {
+                let (attacks, config) = (attacks.clone(), config.clone());
+                std::thread::spawn(move || work(addr, &attacks, config, Some("secret")))
+            })
+            .collect::<Vec<_>>();
+
+        let solution = coordinate(listener, &params, 4, Some("secret"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(solution.attack, "guess_message");
+        assert_eq!(solution.m, Some(m));
+        assert!(solution.verified);
+        // A worker may find all the shards claimed, then the coordinator gone
+        for worker in workers {
+            assert!(worker.join().is_ok());
+        }
+    }
+
+    #[test]
+    fn wrong_token() {
+        let params = Parameters {
+            n: Some(Integer::from(11 * 13)),
+            ..Default::default()
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || coordinate(listener, &params, 1, Some("secret")));
+
+        // The worker gets no shard, and the parameters are not sent
+        let err = work(addr, &[], AttackConfig::default(), Some("guess")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        let err = work(addr, &[], AttackConfig::default(), None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn disconnected_worker() {
+        let params = Parameters {
+            n: Some(Integer::from(11 * 13)),
+            ..Default::default()
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let coordinator = std::thread::spawn(move || coordinate(listener, &params, 1, None));
+
+        // Claim the only shard, send a heartbeat and disconnect without reporting
+        let stream = TcpStream::connect(addr).unwrap();
+        writeln!(&stream, "{}", json!({ "token": null })).unwrap();
+        let mut task = String::new();
+        BufReader::new(&stream).read_line(&mut task).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&task).unwrap()["shard"], 0);
+        writeln!(&stream, "{}", json!({ "alive": true })).unwrap();
+        drop(stream);
+
+        // The shard is dealt again
+        let attacks = ATTACKS
+            .iter()
+            .filter(|attack| attack.name() == "small_prime")
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            work(addr, &attacks, AttackConfig::default(), None).unwrap(),
+            1
+        );
+        let solution = coordinator.join().unwrap().unwrap().unwrap();
+        assert_eq!(solution.attack, "small_prime");
+    }
+
+    #[test]
+    fn sharded_attacks() {
+        for name in SHARDED_ATTACKS {
+            assert!(ATTACKS.iter().any(|attack| attack.name() == name));
+        }
+        assert!(same_token(Some("secret"), "secret"));
+        assert!(!same_token(Some("secre"), "secret"));
+        assert!(!same_token(None, "secret"));
+    }
+
+    #[test]
+    fn parse_solution() {
+        let params = Parameters {
+            n: Some(Integer::from(11 * 13)),
+            e: 7.into(),
+            ..Default::default()
+        };
+        let report = json!({ "shard": 0, "attack": "ecm", "factors": ["11", "13"], "ms": [] });
+        let solution = super::parse_solution(&report, &params).unwrap();
+        assert_eq!(solution.attack, "ecm");
+        assert_eq!(solution.pk.unwrap().n, 143);
+        assert!(super::parse_solution(&json!({ "shard": 0 }), &params).is_none());
+    }
+}
@@ -0,0 +1,234 @@
+use std::time::Duration;
+
+use base64::{engine::general_purpose, Engine};
+use rug::{integer::Order, Integer};
+
+use crate::Parameters;
+
+/// DNS over HTTPS resolver, JSON API
+const DOH_RESOLVER: &str = "https://dns.google/resolve";
+
+/// Timeout of the DNS over HTTPS request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// DNSSEC algorithm numbers of RSA keys (RSAMD5, RSASHA1, RSASHA1-NSEC3-SHA1, RSASHA256, RSASHA512)
+const RSA_ALGORITHMS: [u8; 5] = [1, 5, 7, 8, 10];
+
+/// DNS record type of RRSIG records
+const RRSIG_TYPE: u64 = 46;
+
+/// Parse the public key of a DNSKEY record data, in RFC 3110 format
+///
+/// The exponent length is on one byte, or on the two next ones if it is 0,
+/// followed by the exponent and the modulus.
+fn parse_rsa_public_key(key: &[u8]) -> Option<Parameters> {
+    let (e_len, rest) = match key {
+        [0, high, low, rest @ ..] => (u16::from_be_bytes([*high, *low]) as usize, rest),
+        [len, rest @ ..] => (*len as usize, rest),
+        [] => return None,
+    };
+    if e_len == 0 || rest.len() <= e_len {
+        return None;
+    }
+
+    let (e, n) = rest.split_at(e_len);
+    Some(Parameters {
+        n: Some(Integer::from_digits(n, Order::Msf)),
+        e: Integer::from_digits(e, Order::Msf),
+        ..Default::default()
+    })
+}
+
+/// Key tag of a DNSKEY record data, which identifies the key in the RRSIG records it signed
+///
+/// See RFC 4034, appendix B: the key tag of RSAMD5 keys are bits 8 to 23 of the modulus, the
+/// others are a checksum of the record data.
+fn key_tag(rdata: &[u8]) -> u16 {
+    if rdata.get(3) == Some(&1) {
+        let len = rdata.len();
+        return u16::from_be_bytes([rdata[len - 3], rdata[len - 2]]);
+    }
+    let sum = rdata.iter().enumerate().fold(0u32, |sum, (i, byte)| {
+        sum + if i % 2 == 0 {
+            (*byte as u32) << 8
+        } else {
+            *byte as u32
+        }
+    });
+    (sum + (sum >> 16)) as u16
+}
+
+/// Parse the data of a DNSKEY record: flags, protocol, algorithm and base64 public key
+///
+/// Returns the key tag of the record, and the RSA public key.
+fn parse_dnskey_data(data: &[&str]) -> Option<(u16, u8, Parameters)> {
+    let [flags, protocol, algorithm, key @ ..] = data else {
+        return None;
+    };
+    let algorithm = algorithm.parse().ok()?;
+    if !RSA_ALGORITHMS.contains(&algorithm) {
+        return None;
+    }
+    let key = general_purpose::STANDARD.decode(key.concat()).ok()?;
+    let params = parse_rsa_public_key(&key)?;
+
+    let mut rdata = flags.parse::<u16>().ok()?.to_be_bytes().to_vec();
+    rdata.extend([protocol.parse().ok()?, algorithm]);
+    rdata.extend(key);
+    Some((key_tag(&rdata), algorithm, params))
+}
+
+/// Parse the data of a RRSIG record: type covered, algorithm, labels, original TTL, expiration,
+/// inception, key tag, signer name and base64 signature
+///
+/// Returns the key tag and algorithm of the signing key, and the RSA signature.
+fn parse_rrsig_data(data: &[&str]) -> Option<(u16, u8, Integer)> {
+    let [_, algorithm, _, _, _, _, key_tag, _, signature @ ..] = data else {
+        return None;
+    };
+    let signature = general_purpose::STANDARD.decode(signature.concat()).ok()?;
+    Some((
+        key_tag.parse().ok()?,
+        algorithm.parse().ok()?,
+        Integer::from_digits(&signature, Order::Msf),
+    ))
+}
+
+/// Parse the RSA keys of DNSKEY records, in zone file format
+///
+/// Comments and records split on several lines with parentheses are supported, other records
+/// and non RSA keys are ignored. The signature of the first RRSIG record made by a key, matched
+/// on its key tag and algorithm, is set as the `signature` of the key.
+///
+/// # Example
+///
+/// ```text
+/// example.com. 3600 IN DNSKEY 257 3 8 AwEAAa...
+/// example.com. 3600 IN RRSIG DNSKEY 8 2 3600 20261101000000 20261001000000 12345 example.com. kP0...
+/// ```
+pub fn parse_dnskeys(zone: &str) -> Vec<Parameters> {
+    let mut records = vec![];
+    let mut record = String::new();
+    let mut depth = 0i32;
+    for line in zone.lines() {
+        let line = line.split(';').next().unwrap_or_default();
+        depth += line.matches('(').count() as i32 - line.matches(')').count() as i32;
+        record.push(' ');
+        record.push_str(&line.replace(['(', ')'], " "));
+        if depth <= 0 {
+            records.push(std::mem::take(&mut record));
+            depth = 0;
+        }
+    }
+    records.push(record);
+
+    let mut keys = vec![];
+    let mut signatures = vec![];
+    for record in &records {
+        let tokens = record.split_whitespace().collect::<Vec<_>>();
+        if let Some(start) = tokens.iter().position(|token| *token == "DNSKEY") {
+            // The type covered by a RRSIG record also is a type name
+            if !tokens[..start].contains(&"RRSIG") {
+                keys.extend(parse_dnskey_data(&tokens[start + 1..]));
+                continue;
+            }
+        }
+        if let Some(start) = tokens.iter().position(|token| *token == "RRSIG") {
+            signatures.extend(parse_rrsig_data(&tokens[start + 1..]));
+        }
+    }
+
+    keys.into_iter()
+        .map(|(tag, algorithm, mut params)| {
+            params.signature = signatures
+                .iter()
+                .find(|(signer_tag, signer_algorithm, _)| {
+                    (*signer_tag, *signer_algorithm) == (tag, algorithm)
+                })
+                .map(|(_, _, signature)| signature.clone());
+            params
+        })
+        .collect()
+}
+
+/// Fetch the DNSKEY records of a domain and their RRSIG records with DNS over HTTPS, and parse
+/// their RSA keys
+pub fn fetch_dnskeys(domain: &str) -> Result<Vec<Parameters>, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let response: serde_json::Value = client
+        .get(DOH_RESOLVER)
+        .query(&[("name", domain), ("type", "DNSKEY"), ("do", "true")])
+        .send()
+        .and_then(|response| response.json())
+        .map_err(|err| format!("Failed to fetch the DNSKEY records of {domain}: {err}"))?;
+
+    let zone = response["Answer"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|answer| {
+            let record_type = match answer["type"].as_u64() {
+                Some(RRSIG_TYPE) => "RRSIG",
+                _ => "DNSKEY",
+            };
+            Some(format!(
+                "{domain}. IN {record_type} {}",
+                answer["data"].as_str()?
+            ))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(parse_dnskeys(&zone))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let n = Integer::from_str_radix("c0ffee15deadbeef0123456789abcdef", 16).unwrap();
+        let mut key = vec![3, 1, 0, 1];
+        key.extend(n.to_digits::<u8>(Order::Msf));
+        let key = general_purpose::STANDARD.encode(key);
+        let (head, tail) = key.split_at(8);
+
+        let zone = format!(
+            "; keys of example.com\n\
+             example.com. 3600 IN DNSKEY 256 3 8 {key}\n\
+             example.com. 3600 IN DNSKEY 257 3 13 {key}\n\
+             example.com. 3600 IN DNSKEY 257 3 10 ( {head} ; key\n {tail} )\n"
+        );
+        let keys = parse_dnskeys(&zone);
+        assert_eq!(keys.len(), 2);
+        for params in keys {
+            assert_eq!(params.n, Some(n.clone()));
+            assert_eq!(params.e, 65537);
+        }
+    }
+
+    #[test]
+    fn signatures() {
+        let n = Integer::from_str_radix("c0ffee15deadbeef0123456789abcdef", 16).unwrap();
+        let mut key = vec![3, 1, 0, 1];
+        key.extend(n.to_digits::<u8>(Order::Msf));
+        let key = general_purpose::STANDARD.encode(key);
+        let signature = Integer::from_str_radix("123456789abcdef0", 16).unwrap();
+        let encoded = general_purpose::STANDARD.encode(signature.to_digits::<u8>(Order::Msf));
+
+        // Key tags 61922 and 61925, the first key only signs with another algorithm
+        let zone = format!(
+            "example.com. 3600 IN DNSKEY 256 3 8 {key}\n\
+             example.com. 3600 IN DNSKEY 257 3 10 {key}\n\
+             example.com. 3600 IN RRSIG DNSKEY 13 2 3600 20261101000000 20261001000000 61922 example.com. {encoded}\n\
+             example.com. 3600 IN RRSIG DNSKEY 10 2 3600 ( 20261101000000 20261001000000 61925\n example.com. {encoded} )\n"
+        );
+        let keys = parse_dnskeys(&zone);
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].signature, None);
+        assert_eq!(keys[1].signature, Some(signature));
+    }
+}
@@ -0,0 +1,369 @@
+use std::{fmt::Display, fs, io, path::Path};
+
+use base64::{engine::general_purpose, Engine};
+use openssl::pkey::PKey;
+use rug::{integer::Order, Integer};
+use serde_json::json;
+
+use crate::{integer_to_bytes, KeyError, Parameters, PrivateKey, Solution};
+
+/// Key file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// PKCS#8 private key or X.509 SubjectPublicKeyInfo, in PEM
+    #[default]
+    Pkcs8Pem,
+    /// PKCS#8 private key or X.509 SubjectPublicKeyInfo, in DER
+    Pkcs8Der,
+    /// PKCS#1 RSA private or public key, in PEM
+    Pkcs1Pem,
+    /// PKCS#1 RSA private or public key, in DER
+    Pkcs1Der,
+    /// OpenSSH private key, or authorized_keys line
+    Openssh,
+    /// JSON Web Key (RFC 7517)
+    Jwk,
+    /// .NET RSAKeyValue XML
+    Xml,
+}
+
+impl KeyFormat {
+    /// All the key formats
+    pub const ALL: [Self; 7] = [
+        Self::Pkcs8Pem,
+        Self::Pkcs8Der,
+        Self::Pkcs1Pem,
+        Self::Pkcs1Der,
+        Self::Openssh,
+        Self::Jwk,
+        Self::Xml,
+    ];
+
+    /// Name of a file of a key in this format, e.g. private_key.pem
+    fn file_name(self, stem: &str) -> String {
+        match self {
+            Self::Pkcs8Pem => format!("{stem}.pem"),
+            Self::Pkcs8Der => format!("{stem}.der"),
+            Self::Pkcs1Pem => format!("{stem}_pkcs1.pem"),
+            Self::Pkcs1Der => format!("{stem}_pkcs1.der"),
+            Self::Openssh => format!("{stem}_openssh"),
+            Self::Jwk => format!("{stem}.jwk"),
+            Self::Xml => format!("{stem}.xml"),
+        }
+    }
+}
+
+impl std::str::FromStr for KeyFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "pem" | "pkcs8" | "pkcs8-pem" => Ok(Self::Pkcs8Pem),
+            "der" | "pkcs8-der" => Ok(Self::Pkcs8Der),
+            "pkcs1" | "pkcs1-pem" => Ok(Self::Pkcs1Pem),
+            "pkcs1-der" => Ok(Self::Pkcs1Der),
+            "openssh" | "ssh" => Ok(Self::Openssh),
+            "jwk" => Ok(Self::Jwk),
+            "xml" => Ok(Self::Xml),
+            _ => Err(format!("Unknown key format: {s}")),
+        }
+    }
+}
+
+impl Display for KeyFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pkcs8Pem => write!(f, "pkcs8"),
+            Self::Pkcs8Der => write!(f, "pkcs8-der"),
+            Self::Pkcs1Pem => write!(f, "pkcs1"),
+            Self::Pkcs1Der => write!(f, "pkcs1-der"),
+            Self::Openssh => write!(f, "openssh"),
+            Self::Jwk => write!(f, "jwk"),
+            Self::Xml => write!(f, "xml"),
+        }
+    }
+}
+
+/// Big-endian bytes of a positive integer
+fn to_bytes(n: &Integer) -> Vec<u8> {
+    n.to_digits(Order::Msf)
+}
+
+/// Base64url without padding, as in JWKs
+fn base64url(n: &Integer) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(to_bytes(n))
+}
+
+fn mpint(n: &Integer) -> Result<ssh_key::Mpint, KeyError> {
+    ssh_key::Mpint::from_positive_bytes(&to_bytes(n)).or(Err(KeyError::InvalidKey))
+}
+
+/// XML of the values of a key, in the order of the .NET RSAKeyValue schema
+fn to_xml(values: &[(&str, &Integer)]) -> Vec<u8> {
+    let values = values
+        .iter()
+        .map(|(name, value)| {
+            let value = general_purpose::STANDARD.encode(to_bytes(value));
+            format!("<{name}>{value}</{name}>")
+        })
+        .collect::<String>();
+    format!("<RSAKeyValue>{values}</RSAKeyValue>\n").into_bytes()
+}
+
+/// Export a public key in the given format
+pub fn export_public_key(n: &Integer, e: &Integer, format: KeyFormat) -> Result<Vec<u8>, KeyError> {
+    let bn = |n: &Integer| openssl::bn::BigNum::from_slice(&to_bytes(n));
+    let rsa = bn(n)
+        .and_then(|n| Ok((n, bn(e)?)))
+        .and_then(|(n, e)| openssl::rsa::Rsa::from_public_components(n, e))
+        .or(Err(KeyError::InvalidKey))?;
+
+    let key = match format {
+        KeyFormat::Pkcs8Pem => rsa.public_key_to_pem(),
+        KeyFormat::Pkcs8Der => rsa.public_key_to_der(),
+        KeyFormat::Pkcs1Pem => rsa.public_key_to_pem_pkcs1(),
+        KeyFormat::Pkcs1Der => rsa.public_key_to_der_pkcs1(),
+        KeyFormat::Openssh => {
+            let key_data = ssh_key::public::KeyData::Rsa(ssh_key::public::RsaPublicKey {
+                e: mpint(e)?,
+                n: mpint(n)?,
+            });
+            let line = ssh_key::PublicKey::new(key_data, "")
+                .to_openssh()
+                .or(Err(KeyError::InvalidKey))?;
+            return Ok(format!("{line}\n").into_bytes());
+        }
+        KeyFormat::Jwk => {
+            let jwk = serde_json::json!({ "kty": "RSA", "n": base64url(n), "e": base64url(e) });
+            return Ok(format!("{jwk:#}\n").into_bytes());
+        }
+        KeyFormat::Xml => return Ok(to_xml(&[("Modulus", n), ("Exponent", e)])),
+    };
+    key.or(Err(KeyError::InvalidKey))
+}
+
+/// Export a private key in the given format
+///
+/// Only keys with two factors can be exported.
+pub fn export_private_key(key: &PrivateKey, format: KeyFormat) -> Result<Vec<u8>, KeyError> {
+    let rsa = key.to_openssl().ok_or(KeyError::InvalidKey)?;
+    let (Some(p), Some(q), Some(dp), Some(dq), Some(qinv)) =
+        (key.p(), key.q(), key.dp(), key.dq(), key.qinv())
+    else {
+        return Err(KeyError::InvalidKey);
+    };
+
+    let key = match format {
+        KeyFormat::Pkcs8Pem => PKey::from_rsa(rsa).and_then(|key| key.private_key_to_pem_pkcs8()),
+        KeyFormat::Pkcs8Der => PKey::from_rsa(rsa).and_then(|key| key.private_key_to_pkcs8()),
+        KeyFormat::Pkcs1Pem => rsa.private_key_to_pem(),
+        KeyFormat::Pkcs1Der => rsa.private_key_to_der(),
+        KeyFormat::Openssh => {
+            let keypair = ssh_key::private::RsaKeypair {
+                public: ssh_key::public::RsaPublicKey {
+                    e: mpint(&key.e)?,
+                    n: mpint(&key.n)?,
+                },
+                private: ssh_key::private::RsaPrivateKey {
+                    d: mpint(&key.d)?,
+                    iqmp: mpint(&qinv)?,
+                    p: mpint(&p)?,
+                    q: mpint(&q)?,
+                },
+            };
+            let pem = ssh_key::PrivateKey::new(keypair.into(), "")
+                .and_then(|key| key.to_openssh(ssh_key::LineEnding::LF))
+                .or(Err(KeyError::InvalidKey))?;
+            return Ok(pem.as_bytes().to_vec());
+        }
+        KeyFormat::Jwk => {
+            let jwk = serde_json::json!({
+                "kty": "RSA",
+                "n": base64url(&key.n),
+                "e": base64url(&key.e),
+                "d": base64url(&key.d),
+                "p": base64url(&p),
+                "q": base64url(&q),
+                "dp": base64url(&dp),
+                "dq": base64url(&dq),
+                "qi": base64url(&qinv),
+            });
+            return Ok(format!("{jwk:#}\n").into_bytes());
+        }
+        KeyFormat::Xml => {
+            return Ok(to_xml(&[
+                ("Modulus", &key.n),
+                ("Exponent", &key.e),
+                ("P", &p),
+                ("Q", &q),
+                ("DP", &dp),
+                ("DQ", &dq),
+                ("InverseQ", &qinv),
+                ("D", &key.d),
+            ]))
+        }
+    };
+    key.or(Err(KeyError::InvalidKey))
+}
+
+/// Write everything recovered by a solution to a directory, created if needed, and return the
+/// names of the written files
+///
+/// The bundle holds the private key and the public key in all the formats (the private key only
+/// if it has two factors), the factors of n one per line (`factors.txt`), the plaintexts
+/// (`plaintext.bin`, or `plaintext_1.bin`, ... for the possible ones), and a `manifest.json`
+/// describing the solution and the files.
+pub fn export_bundle(
+    dir: &Path,
+    solution: &Solution,
+    params: &Parameters,
+) -> io::Result<Vec<String>> {
+    fs::create_dir_all(dir)?;
+    let mut files = vec![];
+    let mut write = |name: String, bytes: &[u8]| {
+        fs::write(dir.join(&name), bytes)?;
+        files.push(name);
+        io::Result::Ok(())
+    };
+
+    let pk = solution.pk.as_ref();
+    let n = pk.map(|pk| &pk.n).or(params.n.as_ref());
+    let e = pk.map_or(&params.e, |pk| &pk.e);
+    for format in KeyFormat::ALL {
+        if let Some(bytes) = pk.and_then(|pk| export_private_key(pk, format).ok()) {
+            write(format.file_name("private_key"), &bytes)?;
+        }
+        if let Some(bytes) = n.and_then(|n| export_public_key(n, e, format).ok()) {
+            write(format.file_name("public_key"), &bytes)?;
+        }
+    }
+    let factors = pk.map(|pk| {
+        pk.factors
+            .as_vec()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    });
+    if let Some(factors) = &factors {
+        let lines = factors.iter().map(|factor| format!("{factor}\n"));
+        write(
+            "factors.txt".to_string(),
+            lines.collect::<String>().as_bytes(),
+        )?;
+    }
+
+    let plaintexts = match &solution.m {
+        Some(m) => vec![("plaintext.bin".to_string(), m)],
+        None => (1..)
+            .zip(&solution.ms)
+            .map(|(i, m)| (format!("plaintext_{i}.bin"), m))
+            .collect(),
+    };
+    for (name, m) in &plaintexts {
+        write(name.clone(), &integer_to_bytes(m))?;
+    }
+
+    let plaintexts = plaintexts
+        .iter()
+        .map(|(name, m)| {
+            json!({
+                "file": name,
+                "value": m.to_string(),
+                "text": String::from_utf8_lossy(&integer_to_bytes(m)),
+            })
+        })
+        .collect::<Vec<_>>();
+    let manifest = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "attack": solution.attack,
+        "vulnerability": solution.vulnerability.as_ref().map(ToString::to_string),
+        "verified": solution.verified,
+        "n": n.map(ToString::to_string),
+        "e": e.to_string(),
+        "d": pk.map(|pk| pk.d.to_string()),
+        "factors": factors,
+        "plaintexts": plaintexts,
+        "files": files,
+    });
+    fs::write(dir.join("manifest.json"), format!("{manifest:#}\n"))?;
+    files.push("manifest.json".to_string());
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn private_key() -> PrivateKey {
+        PrivateKey::from_p_q(61, 53, 17).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let params =
+            Parameters::from_private_key(include_bytes!("../tests/keys/private_openssl.pem"), None)
+                .unwrap();
+        let key = PrivateKey::from_p_q(params.p.unwrap(), params.q.unwrap(), params.e).unwrap();
+        for format in KeyFormat::ALL {
+            let bytes = export_private_key(&key, format).unwrap();
+            let params = Parameters::from_private_key(&bytes, None).unwrap();
+            assert_eq!(
+                (params.n, params.d),
+                (Some(key.n.clone()), Some(key.d.clone()))
+            );
+
+            let bytes = export_public_key(&key.n, &key.e, format).unwrap();
+            let params = Parameters::from_public_key(&bytes).unwrap();
+            assert_eq!((params.n, params.e), (Some(key.n.clone()), key.e.clone()));
+        }
+    }
+
+    #[test]
+    fn jwk_and_xml() {
+        let key = private_key();
+        let jwk = export_public_key(&key.n, &key.e, KeyFormat::Jwk).unwrap();
+        let jwk: serde_json::Value = serde_json::from_slice(&jwk).unwrap();
+        // n = 3233 = 0x0ca1, e = 17 = 0x11
+        assert_eq!(jwk["n"], "DKE");
+        assert_eq!(jwk["e"], "EQ");
+
+        let xml = String::from_utf8(export_private_key(&key, KeyFormat::Xml).unwrap()).unwrap();
+        assert!(xml.starts_with("<RSAKeyValue><Modulus>DKE=</Modulus><Exponent>EQ==</Exponent>"));
+    }
+
+    #[test]
+    fn format_names() {
+        for format in KeyFormat::ALL {
+            assert_eq!(format.to_string().parse::<KeyFormat>(), Ok(format));
+        }
+        assert_eq!("PEM".parse::<KeyFormat>(), Ok(KeyFormat::Pkcs8Pem));
+    }
+
+    #[test]
+    fn bundle() {
+        let dir = std::env::temp_dir().join(format!("rsacracker-bundle-{}", std::process::id()));
+        let params =
+            Parameters::from_private_key(include_bytes!("../tests/keys/private_openssl.pem"), None)
+                .unwrap();
+        let key = PrivateKey::from_p_q(params.p.unwrap(), params.q.unwrap(), params.e).unwrap();
+        let solution = Solution::new("ecm", key.clone(), crate::bytes_to_integer(b"hi"));
+
+        let files = export_bundle(&dir, &solution, &Parameters::default()).unwrap();
+        assert_eq!(files.len(), 2 * KeyFormat::ALL.len() + 3);
+        assert_eq!(fs::read(dir.join("plaintext.bin")).unwrap(), b"hi");
+        assert_eq!(
+            fs::read_to_string(dir.join("factors.txt")).unwrap(),
+            format!("{}\n{}\n", key.p().unwrap(), key.q().unwrap())
+        );
+        let manifest = fs::read_to_string(dir.join("manifest.json")).unwrap();
+        let manifest = serde_json::from_str::<serde_json::Value>(&manifest).unwrap();
+        assert_eq!(manifest["attack"], "ecm");
+        assert_eq!(manifest["d"], key.d.to_string());
+        assert_eq!(manifest["plaintexts"][0]["text"], "hi");
+
+        let private_key = fs::read(dir.join("private_key.pem")).unwrap();
+        let params = Parameters::from_private_key(&private_key, None).unwrap();
+        assert_eq!(params.d, Some(key.d));
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,501 @@
+use std::cmp::Ordering;
+
+use itertools::Itertools;
+use openssl::{
+    asn1::{Asn1Object, Asn1OctetString, Asn1Time},
+    bn::{BigNum, MsbOption},
+    error::ErrorStack,
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{PKey, Private},
+    stack::Stack,
+    x509::{
+        extension::SubjectAlternativeName, X509Extension, X509Name, X509NameRef, X509Ref, X509Req,
+        X509,
+    },
+};
+
+use crate::PrivateKey;
+
+/// Subject of the forged certificates without subject nor template
+const DEFAULT_SUBJECT: &str = "CN=rsacracker";
+
+/// Validity of the forged certificates without validity nor template, in days
+const DEFAULT_VALIDITY_DAYS: u32 = 365;
+
+/// Subject alternative name extension
+const SUBJECT_ALT_NAME_OID: &str = "2.5.29.17";
+
+/// Subject and authority key identifiers, stale once the public key changes
+const KEY_IDENTIFIER_OIDS: [&str; 2] = ["2.5.29.14", "2.5.29.35"];
+
+/// Certificate forging error
+#[derive(thiserror::Error, Debug)]
+pub enum ForgeError {
+    /// Key without exactly two factors
+    #[error("invalid key")]
+    InvalidKey,
+    /// Subject not in the `CN=example.com,O=Example` form
+    #[error("invalid subject: {0}")]
+    InvalidSubject(String),
+    /// Template certificate whose extensions can't be decoded
+    #[error("invalid template certificate")]
+    InvalidTemplate,
+    /// OpenSSL failed to build or sign the certificate
+    #[error(transparent)]
+    OpenSsl(#[from] ErrorStack),
+}
+
+/// Options of a forged certificate or certificate signing request
+#[derive(Debug, Clone, Default)]
+pub struct ForgeOptions {
+    /// Subject distinguished name, e.g. `CN=example.com,O=Example`. Default: the template's, or `CN=rsacracker`
+    pub subject: Option<String>,
+    /// Validity from now, in days. Default: the template's, or 365 days
+    pub days: Option<u32>,
+    /// Certificate whose subject, serial number, validity and extensions are cloned
+    pub template: Option<X509>,
+}
+
+/// Modifications of a re-signed certificate
+#[derive(Debug, Clone, Default)]
+pub struct ResignOptions {
+    /// New common name of the subject
+    pub common_name: Option<String>,
+    /// New subject alternative names, DNS names or IP addresses. Default: the certificate's
+    pub subject_alt_names: Vec<String>,
+    /// Validity from now, in days. Default: the certificate's
+    pub days: Option<u32>,
+}
+
+/// Parse a distinguished name, e.g. `CN=example.com,O=Example` or `/CN=example.com/O=Example`
+pub(crate) fn parse_name(name: &str) -> Result<X509Name, ForgeError> {
+    let invalid = || ForgeError::InvalidSubject(name.to_string());
+    let mut builder = X509Name::builder()?;
+    for entry in name
+        .split([',', '/'])
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+    {
+        let (field, value) = entry.split_once('=').ok_or_else(invalid)?;
+        builder
+            .append_entry_by_text(field.trim(), value.trim())
+            .map_err(|_| invalid())?;
+    }
+    Ok(builder.build())
+}
+
+/// Split a DER element into its tag, its contents and the bytes following it
+fn der_element(der: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, der) = der.split_first()?;
+    let (&len, der) = der.split_first()?;
+    let (len, der) = if len < 0x80 {
+        (len as usize, der)
+    } else {
+        let len_size = (len & 0x7f) as usize;
+        if len_size == 0 || len_size > 4 || der.len() < len_size {
+            return None;
+        }
+        let (len, der) = der.split_at(len_size);
+        (len.iter().fold(0, |len, &b| (len << 8) | b as usize), der)
+    };
+    if der.len() < len {
+        return None;
+    }
+    let (contents, der) = der.split_at(len);
+    Some((tag, contents, der))
+}
+
+/// Dotted form of a DER object identifier
+fn oid_to_string(oid: &[u8]) -> Option<String> {
+    let mut arcs = vec![];
+    let mut arc = 0u64;
+    for &b in oid {
+        arc = arc.checked_mul(128)? | (b & 0x7f) as u64;
+        if b & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        }
+    }
+    // The first two arcs are merged as 40 * a + b, with b unbounded when a is 2
+    let first = *arcs.first()?;
+    let (a, b) = if first < 80 {
+        (first / 40, first % 40)
+    } else {
+        (2, first - 80)
+    };
+    Some([a, b].iter().chain(&arcs[1..]).join("."))
+}
+
+/// Extensions field of a DER certificate
+fn der_extensions(der: &[u8]) -> Option<&[u8]> {
+    let (_, certificate, _) = der_element(der)?;
+    let (_, mut tbs_certificate, _) = der_element(certificate)?;
+    // Extensions are the last field of the TBSCertificate, explicitly tagged [3]
+    while !tbs_certificate.is_empty() {
+        let (tag, contents, rest) = der_element(tbs_certificate)?;
+        tbs_certificate = rest;
+        if tag == 0xa3 {
+            return Some(der_element(contents)?.1);
+        }
+    }
+    Some(&[])
+}
+
+/// Split the first DER extension into its object identifier, criticality and value, and the
+/// bytes following it
+fn der_extension(der: &[u8]) -> Option<(String, bool, &[u8], &[u8])> {
+    let (_, extension, der) = der_element(der)?;
+    let (_, oid, fields) = der_element(extension)?;
+    // critical BOOLEAN DEFAULT FALSE, then extnValue OCTET STRING
+    let (tag, value, fields) = der_element(fields)?;
+    let (critical, value) = match tag {
+        0x01 => (value != [0], der_element(fields)?.1),
+        _ => (false, value),
+    };
+    Some((oid_to_string(oid)?, critical, value, der))
+}
+
+/// Extensions of a certificate, with their object identifiers
+///
+/// The openssl crate does not list the extensions of a certificate, they are read from its DER.
+pub(crate) fn certificate_extensions(
+    cert: &X509Ref,
+) -> Result<Vec<(String, X509Extension)>, ForgeError> {
+    let der = cert.to_der()?;
+    let mut extensions = der_extensions(&der).ok_or(ForgeError::InvalidTemplate)?;
+    let mut out = vec![];
+    while !extensions.is_empty() {
+        let (oid, critical, value, rest) =
+            der_extension(extensions).ok_or(ForgeError::InvalidTemplate)?;
+        extensions = rest;
+
+        let object = Asn1Object::from_str(&oid)?;
+        let value = Asn1OctetString::new_from_bytes(value)?;
+        let extension = X509Extension::new_from_der(&object, critical, &value)?;
+        out.push((oid, extension));
+    }
+    Ok(out)
+}
+
+/// Random 128 bits serial number
+pub(crate) fn random_serial_number() -> Result<openssl::asn1::Asn1Integer, ErrorStack> {
+    let mut serial = BigNum::new()?;
+    serial.rand(128, MsbOption::MAYBE_ZERO, false)?;
+    serial.to_asn1_integer()
+}
+
+fn signing_key(key: &PrivateKey) -> Result<PKey<Private>, ForgeError> {
+    Ok(PKey::from_rsa(
+        key.to_openssl().ok_or(ForgeError::InvalidKey)?,
+    )?)
+}
+
+fn subject(options: &ForgeOptions) -> Result<X509Name, ForgeError> {
+    match (&options.subject, &options.template) {
+        (Some(subject), _) => parse_name(subject),
+        (None, Some(template)) => Ok(template.subject_name().to_owned()?),
+        (None, None) => parse_name(DEFAULT_SUBJECT),
+    }
+}
+
+/// Template extensions, except the key identifiers of the template's key
+fn template_extensions(options: &ForgeOptions) -> Result<Vec<X509Extension>, ForgeError> {
+    let Some(template) = &options.template else {
+        return Ok(vec![]);
+    };
+    Ok(certificate_extensions(template)?
+        .into_iter()
+        .filter(|(oid, _)| !KEY_IDENTIFIER_OIDS.contains(&oid.as_str()))
+        .map(|(_, extension)| extension)
+        .collect())
+}
+
+/// Forge a self-signed X.509 certificate of a private key
+///
+/// The template's subject, serial number, validity and extensions are cloned, except the subject
+/// and authority key identifiers, unless overridden by the options.
+pub fn forge_certificate(key: &PrivateKey, options: &ForgeOptions) -> Result<X509, ForgeError> {
+    let pkey = signing_key(key)?;
+    let subject = subject(options)?;
+
+    let mut builder = X509::builder()?;
+    builder.set_version(2)?;
+    match &options.template {
+        Some(template) => builder.set_serial_number(template.serial_number())?,
+        None => {
+            let serial_number = random_serial_number()?;
+            builder.set_serial_number(&serial_number)?
+        }
+    }
+    builder.set_subject_name(&subject)?;
+    builder.set_issuer_name(&subject)?;
+    builder.set_pubkey(&pkey)?;
+    match (options.days, &options.template) {
+        (None, Some(template)) => {
+            builder.set_not_before(template.not_before())?;
+            builder.set_not_after(template.not_after())?;
+        }
+        (days, _) => {
+            let not_before = Asn1Time::days_from_now(0)?;
+            let not_after = Asn1Time::days_from_now(days.unwrap_or(DEFAULT_VALIDITY_DAYS))?;
+            builder.set_not_before(&not_before)?;
+            builder.set_not_after(&not_after)?;
+        }
+    }
+    for extension in template_extensions(options)? {
+        builder.append_extension(extension)?;
+    }
+    builder.sign(&pkey, MessageDigest::sha256())?;
+    Ok(builder.build())
+}
+
+/// Forge a certificate signing request of a private key
+///
+/// The template's subject and extensions are cloned, except the subject and authority key
+/// identifiers, unless overridden by the options.
+pub fn forge_csr(key: &PrivateKey, options: &ForgeOptions) -> Result<X509Req, ForgeError> {
+    let pkey = signing_key(key)?;
+
+    let mut builder = X509Req::builder()?;
+    builder.set_version(0)?;
+    let subject = subject(options)?;
+    builder.set_subject_name(&subject)?;
+    builder.set_pubkey(&pkey)?;
+    let extensions = template_extensions(options)?;
+    if !extensions.is_empty() {
+        let mut stack = Stack::new()?;
+        for extension in extensions {
+            stack.push(extension)?;
+        }
+        builder.add_extensions(&stack)?;
+    }
+    builder.sign(&pkey, MessageDigest::sha256())?;
+    Ok(builder.build())
+}
+
+/// Subject of a certificate with its common name replaced, or added if missing
+fn replace_common_name(subject: &X509NameRef, common_name: &str) -> Result<X509Name, ErrorStack> {
+    let mut builder = X509Name::builder()?;
+    let mut replaced = false;
+    for entry in subject.entries() {
+        if entry.object().nid() == Nid::COMMONNAME && !replaced {
+            builder.append_entry_by_nid(Nid::COMMONNAME, common_name)?;
+            replaced = true;
+        } else {
+            builder.append_entry(entry)?;
+        }
+    }
+    if !replaced {
+        builder.append_entry_by_nid(Nid::COMMONNAME, common_name)?;
+    }
+    Ok(builder.build())
+}
+
+/// Re-sign a certificate with its issuer's private key, after applying the modifications
+///
+/// The subject's public key, the issuer, the serial number and the other extensions are kept. The
+/// issuer of a self-issued certificate follows its subject.
+pub fn resign_certificate(
+    cert: &X509Ref,
+    issuer_key: &PrivateKey,
+    options: &ResignOptions,
+) -> Result<X509, ForgeError> {
+    let pkey = signing_key(issuer_key)?;
+    let self_issued = cert.issuer_name().try_cmp(cert.subject_name())? == Ordering::Equal;
+    let subject = match &options.common_name {
+        Some(common_name) => replace_common_name(cert.subject_name(), common_name)?,
+        None => cert.subject_name().to_owned()?,
+    };
+
+    let mut builder = X509::builder()?;
+    builder.set_version(cert.version())?;
+    builder.set_serial_number(cert.serial_number())?;
+    builder.set_subject_name(&subject)?;
+    if self_issued {
+        builder.set_issuer_name(&subject)?;
+    } else {
+        builder.set_issuer_name(cert.issuer_name())?;
+    }
+    let public_key = cert.public_key()?;
+    builder.set_pubkey(&public_key)?;
+    match options.days {
+        Some(days) => {
+            let not_before = Asn1Time::days_from_now(0)?;
+            let not_after = Asn1Time::days_from_now(days)?;
+            builder.set_not_before(&not_before)?;
+            builder.set_not_after(&not_after)?;
+        }
+        None => {
+            builder.set_not_before(cert.not_before())?;
+            builder.set_not_after(cert.not_after())?;
+        }
+    }
+
+    let subject_alt_names = if options.subject_alt_names.is_empty() {
+        None
+    } else {
+        let mut san = SubjectAlternativeName::new();
+        for name in &options.subject_alt_names {
+            match name.parse::<std::net::IpAddr>() {
+                Ok(_) => san.ip(name),
+                Err(_) => san.dns(name),
+            };
+        }
+        Some(san.build(&builder.x509v3_context(None, None))?)
+    };
+    let mut extensions = certificate_extensions(cert)?;
+    if let Some(san) = subject_alt_names {
+        match extensions
+            .iter()
+            .position(|(oid, _)| oid == SUBJECT_ALT_NAME_OID)
+        {
+            Some(i) => extensions[i].1 = san,
+            None => extensions.push((SUBJECT_ALT_NAME_OID.to_string(), san)),
+        }
+    }
+    for (_, extension) in extensions {
+        builder.append_extension(extension)?;
+    }
+    builder.sign(&pkey, MessageDigest::sha256())?;
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parameters;
+
+    use super::*;
+
+    fn private_key() -> PrivateKey {
+        let params =
+            Parameters::from_private_key(include_bytes!("../tests/keys/private_openssl.pem"), None)
+                .unwrap();
+        PrivateKey::from_p_q(params.p.unwrap(), params.q.unwrap(), params.e).unwrap()
+    }
+
+    fn common_name(name: &X509NameRef) -> String {
+        let entry = name.entries_by_nid(Nid::COMMONNAME).next().unwrap();
+        entry.data().as_utf8().unwrap().to_string()
+    }
+
+    #[test]
+    fn self_signed_certificate() {
+        let key = private_key();
+        let options = ForgeOptions {
+            subject: Some("CN=example.com, O=Example".to_string()),
+            days: Some(30),
+            ..Default::default()
+        };
+        let cert = forge_certificate(&key, &options).unwrap();
+        assert!(cert.verify(&signing_key(&key).unwrap()).unwrap());
+        assert_eq!(common_name(cert.subject_name()), "example.com");
+        assert_eq!(common_name(cert.issuer_name()), "example.com");
+
+        let csr = forge_csr(&key, &options).unwrap();
+        assert!(csr.verify(&signing_key(&key).unwrap()).unwrap());
+        assert_eq!(common_name(csr.subject_name()), "example.com");
+
+        assert!(matches!(
+            parse_name("example.com"),
+            Err(ForgeError::InvalidSubject(_))
+        ));
+    }
+
+    #[test]
+    fn cloned_template() {
+        let key = private_key();
+        let template = forge_certificate(&key, &ForgeOptions::default()).unwrap();
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_serial_number(template.serial_number()).unwrap();
+        builder.set_subject_name(template.subject_name()).unwrap();
+        builder.set_issuer_name(template.issuer_name()).unwrap();
+        builder.set_pubkey(&template.public_key().unwrap()).unwrap();
+        builder.set_not_before(template.not_before()).unwrap();
+        builder.set_not_after(template.not_after()).unwrap();
+        let san = SubjectAlternativeName::new()
+            .dns("example.com")
+            .build(&builder.x509v3_context(None, None))
+            .unwrap();
+        builder.append_extension(san).unwrap();
+        builder
+            .sign(&signing_key(&key).unwrap(), MessageDigest::sha256())
+            .unwrap();
+        let template = builder.build();
+
+        let cert = forge_certificate(
+            &key,
+            &ForgeOptions {
+                template: Some(template.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(common_name(cert.subject_name()), "rsacracker");
+        assert_eq!(
+            cert.serial_number().to_bn().unwrap(),
+            template.serial_number().to_bn().unwrap()
+        );
+        let names = cert.subject_alt_names().unwrap();
+        assert_eq!(names.iter().next().unwrap().dnsname(), Some("example.com"));
+    }
+
+    #[test]
+    fn resigned_certificate() {
+        let key = private_key();
+        let options = ResignOptions {
+            common_name: Some("new.example.com".to_string()),
+            subject_alt_names: vec!["new.example.com".to_string(), "127.0.0.1".to_string()],
+            days: Some(10),
+        };
+
+        // Self-issued certificates follow their new subject
+        let cert = forge_certificate(
+            &key,
+            &ForgeOptions {
+                subject: Some("CN=old.example.com,O=Example".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let resigned = resign_certificate(&cert, &key, &options).unwrap();
+        assert!(resigned.verify(&signing_key(&key).unwrap()).unwrap());
+        assert_eq!(common_name(resigned.subject_name()), "new.example.com");
+        assert_eq!(common_name(resigned.issuer_name()), "new.example.com");
+        assert_eq!(resigned.subject_name().entries().count(), 2);
+        let names = resigned.subject_alt_names().unwrap();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.get(0).unwrap().dnsname(), Some("new.example.com"));
+        assert_eq!(names.get(1).unwrap().ipaddress(), Some(&[127, 0, 0, 1][..]));
+
+        // Other certificates keep their issuer
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_serial_number(cert.serial_number()).unwrap();
+        builder.set_subject_name(cert.subject_name()).unwrap();
+        builder
+            .set_issuer_name(&parse_name("CN=Example CA").unwrap())
+            .unwrap();
+        builder.set_pubkey(&cert.public_key().unwrap()).unwrap();
+        builder.set_not_before(cert.not_before()).unwrap();
+        builder.set_not_after(cert.not_after()).unwrap();
+        builder
+            .sign(&signing_key(&key).unwrap(), MessageDigest::sha256())
+            .unwrap();
+        let resigned = resign_certificate(&builder.build(), &key, &options).unwrap();
+        assert_eq!(common_name(resigned.issuer_name()), "Example CA");
+    }
+
+    #[test]
+    fn object_identifiers() {
+        assert_eq!(
+            oid_to_string(&[0x55, 0x1d, 0x11]).as_deref(),
+            Some("2.5.29.17")
+        );
+        assert_eq!(
+            oid_to_string(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d]).as_deref(),
+            Some("1.2.840.113549")
+        );
+    }
+}
@@ -0,0 +1,187 @@
+// CUDA kernels of the gpu feature, compiled at runtime with NVRTC
+
+#define MAX_LIMBS 32
+
+typedef unsigned int u32;
+typedef unsigned long long u64;
+
+// Trial division: divides[i] is set if n, as 32-bit limbs most significant first, is divisible by primes[i]
+extern "C" __global__ void divides(
+    const u32 *limbs, u32 limbs_len,
+    const u32 *primes, u32 primes_len,
+    unsigned char *divides
+) {
+    u32 i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i >= primes_len) {
+        return;
+    }
+
+    u64 p = primes[i];
+    u64 r = 0;
+    for (u32 j = 0; j < limbs_len; j++) {
+        r = ((r << 32) | limbs[j]) % p;
+    }
+    divides[i] = r == 0;
+}
+
+// Modular arithmetic on len limbs, least significant first, in Montgomery form
+
+__device__ void copy(u32 *r, const u32 *a, u32 len) {
+    for (u32 i = 0; i < len; i++) {
+        r[i] = a[i];
+    }
+}
+
+// r = a - b, returns the borrow
+__device__ u32 sub(u32 *r, const u32 *a, const u32 *b, u32 len) {
+    u64 borrow = 0;
+    for (u32 i = 0; i < len; i++) {
+        u64 d = (u64)a[i] - b[i] - borrow;
+        r[i] = (u32)d;
+        borrow = d >> 63;
+    }
+    return (u32)borrow;
+}
+
+__device__ void mod_add(u32 *r, const u32 *a, const u32 *b, const u32 *n, u32 len) {
+    u32 t[MAX_LIMBS], s[MAX_LIMBS];
+    u64 carry = 0;
+    for (u32 i = 0; i < len; i++) {
+        carry += (u64)a[i] + b[i];
+        t[i] = (u32)carry;
+        carry >>= 32;
+    }
+    u32 borrow = sub(s, t, n, len);
+    copy(r, carry || !borrow ? s : t, len);
+}
+
+__device__ void mod_sub(u32 *r, const u32 *a, const u32 *b, const u32 *n, u32 len) {
+    u32 t[MAX_LIMBS];
+    if (sub(t, a, b, len)) {
+        u64 carry = 0;
+        for (u32 i = 0; i < len; i++) {
+            carry += (u64)t[i] + n[i];
+            t[i] = (u32)carry;
+            carry >>= 32;
+        }
+    }
+    copy(r, t, len);
+}
+
+// r = a * b / 2^(32 len) mod n, ninv being -n^-1 mod 2^32
+__device__ void mod_mul(u32 *r, const u32 *a, const u32 *b, const u32 *n, u32 ninv, u32 len) {
+    u32 t[MAX_LIMBS + 2];
+    for (u32 i = 0; i < len + 2; i++) {
+        t[i] = 0;
+    }
+    for (u32 i = 0; i < len; i++) {
+        u64 c = 0;
+        for (u32 j = 0; j < len; j++) {
+            c += (u64)t[j] + (u64)a[j] * b[i];
+            t[j] = (u32)c;
+            c >>= 32;
+        }
+        c += t[len];
+        t[len] = (u32)c;
+        t[len + 1] = (u32)(c >> 32);
+
+        u32 m = t[0] * ninv;
+        c = ((u64)t[0] + (u64)m * n[0]) >> 32;
+        for (u32 j = 1; j < len; j++) {
+            c += (u64)t[j] + (u64)m * n[j];
+            t[j - 1] = (u32)c;
+            c >>= 32;
+        }
+        c += t[len];
+        t[len - 1] = (u32)c;
+        t[len] = t[len + 1] + (u32)(c >> 32);
+    }
+
+    u32 s[MAX_LIMBS];
+    u32 borrow = sub(s, t, n, len);
+    copy(r, t[len] || !borrow ? s : t, len);
+}
+
+// Montgomery curve x-only arithmetic
+
+// (x, z) = P + Q, knowing P - Q = (xd, zd)
+__device__ void xadd(
+    u32 *x, u32 *z,
+    const u32 *xp, const u32 *zp, const u32 *xq, const u32 *zq,
+    const u32 *xd, const u32 *zd,
+    const u32 *n, u32 ninv, u32 len
+) {
+    u32 a[MAX_LIMBS], b[MAX_LIMBS], u[MAX_LIMBS], v[MAX_LIMBS];
+    mod_sub(a, xp, zp, n, len);
+    mod_add(b, xq, zq, n, len);
+    mod_mul(u, a, b, n, ninv, len);
+    mod_add(a, xp, zp, n, len);
+    mod_sub(b, xq, zq, n, len);
+    mod_mul(v, a, b, n, ninv, len);
+
+    mod_add(a, u, v, n, len);
+    mod_mul(a, a, a, n, ninv, len);
+    mod_sub(b, u, v, n, len);
+    mod_mul(b, b, b, n, ninv, len);
+    mod_mul(x, zd, a, n, ninv, len);
+    mod_mul(z, xd, b, n, ninv, len);
+}
+
+// (x, z) = 2 P, a24 being (A + 2) / 4
+__device__ void xdbl(
+    u32 *x, u32 *z,
+    const u32 *xp, const u32 *zp, const u32 *a24,
+    const u32 *n, u32 ninv, u32 len
+) {
+    u32 s[MAX_LIMBS], d[MAX_LIMBS], t[MAX_LIMBS];
+    mod_add(s, xp, zp, n, len);
+    mod_mul(s, s, s, n, ninv, len);
+    mod_sub(d, xp, zp, n, len);
+    mod_mul(d, d, d, n, ninv, len);
+    mod_sub(t, s, d, n, len);
+    mod_mul(x, s, d, n, ninv, len);
+    mod_mul(s, a24, t, n, ninv, len);
+    mod_add(s, s, d, n, len);
+    mod_mul(z, t, s, n, ninv, len);
+}
+
+// ECM stage 1: multiply the point of each curve by the bits [to, from) of k, most significant first
+//
+// The state of a curve is 7 numbers of len limbs: the ladder points R0 and R1, as (x, z), the
+// point P = R1 - R0 and (A + 2) / 4.
+extern "C" __global__ void ecm_ladder(
+    const u32 *n, u32 ninv, u32 len,
+    const u32 *k, u32 from, u32 to,
+    u32 *states, u32 count
+) {
+    u32 i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i >= count) {
+        return;
+    }
+
+    u32 *state = states + (u64)i * 7 * len;
+    u32 x0[MAX_LIMBS], z0[MAX_LIMBS], x1[MAX_LIMBS], z1[MAX_LIMBS];
+    u32 xp[MAX_LIMBS], zp[MAX_LIMBS], a24[MAX_LIMBS];
+    copy(x0, state, len);
+    copy(z0, state + len, len);
+    copy(x1, state + 2 * len, len);
+    copy(z1, state + 3 * len, len);
+    copy(xp, state + 4 * len, len);
+    copy(zp, state + 5 * len, len);
+    copy(a24, state + 6 * len, len);
+
+    for (u32 bit = from; bit > to; bit--) {
+        if ((k[(bit - 1) / 32] >> ((bit - 1) % 32)) & 1) {
+            xadd(x0, z0, x0, z0, x1, z1, xp, zp, n, ninv, len);
+            xdbl(x1, z1, x1, z1, a24, n, ninv, len);
+        } else {
+            xadd(x1, z1, x0, z0, x1, z1, xp, zp, n, ninv, len);
+            xdbl(x0, z0, x0, z0, a24, n, ninv, len);
+        }
+    }
+
+    copy(state, x0, len);
+    copy(state + len, z0, len);
+    copy(state + 2 * len, x1, len);
+    copy(state + 3 * len, z1, len);
+}
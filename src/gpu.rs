@@ -0,0 +1,323 @@
+//! Trial division and ECM stage 1 offloaded to a CUDA GPU
+//!
+//! The CUDA driver and NVRTC libraries are loaded at runtime: without them, or without a GPU,
+//! [`is_available`] returns false and the callers fall back to the CPU.
+
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use cudarc::{
+    driver::{CudaContext, CudaFunction, CudaStream, LaunchConfig, PushKernelArg},
+    nvrtc::compile_ptx,
+};
+use primal::Primes;
+use rug::{integer::Order, Integer};
+
+use crate::{AttackContext, Error};
+
+/// Kernels of `divides` and `ecm_stage1`
+const KERNELS: &str = include_str!("gpu.cu");
+
+/// Largest modulus of `ecm_stage1`, in 32-bit limbs (`MAX_LIMBS` of the kernels)
+const ECM_MAX_LIMBS: usize = 32;
+
+/// Largest stage 1 bound of `ecm_stage1`
+const ECM_MAX_B1: u64 = 1_000_000;
+
+/// Bits of the stage 1 multiplier processed by each launch of `ecm_stage1`, between which the
+/// attack can be stopped
+const ECM_LAUNCH_BITS: u32 = 4096;
+
+/// Threads per block of the ECM kernel, whose threads use many registers
+const ECM_BLOCK_SIZE: u32 = 64;
+
+/// Kernels loaded on the first GPU
+struct Gpu {
+    stream: Arc<CudaStream>,
+    divides: CudaFunction,
+    ecm_ladder: CudaFunction,
+}
+
+impl Gpu {
+    fn new() -> Option<Self> {
+        // SAFETY: only checks whether the CUDA libraries can be loaded
+        if !unsafe { cudarc::driver::sys::is_culib_present() }
+            || !unsafe { cudarc::nvrtc::sys::is_culib_present() }
+        {
+            return None;
+        }
+
+        let ctx = CudaContext::new(0).ok()?;
+        let module = ctx.load_module(compile_ptx(KERNELS).ok()?).ok()?;
+        Some(Self {
+            stream: ctx.default_stream(),
+            divides: module.load_function("divides").ok()?,
+            ecm_ladder: module.load_function("ecm_ladder").ok()?,
+        })
+    }
+
+    fn divisors(&self, n: &Integer, primes: &[u32]) -> Option<Vec<u32>> {
+        if primes.is_empty() {
+            return Some(vec![]);
+        }
+
+        let limbs = n.to_digits::<u32>(Order::Msf);
+        let limbs_len = u32::try_from(limbs.len()).ok()?;
+        let primes_len = u32::try_from(primes.len()).ok()?;
+        let limbs_dev = self.stream.clone_htod(&limbs).ok()?;
+        let primes_dev = self.stream.clone_htod(primes).ok()?;
+        let mut divides_dev = self.stream.alloc_zeros::<u8>(primes.len()).ok()?;
+
+        let mut launch = self.stream.launch_builder(&self.divides);
+        launch
+            .arg(&limbs_dev)
+            .arg(&limbs_len)
+            .arg(&primes_dev)
+            .arg(&primes_len)
+            .arg(&mut divides_dev);
+        // SAFETY: the arguments match the kernel parameters, and the kernel reads `primes_len`
+        // primes and writes as many flags
+        unsafe { launch.launch(LaunchConfig::for_num_elems(primes_len)) }.ok()?;
+
+        let divides = self.stream.clone_dtoh(&divides_dev).ok()?;
+        Some(
+            primes
+                .iter()
+                .zip(divides)
+                .filter(|&(_, divides)| divides != 0)
+                .map(|(&p, _)| p)
+                .collect(),
+        )
+    }
+
+    /// Run the ladder of the curves over all the bits of k, and return their final states
+    ///
+    /// Returns `Ok(None)` if a CUDA call failed.
+    fn ecm_ladder(
+        &self,
+        n: &[u32],
+        k: &Integer,
+        states: &[u32],
+        count: u32,
+        ctx: &AttackContext,
+    ) -> Result<Option<Vec<u32>>, Error> {
+        let Ok(len) = u32::try_from(n.len()) else {
+            return Ok(None);
+        };
+        // -n^-1 mod 2^32, for the Montgomery reduction, by Newton's iteration (n is odd)
+        let mut inv = n[0];
+        for _ in 0..4 {
+            inv = inv.wrapping_mul(2u32.wrapping_sub(n[0].wrapping_mul(inv)));
+        }
+        let ninv = inv.wrapping_neg();
+        let k_bits = k.significant_bits();
+        let config = LaunchConfig {
+            grid_dim: (count.div_ceil(ECM_BLOCK_SIZE), 1, 1),
+            block_dim: (ECM_BLOCK_SIZE, 1, 1),
+            shared_mem_bytes: 0,
+        };
+
+        let (Ok(n_dev), Ok(k_dev), Ok(mut states_dev)) = (
+            self.stream.clone_htod(n),
+            self.stream.clone_htod(&k.to_digits::<u32>(Order::Lsf)),
+            self.stream.clone_htod(states),
+        ) else {
+            return Ok(None);
+        };
+        let mut from = k_bits;
+        while from > 0 {
+            ctx.check()?;
+            let to = from.saturating_sub(ECM_LAUNCH_BITS);
+            let mut launch = self.stream.launch_builder(&self.ecm_ladder);
+            launch
+                .arg(&n_dev)
+                .arg(&ninv)
+                .arg(&len)
+                .arg(&k_dev)
+                .arg(&from)
+                .arg(&to)
+                .arg(&mut states_dev)
+                .arg(&count);
+            // SAFETY: the arguments match the kernel parameters, the kernel reads `k_bits` bits of
+            // k and updates `count` states of 7 numbers of `len` limbs
+            if unsafe { launch.launch(config) }.is_err() {
+                return Ok(None);
+            }
+            // Wait for the launch, to check the stop flag in between
+            if self.stream.synchronize().is_err() {
+                return Ok(None);
+            }
+            from = to;
+        }
+        Ok(self.stream.clone_dtoh(&states_dev).ok())
+    }
+}
+
+/// Returns the GPU, loaded on first use, or `None` if no GPU is available
+///
+/// The GPU is locked by each batch of work, the kernels sharing its stream.
+fn gpu() -> Option<&'static Mutex<Gpu>> {
+    static GPU: OnceLock<Option<Mutex<Gpu>>> = OnceLock::new();
+    GPU.get_or_init(|| Gpu::new().map(Mutex::new)).as_ref()
+}
+
+/// Returns true if a GPU is available
+pub(crate) fn is_available() -> bool {
+    gpu().is_some()
+}
+
+/// Returns the primes dividing n, checked on the GPU by a single launch, or `None` if no GPU is
+/// available
+pub(crate) fn divisors(n: &Integer, primes: &[u32]) -> Option<Vec<u32>> {
+    gpu()?.lock().ok()?.divisors(n, primes)
+}
+
+/// Returns the product of the largest powers of the primes below b1, the stage 1 multiplier
+fn stage1_multiplier(b1: u64) -> Integer {
+    fn product(values: &[Integer]) -> Integer {
+        match values {
+            [] => Integer::from(1),
+            [value] => value.clone(),
+            _ => {
+                let (left, right) = values.split_at(values.len() / 2);
+                product(left) * product(right)
+            }
+        }
+    }
+
+    let powers = Primes::all()
+        .map(|p| p as u64)
+        .take_while(|&p| p <= b1)
+        .map(|p| {
+            let mut power = p;
+            while power * p <= b1 {
+                power *= p;
+            }
+            Integer::from(power)
+        })
+        .collect::<Vec<_>>();
+    product(&powers)
+}
+
+/// Run the stage 1 of ECM with bound b1 on the GPU, on the Montgomery curves of Suyama's
+/// parametrization with the given sigmas, and return the factor of n found, if any
+///
+/// Returns `Ok(None)` when no GPU is available, or when n is even, larger than `ECM_MAX_LIMBS`
+/// limbs or b1 larger than `ECM_MAX_B1`.
+pub(crate) fn ecm_stage1(
+    n: &Integer,
+    b1: u64,
+    sigmas: Range<u64>,
+    ctx: &AttackContext,
+) -> Result<Option<Integer>, Error> {
+    let len = n.significant_digits::<u32>();
+    let Some(gpu) = gpu() else {
+        return Ok(None);
+    };
+    if n.is_even() || len > ECM_MAX_LIMBS || b1 > ECM_MAX_B1 || sigmas.is_empty() {
+        return Ok(None);
+    }
+
+    // Numbers are given to the kernel in Montgomery form, x 2^(32 len) mod n
+    let montgomery = |x: Integer| {
+        let mut limbs = ((x << (32 * len as u32)) % n).to_digits::<u32>(Order::Lsf);
+        limbs.resize(len, 0);
+        limbs
+    };
+    let mut states = vec![];
+    for sigma in sigmas {
+        let sigma = Integer::from(sigma);
+        let u = (sigma.clone().square() - 5u32) % n;
+        let v = Integer::from(&sigma * 4u32) % n;
+        let x = u.clone().pow_mod(&3.into(), n).unwrap();
+        let z = v.clone().pow_mod(&3.into(), n).unwrap();
+        // (A + 2) / 4 = (v - u)^3 (3u + v) / (16 u^3 v)
+        let numerator = (Integer::from(&v - &u) + n).pow_mod(&3.into(), n).unwrap()
+            * (Integer::from(&u * 3u32) + &v);
+        let a24 = match (Integer::from(&x * &v) * 16u32).invert(n) {
+            Ok(inverse) => numerator * inverse % n,
+            Err(denominator) => {
+                let factor = denominator.gcd(n);
+                if factor != 1 && factor != *n {
+                    return Ok(Some(factor));
+                }
+                continue;
+            }
+        };
+
+        // The ladder starts from R0 = infinity and R1 = P
+        for value in [
+            Integer::from(1),
+            Integer::ZERO,
+            x.clone(),
+            z.clone(),
+            x,
+            z,
+            a24,
+        ] {
+            states.extend(montgomery(value));
+        }
+    }
+    let count = (states.len() / (7 * len)) as u32;
+    if count == 0 {
+        return Ok(None);
+    }
+
+    let mut n_limbs = n.to_digits::<u32>(Order::Lsf);
+    n_limbs.resize(len, 0);
+    let k = stage1_multiplier(b1);
+    let Ok(gpu) = gpu.lock() else {
+        return Ok(None);
+    };
+    let Some(states) = gpu.ecm_ladder(&n_limbs, &k, &states, count, ctx)? else {
+        return Ok(None);
+    };
+
+    // The z of k P is 0 modulo the factors for which the order of P divides k
+    Ok(states.chunks(7 * len).find_map(|state| {
+        let z = Integer::from_digits(&state[len..2 * len], Order::Lsf);
+        let factor = z.gcd(n);
+        (factor != 1 && factor != *n).then_some(factor)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parameters;
+
+    use super::*;
+
+    #[test]
+    fn stage1_multiplier() {
+        assert_eq!(super::stage1_multiplier(10), 8 * 9 * 5 * 7);
+    }
+
+    #[test]
+    #[ignore = "needs a CUDA GPU"]
+    fn divisors() {
+        let n = Integer::from(1000003) * 1000033 * 3;
+        let found = super::divisors(&n, &[3, 5, 1000003, 1000033, 1000037]).unwrap();
+        assert_eq!(found, [3, 1000003, 1000033]);
+    }
+
+    #[test]
+    #[ignore = "needs a CUDA GPU"]
+    fn ecm_stage1() {
+        // 40-bit factor of a 512-bit modulus, found by 97 of the curves
+        let p = Integer::from(1_000_000_000_039u64);
+        let q = Integer::from_str_radix(
+            "e0a4b1a54e2bde9c4d0d3ae1c0fb43ebf2f8e3d5d92cb34f9ad8e5a70b4d7a1d3e1e9f9ecf0b9c8a5b62d0e8dd9a1f5d",
+            16,
+        )
+        .unwrap()
+        .next_prime();
+        let n = Integer::from(&p * &q);
+        let ctx = AttackContext::new(&Parameters::default());
+
+        assert!(is_available());
+        let factor = super::ecm_stage1(&n, 11_000, 6..1030, &ctx).unwrap();
+        assert_eq!(factor, Some(p));
+    }
+}
@@ -1,4 +1,7 @@
-use openssl::rsa::RsaPrivateKeyBuilder;
+use openssl::{
+    pkey::Private,
+    rsa::{Rsa, RsaPrivateKeyBuilder},
+};
 use rayon::iter::{
     IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
 };
@@ -8,7 +11,10 @@ use rug::{
     Integer,
 };
 
-use crate::{factors::Factors, ntheory::crt};
+use crate::{
+    factors::Factors,
+    ntheory::{crt, is_prime},
+};
 
 /// Attack error
 #[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
@@ -19,6 +25,18 @@ pub enum KeyError {
     /// Private exponent computation failed
     #[error("private exponent computation failed")]
     PrivateExponentComputationFailed,
+    /// e is not invertible modulo phi (e.g. even e), the messages are only recoverable as e-th roots
+    #[error("e is not invertible modulo phi: {0:?}")]
+    ExponentNotInvertible(Factors),
+    /// Invalid or unsupported key
+    #[error("invalid key")]
+    InvalidKey,
+    /// Encrypted private key given without passphrase
+    #[error("encrypted key, a passphrase is required")]
+    MissingPassphrase,
+    /// Wrong passphrase of an encrypted private key
+    #[error("wrong passphrase")]
+    WrongPassphrase,
 }
 
 /// RSA private key
@@ -53,23 +71,34 @@ impl PrivateKey {
         let e = Into::<Integer>::into(e);
 
         // Check if factors are prime numbers
-        if factors
-            .factors()
-            .iter()
-            .any(|f| f.is_probably_prime(100) == IsPrime::No)
-        {
+        if factors.factors().iter().any(|f| is_prime(f) == IsPrime::No) {
             return Err(KeyError::FactorsAreNotPrimeNumbers(factors));
         }
 
         let n: Integer = factors.product();
-        let d = e
-            .clone()
-            .invert(&factors.phi())
-            .or(Err(KeyError::PrivateExponentComputationFailed))?;
+        let Ok(d) = e.clone().invert(&factors.phi()) else {
+            return Err(KeyError::ExponentNotInvertible(factors));
+        };
 
         Ok(Self { n, e, factors, d })
     }
 
+    /// Returns true if n is the product of the prime factors and e * d = 1 mod lambda(n)
+    pub fn is_valid(&self) -> bool {
+        let ed_minus_one: Integer = Integer::from(&self.e * &self.d) - 1;
+        self.factors.product() == self.n
+            && self
+                .factors
+                .factors()
+                .iter()
+                .all(|f| is_prime(f) != IsPrime::No)
+            && self
+                .factors
+                .phis()
+                .iter()
+                .all(|phi| ed_minus_one.is_divisible(phi))
+    }
+
     /// Decrypt cipher message
     pub fn decrypt(&self, c: &Integer) -> Integer {
         // Fast decryption using CRT
@@ -95,14 +124,14 @@ impl PrivateKey {
         crt(&p, &factors).unwrap()
     }
 
-    /// Returns P factor
-    pub fn p(&self) -> Integer {
-        self.factors[0].clone()
+    /// Returns P factor, the smallest of the two factors, if the key has exactly two factors
+    pub fn p(&self) -> Option<Integer> {
+        (self.factors.len() == 2).then(|| self.factors[0].clone())
     }
 
-    /// Returns Q factor
-    pub fn q(&self) -> Integer {
-        self.factors[1].clone()
+    /// Returns Q factor, the largest of the two factors, if the key has exactly two factors
+    pub fn q(&self) -> Option<Integer> {
+        (self.factors.len() == 2).then(|| self.factors[1].clone())
     }
 
     /// Returns phi(n) or totient
@@ -111,23 +140,70 @@ impl PrivateKey {
     }
 
     /// Returns dP or dmp1 CRT exponent. (d mod p-1)
-    pub fn dp(&self) -> Integer {
-        self.d.clone() % (self.p() - 1)
+    pub fn dp(&self) -> Option<Integer> {
+        Some(self.d.clone() % (self.p()? - 1))
     }
 
     /// Returns dQ or dmq1 CRT exponent. (d mod q-1)
-    pub fn dq(&self) -> Integer {
-        self.d.clone() % (self.q() - 1)
+    pub fn dq(&self) -> Option<Integer> {
+        Some(self.d.clone() % (self.q()? - 1))
     }
 
     /// Returns qInv or iqmp CRT exponent. (q^-1 mod p)
-    pub fn qinv(&self) -> Integer {
-        self.q().invert(&self.p()).unwrap()
+    pub fn qinv(&self) -> Option<Integer> {
+        self.q()?.invert(&self.p()?).ok()
     }
 
     /// Returns pInv or ipmq CRT exponent. (p^-1 mod q)
-    pub fn pinv(&self) -> Integer {
-        self.p().invert(&self.q()).unwrap()
+    pub fn pinv(&self) -> Option<Integer> {
+        self.p()?.invert(&self.q()?).ok()
+    }
+
+    /// Returns the CRT exponent of each distinct factor r^k, d mod phi(r^k)
+    ///
+    /// With two distinct factors, these are dP and dQ.
+    pub fn crt_exponents(&self) -> Vec<Integer> {
+        self.factors
+            .phis()
+            .iter()
+            .map(|phi| Integer::from(&self.d % phi))
+            .collect()
+    }
+
+    /// Returns the CRT coefficient of each distinct factor r_i^k_i but the first,
+    /// (r_1^k_1 * ... * r_(i-1)^k_(i-1))^-1 mod r_i^k_i
+    ///
+    /// With two distinct factors, the only coefficient is pInv.
+    pub fn crt_coefficients(&self) -> Vec<Integer> {
+        let mut product = Integer::from(1);
+        let mut coefficients = vec![];
+        for (f, k) in &self.factors.0 {
+            let power = f.clone().pow(*k as u32);
+            if product != 1 {
+                // Powers of distinct primes are coprime
+                coefficients.push(product.invert_ref(&power).unwrap().into());
+            }
+            product *= power;
+        }
+        coefficients
+    }
+
+    /// Convert to an openssl RSA key, only keys with two factors can be
+    pub(crate) fn to_openssl(&self) -> Option<Rsa<Private>> {
+        if self.factors.len() != 2 {
+            return None;
+        }
+
+        let bn = |n: &Integer| openssl::bn::BigNum::from_slice(&n.to_digits(Order::Msf)).unwrap();
+        Some(
+            RsaPrivateKeyBuilder::new(bn(&self.n), bn(&self.e), bn(&self.d))
+                .ok()?
+                .set_factors(bn(&self.p()?), bn(&self.q()?))
+                .ok()?
+                .set_crt_params(bn(&self.dp()?), bn(&self.dq()?), bn(&self.qinv()?))
+                .ok()?
+                .build(),
+        )
     }
 
     /// Convert to PEM format
@@ -135,25 +211,7 @@ impl PrivateKey {
         if self.factors.len() != 2 {
             panic!("Only keys with two factors can be converted to PEM format");
         }
-
-        let rsa = RsaPrivateKeyBuilder::new(
-            openssl::bn::BigNum::from_slice(&self.n.to_digits(Order::Msf)).unwrap(),
-            openssl::bn::BigNum::from_slice(&self.e.to_digits(Order::Msf)).unwrap(),
-            openssl::bn::BigNum::from_slice(&self.d.to_digits(Order::Msf)).unwrap(),
-        )
-        .ok()?
-        .set_factors(
-            openssl::bn::BigNum::from_slice(&self.p().to_digits(Order::Msf)).unwrap(),
-            openssl::bn::BigNum::from_slice(&self.q().to_digits(Order::Msf)).unwrap(),
-        )
-        .ok()?
-        .set_crt_params(
-            openssl::bn::BigNum::from_slice(&self.dp().to_digits(Order::Msf)).unwrap(),
-            openssl::bn::BigNum::from_slice(&self.dq().to_digits(Order::Msf)).unwrap(),
-            openssl::bn::BigNum::from_slice(&self.qinv().to_digits(Order::Msf)).unwrap(),
-        )
-        .ok()?
-        .build();
+        let rsa = self.to_openssl()?;
 
         if let Some(passphrase) = passphrase {
             rsa.private_key_to_pem_passphrase(
@@ -213,6 +271,29 @@ mod tests {
         assert_eq!(m, pk.decrypt(&c));
     }
 
+    #[test]
+    fn crt_parameters() {
+        let pk = PrivateKey::from_p_q(61, 53, 17).unwrap();
+        assert_eq!((pk.p(), pk.q()), (Some(53.into()), Some(61.into())));
+        assert_eq!(pk.crt_exponents(), [pk.dp().unwrap(), pk.dq().unwrap()]);
+        assert_eq!(pk.crt_coefficients(), [pk.pinv().unwrap()]);
+        assert_eq!(pk.qinv().unwrap() * 61 % 53, 1);
+
+        // Three factors, one of them repeated
+        let pk = PrivateKey::from_factors([5, 7, 7, 11], 17).unwrap();
+        assert_eq!(
+            (pk.p(), pk.q(), pk.dp(), pk.qinv()),
+            (None, None, None, None)
+        );
+        let exponents = pk.crt_exponents();
+        let coefficients = pk.crt_coefficients();
+        assert_eq!(exponents.len(), 3);
+        assert_eq!(coefficients, [Integer::from(10), Integer::from(4)]);
+        for (exponent, phi) in exponents.iter().zip([4, 42, 10]) {
+            assert_eq!(Integer::from(&pk.e * exponent) % phi, 1);
+        }
+    }
+
     #[test]
     fn decrypt_from_many_duplicated_factors() {
         let m = bytes_to_integer(b"RsaCracker!");
@@ -0,0 +1,353 @@
+use rug::{
+    ops::{DivRounding, Pow},
+    Integer,
+};
+
+/// Lovász condition constant delta = DELTA_NUM / DELTA_DEN
+const DELTA_NUM: u32 = 99;
+const DELTA_DEN: u32 = 100;
+
+/// Margin, in bits, kept below the theoretical bound of Coppersmith's method
+const COPPERSMITH_MARGIN_BITS: u32 = 8;
+
+/// Largest lattice parameter m used by `small_roots_mod_factor` and `small_roots_mod_n`
+const MAX_LATTICE_M: u32 = 12;
+
+fn dot(a: &[Integer], b: &[Integer]) -> Integer {
+    a.iter().zip(b).map(|(x, y)| Integer::from(x * y)).sum()
+}
+
+/// Reduce `lambda / d` to the nearest integer, ties rounded down
+fn round_div(lambda: &Integer, d: &Integer) -> Integer {
+    (Integer::from(lambda * 2u32) + d).div_floor(Integer::from(d * 2u32))
+}
+
+/// LLL-reduce the rows of `basis`, which must be linearly independent
+///
+/// Integral version of the algorithm (Cohen, algorithm 2.6.7), all values stay integers.
+/// Returns `false` if the rows are linearly dependent.
+pub fn lll(basis: &mut [Vec<Integer>]) -> bool {
+    let n = basis.len();
+    if n < 2 {
+        return true;
+    }
+
+    // 1-based indexes, as in the algorithm: d[i] is the product of the squared norms of the
+    // i first Gram-Schmidt vectors, lambda[i][j] = d[j] * mu[i][j]
+    let mut d = vec![Integer::ZERO; n + 1];
+    let mut lambda = vec![vec![Integer::ZERO; n + 1]; n + 1];
+    d[0] = Integer::from(1);
+    d[1] = dot(&basis[0], &basis[0]);
+
+    let mut k = 2;
+    let mut k_max = 1;
+    while k <= n {
+        // Incremental Gram-Schmidt
+        if k > k_max {
+            k_max = k;
+            for j in 1..=k {
+                let mut u = dot(&basis[k - 1], &basis[j - 1]);
+                for i in 1..j {
+                    u = (Integer::from(&d[i] * &u) - Integer::from(&lambda[k][i] * &lambda[j][i]))
+                        / &d[i - 1];
+                }
+                if j < k {
+                    lambda[k][j] = u;
+                } else if u == 0 {
+                    return false;
+                } else {
+                    d[k] = u;
+                }
+            }
+        }
+
+        reduce(basis, &d, &mut lambda, k, k - 1);
+        let lhs = Integer::from(&d[k] * &d[k - 2]) * DELTA_DEN;
+        let rhs = Integer::from(d[k - 1].square_ref()) * DELTA_NUM
+            - Integer::from(lambda[k][k - 1].square_ref()) * DELTA_DEN;
+        if lhs < rhs {
+            swap(basis, &mut d, &mut lambda, k, k_max);
+            k = 2.max(k - 1);
+        } else {
+            for l in (1..k - 1).rev() {
+                reduce(basis, &d, &mut lambda, k, l);
+            }
+            k += 1;
+        }
+    }
+    true
+}
+
+fn reduce(
+    basis: &mut [Vec<Integer>],
+    d: &[Integer],
+    lambda: &mut [Vec<Integer>],
+    k: usize,
+    l: usize,
+) {
+    if Integer::from(lambda[k][l].abs_ref()) * 2u32 <= d[l] {
+        return;
+    }
+
+    let q = round_div(&lambda[k][l], &d[l]);
+    let (low, high) = basis.split_at_mut(k - 1);
+    for (x, y) in high[0].iter_mut().zip(&low[l - 1]) {
+        *x -= Integer::from(&q * y);
+    }
+    let (low, high) = lambda.split_at_mut(k);
+    high[0][l] -= Integer::from(&q * &d[l]);
+    for (x, y) in high[0][1..l].iter_mut().zip(&low[l][1..l]) {
+        *x -= Integer::from(&q * y);
+    }
+}
+
+fn swap(
+    basis: &mut [Vec<Integer>],
+    d: &mut [Integer],
+    lambda: &mut [Vec<Integer>],
+    k: usize,
+    k_max: usize,
+) {
+    basis.swap(k - 1, k - 2);
+    let (low, high) = lambda.split_at_mut(k);
+    low[k - 1][1..k - 1].swap_with_slice(&mut high[0][1..k - 1]);
+
+    let l = lambda[k][k - 1].clone();
+    let b = (Integer::from(&d[k - 2] * &d[k]) + Integer::from(l.square_ref())) / &d[k - 1];
+    for row in &mut lambda[k + 1..=k_max] {
+        let t = row[k].clone();
+        row[k] = (Integer::from(&d[k] * &row[k - 1]) - Integer::from(&l * &t)) / &d[k - 1];
+        row[k - 1] = (Integer::from(&b * &t) + Integer::from(&l * &row[k])) / &d[k];
+    }
+    d[k - 1] = b;
+}
+
+/// Evaluate a polynomial, given by its coefficients from the lowest degree, at x
+pub fn eval(poly: &[Integer], x: &Integer) -> Integer {
+    poly.iter()
+        .rev()
+        .fold(Integer::ZERO, |acc, coef| acc * x + coef)
+}
+
+/// Multiply two polynomials
+fn mul(a: &[Integer], b: &[Integer]) -> Vec<Integer> {
+    let mut out = vec![Integer::ZERO; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            out[i + j] += Integer::from(x * y);
+        }
+    }
+    out
+}
+
+fn derivative(poly: &[Integer]) -> Vec<Integer> {
+    poly.iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, coef)| Integer::from(coef * i as u32))
+        .collect()
+}
+
+/// Returns integers r in [lo, hi] such that poly has a real root in [r, r + 1]
+///
+/// The roots of the derivative split [lo, hi] in intervals where poly is monotonic,
+/// each of them is then searched by bisection.
+fn root_floors(poly: &[Integer], lo: &Integer, hi: &Integer) -> Vec<Integer> {
+    if poly.len() < 2 {
+        return vec![];
+    }
+
+    let mut bounds = vec![lo.clone(), hi.clone()];
+    for c in root_floors(&derivative(poly), lo, hi) {
+        bounds.push(Integer::from(&c + 1).min(hi.clone()));
+        bounds.push(c);
+    }
+    bounds.sort();
+    bounds.dedup();
+
+    let mut floors = vec![];
+    for pair in bounds.windows(2) {
+        let (mut a, mut b) = (pair[0].clone(), pair[1].clone());
+        let sign_a = eval(poly, &a).cmp0();
+        if sign_a.is_eq() {
+            floors.push(a);
+            continue;
+        }
+        if sign_a == eval(poly, &b).cmp0() {
+            continue;
+        }
+        while Integer::from(&b - &a) > 1 {
+            let mid = Integer::from(&a + &b) >> 1;
+            if eval(poly, &mid).cmp0() == sign_a {
+                a = mid;
+            } else {
+                b = mid;
+            }
+        }
+        floors.push(a);
+    }
+    if eval(poly, hi) == 0 {
+        floors.push(hi.clone());
+    }
+    floors
+}
+
+/// Returns the integer roots of a polynomial in [-bound, bound]
+pub fn integer_roots(poly: &[Integer], bound: &Integer) -> Vec<Integer> {
+    let mut roots = root_floors(poly, &Integer::from(-bound), bound)
+        .into_iter()
+        .flat_map(|r| [Integer::from(&r + 1), r])
+        .filter(|r| Integer::from(r.abs_ref()) <= *bound && eval(poly, r) == 0)
+        .collect::<Vec<_>>();
+    roots.sort();
+    roots.dedup();
+    roots
+}
+
+/// Coppersmith's method (Howgrave-Graham's formulation)
+///
+/// Returns the candidate roots |x0| <= x_bound of the monic polynomial f modulo an unknown
+/// factor b of n, with the lattice of the polynomials x^j * n^(m - i) * f^i and x^i * f^m.
+/// Candidates must be checked by the caller.
+pub fn coppersmith(f: &[Integer], n: &Integer, x_bound: &Integer, m: u32, t: u32) -> Vec<Integer> {
+    let degree = f.len() - 1;
+    let mut polys = vec![];
+    let mut f_pow = vec![Integer::from(1)];
+    for i in 0..m {
+        let n_pow = n.clone().pow(m - i);
+        for j in 0..degree {
+            let mut poly = vec![Integer::ZERO; j];
+            poly.extend(f_pow.iter().map(|coef| Integer::from(coef * &n_pow)));
+            polys.push(poly);
+        }
+        f_pow = mul(&f_pow, f);
+    }
+    for i in 0..t as usize {
+        let mut poly = vec![Integer::ZERO; i];
+        poly.extend(f_pow.iter().cloned());
+        polys.push(poly);
+    }
+
+    // Coefficients of g(x * X)
+    let dim = polys.iter().map(|poly| poly.len()).max().unwrap_or(0);
+    let mut basis = polys
+        .iter()
+        .map(|poly| {
+            let mut x_pow = Integer::from(1);
+            (0..dim)
+                .map(|i| {
+                    let coef = poly
+                        .get(i)
+                        .map_or(Integer::ZERO, |c| Integer::from(c * &x_pow));
+                    x_pow *= x_bound;
+                    coef
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if !lll(&mut basis) {
+        return vec![];
+    }
+
+    let mut roots = vec![];
+    for row in basis.iter().take(2) {
+        let mut x_pow = Integer::from(1);
+        let poly = row
+            .iter()
+            .map(|coef| {
+                let c = Integer::from(coef / &x_pow);
+                x_pow *= x_bound;
+                c
+            })
+            .collect::<Vec<_>>();
+        roots.extend(integer_roots(&poly, x_bound));
+    }
+    roots.sort();
+    roots.dedup();
+    roots
+}
+
+/// Returns the candidate roots x0 < 2^x_bits of the monic polynomial f modulo a factor p >= n^(1/2)
+///
+/// The lattice dimension is chosen from the gap between x_bits and the bound n^(1/(4 * deg f)).
+/// Returns `None` if x_bits is too close to that bound.
+pub fn small_roots_mod_factor(f: &[Integer], n: &Integer, x_bits: u32) -> Option<Vec<Integer>> {
+    let degree = f.len() as u32 - 1;
+    let bound_bits = n.significant_bits() / (4 * degree);
+    let slack = bound_bits.checked_sub(x_bits + COPPERSMITH_MARGIN_BITS)?;
+    if slack == 0 {
+        return None;
+    }
+    let m = n.significant_bits().div_ceil(4 * degree * slack).max(1);
+    if m > MAX_LATTICE_M {
+        return None;
+    }
+    Some(coppersmith(
+        f,
+        n,
+        &(Integer::from(1) << x_bits),
+        m,
+        degree * m,
+    ))
+}
+
+/// Returns the candidate roots x0 < 2^x_bits of the monic polynomial f modulo n
+///
+/// The lattice dimension is chosen from the gap between x_bits and the bound n^(1/deg f).
+/// Returns `None` if x_bits is too close to that bound.
+pub fn small_roots_mod_n(f: &[Integer], n: &Integer, x_bits: u32) -> Option<Vec<Integer>> {
+    let degree = f.len() as u32 - 1;
+    let bound_bits = n.significant_bits() / degree;
+    let slack = bound_bits.checked_sub(x_bits + COPPERSMITH_MARGIN_BITS)?;
+    if slack == 0 {
+        return None;
+    }
+    let m = n.significant_bits().div_ceil(degree * slack).max(1);
+    if m > MAX_LATTICE_M {
+        return None;
+    }
+    Some(coppersmith(f, n, &(Integer::from(1) << x_bits), m, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn lll() {
+        let mut basis = [[1, 1, 1], [-1, 0, 2], [3, 5, 6]]
+            .map(|row| row.map(Integer::from).to_vec())
+            .to_vec();
+        assert!(super::lll(&mut basis));
+        assert_eq!(
+            basis,
+            [[0, 1, 0], [1, 0, 1], [-1, 0, 2]].map(|row| row.map(Integer::from).to_vec())
+        );
+    }
+
+    #[test]
+    fn small_roots() {
+        let p = Integer::from_str(
+            "90511711160491104683260185958356471267265849470934538618804038653604797333997",
+        )
+        .unwrap();
+        let q = Integer::from_str(
+            "114596133725443196642584039352225739386449324911628952764126006973006794144319",
+        )
+        .unwrap();
+        let n = Integer::from(&p * &q);
+
+        // p = p_high + x, with the 80 low bits of p unknown
+        let x = Integer::from(&p) & ((Integer::from(1) << 80) - 1);
+        let f = [Integer::from(&p - &x), Integer::from(1)];
+        let roots = small_roots_mod_factor(&f, &n, 80).unwrap();
+        assert!(roots.contains(&x));
+
+        assert_eq!(
+            integer_roots(&[(-6).into(), 1.into(), 1.into()], &10.into()),
+            [Integer::from(-3), Integer::from(2)]
+        );
+    }
+}
@@ -2,46 +2,111 @@
 #![deny(rust_2018_idioms)]
 #![warn(missing_docs)]
 
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use rug::integer::IsPrime;
-use rug::Integer;
-use std::cell::RefCell;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use rug::{integer::IsPrime, Integer};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::mpsc;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ntheory::{is_prime, nth_roots_mod_factors};
 
 mod attack;
+mod audit;
+mod cache;
+mod check;
+pub mod contfrac;
+mod ct;
+mod diagnostics;
+mod distributed;
+mod dnssec;
+mod export;
 mod factors;
+mod forge;
+#[cfg(feature = "gpu")]
+mod gpu;
 mod key;
-mod ntheory;
+mod lattice;
+pub mod ntheory;
+mod oracle;
 mod params;
+mod partial;
+pub mod poly;
+mod ranking;
+mod report;
+mod schedule;
+mod session;
 mod solution;
+mod stats;
 mod utils;
+mod vulnerability;
 
 pub use attack::*;
+pub use audit::{
+    audit_keys, audit_moduli, batch_gcd, fermat_factor, has_roca_fingerprint, parse_moduli,
+    recover_keys, AuditReport, Finding, RecoveredKey, Weakness,
+};
+pub use cache::Cache;
+pub use check::{check_key, KeyMismatch};
+pub use ct::{fetch_ct_certificates, CtCertificate};
+pub use diagnostics::{diagnose, trivial_solution, Diagnostic};
+pub use distributed::{coordinate, work};
+pub use dnssec::{fetch_dnskeys, parse_dnskeys};
+pub use export::{export_bundle, export_private_key, export_public_key, KeyFormat};
 pub use factors::*;
+pub use forge::{
+    forge_certificate, forge_csr, resign_certificate, ForgeError, ForgeOptions, ResignOptions,
+};
 pub use key::*;
+pub use oracle::{answer_ciphers, generate_key, oracle_answer, serve_oracle, OracleKind};
 pub use params::*;
+pub use partial::PartialInteger;
+pub use ranking::{plaintext_score, rank_plaintexts, DEFAULT_FLAG_PATTERN};
+pub use report::{render_report, ReportFormat};
+pub use schedule::{dependent_attacks, promising_attacks, schedule_attacks};
+pub use session::{redact_args, SessionEntry, SessionLog};
 pub use solution::*;
-pub use utils::{bytes_to_integer, integer_to_bytes, integer_to_string, string_to_integer};
-
-fn check_n_prime(n: &Option<Integer>) -> bool {
-    if let Some(n) = &n {
-        match n.is_probably_prime(100) {
-            IsPrime::Yes => {
-                eprintln!("Error: N is prime, no attacks possible");
-                return true;
-            }
-            IsPrime::Probably => {
-                eprintln!("Warning: n is probably prime, but not certain");
-            }
-            _ => {}
-        }
+pub use stats::{AttackEvent, AttackOutcome, AttackStats, RunStats};
+pub use utils::{
+    bytes_to_integer, cipher_file_to_integer, integer_to_bytes, integer_to_bytes_padded,
+    integer_to_string, integer_to_string_with, reverse_bytes, string_to_integer, TextEncoding,
+};
+pub use vulnerability::Vulnerability;
+
+/// Returns the attacks applicable to n
+///
+/// Factorization attacks are pointless on a prime modulus, only the attacks decrypting the cipher
+/// modulo a prime are kept, and none if there is no cipher.
+fn applicable_attacks(
+    params: &Parameters,
+    precomputed: &PrecomputedContext,
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+) -> Vec<Arc<dyn Attack + Sync + Send>> {
+    match precomputed.n_is_prime() {
+        Some(IsPrime::No) | None => return attacks.to_vec(),
+        Some(IsPrime::Yes) => eprintln!("Warning: n is prime"),
+        Some(IsPrime::Probably) => eprintln!("Warning: n is probably prime, but not certain"),
     }
-    false
+
+    if params.c.is_none() {
+        eprintln!("Error: n is prime, no attacks possible without a cipher");
+        return vec![];
+    }
+    attacks
+        .iter()
+        .filter(|attack| attack.kind() != AttackKind::Factorization)
+        .cloned()
+        .collect()
 }
 
-fn create_multi_progress(nb_attacks: usize) -> (Arc<MultiProgress>, Arc<ProgressBar>) {
-    let mp = Arc::new(MultiProgress::new());
+fn create_multi_progress(
+    nb_attacks: usize,
+    refresh_rate: u8,
+) -> (Arc<MultiProgress>, Arc<ProgressBar>) {
+    let mp = Arc::new(MultiProgress::with_draw_target(
+        ProgressDrawTarget::stderr_with_hz(refresh_rate),
+    ));
     let pb_main = Arc::new(mp.add(ProgressBar::new(nb_attacks as u64)));
 
     pb_main.set_style(
@@ -55,7 +120,12 @@ fn create_multi_progress(nb_attacks: usize) -> (Arc<MultiProgress>, Arc<Progress
     (mp, pb_main)
 }
 
-fn create_progress_bar(mp: &MultiProgress) -> ProgressBar {
+fn create_progress_bar(mp: &MultiProgress, aggregate_only: bool) -> ProgressBar {
+    // Hidden bars are not drawn, but still track the progress of the attack
+    if aggregate_only {
+        return ProgressBar::hidden();
+    }
+
     let pb = mp.insert(0, ProgressBar::new(1));
     pb.set_style(
         ProgressStyle::with_template(
@@ -66,51 +136,374 @@ fn create_progress_bar(mp: &MultiProgress) -> ProgressBar {
     pb
 }
 
-async fn _run_attacks<'a>(
+/// Number of rounds of time slices, each round doubling the slice and the effort
+const TIME_SLICE_ROUNDS: u32 = 4;
+
+/// Returns all the possible messages matching the constraint, when n is factored but e has no
+/// inverse (e.g. Rabin, e = 2)
+fn roots_solution(
+    attack: &'static str,
+    params: &Parameters,
+    factors: &Factors,
+    constraint: &PlaintextConstraint,
+) -> Option<Solution> {
+    let mut ms = nth_roots_mod_factors(params.c.as_ref()?, &params.e, factors);
+    ms.retain(|m| constraint.accepts(m));
+    (!ms.is_empty()).then(|| Solution::new_ms(attack, ms))
+}
+
+/// Run an attack, try to decrypt the cipher with the found key and verify the solution
+///
+/// Solutions contradicting the parameters are discarded.
+/// Resumable attacks are resumed from `state`, which is set to their state if they are stopped.
+fn run_attack(
+    attack: &Arc<dyn Attack + Sync + Send>,
+    params: &Parameters,
+    ctx: &AttackContext,
+    state: &mut Option<AttackState>,
+    pb: &ProgressBar,
+) -> Result<Solution, Error> {
+    let result = match attack.as_resumable() {
+        Some(attack) => attack.run_resumable(params, ctx, state, Some(pb)),
+        None => attack.run_with_context(params, ctx, Some(pb)),
+    };
+    let mut solution = match result {
+        // Without a private exponent, all e-th roots of the cipher are possible messages
+        Err(Error::Key(KeyError::ExponentNotInvertible(factors))) => {
+            match roots_solution(attack.name(), params, &factors, &ctx.config().plaintext) {
+                Some(solution) => solution,
+                None => return Err(Error::PartialFactorization(factors)),
+            }
+        }
+        res => res?,
+    };
+    if solution.vulnerability.is_none() {
+        solution.vulnerability = attack.vulnerability();
+    }
+
+    // Try to decrypt the cipher if no message was found
+    if let (Some(pk), None, Some(c)) = (&solution.pk, &solution.m, &params.c) {
+        solution.m = Some(pk.decrypt(c))
+    }
+    if !solution.verify(params) {
+        return Err(Error::NotFound);
+    }
+
+    // Only keep the possible plaintexts matching their known structure
+    let constraint = &ctx.config().plaintext;
+    if !solution.ms.is_empty() && !constraint.is_empty() {
+        solution.ms.retain(|m| constraint.accepts(m));
+        if solution.ms.is_empty() {
+            return Err(Error::NotFound);
+        }
+    }
+
+    // Let the user check the plaintexts, the search goes on if they are all rejected
+    if let Some(check) = &ctx.config().on_candidate {
+        if !solution.keep_candidates(|m| check.accepts(m)) {
+            return Err(Error::NotFound);
+        }
+    }
+    Ok(solution)
+}
+
+/// Record how long an attack ran to completion, to allocate the time budget of future runs
+fn record_timing(attack: &Arc<dyn Attack + Sync + Send>, ctx: &AttackContext, elapsed: Duration) {
+    if let (Some(n_bits), Some(cache)) = (ctx.precomputed().n_bits(), &ctx.config().cache) {
+        cache.store_timing(attack.name(), n_bits, elapsed);
+    }
+}
+
+/// Record the outcome of an attack in the statistics of the run, if they are collected
+fn record_stats(
+    stats: &Option<Arc<RunStats>>,
+    attack: &Arc<dyn Attack + Sync + Send>,
+    solution: &Result<Solution, Error>,
+    run_ctx: &AttackContext,
+    elapsed: Duration,
+    pb: &ProgressBar,
+) {
+    if let Some(stats) = stats {
+        let outcome = AttackOutcome::new(solution, run_ctx.is_stopped());
+        stats.record(AttackStats::new(attack.name(), outcome, elapsed, pb));
+    }
+}
+
+/// Maximum number of times discovered parameters are passed on to the attacks using them
+const MAX_DEPENDENCY_DEPTH: usize = 4;
+
+/// Parameters discovered by the attacks of a run, passed on to the attacks using them
+#[derive(Clone)]
+struct Discoveries {
+    /// Given parameters, completed with the ones discovered so far
+    params: Arc<Mutex<Parameters>>,
+    /// Number of times discovered parameters were passed on to reach the running attacks
+    depth: usize,
+    /// Progress bar of the run, counting the attacks, dependent ones included
+    pb_main: Arc<ProgressBar>,
+}
+
+impl Discoveries {
+    fn new(params: &Parameters, pb_main: &Arc<ProgressBar>) -> Self {
+        Self {
+            params: Arc::new(Mutex::new(params.clone())),
+            depth: 0,
+            pb_main: Arc::clone(pb_main),
+        }
+    }
+}
+
+/// Complete the parameters with the ones discovered by an attack, then spawn the attacks using
+/// them on the runtime of the run, see `dependent_attacks`
+///
+/// The discovered parameters take precedence over the given ones, e.g. the whole of d over its
+/// known bits. The dependent attacks share the context of the run, so they are stopped with it.
+fn spawn_dependent_attacks(
+    discovered: Parameters,
+    discoveries: &Discoveries,
+    ctx: &AttackContext,
+    collect_all: bool,
+    sender: &mpsc::Sender<Result<Solution, Error>>,
+    mp: &Arc<MultiProgress>,
+) {
+    if discoveries.depth >= MAX_DEPENDENCY_DEPTH {
+        let names = discovered.fields().into_iter().map(|(name, _)| name);
+        eprintln!(
+            "Warning: {} discovered past the maximum depth of {MAX_DEPENDENCY_DEPTH}, not passed on",
+            names.collect::<Vec<_>>().join(", ")
+        );
+        return;
+    }
+
+    let (names, params) = {
+        let mut params = discoveries.params.lock().unwrap();
+        let names = dependent_attacks(&params, &discovered);
+        *params = discovered + &*params;
+        (names, params.clone())
+    };
+    let attacks = ATTACKS
+        .iter()
+        .filter(|attack| names.contains(&attack.name()))
+        .cloned()
+        .collect::<Vec<_>>();
+    if attacks.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "Running {} with the discovered parameters",
+        names.join(", ")
+    );
+    let ctx = ctx.with_params(&params);
+    discoveries.pb_main.inc_length(attacks.len() as u64);
+    let discoveries = Discoveries {
+        params: Arc::clone(&discoveries.params),
+        depth: discoveries.depth + 1,
+        pb_main: Arc::clone(&discoveries.pb_main),
+    };
+    tokio::spawn(run_dependent_attacks(
+        Arc::new(params),
+        attacks,
+        ctx,
+        collect_all,
+        sender.clone(),
+        Arc::clone(mp),
+        discoveries,
+    ));
+}
+
+/// Run the dependent attacks, boxed since they can spawn dependent attacks in turn
+fn run_dependent_attacks(
+    params: Arc<Parameters>,
+    attacks: Vec<Arc<dyn Attack + Sync + Send>>,
+    ctx: AttackContext,
+    collect_all: bool,
+    sender: mpsc::Sender<Result<Solution, Error>>,
+    mp: Arc<MultiProgress>,
+    discoveries: Discoveries,
+) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        _run_attacks(
+            params,
+            &attacks,
+            None,
+            ctx,
+            collect_all,
+            sender,
+            mp,
+            None,
+            discoveries,
+        )
+        .await
+    })
+}
+
+/// Run the fast and medium attacks concurrently, then time-slice the slow ones
+///
+/// The scheduling is staged, not interleaved: the slow attacks only start once all the fast and
+/// medium ones completed, or were stopped at the end of their allotted time.
+/// Each slow attack runs alone on all threads for `AttackConfig::time_slice`, then the next one does.
+/// Attacks interrupted at the end of their slice are run again with twice the time and effort,
+/// for `TIME_SLICE_ROUNDS` rounds, resumable attacks continuing where they stopped. The last round
+/// is not time-sliced, the attacks still running then are run to completion.
+/// With a time budget, each attack gets the time allotted by `allocate_time_budget` instead:
+/// fast and medium attacks are stopped at the end of theirs, and slow ones split it in slices.
+/// The first solution stops the other attacks, unless `collect_all` is set.
+/// Parameters discovered by an attack are passed on to the attacks using them, which run
+/// alongside the others.
+#[allow(clippy::too_many_arguments)]
+async fn _run_attacks(
     params: Arc<Parameters>,
     attacks: &[Arc<dyn Attack + Sync + Send>],
+    allotments: Option<Vec<Duration>>,
+    ctx: AttackContext,
+    collect_all: bool,
     sender: mpsc::Sender<Result<Solution, Error>>,
     mp: Arc<MultiProgress>,
+    stats: Option<Arc<RunStats>>,
+    discoveries: Discoveries,
 ) {
     // Create all progress bars
-    let pbs = RefCell::new(Vec::with_capacity(attacks.len()));
-    for _ in 0..attacks.len() {
-        pbs.borrow_mut().push(Arc::new(create_progress_bar(&mp)));
-    }
+    let pbs = Arc::new(
+        (0..attacks.len())
+            .map(|_| Arc::new(create_progress_bar(&mp, ctx.config().aggregate_progress)))
+            .collect::<Vec<_>>(),
+    );
+
+    // Remove the attack progress bar and send its result to the main thread
+    let finish = {
+        let ctx = ctx.clone();
+        let mp = Arc::clone(&mp);
+        let pbs = Arc::clone(&pbs);
+        move |pb: &ProgressBar, solution: Result<Solution, Error>| {
+            mp.remove(pb);
+            // If attack was successful, stop other attacks and clear all progress bars
+            if solution.is_ok() && !collect_all {
+                ctx.stop();
+                for pb in pbs.iter() {
+                    pb.finish_and_clear();
+                }
+            }
 
-    for (attack, pb) in attacks.iter().cloned().zip(pbs.borrow().iter().cloned()) {
+            // Pass the discovered parameters on, unless the attacks were stopped
+            let discovered = match &solution {
+                Ok(solution) => solution.discovered.clone(),
+                Err(Error::Discovered(discovered)) => Some(discovered.clone()),
+                Err(_) => None,
+            };
+            if let Some(discovered) = discovered.filter(|_| !ctx.is_stopped()) {
+                spawn_dependent_attacks(*discovered, &discoveries, &ctx, collect_all, &sender, &mp);
+            }
+
+            // Note: error if channel closed
+            sender.send(solution).ok();
+        }
+    };
+
+    let (slow, others): (Vec<_>, Vec<_>) = attacks
+        .iter()
+        .cloned()
+        .zip(pbs.iter().cloned())
+        .enumerate()
+        .map(|(i, (attack, pb))| {
+            let allotment = allotments.as_ref().map(|allotments| allotments[i]);
+            (attack, pb, allotment, Duration::ZERO, None)
+        })
+        .partition(|(attack, _, _, _, _)| attack.speed() == AttackSpeed::Slow);
+
+    // Run fast and medium attacks to completion, or until the end of their allotted time
+    let mut tasks = Vec::with_capacity(others.len());
+    for (attack, pb, allotment, _, _) in others {
         // Clone variables for closure
         let params = Arc::clone(&params);
-        let sender = sender.clone();
-        let mp = Arc::clone(&mp);
-        let pbs = RefCell::clone(&pbs);
+        let run_ctx = ctx.clone();
+        let stats = stats.clone();
+        let ctx = match allotment {
+            Some(allotment) => ctx.child().with_deadline(Instant::now() + allotment),
+            None => ctx.clone(),
+        };
+        let finish = finish.clone();
 
         // Spawn attack as a task
-        tokio::task::spawn(async move {
+        tasks.push(tokio::task::spawn(async move {
             // This unhide the progress bar
             pb.set_prefix(attack.name());
 
-            let solution = attack.run(&params, Some(&pb)).map(|mut solution| {
-                // Try to decrypt the cipher if no message was found
-                if let (Some(pk), None, Some(c)) = (&solution.pk, &solution.m, &params.c) {
-                    solution.m = Some(pk.decrypt(c))
-                }
-                solution
+            let start = Instant::now();
+            let solution = run_attack(&attack, &params, &ctx, &mut None, &pb);
+            if !matches!(solution, Err(Error::Stopped)) {
+                record_timing(&attack, &ctx, start.elapsed());
+            }
+            record_stats(&stats, &attack, &solution, &run_ctx, start.elapsed(), &pb);
+            finish(&pb, solution);
+        }));
+    }
+    for task in tasks {
+        task.await.ok();
+    }
+
+    // Time-slice slow attacks
+    let mut remaining = slow;
+    for round in 0..TIME_SLICE_ROUNDS {
+        let mut next = Vec::with_capacity(remaining.len());
+        for (attack, pb, allotment, elapsed, mut state) in remaining {
+            if ctx.is_stopped() {
+                return;
+            }
+            pb.set_prefix(attack.name());
+
+            let config = AttackConfig {
+                effort: ctx.config().effort << round,
+                ..ctx.config().clone()
+            };
+            // The allotted time is split in slices doubling each round
+            let time_slice = match allotment {
+                Some(allotment) => allotment * 2u32.pow(round) / (2u32.pow(TIME_SLICE_ROUNDS) - 1),
+                None => ctx.config().time_slice * 2u32.pow(round),
+            };
+            let slice_ctx = ctx.child().with_config(config);
+            // Without a time budget, the last round runs until the attacks complete
+            let timer = (allotment.is_some() || round + 1 < TIME_SLICE_ROUNDS).then(|| {
+                let slice_ctx = slice_ctx.clone();
+                tokio::task::spawn(async move {
+                    tokio::time::sleep(time_slice).await;
+                    slice_ctx.stop();
+                })
             });
 
-            // Remove progress bar from list
-            mp.remove(&pb);
-            // If attack was successful, clear all progress bars
-            if solution.is_ok() {
-                for pb in pbs.borrow().iter() {
-                    pb.finish_and_clear();
-                }
+            let task = {
+                let attack = Arc::clone(&attack);
+                let params = Arc::clone(&params);
+                let pb = Arc::clone(&pb);
+                tokio::task::spawn_blocking(move || {
+                    let solution = run_attack(&attack, &params, &slice_ctx, &mut state, &pb);
+                    (solution, state)
+                })
+            };
+            let start = Instant::now();
+            let (solution, state) = task.await.unwrap_or((Err(Error::NotFound), None));
+            if let Some(timer) = timer {
+                timer.abort();
             }
+            let elapsed = elapsed + start.elapsed();
 
-            // Send result to main thread
-            // Note: error if channel closed
-            sender.send(solution).ok();
-        });
+            // Attacks interrupted at the end of their time slice are resumed in the next round
+            match solution {
+                Err(Error::Stopped) if round + 1 < TIME_SLICE_ROUNDS && !ctx.is_stopped() => {
+                    next.push((attack, pb, allotment, elapsed, state))
+                }
+                Err(Error::Stopped) => {
+                    record_stats(&stats, &attack, &solution, &ctx, elapsed, &pb);
+                    finish(&pb, solution)
+                }
+                _ => {
+                    record_timing(&attack, &ctx, start.elapsed());
+                    record_stats(&stats, &attack, &solution, &ctx, elapsed, &pb);
+                    finish(&pb, solution)
+                }
+            }
+        }
+        remaining = next;
     }
 }
 
@@ -141,7 +534,284 @@ pub fn run_specific_attacks_with_threads(
     attacks: &[Arc<dyn Attack + Sync + Send>],
     threads: usize,
 ) -> Result<Solution, Option<Factors>> {
-    if check_n_prime(&params.n) {
+    run_specific_attacks_with_config(
+        params,
+        attacks,
+        AttackConfig {
+            threads,
+            ..Default::default()
+        },
+    )
+}
+
+/// Run specific attacks with a given configuration.
+///
+/// Composite factors left by the attacks are factored again with the factorization attacks.
+pub fn run_specific_attacks_with_config(
+    params: &Parameters,
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+    config: AttackConfig,
+) -> Result<Solution, Option<Factors>> {
+    run_reduced_attacks(params, attacks, config, false, None)
+        .map(|solutions| solutions.into_iter().next().unwrap())
+}
+
+/// Run specific attacks with a given configuration, and return all their solutions.
+///
+/// Every attack runs to completion, or until the end of the time budget, instead of stopping at
+/// the first solution. Different attacks may find different candidate messages, or a full key.
+pub fn run_all_specific_attacks_with_config(
+    params: &Parameters,
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+    config: AttackConfig,
+) -> Result<Vec<Solution>, Option<Factors>> {
+    run_reduced_attacks(params, attacks, config, true, None)
+}
+
+/// Run specific attacks with a given configuration, and record the outcome of each one in `stats`.
+///
+/// Returns all the solutions with `collect_all`, as `run_all_specific_attacks_with_config` does,
+/// or only the first one.
+pub fn run_specific_attacks_with_stats(
+    params: &Parameters,
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+    config: AttackConfig,
+    collect_all: bool,
+    stats: Arc<RunStats>,
+) -> Result<Vec<Solution>, Option<Factors>> {
+    run_reduced_attacks(params, attacks, config, collect_all, Some(stats))
+}
+
+/// Interval between the progress events of `run_one`, sent when the progress changed
+const EVENT_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Run a single attack by name, with a given configuration
+///
+/// The attack runs alone, without the orchestration of the other functions: no scheduling, no
+/// tuning of the configuration to n, and no factorization of the composite factors it leaves nor
+/// attacks run on the parameters it discovers.
+/// Its solution is completed as usual: the cipher is decrypted with the found key, and the
+/// plaintexts are checked against `AttackConfig::plaintext` and `AttackConfig::on_candidate`.
+/// The attack is stopped at the end of `AttackConfig::time_budget`, if any.
+/// If `events` is given, the start, progress and end of the attack are sent to it.
+pub fn run_one(
+    name: &str,
+    params: &Parameters,
+    config: &AttackConfig,
+    pb: Option<&ProgressBar>,
+    events: Option<&mpsc::Sender<AttackEvent>>,
+) -> Result<Solution, Error> {
+    let ctx = AttackContext::new(params).with_config(config.clone());
+    let ctx = match config.time_budget {
+        Some(budget) => ctx.with_deadline(Instant::now() + budget),
+        None => ctx,
+    };
+    run_one_with_context(name, params, &ctx, pb, events)
+}
+
+/// Run a single attack by name in a given context, see `run_one`
+///
+/// The attack can be stopped from another thread, with `AttackContext::stop` on a clone of the
+/// context.
+pub fn run_one_with_context(
+    name: &str,
+    params: &Parameters,
+    ctx: &AttackContext,
+    pb: Option<&ProgressBar>,
+    events: Option<&mpsc::Sender<AttackEvent>>,
+) -> Result<Solution, Error> {
+    let attack = ATTACKS
+        .iter()
+        .find(|attack| attack.name() == name)
+        .ok_or_else(|| Error::UnknownAttack(name.to_string()))?;
+    let mut params = params.clone();
+    params.reduce_cipher();
+    let hidden = ProgressBar::hidden();
+    let pb = pb.unwrap_or(&hidden);
+    let Some(events) = events else {
+        return run_attack(attack, &params, ctx, &mut None, pb);
+    };
+
+    // Note: the events are dropped if the receiver is closed
+    let attack_name = attack.name();
+    events
+        .send(AttackEvent::Started {
+            attack: attack_name,
+        })
+        .ok();
+    let start = Instant::now();
+    let solution = std::thread::scope(|scope| {
+        // Sample the progress bar, until the attack ends
+        let (done, ended) = mpsc::channel::<()>();
+        scope.spawn(move || {
+            let mut last = None;
+            while let Err(mpsc::RecvTimeoutError::Timeout) =
+                ended.recv_timeout(EVENT_PROGRESS_INTERVAL)
+            {
+                let progress = (pb.position(), pb.length());
+                if last != Some(progress) {
+                    last = Some(progress);
+                    events
+                        .send(AttackEvent::Progress {
+                            attack: attack_name,
+                            position: progress.0,
+                            length: progress.1,
+                        })
+                        .ok();
+                }
+            }
+        });
+        let solution = run_attack(attack, &params, ctx, &mut None, pb);
+        drop(done);
+        solution
+    });
+
+    let timed_out = ctx
+        .deadline()
+        .is_some_and(|deadline| Instant::now() >= deadline);
+    events
+        .send(AttackEvent::Finished {
+            attack: attack_name,
+            outcome: AttackOutcome::new(&solution, ctx.is_stopped() && !timed_out),
+            elapsed: start.elapsed(),
+        })
+        .ok();
+    solution
+}
+
+/// Reduce the cipher modulo n, then run the attacks, unless the input is degenerate
+fn run_reduced_attacks(
+    params: &Parameters,
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+    config: AttackConfig,
+    collect_all: bool,
+    stats: Option<Arc<RunStats>>,
+) -> Result<Vec<Solution>, Option<Factors>> {
+    let mut params = params.clone();
+    if params.reduce_cipher() {
+        eprintln!("Warning: c is negative or not smaller than n, it was reduced modulo n");
+    }
+    if let Some(solution) = trivial_solution(&params, &config.plaintext) {
+        return Ok(vec![solution]);
+    }
+    run_specific_attacks_at_depth(&params, attacks, config, 0, collect_all, stats)
+}
+
+/// Maximum number of times composite factors are factored again
+const MAX_COFACTOR_DEPTH: usize = 4;
+
+/// Maximum time budget of the cheap pass run on each composite factor
+///
+/// The effort being tuned to the time budget, it also keeps the bounds of the pass small.
+const REFINEMENT_TIME_BUDGET: Duration = Duration::from_secs(10);
+
+/// Factorization attacks of the cheap pass run on each composite factor, if they are selected
+const REFINEMENT_ATTACKS: [&str; 2] = ["pollard_rho", "ecm"];
+
+/// Factors of a composite factor found by the given attacks, `None` if it was not split
+fn split_cofactor(
+    params: &Parameters,
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+    config: AttackConfig,
+    depth: usize,
+) -> Option<Factors> {
+    match run_specific_attacks_at_depth(params, attacks, config, depth + 1, false, None) {
+        Ok(solutions) => solutions.into_iter().next()?.pk.map(|pk| pk.factors),
+        Err(partial_factors) => partial_factors,
+    }
+}
+
+/// Factor the composite factors, within the time budget
+///
+/// Each composite factor first gets a cheap pass of Pollard rho and ECM with small bounds, if they
+/// are among the given attacks, then the factorization attacks if it is still not split.
+/// Returns a solution if all factors are now prime.
+fn factor_cofactors(
+    e: &Integer,
+    factors: &mut Factors,
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+    config: &AttackConfig,
+    deadline: Option<Instant>,
+    depth: usize,
+) -> Option<Solution> {
+    let attacks = attacks
+        .iter()
+        .filter(|attack| attack.kind() == AttackKind::Factorization)
+        .cloned()
+        .collect::<Vec<_>>();
+    let refinement_attacks = attacks
+        .iter()
+        .filter(|attack| REFINEMENT_ATTACKS.contains(&attack.name()))
+        .cloned()
+        .collect::<Vec<_>>();
+    let remaining = || deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+    for cofactor in factors.to_vec() {
+        if is_prime(&cofactor) != IsPrime::No {
+            continue;
+        }
+        if remaining().is_some_and(|remaining| remaining.is_zero()) {
+            break;
+        }
+
+        let params = Parameters {
+            n: Some(cofactor.clone()),
+            e: e.clone(),
+            ..Default::default()
+        };
+        let refinement_config = AttackConfig {
+            time_budget: Some(remaining().map_or(REFINEMENT_TIME_BUDGET, |remaining| {
+                remaining.min(REFINEMENT_TIME_BUDGET)
+            })),
+            ..config.clone()
+        };
+        let Some(cofactors) =
+            split_cofactor(&params, &refinement_attacks, refinement_config, depth).or_else(|| {
+                let config = AttackConfig {
+                    time_budget: remaining(),
+                    ..config.clone()
+                };
+                split_cofactor(&params, &attacks, config, depth)
+            })
+        else {
+            continue;
+        };
+
+        // Replace the cofactor by its factors
+        let count = factors.0.remove(&cofactor).unwrap_or(1);
+        for (factor, factor_count) in cofactors.0 {
+            *factors.0.entry(factor).or_insert(0) += factor_count * count;
+        }
+    }
+
+    PrivateKey::from_factors(factors.clone(), e)
+        .ok()
+        .map(|pk| Solution::new_pk("Partial factors", pk))
+}
+
+/// Run the attacks, then factor the composite factors they left
+///
+/// Returns the first solution, or all of them with `collect_all`.
+/// The outcome of each attack is recorded in `stats`, not the ones of the composite factors' or
+/// of the dependent attacks'.
+fn run_specific_attacks_at_depth(
+    params: &Parameters,
+    attacks: &[Arc<dyn Attack + Sync + Send>],
+    config: AttackConfig,
+    depth: usize,
+    collect_all: bool,
+    stats: Option<Arc<RunStats>>,
+) -> Result<Vec<Solution>, Option<Factors>> {
+    let threads = config.threads;
+    let base_config = config.clone();
+    let config = match params.n.as_ref() {
+        Some(n) => config.tuned(n.significant_bits()),
+        None => config,
+    };
+    let deadline = config.time_budget.map(|budget| Instant::now() + budget);
+    let ctx = AttackContext::new(params).with_config(config);
+    let attacks = &applicable_attacks(params, ctx.precomputed(), attacks);
+    if attacks.is_empty() {
         return Err(None);
     }
 
@@ -152,27 +822,57 @@ pub fn run_specific_attacks_with_threads(
     let (sender, receiver) = mpsc::channel();
 
     // Create progress bar
-    let (mp, pb_main) = create_multi_progress(attacks.len());
+    let (mp, pb_main) = create_multi_progress(attacks.len(), ctx.config().progress_refresh_rate);
 
     // Create runtime
-    let params = Arc::new(params.clone());
+    let shared_params = Arc::new(params.clone());
     let r = tokio::runtime::Builder::new_multi_thread()
         .worker_threads(threads)
         .enable_all()
         .build()
         .unwrap();
 
+    // Split the time budget between the attacks, from their previous runs
+    let allotments = ctx.config().time_budget.map(|budget| {
+        let cache = ctx.config().cache.as_ref();
+        let n_bits = ctx.precomputed().n_bits().unwrap_or_default();
+        allocate_time_budget(attacks, budget, |attack| cache?.get_timing(attack, n_bits))
+    });
+
     // Spawn attacks in background
-    let attacks = attacks.to_vec();
-    r.spawn(async move { _run_attacks(params, &attacks, sender, mp).await });
+    let discoveries = Discoveries::new(params, &pb_main);
+    let attacks_to_run = attacks.to_vec();
+    let attacks_ctx = ctx.clone();
+    r.spawn(async move {
+        _run_attacks(
+            shared_params,
+            &attacks_to_run,
+            allotments,
+            attacks_ctx,
+            collect_all,
+            sender,
+            mp,
+            stats,
+            discoveries,
+        )
+        .await
+    });
 
-    // Retrieve result
+    // Retrieve results
+    let mut solutions = vec![];
     let mut partial_factors: Option<Factors> = None;
-    let solution = loop {
+    let mut partial_factors_solved = false;
+    loop {
         // Receive solution or error for each attack
-        match receiver.recv() {
-            Ok(Ok(solution)) => break Some(solution),
-            Ok(Err(Error::PartialFactorization(factor))) => {
+        let result = match deadline {
+            Some(deadline) => receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .map_err(|_| ()),
+            None => receiver.recv().map_err(|_| ()),
+        };
+        match result {
+            Ok(Ok(solution)) => solutions.push(solution),
+            Ok(Err(Error::PartialFactorization(factor))) if !partial_factors_solved => {
                 if let Some(partial_factors) = &mut partial_factors {
                     partial_factors.merge(&factor);
                 } else {
@@ -180,10 +880,24 @@ pub fn run_specific_attacks_with_threads(
                 }
 
                 // Try to create a private key from the partial factors
-                if let Ok(private_key) =
-                    PrivateKey::from_factors(partial_factors.as_ref().unwrap().clone(), &param_e)
+                match PrivateKey::from_factors(partial_factors.as_ref().unwrap().clone(), &param_e)
                 {
-                    break Some(Solution::new_pk("Partial factors", private_key));
+                    Ok(private_key) => {
+                        solutions.push(Solution::new_pk("Partial factors", private_key));
+                        partial_factors_solved = true;
+                    }
+                    Err(KeyError::ExponentNotInvertible(factors)) => {
+                        if let Some(solution) = roots_solution(
+                            "Partial factors",
+                            params,
+                            &factors,
+                            &ctx.config().plaintext,
+                        ) {
+                            solutions.push(solution);
+                            partial_factors_solved = true;
+                        }
+                    }
+                    Err(_) => {}
                 }
                 pb_main.set_message(format!(
                     "({} factors found) ",
@@ -192,31 +906,54 @@ pub fn run_specific_attacks_with_threads(
             }
             Ok(_) => {}
             Err(_) => {
-                // Channel closed, no more results available
-                break None;
+                // Channel closed or time budget exhausted, no more results available
+                break;
             }
         }
+        if !solutions.is_empty() && !collect_all {
+            break;
+        }
 
         // Update progress bar
         pb_main.inc(1);
-    };
+    }
 
-    // Shut down runtime
+    // Stop remaining attacks and shut down runtime
+    ctx.stop();
     r.shutdown_background();
     eprintln!("Elapsed time: {:?}", pb_main.elapsed());
 
-    if let Some(solution) = solution {
-        Ok(solution)
-    } else {
-        Err(partial_factors)
+    // Factor the remaining composite factors
+    if let Some(partial_factors) = partial_factors.as_mut() {
+        let wanted = solutions.is_empty() || collect_all;
+        if wanted && !partial_factors_solved && depth < MAX_COFACTOR_DEPTH {
+            solutions.extend(factor_cofactors(
+                &param_e,
+                partial_factors,
+                attacks,
+                &base_config,
+                deadline,
+                depth,
+            ));
+        }
+    }
+
+    if solutions.is_empty() {
+        return Err(partial_factors);
+    }
+    for solution in solutions.iter_mut() {
+        if !solution.verified {
+            solution.verify(params);
+        }
     }
+    Ok(solutions)
 }
 
 #[cfg(test)]
 mod tests {
     use std::{collections::BTreeMap, str::FromStr};
 
-    use rug::ops::Pow;
+    use rug::{ops::Pow, Integer};
 
     use super::*;
 
@@ -240,6 +977,43 @@ mod tests {
         assert!(run_attacks(&params).is_err());
     }
 
+    #[test]
+    fn prime_n_with_cipher() {
+        let n = Integer::from_str("220375572875274133043506876099").unwrap();
+        let m = Integer::from(1337);
+        let params = Parameters {
+            c: Some(m.clone().pow_mod(&65537.into(), &n).unwrap()),
+            n: Some(n),
+            ..Default::default()
+        };
+
+        let solution = run_attacks(&params).unwrap();
+        assert_eq!(solution.m, Some(m));
+        assert!(solution.vulnerability.is_some());
+    }
+
+    #[test]
+    fn even_exponent() {
+        let p = Integer::from(1779681653u64);
+        let q = Integer::from(1903643191u64);
+        let n = p.clone() * &q;
+        let m = Integer::from(0xdeadbeefu64);
+
+        for e in [2, 4] {
+            let params = Parameters {
+                e: e.into(),
+                c: Some(m.clone().pow_mod(&e.into(), &n).unwrap()),
+                n: Some(n.clone()),
+                p: Some(p.clone()),
+                ..Default::default()
+            };
+
+            let solution = run_attacks(&params).unwrap();
+            assert!(solution.ms.contains(&m), "e = {e}");
+            assert!(solution.verified);
+        }
+    }
+
     #[test]
     fn partial_factors() {
         // n == 2 ^ 63 * 690712633549859897233 ^ 6
@@ -256,4 +1030,349 @@ mod tests {
             BTreeMap::from([(Integer::from(2), 63), (p.pow(5), 1),])
         );
     }
+
+    #[test]
+    fn run_one() {
+        let p = Integer::from(1779681653u64);
+        let q = Integer::from(1903643191u64);
+        let n = p.clone() * &q;
+        let m = Integer::from(1337);
+        let params = Parameters {
+            c: Some(m.clone().pow_mod(&65537.into(), &n).unwrap()),
+            n: Some(n),
+            ..Default::default()
+        };
+        let config = AttackConfig::default();
+
+        let (sender, events) = mpsc::channel();
+        let solution =
+            super::run_one("pollard_rho", &params, &config, None, Some(&sender)).unwrap();
+        assert_eq!(solution.attack, "pollard_rho");
+        assert_eq!(solution.m, Some(m));
+        assert!(solution.verified);
+        drop(sender);
+        let events = events.into_iter().collect::<Vec<_>>();
+        assert_eq!(
+            events.first(),
+            Some(&AttackEvent::Started {
+                attack: "pollard_rho"
+            })
+        );
+        assert!(matches!(
+            events.last(),
+            Some(AttackEvent::Finished {
+                attack: "pollard_rho",
+                outcome: AttackOutcome::Solved,
+                ..
+            })
+        ));
+        assert_eq!(
+            super::run_one("nope", &params, &config, None, None).unwrap_err(),
+            Error::UnknownAttack("nope".to_string())
+        );
+
+        // Stopped from another thread
+        let ctx = AttackContext::new(&params);
+        ctx.clone().stop();
+        assert_eq!(
+            run_one_with_context("fermat", &params, &ctx, None, None).unwrap_err(),
+            Error::Stopped
+        );
+    }
+
+    #[test]
+    fn time_budget() {
+        // Product of two 256-bit primes
+        let p = Integer::from_str(
+            "90511711160491104683260185958356471267265849470934538618804038653604797333997",
+        )
+        .unwrap();
+        let q = Integer::from_str(
+            "114596133725443196642584039352225739386449324911628952764126006973006794144319",
+        )
+        .unwrap();
+        let params = Parameters {
+            n: Some(p * q),
+            ..Default::default()
+        };
+        let config = AttackConfig {
+            time_budget: Some(std::time::Duration::from_secs(1)),
+            ..Default::default()
+        };
+
+        let start = Instant::now();
+        let res = run_specific_attacks_with_config(&params, &[Arc::new(PollardPM1Attack)], config);
+        assert!(res.is_err());
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    /// Slow attack which succeeds at the end of a time slice with at least twice the default effort
+    #[derive(Debug)]
+    struct EscalatingAttack;
+
+    impl Attack for EscalatingAttack {
+        fn name(&self) -> &'static str {
+            "escalating"
+        }
+
+        fn speed(&self) -> AttackSpeed {
+            AttackSpeed::Slow
+        }
+
+        fn run(&self, _params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+            Err(Error::NotFound)
+        }
+
+        fn run_with_context(
+            &self,
+            params: &Parameters,
+            ctx: &AttackContext,
+            _pb: Option<&ProgressBar>,
+        ) -> Result<Solution, Error> {
+            while !ctx.is_stopped() {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            if ctx.config().effort < 200 {
+                return Err(Error::Stopped);
+            }
+
+            let (p, q) = (params.p.clone().unwrap(), params.q.clone().unwrap());
+            Ok(Solution::new_pk(
+                self.name(),
+                PrivateKey::from_p_q(p, q, &params.e)?,
+            ))
+        }
+    }
+
+    #[test]
+    fn time_slices() {
+        let p = Integer::from_str(
+            "90511711160491104683260185958356471267265849470934538618804038653604797333997",
+        )
+        .unwrap();
+        let q = Integer::from_str(
+            "114596133725443196642584039352225739386449324911628952764126006973006794144319",
+        )
+        .unwrap();
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            p: Some(p),
+            q: Some(q),
+            ..Default::default()
+        };
+        let config = AttackConfig {
+            time_slice: std::time::Duration::from_millis(100),
+            ..Default::default()
+        };
+
+        let start = Instant::now();
+        let solution =
+            run_specific_attacks_with_config(&params, &[Arc::new(EscalatingAttack)], config)
+                .unwrap();
+        assert_eq!(solution.attack, "escalating");
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn aggregate_progress() {
+        let p = Integer::from(54269);
+        let q = Integer::from(93089);
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            ..Default::default()
+        };
+        let config = AttackConfig {
+            progress_refresh_rate: 1,
+            aggregate_progress: true,
+            ..Default::default()
+        };
+
+        let solution =
+            run_specific_attacks_with_config(&params, &[Arc::new(SmallPrimeAttack)], config)
+                .unwrap();
+        let pk = solution.pk.unwrap();
+        assert_eq!(pk.p(), Some(p));
+        assert_eq!(pk.q(), Some(q));
+    }
+
+    #[test]
+    fn on_candidate() {
+        let p = Integer::from(54269);
+        let q = Integer::from(93089);
+        let n = p.clone() * &q;
+        let params = Parameters {
+            c: Some(Integer::from(42).pow_mod(&65537.into(), &n).unwrap()),
+            n: Some(n),
+            ..Default::default()
+        };
+        let run = |check: CandidateCheck| {
+            let config = AttackConfig {
+                on_candidate: Some(check),
+                ..Default::default()
+            };
+            run_specific_attacks_with_config(&params, &[Arc::new(SmallPrimeAttack)], config)
+        };
+
+        assert_eq!(
+            run(CandidateCheck::new(|m| *m == 42)).unwrap().m.unwrap(),
+            42
+        );
+        assert!(run(CandidateCheck::new(|_| false)).is_err());
+    }
+
+    #[test]
+    fn plaintext_constraint() {
+        let p = Integer::from(54269);
+        let q = Integer::from(93089);
+        let n = p.clone() * &q;
+        let m = bytes_to_integer(b"ab}");
+        let params = Parameters {
+            e: 2.into(),
+            c: Some(m.clone().pow_mod(&2.into(), &n).unwrap()),
+            n: Some(n),
+            ..Default::default()
+        };
+        let run = |plaintext: PlaintextConstraint| {
+            let config = AttackConfig {
+                plaintext,
+                ..Default::default()
+            };
+            run_specific_attacks_with_config(&params, &[Arc::new(SmallPrimeAttack)], config)
+        };
+
+        // The four square roots of c, without constraint
+        assert_eq!(run(PlaintextConstraint::default()).unwrap().ms.len(), 4);
+        let constraint = PlaintextConstraint {
+            prefix: b"a".to_vec(),
+            suffix: b"}".to_vec(),
+            length: Some(3),
+        };
+        assert_eq!(run(constraint).unwrap().ms, [m]);
+        let constraint = PlaintextConstraint {
+            prefix: b"flag{".to_vec(),
+            ..Default::default()
+        };
+        assert!(run(constraint).is_err());
+    }
+
+    /// Hart attack restricted to moduli of at most 64 bits
+    #[derive(Debug)]
+    struct SmallModulusAttack;
+
+    impl Attack for SmallModulusAttack {
+        fn name(&self) -> &'static str {
+            "small_modulus"
+        }
+
+        fn run(&self, params: &Parameters, pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+            match &params.n {
+                Some(n) if n.significant_bits() <= 64 => HartAttack.run(params, pb),
+                _ => Err(Error::NotFound),
+            }
+        }
+    }
+
+    #[test]
+    fn cofactors() {
+        let r = Integer::from(2071723);
+        let p = Integer::from(1779681653);
+        let q = Integer::from(1903643191);
+        let params = Parameters {
+            n: Some(r.clone() * &p * &q),
+            ..Default::default()
+        };
+
+        // The small prime attack leaves p * q, which is then factored on its own
+        let solution = run_specific_attacks(
+            &params,
+            &[Arc::new(SmallPrimeAttack), Arc::new(SmallModulusAttack)],
+        )
+        .unwrap();
+        assert_eq!(solution.pk.unwrap().factors, Factors::from([r, p, q]));
+    }
+
+    #[test]
+    fn collect_all() {
+        let params = Parameters {
+            n: Some(Integer::from(1000003) * 1000033),
+            ..Default::default()
+        };
+        let attacks: [Arc<dyn Attack + Sync + Send>; 2] =
+            [Arc::new(SmallPrimeAttack), Arc::new(FermatAttack)];
+
+        // Both attacks factor n
+        let solutions =
+            run_all_specific_attacks_with_config(&params, &attacks, AttackConfig::default())
+                .unwrap();
+        let mut names = solutions.iter().map(|s| s.attack).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["fermat", "small_prime"]);
+    }
+
+    #[test]
+    fn cofactors_refinement() {
+        let r = Integer::from(2071723);
+        let p = Integer::from(1779681653);
+        let q = Integer::from(1903643191);
+        let params = Parameters {
+            n: Some(r.clone() * &p * &q),
+            ..Default::default()
+        };
+
+        // The small prime attack leaves p * q, split by Pollard rho
+        let solution = run_specific_attacks(
+            &params,
+            &[Arc::new(SmallPrimeAttack), Arc::new(PollardRhoAttack)],
+        )
+        .unwrap();
+        assert_eq!(
+            solution.pk.unwrap().factors,
+            Factors::from([r.clone(), p.clone(), q.clone()])
+        );
+
+        // The cheap pass is not run when its attacks are not selected
+        let partial_factors = run_specific_attacks(&params, &[Arc::new(SmallPrimeAttack)])
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(partial_factors, Factors::from([r, p * q]));
+    }
+
+    /// Attack which only discovers the whole of d, knowing p and q
+    #[derive(Debug)]
+    struct DiscoveringAttack;
+
+    impl Attack for DiscoveringAttack {
+        fn name(&self) -> &'static str {
+            "discovering"
+        }
+
+        fn run(&self, params: &Parameters, _pb: Option<&ProgressBar>) -> Result<Solution, Error> {
+            let factors = Factors::from([params.p.clone().unwrap(), params.q.clone().unwrap()]);
+            Err(Error::Discovered(Box::new(Parameters {
+                d: params.e.clone().invert(&factors.phi()).ok(),
+                ..Default::default()
+            })))
+        }
+    }
+
+    #[test]
+    fn discovered_parameters() {
+        let p = Integer::from(1779681653);
+        let q = Integer::from(1903643191);
+        let params = Parameters {
+            n: Some(p.clone() * &q),
+            p: Some(p.clone()),
+            q: Some(q.clone()),
+            ..Default::default()
+        };
+
+        // The attacks using d run alongside the slow attack, which they stop
+        let solution = run_specific_attacks(
+            &params,
+            &[Arc::new(DiscoveringAttack), Arc::new(EscalatingAttack)],
+        )
+        .unwrap();
+        assert!(["known_d", "partial_d", "leaked_crt_coefficient"].contains(&solution.attack));
+        assert_eq!(solution.pk.unwrap().factors, Factors::from([p, q]));
+    }
 }
@@ -1,6 +1,7 @@
-use clap::{command, Parser};
+use clap::{Parser, Subcommand};
 use discrete_logarithm::discrete_log_with_factors;
 use display_bytes::display_bytes;
+use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
 use main_error::MainError;
 use rug::{
@@ -8,14 +9,30 @@ use rug::{
     Integer,
 };
 use std::{
-    io::{self, IsTerminal, Read},
+    io::{self, IsTerminal, Read, Write},
+    net::TcpListener,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use rsacracker::{integer_to_bytes, integer_to_string, Attack, IntegerArg, Parameters, ATTACKS};
+use rsacracker::{
+    audit_keys, audit_moduli, check_key, cipher_file_to_integer, export_bundle, export_private_key,
+    export_public_key, fetch_ct_certificates, fetch_dnskeys, forge_certificate, forge_csr,
+    generate_key, integer_to_bytes, integer_to_bytes_padded, integer_to_string,
+    integer_to_string_with,
+    ntheory::{is_prime, set_primality_policy, PrimalityPolicy},
+    parse_moduli, rank_plaintexts, recover_keys, redact_args, render_report, resign_certificate,
+    reverse_bytes, serve_oracle, Attack, AttackConfig, AttackProfile, AuditReport, Cache,
+    CandidateCheck, ForgeOptions, IntegerArg, KeyEntry, KeyError, KeyFormat, MergedParameters,
+    MessageGuesses, OracleKind, Parameters, PartialInteger, PlaintextConstraint, PrivateKey,
+    RecoveredKey, ReportFormat, ResignOptions, RunStats, SessionEntry, SessionLog, Solution,
+    TextEncoding, ATTACKS,
+};
 use update_informer::{registry, Check};
 
+/// Number of passphrase prompts before giving up
+const PASSPHRASE_ATTEMPTS: usize = 3;
+
 #[derive(Debug, Clone)]
 struct AttackArg(Arc<dyn Attack + Sync + Send>);
 
@@ -31,63 +48,199 @@ impl std::str::FromStr for AttackArg {
     }
 }
 
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    /// Check public keys for weaknesses, without running the attacks, and print a vulnerability report. The keys factored by the checks are rebuilt to decrypt their ciphers. Stops the per key checks when the time budget is exhausted.
+    Audit {
+        /// Public or private key files, or directories of key files.
+        #[clap(required = true)]
+        files: Vec<std::path::PathBuf>,
+        /// Cipher files, or directories of cipher files, decrypted when the key they are paired with is factored. A cipher is paired with the key file of the same name without extension (e.g. alice.pem and alice.enc), or else with the key file at the same position.
+        #[clap(long, num_args = 1..)]
+        ciphers: Vec<std::path::PathBuf>,
+    },
+    /// Convert a public or private key to another format, without running any attack.
+    Convert {
+        /// Public or private key file.
+        file: std::path::PathBuf,
+        /// Output format: pkcs8 (alias pem), pkcs8-der (alias der), pkcs1, pkcs1-der, openssh, jwk or xml.
+        #[clap(short, long, default_value_t = KeyFormat::default())]
+        format: KeyFormat,
+        /// Write the converted key to a file instead of stdout.
+        #[clap(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Serve a deliberately vulnerable decryption oracle over TCP, to test the oracle attacks locally. Each line received is a cipher, in decimal or 0x prefixed hexadecimal, answered by 1 or 0.
+    OracleSim {
+        /// Private key file. Default: a generated key.
+        #[clap(long)]
+        key: Option<std::path::PathBuf>,
+        /// Size of the generated key modulus, in bits.
+        #[clap(long, default_value_t = 1024)]
+        bits: u32,
+        /// Leak of the oracle: parity (least significant bit of the plaintext) or pkcs1 (PKCS#1 v1.5 conformant plaintext).
+        #[clap(long, default_value_t = OracleKind::default())]
+        oracle: OracleKind,
+        /// Address to listen on.
+        #[clap(long, default_value = "127.0.0.1:1337")]
+        listen: String,
+        /// Message encrypted with the key and printed, to be recovered through the oracle.
+        #[clap(short, long)]
+        message: Option<IntegerArg>,
+    },
+    /// Replay a run of the session log, with its parameters and attacks, or list the logged runs without an entry. Runs are logged with --session-log to ~/.local/state/rsacracker/sessions.jsonl, or to RSACRACKER_SESSION_LOG.
+    Replay {
+        /// Number of the entry to replay, or "last".
+        entry: Option<String>,
+        /// Only run the attacks of the entry which did not run to completion: not run, timed out or stopped.
+        #[clap(long = "continue")]
+        continue_run: bool,
+    },
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about)]
 struct Args {
-    /// Retrieve values from raw file
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Retrieve values from raw file, e.g. "n = 0x..." lines or the output of openssl rsa -text
     #[clap(short, long)]
     raw: Option<String>,
-    /// Cipher: the message to uncipher.
+    /// Cipher: the message to uncipher. Can be repeated, one per key.
     #[clap(short, long)]
-    cipher: Option<IntegerArg>,
-    /// Cipher file: the file to uncipher.
+    cipher: Vec<IntegerArg>,
+    /// Cipher file: the file to uncipher, binary (e.g. openssl rsautl output) or PEM encoded (e.g. -----BEGIN MESSAGE-----). Can be repeated, one per key.
     #[clap(short = 'f', long)]
-    cipherfile: Option<std::path::PathBuf>,
-    /// Write unciphered data to a file. If many unciphered data are found, they will be written to files suffixed with _1, _2, ...
+    cipherfile: Vec<std::path::PathBuf>,
+    /// Write unciphered data to a file, zero-padded to the length of n, or the forged and re-signed certificates. If many unciphered data are found, they will be written to files suffixed with _1, _2, ...
     #[clap(short = 'o', long)]
     outfile: Option<std::path::PathBuf>,
+    /// Display unciphered data as a hexdump, zero-padded to the length of n.
+    #[clap(long, conflicts_with = "outfile")]
+    hexdump: bool,
+    /// Text encoding of the unciphered data: utf8, utf8-lossy, latin1, utf16le or utf16be.
+    #[clap(long, default_value = "utf8")]
+    encoding: TextEncoding,
+    /// Flag pattern, used to rank many unciphered data from the most to the least likely.
+    #[clap(long, default_value = rsacracker::DEFAULT_FLAG_PATTERN)]
+    flag_format: regex::Regex,
+    /// Shell command run on each plaintext found, given as bytes on its standard input and in decimal in $RSACRACKER_PLAINTEXT. A zero exit status accepts the plaintext, any other keeps searching.
+    #[clap(long)]
+    on_candidate: Option<String>,
+    /// Wordlist of candidate messages, one per line, encrypted and compared to the cipher when the message is short and low entropy.
+    #[clap(long, value_name = "FILE")]
+    message_wordlist: Option<std::path::PathBuf>,
+    /// Characters of the candidate messages brute forced after the wordlist, e.g. 0123456789 for a PIN.
+    #[clap(long)]
+    message_charset: Option<String>,
+    /// Largest length of the messages brute forced with --message-charset.
+    #[clap(long, default_value_t = 6, requires("message_charset"))]
+    message_max_length: u32,
+    /// Known start of the plaintext, e.g. flag{. The candidate plaintexts of the attacks finding several ones are filtered, and the brute forces of the plaintext skip the others.
+    #[clap(long, value_name = "TEXT")]
+    plaintext_prefix: Option<String>,
+    /// Known end of the plaintext, e.g. }. See --plaintext-prefix.
+    #[clap(long, value_name = "TEXT")]
+    plaintext_suffix: Option<String>,
+    /// Known length of the plaintext, in bytes. See --plaintext-prefix.
+    #[clap(long, value_name = "BYTES")]
+    plaintext_length: Option<usize>,
     /// Modulus.
     #[clap(short)]
     n: Option<IntegerArg>,
     /// Public exponent. Default: 65537
     #[clap(short, default_value = "65537")]
     e: IntegerArg,
-    /// Prime number p.
+    /// Prime number p. Unknown hex or binary digits can be given as ?, e.g. 0x1?3?
     #[clap(short)]
-    p: Option<IntegerArg>,
-    /// Prime number q.
+    p: Option<PartialInteger>,
+    /// Prime number q. Unknown hex or binary digits can be given as ?, e.g. 0x1?3?
     #[clap(short)]
-    q: Option<IntegerArg>,
+    q: Option<PartialInteger>,
     /// Private exponent.
     #[clap(short)]
     d: Option<IntegerArg>,
     /// Phi or Euler's totient function of n. (p-1)(q-1)
     #[clap(long)]
     phi: Option<IntegerArg>,
-    /// dP or dmp1 CRT exponent. (d mod p-1)
+    /// dP or dmp1 CRT exponent. (d mod p-1) Unknown hex or binary digits can be given as ?, e.g. 0x1234????
     #[clap(long, alias = "dmp1")]
-    dp: Option<IntegerArg>,
-    /// dQ or dmq1 CRT exponent. (d mod q-1)
+    dp: Option<PartialInteger>,
+    /// dQ or dmq1 CRT exponent. (d mod q-1) Unknown hex or binary digits can be given as ?, e.g. 0x1234????
     #[clap(long, alias = "dmq1")]
-    dq: Option<IntegerArg>,
+    dq: Option<PartialInteger>,
     /// qInv or iqmp CRT coefficient. (q^-1 mod p)
     #[clap(long, alias = "iqmp")]
     qinv: Option<IntegerArg>,
     /// pInv or ipmq CRT coefficient. (p^-1 mod q)
     #[clap(long, alias = "ipmq")]
     pinv: Option<IntegerArg>,
-    /// The sum of the two primes p and q.
+    /// The sum of the two primes p and q. Used by sum_pq and symmetric_pq.
     #[clap(long)]
     sum_pq: Option<IntegerArg>,
-    /// Discrete logarithm attack. When c and e are swapped in the RSA encryption formula. (e^c mod n)
+    /// The sum of the squares of p and q. (p^2 + q^2) Used by symmetric_pq.
+    #[clap(long)]
+    sum_squares_pq: Option<IntegerArg>,
+    /// The difference of the two primes p and q. (|p - q|) Used by symmetric_pq.
+    #[clap(long)]
+    diff_pq: Option<IntegerArg>,
+    /// The xor of the two primes p and q. (p ^ q) Used by xor_pq.
+    #[clap(long)]
+    xor_pq: Option<IntegerArg>,
+    /// The sum of p and q modulo a modulus. (p + q mod m) Used by symmetric_pq.
+    #[clap(long, num_args = 2, value_names = ["VALUE", "MODULUS"])]
+    sum_pq_mod: Option<Vec<IntegerArg>>,
+    /// The product of p and q modulo a modulus, when n is unknown. (p * q mod m) Used by symmetric_pq.
+    #[clap(long, num_args = 2, value_names = ["VALUE", "MODULUS"])]
+    pq_mod: Option<Vec<IntegerArg>>,
+    /// One of the primes modulo a modulus, e.g. 10^10 for its last 10 decimal digits. (p mod m) Used by p_mod.
+    #[clap(long, num_args = 2, value_names = ["VALUE", "MODULUS"])]
+    p_mod: Option<Vec<IntegerArg>>,
+    /// Plaintext of the cipher with unknown hex or binary digits given as ?, e.g. 0x7b22746f6b656e223a22????????????????227d. A small window of unknown digits is recovered when e is small.
+    #[clap(long)]
+    partial_m: Option<PartialInteger>,
+    /// Known plaintext, whose cipher is given with --known-c. Used to check the recovered keys, and to find e if the given one is wrong.
+    #[clap(long, requires = "known_c")]
+    known_m: Option<IntegerArg>,
+    /// Cipher of the known plaintext given with --known-m.
+    #[clap(long, requires = "known_m")]
+    known_c: Option<IntegerArg>,
+    /// Known message, whose textbook signature is given with --signature. Used to check the recovered keys, and to find d from p and q if the order of the message is smooth.
+    #[clap(long, requires = "signature")]
+    signed_m: Option<IntegerArg>,
+    /// Textbook signature of the message given with --signed-m. (m^d mod n)
+    #[clap(long, requires = "signed_m")]
+    signature: Option<IntegerArg>,
+    /// Discrete logarithm attack. When c and e are swapped in the RSA encryption formula. (e^c mod n) Enabled automatically when e looks like a cipher and c is small.
     #[clap(long, alias = "dislog")]
     dlog: bool,
-    /// Public or private key file. (RSA, X509, OPENSSH in PEM and DER formats.)
+    /// Also try the byte-reversed c and n (not the n of a key file) if no attack succeeds, for dumps storing them little-endian. Enabled automatically when n is even while its reversal is odd, or when the known plaintext only encrypts to the byte-reversed known cipher.
+    #[clap(long)]
+    byte_swapped: bool,
+    /// Public or private key file. (RSA, X509, OPENSSH in PEM and DER formats.) Can be repeated, the ciphers are paired with the keys by index. The keys of a file of several PEM blocks, e.g. a certificate chain and its private key, are merged.
     #[clap(short, long)]
-    key: Option<String>,
-    /// Private key password/passphrase if encrypted.
+    key: Vec<String>,
+    /// Fetch the RSA DNSSEC keys (DNSKEY records) of a domain and their signatures (RRSIG records), over DNS over HTTPS.
+    #[clap(long, value_name = "DOMAIN")]
+    dnssec: Option<String>,
+    /// Audit the RSA keys of the certificates of a Certificate Transparency search on crt.sh (e.g. %.example.com) for shared factors, close primes and ROCA fingerprints.
+    #[clap(long, value_name = "QUERY")]
+    ct: Option<String>,
+    /// Maximum number of certificates downloaded by --ct.
+    #[clap(long, default_value_t = 100, requires("ct"))]
+    ct_limit: usize,
+    /// Audit the moduli of a text file, one per line in decimal or hexadecimal, for shared factors, close primes and ROCA fingerprints. Stops the per modulus checks when the time budget is exhausted.
+    #[clap(long, value_name = "FILE")]
+    moduli_file: Option<std::path::PathBuf>,
+    /// Private key password/passphrase if encrypted, prompted for if missing.
     #[clap(long)]
     password: Option<String>,
+    /// Wordlist of candidate passphrases of the encrypted private key, one per line.
+    #[clap(long, conflicts_with("password"))]
+    password_wordlist: Option<std::path::PathBuf>,
+    /// Check that the private key given with --key matches this public key or certificate, and that its n, e, d and CRT values are consistent.
+    #[clap(long, value_name = "PUBLIC_KEY", requires("key"))]
+    check: Option<std::path::PathBuf>,
     /// Print the public key in PEM format.
     #[clap(long)]
     public: bool,
@@ -97,13 +250,43 @@ struct Args {
     /// Add a password/passphrase to the private key.
     #[clap(long, requires("private"))]
     addpassword: Option<String>,
+    /// Print a self-signed X.509 certificate of the private key, in PEM format.
+    #[clap(long)]
+    forge_cert: bool,
+    /// Print a certificate signing request signed by the private key, in PEM format.
+    #[clap(long, conflicts_with("forge_cert"))]
+    forge_csr: bool,
+    /// Subject of the forged certificate or CSR. Default: the template's, or CN=rsacracker (e.g. CN=example.com,O=Example)
+    #[clap(long, value_name = "SUBJECT")]
+    forge_subject: Option<String>,
+    /// Validity of the forged or re-signed certificate, in days from now. Default: the template's, or 365
+    #[clap(long, value_name = "DAYS")]
+    forge_days: Option<u32>,
+    /// Certificate whose subject, serial number, validity and extensions are cloned by the forged certificate or CSR.
+    #[clap(long, value_name = "CERT")]
+    forge_template: Option<std::path::PathBuf>,
+    /// Re-sign this certificate with the private key of its issuer, after applying the --resign-cn, --resign-san and --forge-days modifications.
+    #[clap(long, value_name = "CERT", conflicts_with_all(["forge_cert", "forge_csr"]))]
+    resign: Option<std::path::PathBuf>,
+    /// New common name of the re-signed certificate.
+    #[clap(long, value_name = "CN", requires("resign"))]
+    resign_cn: Option<String>,
+    /// New subject alternative names of the re-signed certificate, DNS names or IP addresses. (e.g. --resign-san example.com,10.0.0.1)
+    #[clap(long, value_name = "NAMES", value_delimiter = ',', requires("resign"))]
+    resign_san: Vec<String>,
+    /// Print or write the forged and re-signed certificates in DER format instead of PEM.
+    #[clap(long)]
+    der: bool,
     /// Print all the input parameters.
     #[clap(long)]
     showinputs: bool,
+    /// Print the input parameters as JSON, with the derived values and the source of each value.
+    #[clap(long, requires("showinputs"))]
+    json: bool,
     /// Print the private RSA key variables n, e, p, q and d.
     #[clap(long)]
     dump: bool,
-    /// Print the extended RSA key variables n, e, p, q, d, dP, dQ, pInv and qInv.
+    /// Print the extended RSA key variables n, e, p, q, d, dP, dQ, pInv and qInv. With more than two factors, the CRT exponent di of each distinct factor and the coefficients t2, t3, ... replace dP, dQ, pInv and qInv.
     #[clap(long)]
     dumpext: bool,
     /// Print all factors of n.
@@ -112,6 +295,73 @@ struct Args {
     /// Number of threads to use. Default: number of CPUs
     #[clap(short, long, default_value_t = num_cpus::get())]
     threads: usize,
+    /// Upper bound of the primes searched by the small prime attack.
+    #[clap(long, default_value_t = AttackConfig::default().small_prime_bound)]
+    small_prime_bound: u64,
+    /// Londahl attack bound B. Phi is searched within B^2 of its approximation.
+    #[clap(long, default_value_t = AttackConfig::default().londahl_b)]
+    londahl_b: u64,
+    /// Size in bits of the factors of the message searched by the meet-in-the-middle attack, for a message m = m1 * m2 with m1, m2 < 2^k.
+    #[clap(long, default_value_t = AttackConfig::default().mitm_bits)]
+    mitm_bits: u32,
+    /// Memory budget shared by the attacks, in MiB. Attacks building tables (Londahl, meet-in-the-middle, batch GCD of audits) use smaller ones to stay within it.
+    #[clap(long, default_value_t = AttackConfig::default().max_memory >> 20)]
+    max_memory: u64,
+    /// Multiplier of the attacks iteration limits and search bounds, in percent. Adapted to the size of n.
+    #[clap(long, default_value_t = AttackConfig::default().effort)]
+    effort: u64,
+    /// Time budget in seconds, split between the attacks from their previous durations. Attacks still running at the end of their share are stopped. Default: no limit
+    #[clap(long, alias = "max-time")]
+    time_budget: Option<u64>,
+    /// Duration in seconds of the first time slice of each slow attack. Slow attacks start once the fast and medium ones completed, then run one after the other, with twice the time and effort each round, the last round running them to completion.
+    #[clap(long, default_value_t = AttackConfig::default().time_slice.as_secs())]
+    time_slice: u64,
+    /// Maximum number of progress bars redraws per second.
+    #[clap(
+        long,
+        default_value_t = AttackConfig::default().progress_refresh_rate,
+        value_parser = clap::value_parser!(u8).range(1..)
+    )]
+    progress_refresh_rate: u8,
+    /// Only show the overall progress bar, not one bar per attack.
+    #[clap(long)]
+    aggregate_progress: bool,
+    /// Repetitions of the probabilistic primality test of the factors and moduli.
+    #[clap(long, default_value_t = PrimalityPolicy::DEFAULT.rounds)]
+    primality_rounds: u32,
+    /// Also run a Baillie-PSW test on the factors and moduli, which has no known counterexample.
+    #[clap(long)]
+    bpsw: bool,
+    /// Size in bits up to which factors and moduli are proven prime or composite (at most 81).
+    #[clap(long, default_value_t = PrimalityPolicy::DEFAULT.prove_bits)]
+    prove_bits: u32,
+    /// Let every attack finish, within the time budget, and print all the solutions found instead of the first one.
+    #[clap(long)]
+    all: bool,
+    /// Print the outcome, time and iterations of every attack run, after the run.
+    #[clap(long)]
+    stats: bool,
+    /// Append the run to the session log, to replay it later (see replay). Values revealing the private key and passphrases are not logged.
+    #[clap(long)]
+    session_log: bool,
+    /// Write a report of the run (parameters, solution, key material, plaintexts and attacks statistics) to a Markdown file, or an HTML one if its extension is .html.
+    #[clap(long, value_name = "FILE")]
+    report: Option<std::path::PathBuf>,
+    /// Write everything recovered to a directory: the private and public keys in all formats, the factors, the plaintexts and a JSON manifest. If many solutions are found, they will be written to directories suffixed with _solution1, _solution2, ...
+    #[clap(long, value_name = "DIR")]
+    export_bundle: Option<std::path::PathBuf>,
+    /// Coordinate a distributed run: listen on this address for workers (see --worker) and deal them shards of the brute force searches, until one of them finds a solution.
+    #[clap(long, value_name = "ADDR", conflicts_with = "worker")]
+    coordinate: Option<String>,
+    /// Number of shards dealt by the coordinator.
+    #[clap(long, default_value_t = 16, requires("coordinate"))]
+    shards: u64,
+    /// Work for the coordinator at this address: claim shards of its parameters, run the attacks on them and report the results.
+    #[clap(long, value_name = "ADDR")]
+    worker: Option<String>,
+    /// Shared secret of a distributed run: the coordinator only deals shards to the workers sending it. The connections are not encrypted.
+    #[clap(long)]
+    token: Option<String>,
     /// Specify attacks to run. Default: all. (e.g. --attacks ecm,wiener,sparse)
     #[clap(
         short,
@@ -121,18 +371,21 @@ struct Args {
         conflicts_with = "exclude"
     )]
     attack: Option<Vec<AttackArg>>,
+    /// Named set of attacks, with tuned bounds: quick, default, thorough, offline or multi-key.
+    #[clap(long, default_value = "default", conflicts_with = "attack")]
+    profile: AttackProfile,
     /// Specify attacks to exclude. Default: none. (e.g. --exclude ecm,wiener,sparse)
     #[clap(long, value_delimiter = ',', conflicts_with = "attack")]
     exclude: Option<Vec<AttackArg>>,
-    /// List all available attacks.
+    /// List all available attacks, with a summary of each one.
     #[clap(long)]
     list: bool,
 }
 
-fn display_unciphered_data(uncipher: &Integer) {
+fn display_unciphered_data(uncipher: &Integer, encoding: TextEncoding) {
     println!("Int = {uncipher}");
     println!("Hex = 0x{uncipher:02x}");
-    if let Some(str) = integer_to_string(uncipher) {
+    if let Some(str) = integer_to_string_with(uncipher, encoding) {
         println!("String = \"{str}\"");
     } else {
         println!(
@@ -142,24 +395,101 @@ fn display_unciphered_data(uncipher: &Integer) {
     }
 }
 
+/// Display bytes as offset, hex and ASCII columns, 16 bytes per line.
+fn display_hexdump(bytes: &[u8]) {
+    for (offset, line) in bytes.chunks(16).enumerate() {
+        let hex = line
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        println!("{:08x}  {hex:<47}  |{ascii}|", offset * 16);
+    }
+}
+
+/// Check the candidate plaintexts with a shell command, see `Args::on_candidate`
+fn candidate_command(command: String) -> CandidateCheck {
+    CandidateCheck::new(move |m| {
+        let child = std::process::Command::new("sh")
+            .args(["-c", &command])
+            .env("RSACRACKER_PLAINTEXT", m.to_string())
+            .stdin(std::process::Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                eprintln!("Warning: cannot run the candidate command: {err}");
+                return false;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            // The command may exit without reading its input
+            stdin.write_all(&integer_to_bytes(m)).ok();
+        }
+        child.wait().is_ok_and(|status| status.success())
+    })
+}
+
+/// Write, hexdump or display unciphered data. Bytes are zero-padded to `len`, the length of n.
 fn display_or_output(
     uncipher: &Integer,
     outfile: &Option<std::path::PathBuf>,
+    len: usize,
+    hexdump: bool,
+    encoding: TextEncoding,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if let Some(outfile) = outfile {
         println!(
             "Write unciphered data to file: {}",
             outfile.to_string_lossy()
         );
-        std::fs::write(outfile, integer_to_bytes(uncipher))?;
+        std::fs::write(outfile, integer_to_bytes_padded(uncipher, len))?;
+    } else if hexdump {
+        println!("Unciphered data:");
+        display_hexdump(&integer_to_bytes_padded(uncipher, len));
     } else {
         println!("Unciphered data:");
-        display_unciphered_data(uncipher);
+        display_unciphered_data(uncipher, encoding);
     }
 
     Ok(())
 }
 
+/// Read a certificate in PEM or DER format.
+fn read_certificate(path: &std::path::Path) -> Result<openssl::x509::X509, MainError> {
+    let bytes = std::fs::read(path)?;
+    openssl::x509::X509::from_pem(&bytes)
+        .or_else(|_| openssl::x509::X509::from_der(&bytes))
+        .map_err(|_| format!("{}: Invalid certificate", path.to_string_lossy()).into())
+}
+
+/// Write a certificate or CSR to a file, or print it, in PEM or DER format.
+fn output_certificate(
+    pem: &[u8],
+    der: &[u8],
+    use_der: bool,
+    outfile: &Option<std::path::PathBuf>,
+) -> Result<(), MainError> {
+    let bytes = if use_der { der } else { pem };
+    if let Some(outfile) = outfile {
+        println!("Write certificate to file: {}", outfile.to_string_lossy());
+        std::fs::write(outfile, bytes)?;
+    } else {
+        io::Write::write_all(&mut io::stdout(), bytes)?;
+    }
+    Ok(())
+}
+
 /// Add a suffix to the file path, before the extension.
 fn suffix_path(path: &std::path::Path, suffix: &str) -> std::path::PathBuf {
     let mut path = path.to_path_buf();
@@ -173,6 +503,213 @@ fn suffix_path(path: &std::path::Path, suffix: &str) -> std::path::PathBuf {
     path
 }
 
+/// Find the passphrase of an encrypted private key in a wordlist.
+fn crack_key_passphrase(
+    bytes: &[u8],
+    wordlist: &std::path::Path,
+    threads: usize,
+) -> Result<Parameters, MainError> {
+    let candidates = String::from_utf8_lossy(&std::fs::read(wordlist)?)
+        .lines()
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    let pb = ProgressBar::new(candidates.len() as u64);
+    pb.set_style(
+        ProgressStyle::with_template(
+            "{prefix:>12.bold} [{elapsed_precise}] [{wide_bar}] {pos}/{len} ({per_sec}, {eta})",
+        )
+        .unwrap()
+        .progress_chars("=> "),
+    );
+    pb.set_prefix("passphrase");
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()?;
+    let res = pool.install(|| Parameters::from_private_key_wordlist(bytes, &candidates, Some(&pb)));
+    pb.finish_and_clear();
+
+    match res {
+        Ok((params, passphrase)) => {
+            eprintln!("Private key passphrase found: {passphrase}");
+            Ok(params)
+        }
+        Err(KeyError::WrongPassphrase) => {
+            Err("Private key passphrase not found in wordlist".into())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Read a private key, prompting for its passphrase if it is encrypted and none was given.
+fn read_key(
+    bytes: &[u8],
+    password: Option<&str>,
+    wordlist: Option<&std::path::Path>,
+    threads: usize,
+) -> Result<Parameters, MainError> {
+    match (Parameters::from_private_key(bytes, password), wordlist) {
+        (Ok(params), _) => Ok(params),
+        (Err(KeyError::MissingPassphrase), Some(wordlist)) => {
+            crack_key_passphrase(bytes, wordlist, threads)
+        }
+        (Err(KeyError::MissingPassphrase), None) if io::stdin().is_terminal() => {
+            for _ in 0..PASSPHRASE_ATTEMPTS {
+                let passphrase = rpassword::prompt_password("Private key passphrase: ")?;
+                match Parameters::from_private_key(bytes, Some(&passphrase)) {
+                    Ok(params) => return Ok(params),
+                    Err(KeyError::WrongPassphrase) => eprintln!("Wrong passphrase"),
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            Err("Wrong private key passphrase".into())
+        }
+        (Err(KeyError::MissingPassphrase), None) => {
+            // Only openssh private keys have their public part stored in clear
+            let params = Parameters::from_openssh_public_key(bytes)
+                .ok_or("Private key is encrypted, a passphrase is required (--password)")?;
+            eprintln!("Warning: Private key is encrypted, but no passphrase was provided, only n and e will be extracted");
+            Ok(params)
+        }
+        (Err(KeyError::WrongPassphrase), _) => Err("Wrong private key passphrase".into()),
+        (Err(_), _) => Ok(Parameters::from_public_key(bytes).ok_or("Invalid key")?),
+    }
+}
+
+/// Print the weaknesses found by an audit, each key being named by `name` from its index.
+fn print_audit_report(report: &AuditReport, total: usize, name: impl Fn(usize) -> String) {
+    println!("Audited {}/{total} keys", report.audited);
+    for finding in &report.findings {
+        println!("{}: {}", name(finding.index), finding.weakness);
+    }
+    if report.findings.is_empty() {
+        println!("No weakness found");
+    }
+    for (class, count) in report.statistics() {
+        println!("{count} key(s) with {class}");
+    }
+}
+
+/// Print the keys factored by an audit, and the messages of their ciphers, one key per line
+fn print_recovered_keys(recovered: &[RecoveredKey], name: impl Fn(usize) -> String) {
+    if recovered.is_empty() {
+        return;
+    }
+    let rows = recovered
+        .iter()
+        .map(|key| {
+            let result = match &key.m {
+                Some(m) => match integer_to_string(m) {
+                    Some(text) => format!("{text:?}"),
+                    None => format!("0x{m:x}"),
+                },
+                None => "no cipher".to_string(),
+            };
+            (name(key.index), result)
+        })
+        .collect::<Vec<_>>();
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+    println!("Recovered {} private key(s):", recovered.len());
+    println!("{:width$}  Plaintext", "Key");
+    for (name, result) in rows {
+        println!("{name:width$}  {result}");
+    }
+}
+
+/// Replace the directories by the files they contain, sorted by name
+fn expand_dirs(paths: &[std::path::PathBuf]) -> Result<Vec<std::path::PathBuf>, MainError> {
+    let mut files = vec![];
+    for path in paths {
+        if path.is_dir() {
+            let mut entries = std::fs::read_dir(path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.retain(|entry| entry.is_file());
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Cipher file of a key file: the one with the same name without extension, or else the one at
+/// the same position
+fn paired_cipher<'a>(
+    key: &std::path::Path,
+    index: usize,
+    keys: &[std::path::PathBuf],
+    ciphers: &'a [std::path::PathBuf],
+) -> Option<&'a std::path::PathBuf> {
+    ciphers
+        .iter()
+        .find(|cipher| cipher.file_stem() == key.file_stem())
+        .or_else(|| {
+            // Ciphers named after a key are not paired by position
+            let cipher = ciphers.get(index)?;
+            let named = keys.iter().any(|key| key.file_stem() == cipher.file_stem());
+            (!named).then_some(cipher)
+        })
+}
+
+/// Returns the merged input parameters and their derived values as JSON, with the source of each value.
+fn inputs_json(params: &Parameters, sources: &[(&str, Parameters)]) -> serde_json::Value {
+    // Only keys always set e, other inputs fall back to the default one
+    let default_e = Parameters::default().e;
+    let is_explicit = |source: &str, name: &str, value: &Integer| {
+        name != "e" || *value != default_e || source == "key file"
+    };
+
+    let fields = params
+        .derived()
+        .fields()
+        .into_iter()
+        .map(|(name, value)| {
+            let source = sources
+                .iter()
+                .find(|(source, params)| {
+                    params
+                        .fields()
+                        .iter()
+                        .any(|&(n, v)| n == name && v == value && is_explicit(source, n, v))
+                })
+                .map(|(source, _)| *source)
+                .unwrap_or(if name == "e" { "default" } else { "derived" });
+            (
+                name.to_string(),
+                serde_json::json!({ "value": value.to_string(), "source": source }),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+    serde_json::Value::Object(fields)
+}
+
+/// Entry of the session log to replay, by number or "last"
+fn replay_entry(log: &SessionLog, entry: &str) -> Result<SessionEntry, MainError> {
+    let number = match entry {
+        "last" => log.entries().last().map_or(0, |(number, _)| *number),
+        number => number.parse().or(Err("Invalid entry number"))?,
+    };
+    log.entry(number)
+        .ok_or_else(|| format!("No entry {entry} in {}", log.path().to_string_lossy()).into())
+}
+
+/// Returns how long ago a time was, in seconds since the Unix epoch, e.g. "3 days ago"
+fn time_ago(time: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(time, |now| now.as_secs());
+    match now.saturating_sub(time) {
+        secs if secs < 60 => "just now".to_string(),
+        secs if secs < 60 * 60 => format!("{} min ago", secs / 60),
+        secs if secs < 24 * 60 * 60 => format!("{} h ago", secs / (60 * 60)),
+        secs => format!("{} days ago", secs / (24 * 60 * 60)),
+    }
+}
+
 fn main() -> Result<(), MainError> {
     let pkg_name = env!("CARGO_PKG_NAME");
     let current_version = env!("CARGO_PKG_VERSION");
@@ -185,73 +722,340 @@ fn main() -> Result<(), MainError> {
 
     // Parse command line arguments
     let args = Args::parse();
+    set_primality_policy(PrimalityPolicy {
+        rounds: args.primality_rounds,
+        bpsw: args.bpsw,
+        prove_bits: args.prove_bits,
+    });
 
     // List attacks
     if args.list {
         println!("Available attacks:");
         for attack in ATTACKS.iter() {
-            println!("  {}", attack.name());
+            match attack.doc() {
+                Some(doc) => println!("  {:<24} {}", attack.name(), doc.summary),
+                None => println!("  {}", attack.name()),
+            }
         }
         return Ok(());
     }
 
-    // Read cipher
-    let c = if args.cipher.is_some() {
-        args.cipher.map(|n| n.0)
-    } else if let Some(cipher_path) = args.cipherfile.as_ref() {
+    // Audit Certificate Transparency logs
+    if let Some(query) = args.ct.as_ref() {
+        let certificates = fetch_ct_certificates(query, args.ct_limit)?;
+        let moduli = certificates
+            .iter()
+            .filter_map(|cert| cert.params.n.clone())
+            .collect::<Vec<_>>();
+        println!("Audited {} RSA certificates", moduli.len());
+        for finding in audit_moduli(&moduli) {
+            let cert = &certificates[finding.index];
+            println!(
+                "crt.sh id {} ({}): {}",
+                cert.id, cert.common_name, finding.weakness
+            );
+        }
+        return Ok(());
+    }
+
+    // Audit a list of moduli
+    if let Some(path) = args.moduli_file.as_ref() {
+        let keys = parse_moduli(&std::fs::read_to_string(path)?)?
+            .into_iter()
+            .map(|n| KeyEntry {
+                n,
+                e: args.e.0.clone(),
+                c: None,
+                known: Parameters::default(),
+            })
+            .collect::<Vec<_>>();
+        let deadline = args
+            .time_budget
+            .map(|budget| Instant::now() + Duration::from_secs(budget));
+
+        print_audit_report(
+            &audit_keys(&keys, deadline, args.max_memory << 20),
+            keys.len(),
+            |index| format!("Modulus #{index}"),
+        );
+        return Ok(());
+    }
+
+    // Audit public keys
+    if let Some(Command::Audit { files, ciphers }) = args.command.as_ref() {
+        let files = expand_dirs(files)?;
+        let ciphers = expand_dirs(ciphers)?;
+        let keys = files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| -> Result<KeyEntry, MainError> {
+                let bytes = std::fs::read(file)?;
+                let params = Parameters::from_public_key(&bytes)
+                    .or_else(|| Parameters::from_private_key(&bytes, None).ok());
+                let c = match paired_cipher(file, i, &files, &ciphers) {
+                    Some(cipher) => Some(cipher_file_to_integer(&std::fs::read(cipher)?)),
+                    None => None,
+                };
+                match params {
+                    Some(Parameters { n: Some(n), e, .. }) => Ok(KeyEntry {
+                        n,
+                        e,
+                        c,
+                        known: Parameters::default(),
+                    }),
+                    _ => Err(format!("{}: Invalid key", file.to_string_lossy()).into()),
+                }
+            })
+            .collect::<Result<Vec<_>, MainError>>()?;
+        let deadline = args
+            .time_budget
+            .map(|budget| Instant::now() + Duration::from_secs(budget));
+
+        let report = audit_keys(&keys, deadline, args.max_memory << 20);
+        let name = |index: usize| files[index].to_string_lossy().to_string();
+        print_audit_report(&report, keys.len(), name);
+        print_recovered_keys(&recover_keys(&keys, &report), name);
+        return Ok(());
+    }
+
+    // Convert a key
+    if let Some(Command::Convert {
+        file,
+        format,
+        output,
+    }) = args.command.as_ref()
+    {
+        let params = read_key(
+            &std::fs::read(file)?,
+            args.password.as_deref(),
+            args.password_wordlist.as_deref(),
+            args.threads,
+        )?;
+        let bytes = match (&params.n, &params.p, &params.q) {
+            (_, Some(p), Some(q)) => {
+                let mut private_key = PrivateKey::from_p_q(p.clone(), q.clone(), params.e.clone())?;
+                if let Some(d) = &params.d {
+                    private_key.d = d.clone();
+                }
+                export_private_key(&private_key, *format)?
+            }
+            (Some(n), _, _) => export_public_key(n, &params.e, *format)?,
+            _ => return Err("Invalid key".into()),
+        };
+        match output {
+            Some(output) => std::fs::write(output, bytes)?,
+            None => io::Write::write_all(&mut io::stdout(), &bytes)?,
+        }
+        return Ok(());
+    }
+
+    // Serve a decryption oracle
+    if let Some(Command::OracleSim {
+        key,
+        bits,
+        oracle,
+        listen,
+        message,
+    }) = args.command.as_ref()
+    {
+        let private_key = match key {
+            Some(key) => {
+                let params = read_key(
+                    &std::fs::read(key)?,
+                    args.password.as_deref(),
+                    args.password_wordlist.as_deref(),
+                    args.threads,
+                )?;
+                match (params.p, params.q) {
+                    (Some(p), Some(q)) => PrivateKey::from_p_q(p, q, params.e)?,
+                    _ => return Err("Private key required".into()),
+                }
+            }
+            None => generate_key(*bits)?,
+        };
+        let listener = TcpListener::bind(listen)?;
+        println!("n = {}", private_key.n);
+        println!("e = {}", private_key.e);
+        if let Some(IntegerArg(m)) = message {
+            let c = m.clone().pow_mod(&private_key.e, &private_key.n).unwrap();
+            println!("c = {c}");
+        }
+        eprintln!("{oracle} oracle listening on {}", listener.local_addr()?);
+        serve_oracle(listener, &private_key, *oracle)?;
+        return Ok(());
+    }
+
+    // Replay a logged run, or list them
+    let session_log = SessionLog::from_env();
+    let logged_to = session_log.as_ref().filter(|_| args.session_log);
+    if args.session_log && session_log.is_none() {
+        eprintln!("Warning: no state directory for the session log, set RSACRACKER_SESSION_LOG");
+    }
+    let replayed = match args.command.as_ref() {
+        Some(Command::Replay { entry, .. }) => {
+            let log = session_log
+                .as_ref()
+                .ok_or("No session log, set RSACRACKER_SESSION_LOG")?;
+            let Some(entry) = entry else {
+                for (number, entry) in log.entries() {
+                    println!(
+                        "{number:>4}  {:<14}  {:>5}-bit n  {:>3} attacks  {:>9.1}s  {}",
+                        time_ago(entry.time),
+                        entry.modulus_bits(),
+                        entry.attacks.len(),
+                        entry.elapsed.as_secs_f64(),
+                        entry
+                            .solved_by
+                            .map_or("unsolved".to_string(), |attack| format!(
+                                "solved by {attack}"
+                            ))
+                    );
+                }
+                return Ok(());
+            };
+            Some(replay_entry(log, entry)?)
+        }
+        _ => None,
+    };
+
+    // The inputs are moved into the parameters, keep the arguments around to print the solutions
+    let output_args = args.clone();
+
+    // Read ciphers
+    let mut ciphers = args.cipher.into_iter().map(|c| c.0).collect::<Vec<_>>();
+    for cipher_path in &args.cipherfile {
         match std::fs::read(cipher_path) {
-            Ok(bytes) => Some(Integer::from_digits(&bytes, Order::Msf)),
+            Ok(bytes) => ciphers.push(cipher_file_to_integer(&bytes)),
             Err(err) => return Err(format!("{}: {err}", cipher_path.to_string_lossy()).into()),
         }
-    } else {
-        None
-    };
+    }
+
+    // Read public and private keys
+    let mut keys = args
+        .key
+        .iter()
+        .map(|key| {
+            read_key(
+                &std::fs::read(key)?,
+                args.password.as_deref(),
+                args.password_wordlist.as_deref(),
+                args.threads,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // Check the private key against a public key
+    if let Some(public_key) = args.check.as_ref() {
+        let public = Parameters::from_public_key(&std::fs::read(public_key)?)
+            .ok_or("Invalid public key or certificate")?;
+        let mismatches = check_key(&keys[0], Some(&public));
+        if !mismatches.is_empty() {
+            for mismatch in mismatches {
+                println!("Mismatch: {mismatch}");
+            }
+            return Err("The private key does not match".into());
+        }
+        println!("The private key matches the public key, and is consistent");
+        return Ok(());
+    }
+    if let Some(domain) = args.dnssec.as_ref() {
+        let dnskeys = fetch_dnskeys(domain)?;
+        if dnskeys.is_empty() {
+            return Err(format!("No RSA DNSKEY record found for {domain}").into());
+        }
+        keys.extend(dnskeys);
+    }
+    if ciphers.len() > keys.len().max(1) {
+        return Err("More ciphers than keys, each cipher must be paired with a key".into());
+    }
+    let c = if keys.len() <= 1 { ciphers.pop() } else { None };
+
+    // Parameters of each input, in order of precedence
+    let mut sources = vec![];
 
     // Parse raw
     let mut stdin = io::stdin();
-    let mut params = if !stdin.is_terminal() {
+    if !stdin.is_terminal() {
         // Piped input
         let mut raw = String::new();
         stdin.read_to_string(&mut raw)?;
-        Parameters::from_raw(&raw)
+        sources.push(("stdin", Parameters::from_raw(&raw)));
     } else if let Some(raw) = args.raw.as_ref() {
         // rsacracker --raw
         let raw = std::fs::read_to_string(raw)?;
-        Parameters::from_raw(&raw)
-    } else {
-        Parameters::default()
-    };
+        sources.push(("raw file", Parameters::from_raw(&raw)));
+    }
 
     // Build parameters
-    params += Parameters {
-        c,
-        n: args.n.map(|n| n.0),
-        e: args.e.0,
-        p: args.p.map(|n| n.0),
-        q: args.q.map(|n| n.0),
-        d: args.d.map(|n| n.0),
-        phi: args.phi.map(|n| n.0),
-        dp: args.dp.map(|n| n.0),
-        dq: args.dq.map(|n| n.0),
-        qinv: args.qinv.map(|n| n.0),
-        pinv: args.pinv.map(|n| n.0),
-        sum_pq: args.sum_pq.map(|n| n.0),
-    };
+    sources.push((
+        "cli",
+        Parameters {
+            c,
+            n: args.n.map(|n| n.0),
+            e: args.e.0,
+            p: args.p.as_ref().and_then(|p| p.known().cloned()),
+            q: args.q.as_ref().and_then(|q| q.known().cloned()),
+            d: args.d.map(|n| n.0),
+            phi: args.phi.map(|n| n.0),
+            dp: args.dp.as_ref().and_then(|dp| dp.known().cloned()),
+            dq: args.dq.as_ref().and_then(|dq| dq.known().cloned()),
+            qinv: args.qinv.map(|n| n.0),
+            pinv: args.pinv.map(|n| n.0),
+            sum_pq: args.sum_pq.map(|n| n.0),
+            sum_squares_pq: args.sum_squares_pq.map(|n| n.0),
+            diff_pq: args.diff_pq.map(|n| n.0),
+            xor_pq: args.xor_pq.map(|n| n.0),
+            sum_pq_mod: args.sum_pq_mod.map(|v| (v[0].0.clone(), v[1].0.clone())),
+            pq_mod: args.pq_mod.map(|v| (v[0].0.clone(), v[1].0.clone())),
+            p_mod: args.p_mod.map(|v| (v[0].0.clone(), v[1].0.clone())),
+            known_m: args.known_m.map(|n| n.0),
+            known_c: args.known_c.map(|n| n.0),
+            signed_m: args.signed_m.map(|n| n.0),
+            signature: args.signature.map(|n| n.0),
+            partial_p: args.p.filter(|p| p.known().is_none()),
+            partial_q: args.q.filter(|q| q.known().is_none()),
+            partial_dp: args.dp.filter(|dp| dp.known().is_none()),
+            partial_dq: args.dq.filter(|dq| dq.known().is_none()),
+            partial_m: args.partial_m,
+            keys: vec![],
+        },
+    ));
+    if keys.len() > 1 {
+        sources.push((
+            "key file",
+            Parameters::from_keys(keys, ciphers).ok_or("Keys without modulus can't be paired")?,
+        ));
+    } else if let Some(key) = keys.into_iter().next() {
+        sources.push(("key file", key));
+    }
 
-    // Read public and private keys
-    if let Some(key) = args.key {
-        let bytes = std::fs::read(key)?;
+    // The logged parameters replace the inputs
+    if let Some(entry) = &replayed {
+        sources = vec![("session log", entry.params.clone())];
+    }
 
-        params += Parameters::from_private_key(&bytes, args.password.as_deref())
-            .or_else(|| Parameters::from_public_key(&bytes))
-            .ok_or("Invalid key")?;
-    };
+    // Merge the sources, warning about the values they disagree on
+    let merged = MergedParameters::merge(sources.iter().map(|(source, params)| (*source, params)));
+    for conflict in &merged.conflicts {
+        eprintln!("Warning: {conflict}");
+    }
+    let n_from_key = merged.source("n") == Some("key file");
+    let mut params = merged.params;
 
     if args.showinputs {
-        println!("{params}");
+        if args.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&inputs_json(&params, &sources))?
+            );
+        } else {
+            println!("{params}");
+        }
         return Ok(());
     }
 
+    // Check the hints on p and q
+    params.check_pq_hints()?;
+
     // Check if discrete logarithm can be computed
     if args.dlog && params.c.is_none() {
         return Err("Discrete logarithm requires a cipher".into());
@@ -277,7 +1081,7 @@ fn main() -> Result<(), MainError> {
     }
 
     // Build attack list
-    let attacks = args
+    let mut attacks = args
         .attack
         // Collect attacks
         .map(|attacks| {
@@ -286,7 +1090,7 @@ fn main() -> Result<(), MainError> {
                 .map(|attack| attack.0)
                 .collect::<Vec<_>>()
         })
-        .unwrap_or(ATTACKS.to_vec())
+        .unwrap_or_else(|| args.profile.attacks())
         // Exclude attacks
         .into_iter()
         .filter(|attack| {
@@ -299,17 +1103,211 @@ fn main() -> Result<(), MainError> {
         .sorted_by_key(|a| (a.kind(), a.speed()))
         .collect::<Vec<_>>();
 
+    // Run the logged attacks, or the ones left to continue the run
+    if let Some(entry) = &replayed {
+        let names = match args.command.as_ref() {
+            Some(Command::Replay {
+                continue_run: true, ..
+            }) => entry.unfinished_attacks(),
+            _ => entry.attacks.iter().map(String::as_str).collect(),
+        };
+        attacks = ATTACKS
+            .iter()
+            .filter(|attack| names.contains(&attack.name()))
+            .cloned()
+            .sorted_by_key(|a| (a.kind(), a.speed()))
+            .collect();
+    }
+
+    // Warn about suspicious inputs
+    let mut dlog = args.dlog;
+    let mut byte_swapped = args.byte_swapped;
+    for diagnostic in rsacracker::diagnose(&params) {
+        match diagnostic.attacks() {
+            [] => eprintln!("Warning: {diagnostic}"),
+            attacks => eprintln!("Warning: {diagnostic} (see {})", attacks.join(", ")),
+        }
+        if diagnostic == rsacracker::Diagnostic::SwappedExponentAndCipher && !dlog {
+            eprintln!("Warning: e and c may be swapped (e^c mod n), enabling --dlog");
+            dlog = true;
+        }
+        if diagnostic == rsacracker::Diagnostic::ByteSwapped && !byte_swapped {
+            eprintln!("Warning: c and n may be stored little-endian, enabling --byte-swapped");
+            byte_swapped = true;
+        }
+        if let rsacracker::Diagnostic::KnownPairExponent(e) = diagnostic {
+            eprintln!("Warning: using e = {e}, from the known plaintext and cipher");
+            params.e = e.into();
+        }
+        if let rsacracker::Diagnostic::PrivateExponentAsE(e) = diagnostic {
+            if params.d.is_none() {
+                eprintln!("Warning: using the given e as d, and e = {e}");
+                params.d = Some(std::mem::replace(&mut params.e, e.into()));
+            }
+        }
+    }
+
+    // Run the most promising attacks first, from the extra information, a probe of n and the
+    // diagnostics
+    rsacracker::schedule_attacks(&params, &mut attacks);
+
     // Run attacks
-    let res = rsacracker::run_specific_attacks_with_threads(&params, &attacks, args.threads);
-    let solution = match res {
-        Ok(solution) => solution,
+    let config = AttackConfig {
+        threads: args.threads,
+        small_prime_bound: args.small_prime_bound,
+        londahl_b: args.londahl_b,
+        mitm_bits: args.mitm_bits,
+        max_memory: args.max_memory << 20,
+        effort: args.effort,
+        time_effort: 100,
+        time_budget: args.time_budget.map(Duration::from_secs),
+        time_slice: Duration::from_secs(args.time_slice),
+        progress_refresh_rate: args.progress_refresh_rate,
+        aggregate_progress: args.aggregate_progress,
+        on_candidate: args.on_candidate.clone().map(candidate_command),
+        shard: None,
+        message_guesses: MessageGuesses {
+            words: Arc::new(match &args.message_wordlist {
+                Some(wordlist) => std::fs::read(wordlist)?
+                    .split(|&b| b == b'\n')
+                    .map(|word| word.strip_suffix(b"\r").unwrap_or(word).to_vec())
+                    .filter(|word| !word.is_empty())
+                    .collect(),
+                None => vec![],
+            }),
+            charset: args
+                .message_charset
+                .clone()
+                .unwrap_or_default()
+                .into_bytes(),
+            max_length: args.message_max_length,
+        },
+        plaintext: PlaintextConstraint {
+            prefix: args
+                .plaintext_prefix
+                .clone()
+                .unwrap_or_default()
+                .into_bytes(),
+            suffix: args
+                .plaintext_suffix
+                .clone()
+                .unwrap_or_default()
+                .into_bytes(),
+            length: args.plaintext_length,
+        },
+        cache: Cache::from_env(),
+    };
+    let config = args.profile.tuned(config);
+
+    // Claim shards of a coordinated run, whose parameters replace the given ones
+    if let Some(addr) = &args.worker {
+        let searched = rsacracker::work(addr.as_str(), &attacks, config, args.token.as_deref())?;
+        println!("Searched {searched} shards for {addr}");
+        return Ok(());
+    }
+
+    let threads = config.threads;
+    let retry_config = byte_swapped.then(|| config.clone());
+    let started = (SystemTime::now(), Instant::now());
+    let stats = Arc::new(RunStats::new());
+    let res = if let Some(addr) = &args.coordinate {
+        let listener = TcpListener::bind(addr)?;
+        eprintln!(
+            "Dealing {} shards to the workers on {}",
+            args.shards,
+            listener.local_addr()?
+        );
+        if args.token.is_none() && !listener.local_addr()?.ip().is_loopback() {
+            eprintln!("Warning: Anyone reaching {addr} can get the parameters and report solutions, set a --token");
+        }
+        rsacracker::coordinate(listener, &params, args.shards, args.token.as_deref())?
+            .map(|solution| vec![solution])
+            .ok_or(None)
+    } else if args.stats || args.report.is_some() || logged_to.is_some() {
+        rsacracker::run_specific_attacks_with_stats(
+            &params,
+            &attacks,
+            config,
+            args.all,
+            Arc::clone(&stats),
+        )
+    } else if args.all {
+        rsacracker::run_all_specific_attacks_with_config(&params, &attacks, config)
+    } else {
+        rsacracker::run_specific_attacks_with_config(&params, &attacks, config)
+            .map(|solution| vec![solution])
+    };
+    // Try again with the byte-reversed inputs, keeping the n of a key file
+    let res = match (res, retry_config) {
+        (Err(_), Some(config)) if args.coordinate.is_none() => {
+            eprintln!("No attack succeeded, trying the byte-reversed c and n");
+            let mut swapped = params.byte_swapped();
+            if n_from_key {
+                swapped.n = params.n.clone();
+            }
+            params = swapped;
+            rsacracker::run_specific_attacks_with_stats(
+                &params,
+                &attacks,
+                config,
+                args.all,
+                Arc::clone(&stats),
+            )
+        }
+        (res, _) => res,
+    };
+    if args.stats {
+        eprint!("{stats}");
+    }
+    if let Some(log) = logged_to {
+        let entry = SessionEntry {
+            time: started
+                .0
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs()),
+            args: redact_args(std::env::args()),
+            params: params.clone(),
+            attacks: attacks
+                .iter()
+                .map(|attack| attack.name().to_string())
+                .collect(),
+            outcomes: stats.attacks(),
+            solved_by: res
+                .as_deref()
+                .ok()
+                .and_then(|solutions| solutions.first())
+                .map(|solution| solution.attack.to_string()),
+            elapsed: started.1.elapsed(),
+            version: current_version.to_string(),
+            platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+            threads,
+        };
+        // Logging is best effort, errors are only reported
+        match log.append(&entry) {
+            Ok(number) => eprintln!("Logged as session entry {number}"),
+            Err(err) => eprintln!("Warning: can't write the session log: {err}"),
+        }
+    }
+    if let Some(path) = &args.report {
+        let solutions = res.as_deref().unwrap_or_default();
+        let report = render_report(
+            &params,
+            solutions,
+            Some(&stats),
+            ReportFormat::from_path(path),
+        );
+        println!("Write report to file: {}", path.to_string_lossy());
+        std::fs::write(path, report)?;
+    }
+    let solutions = match res {
+        Ok(solutions) => solutions,
         Err(partial_factors) => {
             // Print partial factors if any
             if let Some(partial_factors) = partial_factors {
                 println!("Partial factors of n:");
                 for (i, p) in partial_factors.as_vec().into_iter().enumerate() {
                     print!("p{} = {}", i + 1, p);
-                    if p.is_probably_prime(100) != IsPrime::Yes {
+                    if is_prime(p) != IsPrime::Yes {
                         print!(" (composite)");
                     }
                     println!();
@@ -318,15 +1316,82 @@ fn main() -> Result<(), MainError> {
             return Err("No attack succeeded".into());
         }
     };
+    // Print each solution, to its own files if there are many
+    let many = solutions.len() > 1;
+    for (mut solution, i) in solutions.into_iter().zip(1..) {
+        if let Some(m) = byte_swapped_plaintext(&solution, &params, &args.flag_format) {
+            eprintln!("Warning: c looks byte-swapped, its byte reversal decrypts to a flag");
+            solution.m = Some(m);
+        }
+        if let Some(dir) = &args.export_bundle {
+            let dir = if many {
+                suffix_path(dir, &format!("_solution{i}"))
+            } else {
+                dir.clone()
+            };
+            let files = export_bundle(&dir, &solution, &params)?;
+            println!(
+                "Write {} files to directory: {}",
+                files.len(),
+                dir.to_string_lossy()
+            );
+        }
+        let outfile = match &args.outfile {
+            Some(outfile) if many => Some(suffix_path(outfile, &format!("_solution{i}"))),
+            outfile => outfile.clone(),
+        };
+        if many {
+            println!("Solution {i}:");
+        }
+        print_solution(solution, &output_args, &params, dlog, &outfile)?;
+    }
+
+    Ok(())
+}
+
+/// Plaintext of the byte-reversed cipher, if it matches the flag pattern while the plaintext of the
+/// solution does not
+fn byte_swapped_plaintext(
+    solution: &Solution,
+    params: &Parameters,
+    flag: &regex::Regex,
+) -> Option<Integer> {
+    let (pk, m, c) = (
+        solution.pk.as_ref()?,
+        solution.m.as_ref()?,
+        params.c.as_ref()?,
+    );
+    let is_flag = |m: &Integer| flag.is_match(&String::from_utf8_lossy(&integer_to_bytes(m)));
+    if is_flag(m) {
+        return None;
+    }
+    let m = pk.decrypt(&reverse_bytes(c));
+    is_flag(&m).then_some(m)
+}
+
+/// Print a solution as requested by the arguments: factors, private key or unciphered data.
+fn print_solution(
+    solution: Solution,
+    args: &Args,
+    params: &Parameters,
+    dlog: bool,
+    outfile: &Option<std::path::PathBuf>,
+) -> Result<(), MainError> {
     println!("Succeeded with attack: {}", solution.attack);
+    if let Some(vulnerability) = &solution.vulnerability {
+        println!("Vulnerability: {vulnerability}");
+    }
+    if !solution.verified {
+        eprintln!("Warning: the solution could not be verified against the inputs");
+    }
 
     // Print factors
     if args.factors {
         if let Some(private_key) = &solution.pk {
             println!("Factors of n:");
-            if private_key.factors.len() == 2 {
-                println!("p = {}", private_key.p());
-                println!("q = {}", private_key.q());
+            if let (Some(p), Some(q)) = (private_key.p(), private_key.q()) {
+                println!("p = {p}");
+                println!("q = {q}");
             } else {
                 for (i, p) in private_key.factors.as_vec().into_iter().enumerate() {
                     println!("p{} = {}", i + 1, p);
@@ -350,9 +1415,9 @@ fn main() -> Result<(), MainError> {
                 println!("e = {}", private_key.e);
 
                 // Print factors
-                if private_key.factors.len() == 2 {
-                    println!("p = {}", private_key.p());
-                    println!("q = {}", private_key.q());
+                if let (Some(p), Some(q)) = (private_key.p(), private_key.q()) {
+                    println!("p = {p}");
+                    println!("q = {q}");
                 } else {
                     for (i, p) in private_key.factors.as_vec().into_iter().enumerate() {
                         println!("p{} = {}", i + 1, p);
@@ -363,10 +1428,29 @@ fn main() -> Result<(), MainError> {
             if args.dumpext {
                 println!("Extended private key:");
                 println!("phi = {}", private_key.phi());
-                println!("dP = {}", private_key.dp());
-                println!("dQ = {}", private_key.dq());
-                println!("pInv = {}", private_key.pinv());
-                println!("qInv = {}", private_key.qinv());
+                match (
+                    private_key.dp(),
+                    private_key.dq(),
+                    private_key.pinv(),
+                    private_key.qinv(),
+                ) {
+                    (Some(dp), Some(dq), Some(pinv), Some(qinv)) => {
+                        println!("dP = {dp}");
+                        println!("dQ = {dq}");
+                        println!("pInv = {pinv}");
+                        println!("qInv = {qinv}");
+                    }
+                    _ => {
+                        // Multi-prime key: an exponent per distinct factor, and a coefficient per
+                        // distinct factor but the first (RFC 8017)
+                        for (i, d) in private_key.crt_exponents().iter().enumerate() {
+                            println!("d{} = {}", i + 1, d);
+                        }
+                        for (i, t) in private_key.crt_coefficients().iter().enumerate() {
+                            println!("t{} = {}", i + 2, t);
+                        }
+                    }
+                }
             }
         } else {
             return Err("No private key found".into());
@@ -374,21 +1458,74 @@ fn main() -> Result<(), MainError> {
         return Ok(());
     }
 
+    // Print a forged certificate or CSR
+    if args.forge_cert || args.forge_csr {
+        let Some(private_key) = &solution.pk else {
+            return Err("No private key found".into());
+        };
+        let template = match &args.forge_template {
+            Some(path) => Some(read_certificate(path)?),
+            None => None,
+        };
+        let options = ForgeOptions {
+            subject: args.forge_subject.clone(),
+            days: args.forge_days,
+            template,
+        };
+        let (pem, der) = if args.forge_cert {
+            let cert = forge_certificate(private_key, &options)?;
+            (cert.to_pem()?, cert.to_der()?)
+        } else {
+            let csr = forge_csr(private_key, &options)?;
+            (csr.to_pem()?, csr.to_der()?)
+        };
+        return output_certificate(&pem, &der, args.der, outfile);
+    }
+
+    // Re-sign a certificate with its issuer's key
+    if let Some(path) = &args.resign {
+        let Some(private_key) = &solution.pk else {
+            return Err("No private key found".into());
+        };
+        let options = ResignOptions {
+            common_name: args.resign_cn.clone(),
+            subject_alt_names: args.resign_san.clone(),
+            days: args.forge_days,
+        };
+        let tampered = read_certificate(path)?;
+        let cert = resign_certificate(&tampered, private_key, &options)?;
+        return output_certificate(&cert.to_pem()?, &cert.to_der()?, args.der, outfile);
+    }
+
+    // Byte length of n, to keep the leading zeros of the padding
+    let len = solution
+        .pk
+        .as_ref()
+        .map(|pk| &pk.n)
+        .or(params.n.as_ref())
+        .map_or(0, |n| n.significant_bits().div_ceil(8) as usize);
+
     // Print unciphered data
     if let Some(uncipher) = solution.m {
-        display_or_output(&uncipher, &args.outfile)?;
+        display_or_output(&uncipher, outfile, len, args.hexdump, args.encoding)?;
 
         // Print discrete logarithm
-        if args.dlog {
+        if dlog {
             if let Some(pk) = &solution.pk {
                 println!("Compute discrete logarithm...");
                 if let Ok(dlog) = discrete_log_with_factors(
                     &pk.n,
-                    &params.c.unwrap(),
+                    params.c.as_ref().unwrap(),
                     &pk.e,
                     &pk.factors.to_hash_map(),
                 ) {
-                    display_or_output(&dlog, &args.outfile.map(|f| suffix_path(&f, "_dlog")))?;
+                    display_or_output(
+                        &dlog,
+                        &outfile.as_ref().map(|f| suffix_path(f, "_dlog")),
+                        len,
+                        args.hexdump,
+                        args.encoding,
+                    )?;
                 } else {
                     return Err("Discrete logarithm failed".into());
                 }
@@ -399,15 +1536,19 @@ fn main() -> Result<(), MainError> {
     }
     // Print multiple unciphered data
     else if !solution.ms.is_empty() {
-        println!("Multiple unciphered data found:");
-        for (uncipher, i) in solution.ms.iter().zip(1..) {
+        let n = solution.pk.as_ref().map(|pk| &pk.n).or(params.n.as_ref());
+        let mut ms = solution.ms.clone();
+        rank_plaintexts(&mut ms, n, &args.flag_format);
+
+        println!("Multiple unciphered data found, most likely first:");
+        for (uncipher, i) in ms.iter().zip(1..) {
             println!();
             display_or_output(
                 uncipher,
-                &args
-                    .outfile
-                    .clone()
-                    .map(|f| suffix_path(&f, &format!("_{i}"))),
+                &outfile.as_ref().map(|f| suffix_path(f, &format!("_{i}"))),
+                len,
+                args.hexdump,
+                args.encoding,
             )?;
         }
     }
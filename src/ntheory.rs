@@ -1,71 +1,629 @@
+//! Number theory helpers: primality tests, CRT, modular square roots and e-th roots, roots of
+//! polynomials modulo prime powers (Hensel lifting) and p-adic expansions.
+
+use std::sync::RwLock;
+
+use itertools::Itertools;
 use rug::{
-    ops::{DivRounding, Pow},
+    integer::IsPrime,
+    ops::{Pow, RemRounding},
     Integer,
 };
 
-pub fn rational_to_contfrac(x: &Integer, y: &Integer) -> Vec<Integer> {
-    let a = x.clone().div_floor(y);
+use crate::{poly::Polynomial, Factors};
 
-    if a.clone() * y == *x {
-        vec![a]
-    } else {
-        let mut pquotients = rational_to_contfrac(y, &(x - a.clone() * y));
-        pquotients.insert(0, a);
-        pquotients
+/// Returns p and q from n = p * q and phi = (p - 1) * (q - 1), if they are consistent
+pub fn trivial_factorization_with_n_phi(n: &Integer, phi: &Integer) -> Option<(Integer, Integer)> {
+    let m = n.clone() - phi.clone() + Integer::from(1);
+    let m2n2 = m.clone().pow(2) - Integer::from(n << 2);
+
+    if m2n2 > 0 {
+        let (i, _) = m2n2.sqrt_rem(Integer::new());
+        let roots: (Integer, Integer) = ((m.clone() - i.clone()) >> 1, (m + i) >> 1);
+
+        if roots.0.clone() * roots.1.clone() == *n {
+            return Some(roots);
+        }
+    }
+    None
+}
+
+/// Returns x such that x = residues[i] mod modulli[i] for all i, if the congruences are consistent
+///
+/// The modulli may share factors, see `crt_lcm`.
+pub fn crt(residues: &[Integer], modulli: &[Integer]) -> Option<Integer> {
+    crt_lcm(residues, modulli).map(|(x, _)| x)
+}
+
+/// Returns x and lcm(moduli) such that x = residues[i] mod moduli[i] for all i, with 0 <= x < lcm
+///
+/// The moduli do not need to be coprime: the congruences are merged one by one, each of them must
+/// agree with the previous ones modulo the factors they share. Returns `None` otherwise, or if a
+/// modulus is not positive.
+pub fn crt_lcm(residues: &[Integer], moduli: &[Integer]) -> Option<(Integer, Integer)> {
+    let mut x = Integer::ZERO;
+    let mut lcm = Integer::from(1);
+    for (residue, modulus) in residues.iter().zip(moduli) {
+        if *modulus <= 0 {
+            return None;
+        }
+        let g = Integer::from(lcm.gcd_ref(modulus));
+        let diff = Integer::from(residue - &x);
+        if !diff.is_divisible(&g) {
+            return None;
+        }
+        let m = Integer::from(modulus / &g);
+        if m == 1 {
+            // Already implied by the previous congruences
+            continue;
+        }
+
+        // x + lcm * k = residue mod modulus, with k = (diff / g) * (lcm / g)^-1 mod (modulus / g)
+        let inverse = Integer::from(&lcm / &g).invert(&m).ok()?;
+        let k = (diff / &g * inverse).rem_euc(&m);
+        x += Integer::from(&lcm * &k);
+        lcm *= m;
+    }
+    Some((x, lcm))
+}
+
+/// Maximum number of roots computed by `nth_roots_mod_factors` and `roots_mod_prime_power`
+const MAX_ROOTS: usize = 1 << 16;
+
+/// Returns a square root of a modulo the prime p, using the Tonelli-Shanks algorithm
+pub fn tonelli_shanks(a: &Integer, p: &Integer) -> Option<Integer> {
+    let a = a.clone().rem_euc(p);
+    if a == 0 || *p == 2 {
+        return Some(a);
+    }
+    if a.legendre(p) != 1 {
+        return None;
+    }
+
+    // p - 1 = q * 2^s, with q odd
+    let pm1 = Integer::from(p - 1);
+    let s = pm1.find_one(0).unwrap();
+    let q = Integer::from(&pm1 >> s);
+
+    // Find a quadratic non-residue
+    let mut z = Integer::from(2);
+    while z.legendre(p) != -1 {
+        z += 1;
+    }
+
+    let mut m = s;
+    let mut c = z.pow_mod(&q, p).unwrap();
+    let mut t = a.clone().pow_mod(&q, p).unwrap();
+    let mut r = a.pow_mod(&(Integer::from(&q + 1) >> 1), p).unwrap();
+    while t != 1 {
+        // Find the least i such that t^(2^i) = 1
+        let mut i = 0;
+        let mut t2 = t.clone();
+        while t2 != 1 {
+            t2 = t2.square() % p;
+            i += 1;
+        }
+
+        let b = c.pow_mod(&(Integer::from(1) << (m - i - 1)), p).unwrap();
+        m = i;
+        c = b.clone().square() % p;
+        t = t * &c % p;
+        r = r * b % p;
+    }
+    Some(r)
+}
+
+/// Returns a square root of a modulo the odd prime p, using Cipolla's algorithm
+///
+/// Finds r such that r^2 - a is not a square, then computes (r + w)^((p + 1) / 2) in F_p(w),
+/// with w^2 = r^2 - a.
+pub fn cipolla(a: &Integer, p: &Integer) -> Option<Integer> {
+    let a = a.clone().rem_euc(p);
+    if a == 0 || *p == 2 {
+        return Some(a);
+    }
+    if a.legendre(p) != 1 {
+        return None;
+    }
+
+    let mut r = Integer::from(1);
+    let w2 = loop {
+        let w2 = (r.clone().square() - &a).rem_euc(p);
+        if w2.legendre(p) == -1 {
+            break w2;
+        }
+        r += 1;
+    };
+
+    // (x0 + x1 * w) * (y0 + y1 * w) = (x0 * y0 + x1 * y1 * w^2) + (x0 * y1 + x1 * y0) * w
+    let mul = |(x0, x1): &(Integer, Integer), (y0, y1): &(Integer, Integer)| {
+        let real = (Integer::from(x0 * y0) + Integer::from(x1 * y1) * &w2) % p;
+        let imag = (Integer::from(x0 * y1) + Integer::from(x1 * y0)) % p;
+        (real, imag)
+    };
+    let exponent: Integer = Integer::from(p + 1) >> 1;
+    let mut base = (r, Integer::from(1));
+    let mut result = (Integer::from(1), Integer::ZERO);
+    for bit in 0..exponent.significant_bits() {
+        if exponent.get_bit(bit) {
+            result = mul(&result, &base);
+        }
+        base = mul(&base, &base);
     }
+    Some(result.0)
 }
 
-pub fn contfrac_to_rational(frac: &[Integer]) -> (Integer, Integer) {
-    if frac.is_empty() {
-        (0.into(), 1.into())
-    } else if frac.len() == 1 {
-        (frac[0].clone(), 1.into())
+/// Returns a square root of a modulo the prime p
+///
+/// Tonelli-Shanks is quadratic in the 2-adic valuation s of p - 1, so Cipolla's algorithm is used
+/// instead when s is large compared to the size of p.
+pub fn sqrt_mod_prime(a: &Integer, p: &Integer) -> Option<Integer> {
+    let s = Integer::from(p - 1).find_one(0).unwrap_or(0);
+    if s * s.saturating_sub(1) > 8 * p.significant_bits() + 20 {
+        cipolla(a, p)
     } else {
-        let remainder = frac[1..frac.len()].to_vec();
-        let (num, denom) = contfrac_to_rational(&remainder);
-        (frac[0].clone() * num.clone() + denom, num)
+        tonelli_shanks(a, p)
+    }
+}
+
+/// Returns a square root of a modulo p^k, with p an odd prime, lifted from a root modulo p
+///
+/// a must be coprime with p (or 0 modulo p^k), each Hensel step doubles the precision of the root:
+/// r <- r - (r^2 - a) / (2 * r).
+pub fn sqrt_mod_prime_power(a: &Integer, p: &Integer, k: u32) -> Option<Integer> {
+    let pk = Integer::from(p.pow(k));
+    let a = a.clone().rem_euc(&pk);
+    if a == 0 {
+        return Some(a);
+    }
+    if a.is_divisible(p) {
+        return None;
     }
+
+    let r = sqrt_mod_prime(&a, p)?;
+    let f = [Integer::from(-&a), Integer::ZERO, Integer::from(1)];
+    hensel_lift_root(&f, &r, p, k)
 }
 
-#[allow(dead_code)]
-pub fn convergents_from_contfrac(frac: &[Integer]) -> Vec<(Integer, Integer)> {
-    let mut convs = Vec::new();
+/// Returns all the square roots of a modulo the product of the given factors, sorted
+///
+/// Roots modulo each odd prime power are combined with the CRT. Powers of 2 greater than 2 are not
+/// supported, nor are a sharing a factor with an odd prime power, no roots are returned for them.
+pub fn sqrt_mod_factors(a: &Integer, factors: &Factors) -> Vec<Integer> {
+    let mut roots = vec![];
+    let mut modulus = Integer::from(1);
+    for (p, &k) in &factors.0 {
+        let pk = Integer::from(p.pow(k as u32));
+        let pk_roots = match (p.to_u32(), k) {
+            (Some(2), 1) => vec![Integer::from(a.mod_u(2))],
+            (Some(2), _) => return vec![],
+            _ if k > 1 && a.is_divisible(p) => return vec![],
+            _ => match sqrt_mod_prime_power(a, p, k as u32) {
+                Some(r) if r == 0 => vec![r],
+                Some(r) => vec![Integer::from(&pk - &r), r],
+                None => return vec![],
+            },
+        };
 
-    for i in 0..frac.len() {
-        convs.push(contfrac_to_rational(&frac[0..i]));
+        if roots.is_empty() {
+            roots = pk_roots;
+        } else {
+            let mut combined = vec![];
+            for root in &roots {
+                for r in &pk_roots {
+                    let residues = [root.clone(), r.clone()];
+                    combined.push(crt(&residues, &[modulus.clone(), pk.clone()]).unwrap());
+                }
+            }
+            roots = combined;
+        }
+        modulus *= pk;
     }
-    convs
+    roots.sort();
+    roots.dedup();
+    roots
 }
 
-pub fn trivial_factorization_with_n_phi(n: &Integer, phi: &Integer) -> Option<(Integer, Integer)> {
-    let m = n.clone() - phi.clone() + Integer::from(1);
-    let m2n2 = m.clone().pow(2) - Integer::from(n << 2);
+/// Returns all the e-th roots of c modulo the product of the given factors
+///
+/// e must be of the form 2^k * e', with e' coprime with all p - 1, which covers Rabin (e = 2)
+/// and keys whose exponent shares only factors of 2 with phi. Roots modulo a prime power p^k are
+/// lifted from the roots modulo p, so p must divide neither e nor c.
+pub fn nth_roots_mod_factors(c: &Integer, e: &Integer, factors: &Factors) -> Vec<Integer> {
+    if e.is_zero() {
+        return vec![];
+    }
+    let k = e.find_one(0).unwrap();
+    let odd = Integer::from(e >> k);
 
-    if m2n2 > 0 {
-        let (i, _) = m2n2.sqrt_rem(Integer::new());
-        let roots: (Integer, Integer) = ((m.clone() - i.clone()) >> 1, (m + i) >> 1);
+    // Roots modulo each prime power
+    let mut moduli = Vec::with_capacity(factors.len());
+    let mut roots = Vec::with_capacity(factors.len());
+    for (p, &count) in &factors.0 {
+        let pm1 = Integer::from(p - 1);
+        let d = match odd.invert_ref(&pm1) {
+            Some(d) => Integer::from(d),
+            None if *p == 2 => Integer::from(1),
+            None => return vec![],
+        };
+        let mut p_roots = vec![c.clone().rem_euc(p).pow_mod(&d, p).unwrap()];
+        for _ in 0..k {
+            p_roots = p_roots
+                .iter()
+                .filter_map(|r| sqrt_mod_prime(r, p))
+                .flat_map(|r| [Integer::from(p - &r) % p, r])
+                .collect();
+            p_roots.sort();
+            p_roots.dedup();
+            if p_roots.is_empty() || p_roots.len() > MAX_ROOTS {
+                return vec![];
+            }
+        }
 
-        if roots.0.clone() * roots.1.clone() == *n {
-            return Some(roots);
+        let count = count as u32;
+        if count > 1 {
+            // The roots of x^e - c are simple modulo p
+            if e.is_divisible(p) || c.is_divisible(p) {
+                return vec![];
+            }
+            let lift = |r: &Integer| {
+                newton_lift(r, p, count, |r, modulus| {
+                    let r_e1 = r.clone().pow_mod(&Integer::from(e - 1), modulus).unwrap();
+                    (Integer::from(&r_e1 * r) - c, r_e1 * e)
+                })
+            };
+            p_roots = p_roots.iter().filter_map(lift).collect();
         }
+        moduli.push(Integer::from(p.pow(count)));
+        roots.push(p_roots);
     }
-    None
+
+    // Combine all roots with the CRT
+    let mut ms = vec![(Integer::ZERO, Integer::from(1))];
+    for (pk, pk_roots) in moduli.iter().zip(roots) {
+        if ms.len() * pk_roots.len() > MAX_ROOTS {
+            return vec![];
+        }
+        ms = ms
+            .iter()
+            .flat_map(|(m, modulus)| {
+                pk_roots.iter().map(move |r| {
+                    let m = crt(&[m.clone(), r.clone()], &[modulus.clone(), pk.clone()]).unwrap();
+                    (m, Integer::from(modulus * pk))
+                })
+            })
+            .collect();
+    }
+    let mut ms = ms.into_iter().map(|(m, _)| m).collect::<Vec<_>>();
+    ms.sort();
+    ms
 }
 
-pub fn crt(residues: &[Integer], modulli: &[Integer]) -> Option<Integer> {
-    let prod = modulli.iter().product::<Integer>();
-    let mut sum = Integer::ZERO;
+/// Below this prime, the roots of a polynomial modulo p are found by trying all residues
+const MAX_EXHAUSTIVE_PRIME: u32 = 1 << 10;
 
-    for (residue, modulus) in residues.iter().zip(modulli) {
-        let p = prod.clone() / modulus;
-        sum += residue * Integer::from(p.invert_ref(modulus)?) * p
+/// Lifts a simple root r modulo the prime p to the root modulo p^k it is congruent to
+///
+/// `eval` returns f(r) and f'(r) modulo the given power of p. Each Newton step doubles the
+/// precision of the root: r <- r - f(r) / f'(r).
+fn newton_lift(
+    r: &Integer,
+    p: &Integer,
+    k: u32,
+    eval: impl Fn(&Integer, &Integer) -> (Integer, Integer),
+) -> Option<Integer> {
+    let mut r = r.clone().rem_euc(p);
+    let mut precision = 1;
+    while precision < k {
+        precision = (precision * 2).min(k);
+        let modulus = Integer::from(p.pow(precision));
+        let (value, slope) = eval(&r, &modulus);
+        let inverse = slope.invert(&modulus).ok()?;
+        r = (r - value * inverse).rem_euc(&modulus);
     }
+    Some(r)
+}
 
-    Some(sum % prod)
+/// Derivative of a polynomial, given by its coefficients from the lowest degree
+fn derivative(f: &[Integer]) -> Vec<Integer> {
+    f.iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, coef)| Integer::from(coef * i as u32))
+        .collect()
+}
+
+/// Lifts a root r of f modulo the prime p to the root modulo p^k it is congruent to (Hensel)
+///
+/// f is given by its coefficients from the lowest degree. Returns `None` if r is not a root modulo
+/// p, or if it is not simple (f'(r) = 0 mod p) and k > 1: such roots lift to none or several
+/// roots, see `roots_mod_prime_power`.
+pub fn hensel_lift_root(f: &[Integer], r: &Integer, p: &Integer, k: u32) -> Option<Integer> {
+    if !Polynomial::new(f, p).eval(r).is_zero() {
+        return None;
+    }
+    let df = derivative(f);
+    newton_lift(r, p, k, |r, modulus| {
+        let value = Polynomial::new(f, modulus).eval(r);
+        (value, Polynomial::new(&df, modulus).eval(r))
+    })
+}
+
+/// Push the roots of g, a monic product of distinct linear factors modulo an odd prime
+///
+/// g is split by its GCD with (x + a)^((p - 1) / 2) - 1, whose roots are the x such that x + a is
+/// a quadratic residue, until a is found for which it is a proper factor (Cantor-Zassenhaus).
+fn split_linear_factors(g: Polynomial, roots: &mut Vec<Integer>) {
+    let p = g.modulus().clone();
+    let degree = match g.degree() {
+        None | Some(0) => return,
+        Some(1) => {
+            roots.push(Integer::from(&p - &g.coefficients()[0]) % &p);
+            return;
+        }
+        Some(degree) => degree,
+    };
+
+    let exponent = Integer::from(&p - 1) >> 1;
+    let one = Polynomial::constant(1, &p);
+    for a in 1u32.. {
+        let Ok(h) = Polynomial::new([a, 1], &p).pow_mod(&exponent, &g) else {
+            return;
+        };
+        let Ok(factor) = g.gcd(&(&h - &one)) else {
+            return;
+        };
+        if factor.degree().is_some_and(|d| 0 < d && d < degree) {
+            let Ok((quotient, _)) = g.div_rem(&factor) else {
+                return;
+            };
+            split_linear_factors(factor, roots);
+            split_linear_factors(quotient, roots);
+            return;
+        }
+    }
+}
+
+/// Returns the roots of f modulo the prime p, sorted
+///
+/// f is given by its coefficients from the lowest degree, its roots are those of
+/// gcd(f, x^p - x), the product of its distinct linear factors. Small primes are searched
+/// exhaustively. Returns no roots if f is zero modulo p.
+pub fn roots_mod_prime(f: &[Integer], p: &Integer) -> Vec<Integer> {
+    let f = Polynomial::new(f, p);
+    if f.is_zero() {
+        return vec![];
+    }
+    if *p <= MAX_EXHAUSTIVE_PRIME {
+        return (0..p.to_u32().unwrap_or(0))
+            .map(Integer::from)
+            .filter(|x| f.eval(x).is_zero())
+            .collect();
+    }
+
+    let x = Polynomial::monomial(1, p);
+    let Ok(g) = x.pow_mod(p, &f).and_then(|x_p| f.gcd(&(&x_p - &x))) else {
+        return vec![];
+    };
+    let mut roots = vec![];
+    split_linear_factors(g, &mut roots);
+    roots.sort();
+    roots
+}
+
+/// Returns the roots of f modulo p^k, lifted from its roots modulo the prime p, sorted
+///
+/// Simple roots lift to a single root with Newton's method. A root r modulo p^i with f'(r) = 0
+/// mod p lifts to r + t * p^i for all t in [0, p) if f(r) = 0 mod p^(i + 1), and to none
+/// otherwise, so there may be many of them: no roots are returned if there are too many.
+pub fn roots_mod_prime_power(f: &[Integer], p: &Integer, k: u32) -> Vec<Integer> {
+    if k == 0 {
+        return vec![];
+    }
+    let df = Polynomial::new(derivative(f), p);
+    let (singular, simple): (Vec<_>, Vec<_>) = roots_mod_prime(f, p)
+        .into_iter()
+        .partition(|r| df.eval(r).is_zero());
+    let mut roots = simple
+        .iter()
+        .filter_map(|r| hensel_lift_root(f, r, p, k))
+        .collect::<Vec<_>>();
+
+    // Lift the other roots one digit at a time
+    let mut singular = singular;
+    let mut pi = p.clone();
+    for _ in 1..k {
+        let next = Integer::from(&pi * p);
+        let f_next = Polynomial::new(f, &next);
+        singular.retain(|r| f_next.eval(r).is_zero());
+        let digits = match p.to_u32() {
+            Some(digits) if singular.len() * digits as usize <= MAX_ROOTS => digits,
+            _ if singular.is_empty() => break,
+            _ => return vec![],
+        };
+        singular = singular
+            .iter()
+            .cartesian_product(0..digits)
+            .map(|(r, t)| Integer::from(&pi * t) + r)
+            .collect();
+        pi = next;
+    }
+    roots.extend(singular);
+    roots.sort();
+    roots
+}
+
+/// Returns the p-adic valuation of n, the largest v such that p^v divides n
+///
+/// Returns `None` if n is zero, whose valuation is infinite, or if p is not greater than 1.
+pub fn p_adic_valuation(n: &Integer, p: &Integer) -> Option<u32> {
+    if n.is_zero() || *p <= 1 {
+        return None;
+    }
+    Some(n.clone().remove_factor(p).1)
+}
+
+/// Returns the first k digits of the p-adic expansion of n, from the lowest
+///
+/// These are the digits of n mod p^k in base p, so negative integers have the digits of their
+/// p-adic representation, e.g. -1 = (p - 1) + (p - 1) * p + (p - 1) * p^2 + ...
+pub fn p_adic_digits(n: &Integer, p: &Integer, k: u32) -> Vec<Integer> {
+    let mut n = n.clone().rem_euc(Integer::from(p.pow(k)));
+    (0..k)
+        .map(|_| {
+            let (quotient, digit) = std::mem::take(&mut n).div_rem_euc(p.clone());
+            n = quotient;
+            digit
+        })
+        .collect()
+}
+
+/// Bases of the Miller-Rabin test proving primality below 3.3 * 10^24 (about 2^81)
+const DETERMINISTIC_BASES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Largest bit length proven prime with `DETERMINISTIC_BASES`
+const MAX_DETERMINISTIC_BITS: u32 = 81;
+
+/// How primality is tested, for keys, factors and attacks alike
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrimalityPolicy {
+    /// Repetitions of GMP's probabilistic test
+    pub rounds: u32,
+    /// Also run a Baillie-PSW test (strong probable prime to base 2 and strong Lucas probable
+    /// prime), which has no known counterexample
+    pub bpsw: bool,
+    /// Integers of up to this number of bits (at most 81) are proven prime or composite, with a
+    /// deterministic set of Miller-Rabin bases
+    pub prove_bits: u32,
+}
+
+impl PrimalityPolicy {
+    /// Default policy: 100 rounds, without BPSW, integers of up to 64 bits proven
+    pub const DEFAULT: Self = Self {
+        rounds: 100,
+        bpsw: false,
+        prove_bits: 64,
+    };
+
+    /// Test the primality of n with this policy
+    pub fn is_prime(&self, n: &Integer) -> IsPrime {
+        if *n < 2 {
+            return IsPrime::No;
+        }
+        if n.significant_bits() <= self.prove_bits.min(MAX_DETERMINISTIC_BITS) {
+            let prime = DETERMINISTIC_BASES.iter().all(|&base| {
+                *n == base || (!n.is_divisible_u(base) && strong_probable_prime(n, base))
+            });
+            return if prime { IsPrime::Yes } else { IsPrime::No };
+        }
+        if self.bpsw
+            && *n != 2
+            && (n.is_even() || !strong_probable_prime(n, 2) || !strong_lucas_probable_prime(n))
+        {
+            return IsPrime::No;
+        }
+        n.is_probably_prime(self.rounds)
+    }
+}
+
+impl Default for PrimalityPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Policy of `is_prime`
+static PRIMALITY_POLICY: RwLock<PrimalityPolicy> = RwLock::new(PrimalityPolicy::DEFAULT);
+
+/// Set the policy of `is_prime`, for the whole process
+pub fn set_primality_policy(policy: PrimalityPolicy) {
+    *PRIMALITY_POLICY.write().unwrap() = policy;
+}
+
+/// Returns the policy of `is_prime`
+pub fn primality_policy() -> PrimalityPolicy {
+    *PRIMALITY_POLICY.read().unwrap()
+}
+
+/// Test the primality of n with the policy set by `set_primality_policy`
+pub fn is_prime(n: &Integer) -> IsPrime {
+    primality_policy().is_prime(n)
+}
+
+/// Returns true if the odd n > 2 is a strong probable prime to the given base (Miller-Rabin)
+fn strong_probable_prime(n: &Integer, base: u32) -> bool {
+    let n_1 = Integer::from(n - 1);
+    let s = n_1.find_one(0).unwrap_or(0);
+    let mut x = Integer::from(base).pow_mod(&(n_1.clone() >> s), n).unwrap();
+    if x == 1 || x == n_1 {
+        return true;
+    }
+    (1..s).any(|_| {
+        x.square_mut();
+        x %= n;
+        x == n_1
+    })
+}
+
+/// Returns true if the odd n > 2 is a strong Lucas probable prime, with Selfridge's parameters
+///
+/// D is the first of 5, -7, 9, -11, ... with (D / n) = -1, P = 1 and Q = (1 - D) / 4. With
+/// n + 1 = d * 2^s, d odd, either U_d = 0 or V_(d * 2^r) = 0 mod n for some r < s.
+fn strong_lucas_probable_prime(n: &Integer) -> bool {
+    // No D would be found
+    if n.is_perfect_square() {
+        return false;
+    }
+    let mut d = 5i64;
+    loop {
+        match Integer::from(d).jacobi(n) {
+            -1 => break,
+            0 if *n != d.abs() => return false,
+            _ => d = if d > 0 { -d - 2 } else { -d + 2 },
+        }
+    }
+    let q = Integer::from((1 - d) / 4);
+
+    let n_1 = Integer::from(n + 1);
+    let s = n_1.find_one(0).unwrap_or(0);
+    let k = n_1 >> s;
+    // Division by 2 modulo n
+    let half = |x: Integer| {
+        let x = x.modulo(n);
+        if x.is_odd() {
+            (x + n) >> 1
+        } else {
+            x >> 1
+        }
+    };
+
+    let (mut u, mut v, mut q_k) = (Integer::from(1), Integer::from(1), q.clone().modulo(n));
+    for bit in (0..k.significant_bits() - 1).rev() {
+        u = (u * &v) % n;
+        v = (v.square() - Integer::from(&q_k * 2)).modulo(n);
+        q_k = q_k.square() % n;
+        if k.get_bit(bit) {
+            (u, v) = (half(Integer::from(&u + &v)), half(u * d + &v));
+            q_k = (q_k * &q).modulo(n);
+        }
+    }
+    if u == 0 || v == 0 {
+        return true;
+    }
+    (1..s).any(|_| {
+        v.square_mut();
+        v -= Integer::from(&q_k * 2);
+        v.modulo_mut(n);
+        q_k.square_mut();
+        q_k %= n;
+        v == 0
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
 
     #[test]
@@ -98,5 +656,189 @@ mod tests {
             ),
             None
         );
+        assert_eq!(
+            crt_lcm(
+                &[2.into(), 5.into(), 8.into()],
+                &[6.into(), 9.into(), 15.into()]
+            ),
+            Some((68.into(), 90.into()))
+        );
+        // The same congruence twice
+        assert_eq!(
+            crt_lcm(&[4.into(), 4.into()], &[7.into(), 7.into()]),
+            Some((4.into(), 7.into()))
+        );
+    }
+
+    #[test]
+    fn sqrt_mod() {
+        for (a, p) in [(2, 7), (5, 41), (10, 13), (1, 2), (0, 11), (58, 101)] {
+            let p = Integer::from(p);
+            for sqrt in [tonelli_shanks, cipolla, sqrt_mod_prime] {
+                let r = sqrt(&a.into(), &p).unwrap();
+                assert_eq!(r.square() % &p, a);
+            }
+        }
+        assert_eq!(tonelli_shanks(&3.into(), &7.into()), None);
+        assert_eq!(cipolla(&3.into(), &7.into()), None);
+
+        // p - 1 = 3 * 2^30, Cipolla's algorithm is used
+        let p = Integer::from(3221225473u64);
+        let r = sqrt_mod_prime(&1234567.into(), &p).unwrap();
+        assert_eq!(r.square() % &p, 1234567);
+    }
+
+    #[test]
+    fn cipolla_small_numbers() {
+        for (a, p, r) in [(1, 43, 1), (2, 23, 5), (17, 83, 10)] {
+            let root = cipolla(&a.into(), &p.into()).unwrap();
+            assert!(root == r || root == p - r);
+        }
+    }
+
+    #[test]
+    fn cipolla_random_numbers() {
+        for (a, p, r) in [
+            (392203, 852167, 413252),
+            (379606557, 425172197, 143417827),
+            (585251669, 892950901, 192354555),
+            (404690348, 430183399, 57227138),
+            (210205747, 625380647, 76810367),
+        ] {
+            let p = Integer::from(p);
+            let root = cipolla(&a.into(), &p).unwrap();
+            assert!(root == r || root == Integer::from(&p - r));
+        }
+        assert_eq!(cipolla(&650927.into(), &852167.into()), None);
+    }
+
+    #[test]
+    fn sqrt_mod_prime_power() {
+        let p = Integer::from(10007);
+        let a = Integer::from(424242).square();
+        for k in [1, 2, 3, 5] {
+            let pk = Integer::from((&p).pow(k));
+            let r = super::sqrt_mod_prime_power(&a, &p, k).unwrap();
+            assert_eq!(r.square() % &pk, Integer::from(&a % &pk));
+        }
+        assert_eq!(super::sqrt_mod_prime_power(&10007.into(), &p, 2), None);
+        assert_eq!(super::sqrt_mod_prime_power(&5.into(), &p, 2), None);
+    }
+
+    #[test]
+    fn sqrt_mod_factors() {
+        let factors = Factors::from([2, 10007, 10037]);
+        let n = factors.product();
+        let m = Integer::from(424243);
+        let roots = super::sqrt_mod_factors(&m.clone().square(), &factors);
+        assert_eq!(roots.len(), 4);
+        assert!(roots.contains(&(m.clone() % &n)));
+        assert!(roots
+            .iter()
+            .all(|r| r.clone().square() % &n == m.clone().square() % &n));
+
+        // Square roots modulo p^2 * q
+        let factors = Factors::from([10007, 10007, 10037]);
+        let n = factors.product();
+        let roots = super::sqrt_mod_factors(&m.clone().square(), &factors);
+        assert_eq!(roots.len(), 4);
+        assert!(roots.contains(&(m % &n)));
+    }
+
+    #[test]
+    fn nth_roots() {
+        let factors = Factors::from([10007, 10037]);
+        let n = factors.product();
+        let m = Integer::from(424242);
+
+        for e in [2, 4, 6, 16] {
+            let c = m.clone().pow_mod(&e.into(), &n).unwrap();
+            let ms = nth_roots_mod_factors(&c, &e.into(), &factors);
+            assert!(ms.contains(&m), "e = {e}");
+            assert!(ms
+                .iter()
+                .all(|r| r.clone().pow_mod(&e.into(), &n).unwrap() == c));
+        }
+
+        // Roots modulo p^2 * q, lifted from the roots modulo p
+        let factors = Factors::from([10007, 10007, 10037]);
+        let n = factors.product();
+        for e in [2, 3, 6] {
+            let c = m.clone().pow_mod(&e.into(), &n).unwrap();
+            let ms = nth_roots_mod_factors(&c, &e.into(), &factors);
+            assert!(ms.contains(&m), "e = {e}");
+            assert!(ms
+                .iter()
+                .all(|r| r.clone().pow_mod(&e.into(), &n).unwrap() == c));
+        }
+        assert!(nth_roots_mod_factors(&10007.into(), &2.into(), &factors).is_empty());
+    }
+
+    #[test]
+    fn polynomial_roots() {
+        // (x - 3) * (x - 5) * (x - 100) * (x^2 + 1)
+        let f = [-1500, 815, -1608, 816, -108, 1].map(Integer::from);
+        assert_eq!(roots_mod_prime(&f, &10007.into()), [3, 5, 100]);
+        assert_eq!(roots_mod_prime(&f, &13.into()), [3, 5, 8, 9]);
+        assert!(roots_mod_prime(&[0.into(), 10007.into()], &10007.into()).is_empty());
+
+        let p = Integer::from(10007);
+        let p3 = Integer::from((&p).pow(3));
+        assert_eq!(roots_mod_prime_power(&f, &p, 3), [3, 5, 100]);
+        let r = hensel_lift_root(&f, &Integer::from(&p3 + 5), &p, 3).unwrap();
+        assert_eq!(r, 5);
+        assert_eq!(hensel_lift_root(&f, &4.into(), &p, 3), None);
+
+        // x^2 * (x - 1), 0 is a double root
+        let f = [0, 0, -1, 1].map(Integer::from);
+        assert_eq!(roots_mod_prime_power(&f, &3.into(), 3), [0, 1, 9, 18]);
+        assert_eq!(hensel_lift_root(&f, &0.into(), &3.into(), 3), None);
+        assert_eq!(
+            hensel_lift_root(&f, &0.into(), &3.into(), 1),
+            Some(0.into())
+        );
+    }
+
+    #[test]
+    fn p_adic() {
+        assert_eq!(p_adic_valuation(&72.into(), &2.into()), Some(3));
+        assert_eq!(p_adic_valuation(&(-72).into(), &3.into()), Some(2));
+        assert_eq!(p_adic_valuation(&72.into(), &5.into()), Some(0));
+        assert_eq!(p_adic_valuation(&0.into(), &5.into()), None);
+
+        assert_eq!(p_adic_digits(&1234.into(), &10.into(), 5), [4, 3, 2, 1, 0]);
+        assert_eq!(p_adic_digits(&(-1).into(), &5.into(), 3), [4, 4, 4]);
+    }
+
+    #[test]
+    fn primality() {
+        let policy = PrimalityPolicy::default();
+        assert_eq!(policy.is_prime(&1779681653u64.into()), IsPrime::Yes);
+        assert_eq!(policy.is_prime(&2.into()), IsPrime::Yes);
+        assert_eq!(policy.is_prime(&1.into()), IsPrime::No);
+        // Strong pseudoprimes to base 2, and to bases 2, 3, 5 and 7
+        assert_eq!(policy.is_prime(&2047.into()), IsPrime::No);
+        assert_eq!(policy.is_prime(&3215031751u64.into()), IsPrime::No);
+        let p = Integer::from_str("220375572875274133043506876099").unwrap();
+        assert_eq!(policy.is_prime(&p), IsPrime::Probably);
+
+        let bpsw = PrimalityPolicy {
+            rounds: 1,
+            bpsw: true,
+            prove_bits: 0,
+        };
+        assert_eq!(bpsw.is_prime(&p), IsPrime::Probably);
+        assert_eq!(bpsw.is_prime(&3215031751u64.into()), IsPrime::No);
+        assert_eq!(bpsw.is_prime(&(p.clone() * 3)), IsPrime::No);
+
+        // Strong Lucas pseudoprimes, not strong pseudoprimes to base 2
+        for n in [5459, 5777, 10877, 16109, 18971] {
+            let n = Integer::from(n);
+            assert!(strong_lucas_probable_prime(&n), "{n}");
+            assert!(!strong_probable_prime(&n, 2), "{n}");
+            assert_eq!(bpsw.is_prime(&n), IsPrime::No);
+        }
+        assert!(strong_lucas_probable_prime(&p));
+        assert!(!strong_lucas_probable_prime(&2047.into()));
     }
 }
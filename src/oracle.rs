@@ -0,0 +1,202 @@
+use std::{
+    fmt::Display,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpListener,
+};
+
+use openssl::rsa::Rsa;
+use rug::Integer;
+
+use crate::{IntegerArg, KeyError, PrivateKey};
+
+/// Leak of a deliberately vulnerable decryption oracle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OracleKind {
+    /// Least significant bit of the plaintext
+    #[default]
+    Parity,
+    /// Whether the plaintext is PKCS#1 v1.5 conformant, i.e. starts with 0x00 0x02
+    Pkcs1,
+}
+
+impl std::str::FromStr for OracleKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "parity" | "lsb" => Ok(Self::Parity),
+            "pkcs1" | "padding" => Ok(Self::Pkcs1),
+            _ => Err(format!("Unknown oracle: {s}")),
+        }
+    }
+}
+
+impl Display for OracleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parity => write!(f, "parity"),
+            Self::Pkcs1 => write!(f, "pkcs1"),
+        }
+    }
+}
+
+/// Generate a private key with a modulus of the given size, and e = 65537
+pub fn generate_key(bits: u32) -> Result<PrivateKey, KeyError> {
+    let rsa = Rsa::generate(bits).or(Err(KeyError::InvalidKey))?;
+    let integer = |bn: Option<&openssl::bn::BigNumRef>| {
+        bn.map(|bn| Integer::from_digits(&bn.to_vec(), rug::integer::Order::Msf))
+    };
+    match (integer(rsa.p()), integer(rsa.q())) {
+        (Some(p), Some(q)) => PrivateKey::from_p_q(p, q, 65537),
+        _ => Err(KeyError::InvalidKey),
+    }
+}
+
+/// Returns the answer of the oracle to the cipher c
+pub fn oracle_answer(pk: &PrivateKey, kind: OracleKind, c: &Integer) -> bool {
+    let m = pk.decrypt(c);
+    match kind {
+        OracleKind::Parity => m.is_odd(),
+        OracleKind::Pkcs1 => {
+            // 2 * B <= m < 3 * B, with B = 2^(8 * (k - 2)) and k the size of n in bytes
+            let k = pk.n.significant_bits().div_ceil(8);
+            if k < 2 {
+                return false;
+            }
+            let b = Integer::from(1) << (8 * (k - 2));
+            m >= Integer::from(&b * 2) && m < b * 3
+        }
+    }
+}
+
+/// Answer the ciphers read line by line, "1" or "0" for each of them
+///
+/// The ciphers are parsed as the command line integers, e.g. decimal or 0x prefixed hexadecimal.
+pub fn answer_ciphers(
+    pk: &PrivateKey,
+    kind: OracleKind,
+    reader: impl BufRead,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        let answer = match line.trim().parse::<IntegerArg>() {
+            Ok(IntegerArg(c)) if c >= 0 && c < pk.n => {
+                if oracle_answer(pk, kind, &c) {
+                    "1"
+                } else {
+                    "0"
+                }
+            }
+            _ => "error: invalid cipher",
+        };
+        writeln!(writer, "{answer}")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Serve the oracle to each client of the listener, in its own thread
+pub fn serve_oracle(listener: TcpListener, pk: &PrivateKey, kind: OracleKind) -> io::Result<()> {
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            scope.spawn(move || {
+                let reader = BufReader::new(stream.try_clone()?);
+                answer_ciphers(pk, kind, reader, stream)
+            });
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{bytes_to_integer, cipher_file_to_integer, integer_to_bytes_padded, Parameters};
+
+    use super::*;
+
+    fn private_key() -> PrivateKey {
+        let p = Integer::from(1u64 << 63).next_prime();
+        let q = (Integer::from(1u64 << 63) + (1u64 << 40)).next_prime();
+        PrivateKey::from_p_q(p, q, 65537).unwrap()
+    }
+
+    #[test]
+    fn oracle_answer() {
+        let pk = private_key();
+        let encrypt = |m: &Integer| m.clone().pow_mod(&pk.e, &pk.n).unwrap();
+
+        assert!(super::oracle_answer(
+            &pk,
+            OracleKind::Parity,
+            &encrypt(&1.into())
+        ));
+        assert!(!super::oracle_answer(
+            &pk,
+            OracleKind::Parity,
+            &encrypt(&42.into())
+        ));
+
+        let k = pk.n.significant_bits().div_ceil(8) as usize;
+        let mut padded = vec![0, 2];
+        padded.resize(k - 1, 0xff);
+        padded.push(0);
+        assert!(super::oracle_answer(
+            &pk,
+            OracleKind::Pkcs1,
+            &encrypt(&bytes_to_integer(&padded))
+        ));
+        padded[1] = 3;
+        assert!(!super::oracle_answer(
+            &pk,
+            OracleKind::Pkcs1,
+            &encrypt(&bytes_to_integer(&padded))
+        ));
+        assert!(!super::oracle_answer(
+            &pk,
+            OracleKind::Pkcs1,
+            &encrypt(&1.into())
+        ));
+    }
+
+    #[test]
+    fn padded_ciphers() {
+        let params =
+            Parameters::from_private_key(include_bytes!("../tests/keys/private_rsa.pem"), None)
+                .unwrap();
+        let pk = PrivateKey::from_p_q(params.p.unwrap(), params.q.unwrap(), params.e).unwrap();
+        let k = pk.n.significant_bits().div_ceil(8) as usize;
+        let decrypt = |bytes: &[u8]| {
+            let c = cipher_file_to_integer(bytes);
+            (integer_to_bytes_padded(&pk.decrypt(&c), k), c)
+        };
+
+        let (m, c) = decrypt(include_bytes!("../tests/keys/cipher_textbook.bin"));
+        assert!(m.ends_with(b"RsaCracker!"));
+        assert!(!super::oracle_answer(&pk, OracleKind::Pkcs1, &c));
+
+        let (m, c) = decrypt(include_bytes!("../tests/keys/cipher_pkcs1.bin"));
+        assert!(m.starts_with(&[0, 2]) && m.ends_with(b"\0RsaCracker!"));
+        assert!(super::oracle_answer(&pk, OracleKind::Pkcs1, &c));
+
+        let (m, _) = decrypt(include_bytes!("../tests/keys/cipher_oaep.bin"));
+        assert_eq!(m[0], 0);
+        assert!(!m.ends_with(b"RsaCracker!"));
+    }
+
+    #[test]
+    fn answer_ciphers() {
+        let pk = private_key();
+        let c = Integer::from(3).pow_mod(&pk.e, &pk.n).unwrap();
+        let input = format!("{c}\n0x{c:x}\n0\nfoo\n{}\n", pk.n);
+        let mut output = vec![];
+        super::answer_ciphers(&pk, OracleKind::Parity, Cursor::new(input), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "1\n1\n0\nerror: invalid cipher\nerror: invalid cipher\n"
+        );
+    }
+}
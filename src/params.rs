@@ -1,6 +1,11 @@
 use base64::{engine::general_purpose, Engine};
-use rug::Integer;
+use indicatif::ProgressBar;
+use rayon::prelude::*;
+use rug::{integer::Order, Integer};
+
+use crate::{reverse_bytes, KeyError, PartialInteger};
 use std::{
+    collections::BTreeMap,
     fmt::Display,
     ops::{Add, AddAssign},
     str::FromStr,
@@ -8,17 +13,63 @@ use std::{
 
 #[derive(Debug, Clone)]
 /// Struct used to parse integers from different bases and formats
+///
+/// A leading `-` or `+` sign applies to the value in any format, e.g. `-0x10` is -16.
+/// `text:hello` is the integer of the bytes of `hello` (quotes around the text are removed),
+/// and `file:path` the integer of the content of the file at `path`, raw or PEM encoded.
+///
+/// Hexadecimal values may be pasted from dumps: digits grouped by spaces, colons, commas or
+/// underscores (`0xdead:beef`), bytes without prefix (`de:ad:be:ef` or `de ad be ef`), or a list of
+/// words (`[0x1234, 0x5678]`), each padded to the size of the largest one. A `@le` suffix gives
+/// the bytes (or the words of a list) of a hexadecimal, base64 or text value least significant
+/// first, `@be` the default most significant first.
 pub struct IntegerArg(pub Integer);
 
-impl std::str::FromStr for IntegerArg {
-    type Err = String;
+/// Separators of the groups of hexadecimal digits of a dump
+const HEX_SEPARATORS: [char; 4] = [' ', ':', ',', '_'];
 
-    fn from_str(n: &str) -> Result<Self, Self::Err> {
-        if let Some(n) = n.strip_prefix("0x").or_else(|| n.strip_prefix("0X")) {
-            Ok(Self(
-                Integer::from_str_radix(n, 16).or(Err("Invalid hex number".to_string()))?,
-            ))
-        } else if let Some(n) = n.strip_prefix("0b").or_else(|| n.strip_prefix("0B")) {
+impl IntegerArg {
+    /// Parse a value whose bytes are in the given order
+    fn parse(n: &str, order: Order) -> Result<Self, String> {
+        if let Some(text) = n.strip_prefix("text:") {
+            let text = ['"', '\'']
+                .iter()
+                .find_map(|&quote| text.strip_prefix(quote)?.strip_suffix(quote))
+                .unwrap_or(text);
+            return Ok(Self(Integer::from_digits(text.as_bytes(), order)));
+        }
+        if let Some(n) = n.strip_prefix("b64").or_else(|| n.strip_prefix("B64")) {
+            let bytes = general_purpose::STANDARD
+                .decode(n.as_bytes())
+                .or(Err("Invalid base64 number".to_string()))?;
+            return Ok(Self(Integer::from_digits(&bytes, order)));
+        }
+        if let Some(value) = Self::parse_hex_dump(n, order) {
+            return value.map(Self);
+        }
+
+        // Signed value
+        if let Some((sign, unsigned)) = n
+            .strip_prefix('-')
+            .map(|n| (-1, n))
+            .or_else(|| n.strip_prefix('+').map(|n| (1, n)))
+        {
+            if unsigned.starts_with(['-', '+']) {
+                return Err("Invalid number".to_string());
+            }
+            return Ok(Self(Self::parse(unsigned, order)?.0 * sign));
+        }
+        if order == Order::Lsf {
+            return Err("Byte order of a value neither hexadecimal, base64 nor text".to_string());
+        }
+        if let Some(path) = n.strip_prefix("file:") {
+            let bytes =
+                std::fs::read(path).map_err(|err| format!("Failed to read {path}: {err}"))?;
+            return Ok(Self(crate::cipher_file_to_integer(&bytes)));
+        }
+
+        // Hexadecimal values are parsed as dumps, see `parse_hex_dump`
+        if let Some(n) = n.strip_prefix("0b").or_else(|| n.strip_prefix("0B")) {
             Ok(Self(
                 Integer::from_str_radix(n, 2).or(Err("Invalid binary number".to_string()))?,
             ))
@@ -26,17 +77,100 @@ impl std::str::FromStr for IntegerArg {
             Ok(Self(
                 Integer::from_str_radix(n, 8).or(Err("Invalid octal number".to_string()))?,
             ))
-        } else if let Some(n) = n.strip_prefix("b64").or_else(|| n.strip_prefix("B64")) {
-            let bytes = general_purpose::STANDARD
-                .decode(n.as_bytes())
-                .or(Err("Invalid base64 number".to_string()))?;
-            Ok(Self(Integer::from_digits(&bytes, rug::integer::Order::Msf)))
         } else {
             Ok(Self(
                 Integer::from_str(n).or(Err("Invalid number".to_string()))?,
             ))
         }
     }
+
+    /// Parse a hexadecimal value with grouped digits, unprefixed bytes or a list of words
+    ///
+    /// Returns `None` if the value is not one of them, to be parsed as another format.
+    fn parse_hex_dump(n: &str, order: Order) -> Option<Result<Integer, String>> {
+        let list = n
+            .trim_start_matches(['[', '{'])
+            .trim_end_matches([']', '}']);
+        let groups = list
+            .split(HEX_SEPARATORS)
+            .filter(|group| !group.is_empty())
+            .collect::<Vec<_>>();
+        fn unprefixed(group: &str) -> Option<&str> {
+            group
+                .strip_prefix("0x")
+                .or_else(|| group.strip_prefix("0X"))
+        }
+        let is_hex = |group: &str| group.chars().all(|c| c.is_ascii_hexdigit());
+
+        let words = if groups.len() > 1 && groups.iter().all(|group| unprefixed(group).is_some()) {
+            groups
+                .iter()
+                .filter_map(|group| unprefixed(group))
+                .collect()
+        } else if let Some(first) = groups.first().and_then(|group| unprefixed(group)) {
+            // A single number, its groups are only there for readability
+            let digits = [first].into_iter().chain(groups[1..].iter().copied());
+            let mut digits = digits.collect::<String>();
+            if digits.len() % 2 == 1 {
+                digits.insert(0, '0');
+            }
+            let bytes = (0..digits.len())
+                .step_by(2)
+                .map(|i| digits.get(i..i + 2).unwrap_or_default())
+                .collect::<Vec<_>>();
+            return Some(Self::hex_words(&bytes, order));
+        } else if groups.len() > 1 && groups.iter().all(|group| group.len() == 2 && is_hex(group)) {
+            groups
+        } else {
+            return None;
+        };
+        Some(Self::hex_words(&words, order))
+    }
+
+    /// Concatenate hexadecimal words, padded to the size of the largest one
+    fn hex_words(words: &[&str], order: Order) -> Result<Integer, String> {
+        let width = words.iter().map(|word| word.len()).max().unwrap_or(0);
+        let width = width + width % 2;
+        let mut digits = String::with_capacity(width * words.len());
+        let mut append = |word: &&str| {
+            digits.extend(std::iter::repeat_n('0', width - word.len()));
+            digits.push_str(word);
+        };
+        if order == Order::Lsf {
+            words.iter().rev().for_each(&mut append);
+        } else {
+            words.iter().for_each(&mut append);
+        }
+        if digits.is_empty() {
+            return Err("Invalid hex number".to_string());
+        }
+        Integer::from_str_radix(&digits, 16).or(Err("Invalid hex number".to_string()))
+    }
+}
+
+impl std::str::FromStr for IntegerArg {
+    type Err = String;
+
+    fn from_str(n: &str) -> Result<Self, Self::Err> {
+        if let Some(n) = n.strip_suffix("@le") {
+            Self::parse(n, Order::Lsf)
+        } else {
+            Self::parse(n.strip_suffix("@be").unwrap_or(n), Order::Msf)
+        }
+    }
+}
+
+/// Public key and cipher of one of several keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEntry {
+    /// Modulus.
+    pub n: Integer,
+    /// Public exponent.
+    pub e: Integer,
+    /// Cipher message encrypted with this key.
+    pub c: Option<Integer>,
+    /// Other known values of this key, e.g. its factors or private exponent.
+    pub known: Parameters,
 }
 
 /// Known parameters
@@ -66,6 +200,38 @@ pub struct Parameters {
     pub pinv: Option<Integer>,
     /// The sum of the two primes p and q.
     pub sum_pq: Option<Integer>,
+    /// The sum of the squares of p and q. (p^2 + q^2)
+    pub sum_squares_pq: Option<Integer>,
+    /// The difference of the two primes p and q. (|p - q|)
+    pub diff_pq: Option<Integer>,
+    /// The xor of the two primes p and q. (p ^ q)
+    pub xor_pq: Option<Integer>,
+    /// The sum of p and q modulo a given modulus, as (value, modulus).
+    pub sum_pq_mod: Option<(Integer, Integer)>,
+    /// The product of p and q modulo a given modulus, as (value, modulus).
+    pub pq_mod: Option<(Integer, Integer)>,
+    /// One of the primes modulo a given modulus, e.g. its last decimal digits, as (value, modulus).
+    pub p_mod: Option<(Integer, Integer)>,
+    /// Known plaintext, whose cipher under the key is `known_c`.
+    pub known_m: Option<Integer>,
+    /// Cipher of the known plaintext `known_m`.
+    pub known_c: Option<Integer>,
+    /// Known message, whose textbook signature under the key is `signature`.
+    pub signed_m: Option<Integer>,
+    /// Textbook signature of the known message `signed_m`. (signed_m^d mod n)
+    pub signature: Option<Integer>,
+    /// Prime p with unknown bits.
+    pub partial_p: Option<PartialInteger>,
+    /// Prime q with unknown bits.
+    pub partial_q: Option<PartialInteger>,
+    /// dP with unknown bits.
+    pub partial_dp: Option<PartialInteger>,
+    /// dQ with unknown bits.
+    pub partial_dq: Option<PartialInteger>,
+    /// Message with unknown bits.
+    pub partial_m: Option<PartialInteger>,
+    /// All keys and their ciphers, when several are given. The first one is also in n, e and c.
+    pub keys: Vec<KeyEntry>,
 }
 
 impl Default for Parameters {
@@ -83,84 +249,245 @@ impl Default for Parameters {
             qinv: None,
             pinv: None,
             sum_pq: None,
+            sum_squares_pq: None,
+            diff_pq: None,
+            xor_pq: None,
+            sum_pq_mod: None,
+            pq_mod: None,
+            p_mod: None,
+            known_m: None,
+            known_c: None,
+            signed_m: None,
+            signature: None,
+            partial_p: None,
+            partial_q: None,
+            partial_dp: None,
+            partial_dq: None,
+            partial_m: None,
+            keys: vec![],
         }
     }
 }
 
 impl Display for Parameters {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut s = String::new();
-
-        if let Some(n) = &self.n {
-            s += &format!("n = {}\n", n);
+        let mut lines = self
+            .fields()
+            .iter()
+            .map(|(name, value)| format!("{name} = {value}"))
+            .collect::<Vec<_>>();
+        for (name, hint) in [
+            ("sum_pq_mod", &self.sum_pq_mod),
+            ("pq_mod", &self.pq_mod),
+            ("p_mod", &self.p_mod),
+        ] {
+            if let Some((value, modulus)) = hint {
+                lines.push(format!("{name} = {value} mod {modulus}"));
+            }
         }
-        s += &format!("e = {}\n", self.e);
-        if let Some(c) = &self.c {
-            s += &format!("c = {}\n", c);
+        for (name, partial) in [
+            ("p", &self.partial_p),
+            ("q", &self.partial_q),
+            ("dp", &self.partial_dp),
+            ("dq", &self.partial_dq),
+            ("m", &self.partial_m),
+        ] {
+            if let Some(partial) = partial {
+                lines.push(format!("{name} = {partial}"));
+            }
         }
-        if let Some(p) = &self.p {
-            s += &format!("p = {}\n", p);
+        for (i, key) in self.keys.iter().enumerate() {
+            lines.push(format!("n{i} = {}", key.n));
+            lines.push(format!("e{i} = {}", key.e));
+            if let Some(c) = &key.c {
+                lines.push(format!("c{i} = {c}"));
+            }
+            for (name, value) in key.known.fields() {
+                if !["n", "e", "c"].contains(&name) {
+                    lines.push(format!("{name}{i} = {value}"));
+                }
+            }
         }
-        if let Some(q) = &self.q {
-            s += &format!("q = {}\n", q);
+        let s = lines.join("\n");
+        write!(f, "{}", s)
+    }
+}
+
+impl Parameters {
+    /// Create parameters from several public keys and their ciphers, paired by index
+    ///
+    /// The first key and cipher are also used as n, e and c.
+    /// Returns `None` if there are more ciphers than keys.
+    pub fn from_keys(keys: Vec<Parameters>, ciphers: Vec<Integer>) -> Option<Self> {
+        if ciphers.len() > keys.len() {
+            return None;
         }
-        if let Some(d) = &self.d {
-            s += &format!("d = {}\n", d);
+
+        let mut ciphers = ciphers.into_iter();
+        let entries = keys
+            .iter()
+            .map(|key| {
+                Some(KeyEntry {
+                    n: key.n.clone()?,
+                    e: key.e.clone(),
+                    c: ciphers.next(),
+                    known: key.clone(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut params = keys.into_iter().next().unwrap_or_default();
+        params.c = entries.first().and_then(|entry| entry.c.clone());
+        if entries.len() > 1 {
+            params.keys = entries;
         }
-        if let Some(phi) = &self.phi {
-            s += &format!("phi = {}\n", phi);
+        Some(params)
+    }
+
+    /// Returns the name and value of all set parameters, e included
+    pub fn fields(&self) -> Vec<(&'static str, &Integer)> {
+        [
+            ("n", self.n.as_ref()),
+            ("e", Some(&self.e)),
+            ("c", self.c.as_ref()),
+            ("p", self.p.as_ref()),
+            ("q", self.q.as_ref()),
+            ("d", self.d.as_ref()),
+            ("phi", self.phi.as_ref()),
+            ("dp", self.dp.as_ref()),
+            ("dq", self.dq.as_ref()),
+            ("qinv", self.qinv.as_ref()),
+            ("pinv", self.pinv.as_ref()),
+            ("sum_pq", self.sum_pq.as_ref()),
+            ("sum_squares_pq", self.sum_squares_pq.as_ref()),
+            ("diff_pq", self.diff_pq.as_ref()),
+            ("xor_pq", self.xor_pq.as_ref()),
+            ("known_m", self.known_m.as_ref()),
+            ("known_c", self.known_c.as_ref()),
+            ("signed_m", self.signed_m.as_ref()),
+            ("signature", self.signature.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .collect()
+    }
+
+    /// Returns the parameters completed with the values trivially derived from them
+    ///
+    /// n is derived from p and q, p or q from n and the other factor, and phi from p and q.
+    pub fn derived(&self) -> Self {
+        let mut params = self.clone();
+        if let (None, Some(p), Some(q)) = (&params.n, &params.p, &params.q) {
+            params.n = Some(Integer::from(p * q));
         }
-        if let Some(dp) = &self.dp {
-            s += &format!("dp = {}\n", dp);
+        if let (Some(n), Some(p), None) = (&params.n, &params.p, &params.q) {
+            if *p > 1 && n.is_divisible(p) {
+                params.q = Some(Integer::from(n / p));
+            }
         }
-        if let Some(dq) = &self.dq {
-            s += &format!("dq = {}\n", dq);
+        if let (Some(n), None, Some(q)) = (&params.n, &params.p, &params.q) {
+            if *q > 1 && n.is_divisible(q) {
+                params.p = Some(Integer::from(n / q));
+            }
         }
-        if let Some(qinv) = &self.qinv {
-            s += &format!("qinv = {}\n", qinv);
+        if let (None, Some(n), Some(p), Some(q)) = (&params.phi, &params.n, &params.p, &params.q) {
+            if Integer::from(p * q) == *n {
+                params.phi = Some(Integer::from(p - 1) * Integer::from(q - 1));
+            }
         }
-        if let Some(pinv) = &self.pinv {
-            s += &format!("pinv = {}\n", pinv);
+        params
+    }
+
+    /// Check the hints on the relationship between p and q against n
+    ///
+    /// p + q, |p - q| and p^2 + q^2 must give a square discriminant with n, p ^ q must be even
+    /// for an odd n, and the moduli of the hints modulo m must be greater than 1.
+    /// Returns an error naming the first inconsistent hint.
+    pub fn check_pq_hints(&self) -> Result<(), String> {
+        let is_square = |value: Integer| value >= 0 && value.is_perfect_square();
+        if let Some(n) = &self.n {
+            let four_n = Integer::from(n * 4u32);
+            // (p - q)^2 = (p + q)^2 - 4n, and (p + q)^2 = (p - q)^2 + 4n = p^2 + q^2 + 2n
+            let squares = [
+                (
+                    "sum_pq",
+                    self.sum_pq
+                        .as_ref()
+                        .map(|s| Integer::from(s.square_ref()) - &four_n),
+                ),
+                (
+                    "diff_pq",
+                    self.diff_pq
+                        .as_ref()
+                        .map(|d| Integer::from(d.square_ref()) + &four_n),
+                ),
+                (
+                    "sum_squares_pq",
+                    self.sum_squares_pq
+                        .as_ref()
+                        .map(|s2| Integer::from(n * 2u32) + s2),
+                ),
+            ];
+            for (name, square) in squares {
+                if square.is_some_and(|square| !is_square(square)) {
+                    return Err(format!("{name} is inconsistent with n"));
+                }
+            }
+            if let Some(xor) = &self.xor_pq {
+                if n.is_odd() && xor.is_odd() {
+                    return Err("xor_pq is inconsistent with n".to_string());
+                }
+            }
+            if let Some((value, m)) = &self.pq_mod {
+                if *m > 1 && Integer::from(n - value).modulo(m) != 0 {
+                    return Err("pq_mod is inconsistent with n".to_string());
+                }
+            }
         }
-        if let Some(sum_pq) = &self.sum_pq {
-            s += &format!("sum_pq = {}\n", sum_pq);
+        if self.xor_pq.as_ref().is_some_and(|xor| *xor < 0) {
+            return Err("xor_pq must not be negative".to_string());
         }
-
-        // Remove trailing newline
-        if s.ends_with('\n') {
-            s.pop();
+        for (name, hint) in [
+            ("sum_pq_mod", &self.sum_pq_mod),
+            ("pq_mod", &self.pq_mod),
+            ("p_mod", &self.p_mod),
+        ] {
+            if hint.as_ref().is_some_and(|(_, m)| *m <= 1) {
+                return Err(format!("The modulus of {name} must be greater than 1"));
+            }
         }
-
-        write!(f, "{}", s)
+        Ok(())
     }
-}
 
-impl Parameters {
     /// Create parameters from raw file
     ///
     /// # Example
     ///
-    /// ```
+    /// ```text
     /// // Example of a raw file
     /// n = 1
     /// # This is a comment
     /// e: 0x1
     /// C 0x00
     /// phi: 0x1
+    /// // Values of several keys are suffixed with the index of their key
+    /// n1 = 0x1
+    /// p1 = 0x1
     /// ```
     pub fn from_raw(raw: &str) -> Self {
         let mut params = Self::default();
+        // Values of each key, e is 0 until given
+        let mut keys = BTreeMap::<usize, Parameters>::new();
 
-        for line in raw.lines() {
+        let mut lines = raw.lines().peekable();
+        while let Some(line) = lines.next() {
             let line = line.trim();
             if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
                 continue;
             }
 
-            let (key, value) = if let Some(idx) = line.find(':') {
-                let (key, value) = line.split_at(idx);
-                (key.trim(), value[1..].trim())
-            } else if let Some(idx) = line.find('=') {
+            // The first separator, values may contain colons, e.g. `n = de:ad:be:ef`
+            let (key, value) = if let Some(idx) = line.find([':', '=']) {
                 let (key, value) = line.split_at(idx);
                 (key.trim(), value[1..].trim())
             } else {
@@ -170,6 +497,52 @@ impl Parameters {
             // Clean up key
             let key = key.replace("_", "").replace("-", "");
 
+            // Dump of `openssl rsa -text`: the header's bit length is skipped, values are given
+            // in decimal followed by their hexadecimal, or as hexadecimal bytes on the next lines
+            if value.starts_with('(') {
+                continue;
+            }
+            let dump;
+            let value = match value.split_once(" (") {
+                Some((value, hex)) if hex.ends_with(')') => value,
+                _ if value.is_empty() => {
+                    let hex =
+                        std::iter::from_fn(|| lines.next_if(|line| is_openssl_dump_hex(line)))
+                            .map(|line| line.trim().replace(':', ""))
+                            .collect::<String>();
+                    dump = format!("0x{hex}");
+                    if hex.is_empty() {
+                        value
+                    } else {
+                        &dump
+                    }
+                }
+                _ => value,
+            };
+
+            if let Some((value, modulus)) = value.split_once(" mod ") {
+                let hint = IntegerArg::from_str(value.trim())
+                    .and_then(|value| Ok((value.0, IntegerArg::from_str(modulus.trim())?.0)));
+                match (key.to_lowercase().as_str(), hint) {
+                    ("sumpq" | "sumpqmod", Ok(hint)) => params.sum_pq_mod = Some(hint),
+                    ("n" | "pq" | "pqmod", Ok(hint)) => params.pq_mod = Some(hint),
+                    ("p" | "q" | "pmod" | "qmod", Ok(hint)) => params.p_mod = Some(hint),
+                    _ => eprintln!("Warning: Failed to parse {} value: {}", key, value),
+                }
+                continue;
+            }
+            if value.contains('?') {
+                match (key.to_lowercase().as_str(), PartialInteger::from_str(value)) {
+                    ("p", Ok(partial)) => params.partial_p = Some(partial),
+                    ("q", Ok(partial)) => params.partial_q = Some(partial),
+                    ("dp" | "dmp1", Ok(partial)) => params.partial_dp = Some(partial),
+                    ("dq" | "dmq1", Ok(partial)) => params.partial_dq = Some(partial),
+                    ("m", Ok(partial)) => params.partial_m = Some(partial),
+                    _ => eprintln!("Warning: Failed to parse {} value: {}", key, value),
+                }
+                continue;
+            }
+
             let value = if let Ok(value) = IntegerArg::from_str(value) {
                 value.0
             } else {
@@ -177,31 +550,137 @@ impl Parameters {
                 continue;
             };
 
-            match key.to_lowercase().as_str() {
-                "n" => params.n = Some(value),
-                "e" => params.e = value,
-                "c" => params.c = Some(value),
-                "p" => params.p = Some(value),
-                "q" => params.q = Some(value),
-                "d" => params.d = Some(value),
-                "phi" => params.phi = Some(value),
-                "dp" | "dmp1" => params.dp = Some(value),
-                "dq" | "dmq1" => params.dq = Some(value),
-                "qinv" | "iqmp" => params.qinv = Some(value),
-                "pinv" | "ipmq" => params.pinv = Some(value),
-                "sumpq" => params.sum_pq = Some(value),
-                _ => {}
+            let key = key.to_lowercase();
+            let Err(value) = params.set_field(&key, value) else {
+                continue;
+            };
+            // Value of one of several keys, e.g. n1, c1 or p1
+            let name = key.trim_end_matches(|c: char| c.is_ascii_digit());
+            if let Ok(index) = key[name.len()..].parse::<usize>() {
+                let entry = keys.entry(index).or_insert_with(|| Parameters {
+                    e: Integer::ZERO,
+                    ..Default::default()
+                });
+                if name.is_empty() || entry.set_field(name, value).is_err() {
+                    eprintln!("Warning: Unknown key {key}");
+                }
             }
         }
 
+        let keys = keys
+            .into_iter()
+            .filter_map(|(index, mut key)| {
+                if key.e == 0 {
+                    key.e = params.e.clone();
+                }
+                let Some(n) = key.n.clone() else {
+                    eprintln!("Warning: Missing n{index}, values of key {index} are ignored");
+                    return None;
+                };
+                Some(KeyEntry {
+                    n,
+                    e: key.e.clone(),
+                    c: key.c.clone(),
+                    known: key,
+                })
+            })
+            .collect::<Vec<_>>();
+        // The first key is also used as n, e and c
+        if let Some(first) = keys.first() {
+            params += first.known.clone();
+        }
+        if keys.len() > 1 {
+            params.keys = keys;
+        }
+
         params
     }
 
+    /// Set the value of a field of a raw file by name, e.g. `n`, `phi` or `dmp1`
+    ///
+    /// The names of `openssl rsa -text` dumps are also known, e.g. `modulus` or `prime1`.
+    /// Returns the value back if the name is unknown.
+    fn set_field(&mut self, name: &str, value: Integer) -> Result<(), Integer> {
+        match name {
+            "n" | "modulus" => self.n = Some(value),
+            "e" | "publicexponent" | "exponent" => self.e = value,
+            "c" => self.c = Some(value),
+            "p" | "prime1" => self.p = Some(value),
+            "q" | "prime2" => self.q = Some(value),
+            "d" | "privateexponent" => self.d = Some(value),
+            "phi" => self.phi = Some(value),
+            "dp" | "dmp1" | "exponent1" => self.dp = Some(value),
+            "dq" | "dmq1" | "exponent2" => self.dq = Some(value),
+            "qinv" | "iqmp" | "coefficient" => self.qinv = Some(value),
+            "pinv" | "ipmq" => self.pinv = Some(value),
+            "sumpq" => self.sum_pq = Some(value),
+            "sumsquarespq" | "p2q2" => self.sum_squares_pq = Some(value),
+            "diffpq" | "pminusq" => self.diff_pq = Some(value),
+            "xorpq" | "pxorq" => self.xor_pq = Some(value),
+            "knownm" => self.known_m = Some(value),
+            "knownc" => self.known_c = Some(value),
+            "signedm" => self.signed_m = Some(value),
+            "signature" | "sig" => self.signature = Some(value),
+            _ => return Err(value),
+        }
+        Ok(())
+    }
+
+    /// Reduce the cipher modulo n if it is negative or not smaller than n
+    ///
+    /// Returns true if the cipher was reduced.
+    pub fn reduce_cipher(&mut self) -> bool {
+        match (&mut self.c, &self.n) {
+            (Some(c), Some(n)) if *n > 0 && (*c < 0 || *c >= *n) => {
+                c.modulo_mut(n);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the parameters with the bytes of c and n reversed, for dumps storing them
+    /// little-endian
+    pub fn byte_swapped(&self) -> Self {
+        Self {
+            n: self.n.as_ref().map(reverse_bytes),
+            c: self.c.as_ref().map(reverse_bytes),
+            ..self.clone()
+        }
+    }
+
     /// Create parameters from public key
+    ///
+    /// The public keys of a file of several PEM blocks are merged, see `from_pem_bundle`.
     pub fn from_public_key(key: &[u8]) -> Option<Self> {
+        let blocks = pem_blocks(key);
+        if blocks.len() > 1 {
+            let keys = blocks
+                .iter()
+                .filter_map(|block| Self::from_public_key(block.as_bytes()));
+            if let Some(params) = Self::merge_keys(keys) {
+                return Some(params);
+            }
+        }
+
         Self::from_rsa_public_key(key)
             .or_else(|| Self::from_x509_cert(key))
             .or_else(|| Self::from_openssh_public_key(key))
+            .or_else(|| Self::from_dnskey(key))
+            .or_else(|| {
+                let params = Self::from_jwk(key).or_else(|| Self::from_xml_key(key))?;
+                Some(Self {
+                    n: params.n,
+                    e: params.e,
+                    ..Default::default()
+                })
+            })
+    }
+
+    /// Create parameters from the first RSA key of DNSKEY records, in zone file format
+    pub fn from_dnskey(key: &[u8]) -> Option<Self> {
+        let zone = std::str::from_utf8(key).ok()?;
+        crate::parse_dnskeys(zone).into_iter().next()
     }
 
     /// Create parameters from rsa public key
@@ -253,6 +732,13 @@ impl Parameters {
                 } else {
                     None
                 }
+            })
+            .or_else(|| {
+                // The public part of openssh private keys is never encrypted
+                ssh_key::private::PrivateKey::from_openssh(key)
+                    .or_else(|_| ssh_key::private::PrivateKey::from_bytes(key))
+                    .ok()
+                    .map(|key| key.public_key().clone())
             })?;
         let rsa = public_key.key_data().rsa()?;
 
@@ -267,42 +753,115 @@ impl Parameters {
     }
 
     /// Create parameters from private key
-    pub fn from_private_key(key: &[u8], passphrase: Option<&str>) -> Option<Self> {
-        Self::from_rsa_private_key(key, passphrase)
-            .or_else(|| Self::from_openssh_private_key(key, passphrase))
+    ///
+    /// Returns `KeyError::MissingPassphrase` if the key is encrypted and no passphrase is given,
+    /// or `KeyError::WrongPassphrase` if the passphrase is wrong.
+    /// The keys of a file of several PEM blocks are merged, see `from_pem_bundle`.
+    pub fn from_private_key(key: &[u8], passphrase: Option<&str>) -> Result<Self, KeyError> {
+        if pem_blocks(key).len() > 1 {
+            let params = Self::from_pem_bundle(key, passphrase)?;
+            return match params.d {
+                Some(_) => Ok(params),
+                None => Err(KeyError::InvalidKey),
+            };
+        }
+
+        match Self::from_rsa_private_key(key, passphrase) {
+            Err(KeyError::InvalidKey) => match Self::from_openssh_private_key(key, passphrase) {
+                Err(KeyError::InvalidKey) => Self::from_jwk(key)
+                    .or_else(|| Self::from_xml_key(key))
+                    .filter(|params| params.d.is_some())
+                    .ok_or(KeyError::InvalidKey),
+                res => res,
+            },
+            res => res,
+        }
+    }
+
+    /// Create parameters from a file of several PEM blocks, e.g. a certificate chain and its key
+    ///
+    /// The RSA keys of all blocks are merged, the values of the private keys taking priority.
+    /// Keys of other moduli, such as the other certificates of a chain, become extra keys.
+    pub fn from_pem_bundle(key: &[u8], passphrase: Option<&str>) -> Result<Self, KeyError> {
+        let mut keys = vec![];
+        for block in pem_blocks(key) {
+            match Self::from_private_key(block.as_bytes(), passphrase) {
+                Ok(params) => keys.push(params),
+                Err(KeyError::InvalidKey) => keys.extend(Self::from_public_key(block.as_bytes())),
+                Err(err) => return Err(err),
+            }
+        }
+        Self::merge_keys(keys).ok_or(KeyError::InvalidKey)
+    }
+
+    /// Merge the keys of a same modulus, the private keys first, and keep the others as extra keys
+    fn merge_keys(keys: impl IntoIterator<Item = Self>) -> Option<Self> {
+        let mut keys = keys.into_iter().collect::<Vec<_>>();
+        keys.sort_by_key(|key| key.d.is_none());
+
+        let mut merged = Vec::<Self>::new();
+        for key in keys {
+            match merged.iter_mut().find(|merged| merged.n == key.n) {
+                Some(merged) => *merged += key,
+                None => merged.push(key),
+            }
+        }
+        Self::from_keys(merged, vec![]).filter(|params| params.n.is_some())
+    }
+
+    /// Create parameters from an encrypted private key, trying each candidate passphrase
+    ///
+    /// Candidates are tried in parallel on the current rayon thread pool.
+    /// Returns the parameters and the passphrase found, or `KeyError::WrongPassphrase` if none matched.
+    pub fn from_private_key_wordlist(
+        key: &[u8],
+        candidates: &[String],
+        pb: Option<&ProgressBar>,
+    ) -> Result<(Self, String), KeyError> {
+        match Self::from_private_key(key, None) {
+            Err(KeyError::MissingPassphrase) => {}
+            Ok(params) => return Ok((params, String::new())),
+            Err(err) => return Err(err),
+        }
+
+        if let Some(pb) = pb {
+            pb.set_length(candidates.len() as u64);
+        }
+        candidates
+            .par_iter()
+            .find_map_any(|passphrase| {
+                let params = Self::from_private_key(key, Some(passphrase)).ok();
+                if let Some(pb) = pb {
+                    pb.inc(1);
+                }
+                params.map(|params| (params, passphrase.clone()))
+            })
+            .ok_or(KeyError::WrongPassphrase)
     }
 
     /// Create parameters from rsa private key
-    pub fn from_rsa_private_key(key: &[u8], passphrase: Option<&str>) -> Option<Self> {
+    pub fn from_rsa_private_key(key: &[u8], passphrase: Option<&str>) -> Result<Self, KeyError> {
+        // An empty passphrase is given to keep openssl from prompting on the terminal
+        let passphrase_bytes = passphrase.unwrap_or_default().as_bytes();
         let private_key = openssl::pkey::PKey::private_key_from_der(key)
             .or_else(|_| {
-                if let Some(passphrase) = passphrase {
-                    openssl::pkey::PKey::private_key_from_pkcs8_passphrase(
-                        key,
-                        passphrase.as_bytes(),
-                    )
-                    .or_else(|_| {
-                        openssl::pkey::PKey::private_key_from_pem_passphrase(
-                            key,
-                            passphrase.as_bytes(),
-                        )
-                    })
-                } else {
-                    openssl::pkey::PKey::private_key_from_pkcs8(key)
-                        .or_else(|_| openssl::pkey::PKey::private_key_from_pem(key))
-                }
+                openssl::pkey::PKey::private_key_from_pkcs8_passphrase(key, passphrase_bytes)
             })
-            .map_err(|e| {
-                e.errors().first().map(|e| {
-                    if e.reason() == Some("bad decrypt") {
-                        panic!("Failed to decrypt private key")
-                    }
-                })
+            .or_else(|_| {
+                openssl::pkey::PKey::private_key_from_pem_passphrase(key, passphrase_bytes)
             })
-            .ok()?;
-        let rsa = private_key.rsa().ok()?;
+            .map_err(|e| {
+                if !e.errors().iter().any(|e| e.reason() == Some("bad decrypt")) {
+                    KeyError::InvalidKey
+                } else if passphrase.is_none() {
+                    KeyError::MissingPassphrase
+                } else {
+                    KeyError::WrongPassphrase
+                }
+            })?;
+        let rsa = private_key.rsa().or(Err(KeyError::InvalidKey))?;
 
-        Some(Self {
+        Ok(Self {
             n: Some(Integer::from_digits(
                 &rsa.n().to_vec(),
                 rug::integer::Order::Msf,
@@ -314,38 +873,45 @@ impl Parameters {
             q: rsa
                 .q()
                 .map(|n| Integer::from_digits(&n.to_vec(), rug::integer::Order::Msf)),
+            d: Some(Integer::from_digits(
+                &rsa.d().to_vec(),
+                rug::integer::Order::Msf,
+            )),
+            dp: rsa
+                .dmp1()
+                .map(|n| Integer::from_digits(&n.to_vec(), rug::integer::Order::Msf)),
+            dq: rsa
+                .dmq1()
+                .map(|n| Integer::from_digits(&n.to_vec(), rug::integer::Order::Msf)),
+            qinv: rsa
+                .iqmp()
+                .map(|n| Integer::from_digits(&n.to_vec(), rug::integer::Order::Msf)),
             ..Default::default()
         })
     }
 
     /// Create parameters from openssh private key
-    pub fn from_openssh_private_key(key: &[u8], passphrase: Option<&str>) -> Option<Self> {
+    pub fn from_openssh_private_key(
+        key: &[u8],
+        passphrase: Option<&str>,
+    ) -> Result<Self, KeyError> {
         let mut private_key = ssh_key::private::PrivateKey::from_openssh(key)
             .or_else(|_| ssh_key::private::PrivateKey::from_bytes(key))
-            .ok()?;
+            .or(Err(KeyError::InvalidKey))?;
         let rsa = if private_key.key_data().is_encrypted() {
             if let Some(passphrase) = passphrase {
                 private_key = private_key
                     .decrypt(passphrase)
-                    .expect("Failed to decrypt private key");
-                private_key.key_data().rsa()?
+                    .or(Err(KeyError::WrongPassphrase))?;
+                private_key.key_data().rsa().ok_or(KeyError::InvalidKey)?
             } else {
-                eprintln!("Warning: Private key is encrypted, but no passphrase was provided, only n and e will be extracted");
-                let public_rsa = private_key.public_key().key_data().rsa()?;
-                return Some(Self {
-                    n: Some(Integer::from_digits(
-                        public_rsa.n.as_bytes(),
-                        rug::integer::Order::Msf,
-                    )),
-                    e: Integer::from_digits(public_rsa.e.as_bytes(), rug::integer::Order::Msf),
-                    ..Default::default()
-                });
+                return Err(KeyError::MissingPassphrase);
             }
         } else {
-            private_key.key_data().rsa()?
+            private_key.key_data().rsa().ok_or(KeyError::InvalidKey)?
         };
 
-        Some(Self {
+        Ok(Self {
             n: Some(Integer::from_digits(
                 rsa.public.n.as_bytes(),
                 rug::integer::Order::Msf,
@@ -359,11 +925,101 @@ impl Parameters {
                 rsa.private.q.as_bytes(),
                 rug::integer::Order::Msf,
             )),
+            d: Some(Integer::from_digits(
+                rsa.private.d.as_bytes(),
+                rug::integer::Order::Msf,
+            )),
+            qinv: Some(Integer::from_digits(
+                rsa.private.iqmp.as_bytes(),
+                rug::integer::Order::Msf,
+            )),
+            ..Default::default()
+        })
+    }
+
+    /// Create parameters from a JSON Web Key (RFC 7517), with its private values if any
+    pub fn from_jwk(key: &[u8]) -> Option<Self> {
+        let jwk: serde_json::Value = serde_json::from_slice(key).ok()?;
+        if jwk.get("kty")? != "RSA" {
+            return None;
+        }
+        let value = |name: &str| {
+            let value = jwk.get(name)?.as_str()?.trim_end_matches('=');
+            let bytes = general_purpose::URL_SAFE_NO_PAD.decode(value).ok()?;
+            Some(Integer::from_digits(&bytes, rug::integer::Order::Msf))
+        };
+
+        Some(Self {
+            n: Some(value("n")?),
+            e: value("e")?,
+            p: value("p"),
+            q: value("q"),
+            d: value("d"),
+            dp: value("dp"),
+            dq: value("dq"),
+            qinv: value("qi"),
+            ..Default::default()
+        })
+    }
+
+    /// Create parameters from a .NET RSAKeyValue XML key, with its private values if any
+    pub fn from_xml_key(key: &[u8]) -> Option<Self> {
+        let xml = std::str::from_utf8(key).ok()?;
+        let xml = xml.split_once("<RSAKeyValue>")?.1;
+        let value = |name: &str| {
+            let value = xml.split_once(&format!("<{name}>"))?.1;
+            let value = value.split_once(&format!("</{name}>"))?.0;
+            let value = value.split_whitespace().collect::<String>();
+            let bytes = general_purpose::STANDARD.decode(value).ok()?;
+            Some(Integer::from_digits(&bytes, rug::integer::Order::Msf))
+        };
+
+        Some(Self {
+            n: Some(value("Modulus")?),
+            e: value("Exponent")?,
+            p: value("P"),
+            q: value("Q"),
+            d: value("D"),
+            dp: value("DP"),
+            dq: value("DQ"),
+            qinv: value("InverseQ"),
             ..Default::default()
         })
     }
 }
 
+/// Returns true if the line is hexadecimal bytes of an openssl `-text` dump, e.g. `00:c3:5f:`
+fn is_openssl_dump_hex(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty()
+        && line.split(':').all(|byte| {
+            byte.is_empty() || (byte.len() == 2 && byte.chars().all(|c| c.is_ascii_hexdigit()))
+        })
+}
+
+/// PEM blocks of a file, each with its BEGIN and END lines
+fn pem_blocks(key: &[u8]) -> Vec<String> {
+    let Ok(text) = std::str::from_utf8(key) else {
+        return vec![];
+    };
+
+    let mut blocks = vec![];
+    let mut block: Option<String> = None;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with("-----BEGIN ") {
+            block = Some(String::new());
+        }
+        if let Some(block) = &mut block {
+            block.push_str(line);
+            block.push('\n');
+        }
+        if line.starts_with("-----END ") {
+            blocks.extend(block.take());
+        }
+    }
+    blocks
+}
+
 impl Add for Parameters {
     type Output = Self;
 
@@ -421,5 +1077,484 @@ impl AddAssign for Parameters {
         if self.sum_pq.is_none() {
             self.sum_pq = rhs.sum_pq;
         }
+        if self.sum_squares_pq.is_none() {
+            self.sum_squares_pq = rhs.sum_squares_pq;
+        }
+        if self.diff_pq.is_none() {
+            self.diff_pq = rhs.diff_pq;
+        }
+        if self.xor_pq.is_none() {
+            self.xor_pq = rhs.xor_pq;
+        }
+        if self.sum_pq_mod.is_none() {
+            self.sum_pq_mod = rhs.sum_pq_mod;
+        }
+        if self.pq_mod.is_none() {
+            self.pq_mod = rhs.pq_mod;
+        }
+        if self.p_mod.is_none() {
+            self.p_mod = rhs.p_mod;
+        }
+        if self.known_m.is_none() {
+            self.known_m = rhs.known_m;
+        }
+        if self.known_c.is_none() {
+            self.known_c = rhs.known_c;
+        }
+        if self.signed_m.is_none() {
+            self.signed_m = rhs.signed_m;
+        }
+        if self.signature.is_none() {
+            self.signature = rhs.signature;
+        }
+        if self.partial_p.is_none() {
+            self.partial_p = rhs.partial_p;
+        }
+        if self.partial_q.is_none() {
+            self.partial_q = rhs.partial_q;
+        }
+        if self.partial_dp.is_none() {
+            self.partial_dp = rhs.partial_dp;
+        }
+        if self.partial_dq.is_none() {
+            self.partial_dq = rhs.partial_dq;
+        }
+        if self.partial_m.is_none() {
+            self.partial_m = rhs.partial_m;
+        }
+        if self.keys.is_empty() {
+            self.keys = rhs.keys;
+        }
+    }
+}
+
+impl Parameters {
+    /// Returns the name and value of all set parameters, hints and partially known values
+    /// included, as merged by `AddAssign`: e is left out when it is the default one
+    fn merged_values(&self) -> Vec<(&'static str, String)> {
+        let mut values = self
+            .fields()
+            .into_iter()
+            .filter(|(name, value)| *name != "e" || **value != 65537)
+            .map(|(name, value)| (name, value.to_string()))
+            .collect::<Vec<_>>();
+        for (name, hint) in [
+            ("sum_pq_mod", &self.sum_pq_mod),
+            ("pq_mod", &self.pq_mod),
+            ("p_mod", &self.p_mod),
+        ] {
+            if let Some((value, modulus)) = hint {
+                values.push((name, format!("{value} mod {modulus}")));
+            }
+        }
+        for (name, partial) in [
+            ("partial_p", &self.partial_p),
+            ("partial_q", &self.partial_q),
+            ("partial_dp", &self.partial_dp),
+            ("partial_dq", &self.partial_dq),
+            ("partial_m", &self.partial_m),
+        ] {
+            if let Some(partial) = partial {
+                values.push((name, partial.to_string()));
+            }
+        }
+        values
+    }
+}
+
+/// Value of a parameter given differently by two sources, the first one being kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterConflict {
+    /// Name of the parameter
+    pub name: &'static str,
+    /// Source of the kept value
+    pub source: String,
+    /// Kept value
+    pub value: String,
+    /// Source of the discarded value
+    pub other_source: String,
+    /// Discarded value
+    pub other_value: String,
+}
+
+impl Display for ParameterConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} from {} ({}) differs from {} ({}), the {} one is used",
+            self.name, self.source, self.value, self.other_source, self.other_value, self.source
+        )
+    }
+}
+
+/// Parameters merged from several sources, in order of precedence, with the source of each value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedParameters {
+    /// Merged parameters, each value coming from the first source giving it
+    pub params: Parameters,
+    /// Name, source and value of each merged value
+    pub values: Vec<(&'static str, String, String)>,
+    /// Values discarded because an earlier source gave another one
+    pub conflicts: Vec<ParameterConflict>,
+}
+
+impl MergedParameters {
+    /// Merge the parameters of the sources, named e.g. "cli" or "key file", the first ones taking
+    /// precedence as with `AddAssign`
+    ///
+    /// e being 65537 unless given, it is only compared between the sources giving another one.
+    pub fn merge<'a>(sources: impl IntoIterator<Item = (&'a str, &'a Parameters)>) -> Self {
+        let mut merged = Self {
+            params: Parameters::default(),
+            values: vec![],
+            conflicts: vec![],
+        };
+        for (source, params) in sources {
+            for (name, value) in params.merged_values() {
+                let Some((_, kept_source, kept_value)) =
+                    merged.values.iter().find(|(kept, _, _)| *kept == name)
+                else {
+                    merged.values.push((name, source.to_string(), value));
+                    continue;
+                };
+                if *kept_value != value {
+                    merged.conflicts.push(ParameterConflict {
+                        name,
+                        source: kept_source.clone(),
+                        value: kept_value.clone(),
+                        other_source: source.to_string(),
+                        other_value: value,
+                    });
+                }
+            }
+            merged.params += params.clone();
+        }
+        merged
+    }
+
+    /// Returns the source of the merged value of a parameter, if it was given
+    pub fn source(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(kept, _, _)| *kept == name)
+            .map(|(_, source, _)| source.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_parameters() {
+        let cli = Parameters {
+            n: Some(143.into()),
+            c: Some(2.into()),
+            ..Default::default()
+        };
+        let key = Parameters {
+            n: Some(221.into()),
+            e: 3.into(),
+            p_mod: Some((1.into(), 10.into())),
+            ..Default::default()
+        };
+        let raw = Parameters {
+            e: 5.into(),
+            c: Some(2.into()),
+            ..Default::default()
+        };
+        let merged = MergedParameters::merge([("cli", &cli), ("key file", &key), ("raw", &raw)]);
+
+        assert_eq!(merged.params, cli.clone() + key.clone() + raw.clone());
+        assert_eq!(merged.source("n"), Some("cli"));
+        assert_eq!(merged.source("e"), Some("key file"));
+        assert_eq!(merged.source("p_mod"), Some("key file"));
+        assert_eq!(merged.source("d"), None);
+        assert_eq!(
+            merged.conflicts,
+            [
+                ParameterConflict {
+                    name: "n",
+                    source: "cli".to_string(),
+                    value: "143".to_string(),
+                    other_source: "key file".to_string(),
+                    other_value: "221".to_string(),
+                },
+                ParameterConflict {
+                    name: "e",
+                    source: "key file".to_string(),
+                    value: "3".to_string(),
+                    other_source: "raw".to_string(),
+                    other_value: "5".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            merged.conflicts[0].to_string(),
+            "n from cli (143) differs from key file (221), the cli one is used"
+        );
+    }
+
+    #[test]
+    fn signed_integer_arg() {
+        assert_eq!(IntegerArg::from_str("-0x10").unwrap().0, -16);
+        assert_eq!(IntegerArg::from_str("+0b101").unwrap().0, 5);
+        assert_eq!(IntegerArg::from_str("-42").unwrap().0, -42);
+        assert!(IntegerArg::from_str("--42").is_err());
+    }
+
+    #[test]
+    fn typed_integer_arg() {
+        let hello = Integer::from(0x68656c6c6fu64);
+        assert_eq!(IntegerArg::from_str("text:hello").unwrap().0, hello);
+        assert_eq!(IntegerArg::from_str("text:\"hello\"").unwrap().0, hello);
+        assert_eq!(IntegerArg::from_str("text:'-0x1'").unwrap().0, 0x2d307831);
+
+        let path = std::env::temp_dir().join("rsacracker_typed_integer_arg");
+        std::fs::write(&path, b"\x01\x00").unwrap();
+        let arg = IntegerArg::from_str(&format!("file:{}", path.display()));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(arg.unwrap().0, 256);
+        assert!(IntegerArg::from_str("file:/nonexistent/rsacracker").is_err());
+    }
+
+    #[test]
+    fn hex_dump_integer_arg() {
+        let parse = |n: &str| IntegerArg::from_str(n).map(|n| n.0);
+        let deadbeef = Integer::from(0xdeadbeefu32);
+        assert_eq!(parse("0xdead:beef"), Ok(deadbeef.clone()));
+        assert_eq!(parse("0xde ad be ef"), Ok(deadbeef.clone()));
+        assert_eq!(parse("de:ad:be:ef:"), Ok(deadbeef.clone()));
+        assert_eq!(parse("de ad be ef@be"), Ok(deadbeef.clone()));
+        assert_eq!(parse("ef:be:ad:de@le"), Ok(deadbeef.clone()));
+        assert_eq!(parse("0xefbeadde@le"), Ok(deadbeef.clone()));
+        assert_eq!(parse("[0xbeef, 0xdead]@le"), Ok(deadbeef.clone()));
+        // Words padded to the largest one
+        assert_eq!(parse("{0x1, 0x0002}"), Ok(Integer::from(0x00010002)));
+        assert_eq!(parse("text:hello@le"), Ok(Integer::from(0x6f6c6c6568u64)));
+        assert_eq!(parse("-0x10"), Ok(Integer::from(-16)));
+        assert_eq!(parse("65537"), Ok(Integer::from(65537)));
+        assert!(parse("65537@le").is_err());
+        assert!(parse("0x").is_err());
+        assert!(parse("0xdead:beeg").is_err());
+    }
+
+    #[test]
+    fn check_pq_hints() {
+        let (p, q) = (Integer::from(1779681653u64), Integer::from(1903643191u64));
+        let params = Parameters {
+            n: Some(Integer::from(&p * &q)),
+            sum_pq: Some(Integer::from(&p + &q)),
+            diff_pq: Some(Integer::from(&q - &p)),
+            sum_squares_pq: Some(Integer::from(p.square_ref()) + Integer::from(q.square_ref())),
+            xor_pq: Some(Integer::from(&p ^ &q)),
+            pq_mod: Some((Integer::from(&p * &q) % 1000, 1000.into())),
+            ..Default::default()
+        };
+        assert_eq!(params.check_pq_hints(), Ok(()));
+
+        for (hint, name) in [
+            (
+                Parameters {
+                    sum_pq: Some(Integer::from(&p + &q) + 2),
+                    ..params.clone()
+                },
+                "sum_pq",
+            ),
+            (
+                Parameters {
+                    diff_pq: Some(Integer::from(&q - &p) + 2),
+                    ..params.clone()
+                },
+                "diff_pq",
+            ),
+            (
+                Parameters {
+                    xor_pq: Some(Integer::from(&p ^ &q) + 1),
+                    ..params.clone()
+                },
+                "xor_pq",
+            ),
+            (
+                Parameters {
+                    sum_pq_mod: Some((1.into(), 1.into())),
+                    ..params.clone()
+                },
+                "sum_pq_mod",
+            ),
+        ] {
+            assert!(hint.check_pq_hints().unwrap_err().contains(name));
+        }
+    }
+
+    #[test]
+    fn reduce_cipher() {
+        let mut params = Parameters {
+            n: Some(77.into()),
+            c: Some(80.into()),
+            ..Default::default()
+        };
+        assert!(params.reduce_cipher());
+        assert_eq!(params.c, Some(3.into()));
+        assert!(!params.reduce_cipher());
+
+        params.c = Some((-1).into());
+        assert!(params.reduce_cipher());
+        assert_eq!(params.c, Some(76.into()));
+    }
+
+    #[test]
+    fn wrong_passphrase() {
+        for key in [
+            &include_bytes!("../tests/keys/private_rsa_passphrase.pem")[..],
+            &include_bytes!("../tests/keys/private_openssl_passphrase.pem")[..],
+            &include_bytes!("../tests/keys/private_openssh_passphrase.pem")[..],
+        ] {
+            assert_eq!(
+                Parameters::from_private_key(key, Some("wrong")).unwrap_err(),
+                KeyError::WrongPassphrase
+            );
+            assert_eq!(
+                Parameters::from_private_key(key, None).unwrap_err(),
+                KeyError::MissingPassphrase
+            );
+        }
+        assert_eq!(
+            Parameters::from_private_key(b"not a key", None).unwrap_err(),
+            KeyError::InvalidKey
+        );
+    }
+
+    #[test]
+    fn private_key_wordlist() {
+        let key = include_bytes!("../tests/keys/private_openssh_passphrase.pem");
+        let candidates = ["123456", "password", "Skyf0l", "qwerty"].map(String::from);
+
+        let (params, passphrase) =
+            Parameters::from_private_key_wordlist(key, &candidates, None).unwrap();
+        assert_eq!(passphrase, "Skyf0l");
+        assert!(params.p.is_some());
+        assert_eq!(
+            Parameters::from_private_key_wordlist(key, &candidates[..2], None).unwrap_err(),
+            KeyError::WrongPassphrase
+        );
+    }
+
+    #[test]
+    fn derived() {
+        let params = Parameters {
+            n: Some(77.into()),
+            p: Some(7.into()),
+            ..Default::default()
+        }
+        .derived();
+
+        assert_eq!(params.q, Some(11.into()));
+        assert_eq!(params.phi, Some(60.into()));
+        assert_eq!(
+            params
+                .fields()
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+            ["n", "e", "p", "q", "phi"]
+        );
+    }
+
+    #[test]
+    fn from_keys() {
+        let keys = [77, 91, 143]
+            .map(|n| Parameters {
+                n: Some(n.into()),
+                e: 3.into(),
+                ..Default::default()
+            })
+            .to_vec();
+
+        let params = Parameters::from_keys(keys.clone(), vec![5.into(), 6.into()]).unwrap();
+        assert_eq!(params.n, Some(77.into()));
+        assert_eq!(params.c, Some(5.into()));
+        assert_eq!(params.keys.len(), 3);
+        assert_eq!(params.keys[1].c, Some(6.into()));
+        assert_eq!(params.keys[2].c, None);
+
+        assert!(Parameters::from_keys(keys[..1].to_vec(), vec![5.into(), 6.into()]).is_none());
+    }
+
+    #[test]
+    fn pem_bundle() {
+        let private_key = include_bytes!("../tests/keys/private_openssl.pem");
+        let key = Parameters::from_private_key(private_key, None).unwrap();
+        let other_key =
+            crate::export_public_key(&3233.into(), &17.into(), crate::KeyFormat::Pkcs8Pem).unwrap();
+        // A certificate, another public key, then the private key of the certificate
+        let bundle = [
+            &include_bytes!("../tests/keys/x509_certificate.cer")[..],
+            &other_key[..],
+            &private_key[..],
+        ]
+        .join(&b'\n');
+
+        let params = Parameters::from_private_key(&bundle, None).unwrap();
+        assert_eq!((&params.n, &params.d), (&key.n, &key.d));
+        let moduli = params.keys.iter().map(|key| &key.n).collect::<Vec<_>>();
+        assert_eq!(moduli, [key.n.as_ref().unwrap(), &Integer::from(3233)]);
+        assert_eq!(params.keys[1].e, 17);
+
+        // Only the public keys
+        let params = Parameters::from_public_key(&bundle).unwrap();
+        assert_eq!((&params.n, &params.d), (&key.n, &None));
+        assert_eq!(params.keys.len(), 2);
+    }
+
+    #[test]
+    fn from_raw_indexed_keys() {
+        let raw = "e = 3\nn1 = 77\nc1 = 5\np1 = 7\nn2 = 91\ne2 = 5\nq2 = 13\ndmp1 = 1\nc3 = 1\n";
+        let params = Parameters::from_raw(raw);
+        assert_eq!(params.n, Some(77.into()));
+        assert_eq!(params.e, 3);
+        assert_eq!(params.c, Some(5.into()));
+        assert_eq!(params.p, Some(7.into()));
+        assert_eq!(params.dp, Some(1.into()));
+        assert_eq!(params.keys.len(), 2);
+        assert_eq!(params.keys[0].e, 3);
+        assert_eq!(params.keys[1].e, 5);
+        assert_eq!(params.keys[1].known.q, Some(13.into()));
+
+        assert_eq!(Parameters::from_raw(&params.to_string()).keys, params.keys);
+    }
+
+    #[test]
+    fn from_raw_openssl_dump() {
+        let raw = "Private-Key: (12 bit, 2 primes)
+modulus:
+    0c:
+    a1
+publicExponent: 17 (0x11)
+privateExponent:
+    0a:c1:
+prime1: 61 (0x3d)
+prime2: 53 (0x35)
+exponent1: 53 (0x35)
+exponent2: 49 (0x31)
+coefficient: 38 (0x26)
+";
+        let params = Parameters::from_raw(raw);
+        assert_eq!(params.n, Some(3233.into()));
+        assert_eq!(params.e, 17);
+        assert_eq!(params.d, Some(2753.into()));
+        assert_eq!((params.p, params.q), (Some(61.into()), Some(53.into())));
+        assert_eq!((params.dp, params.dq), (Some(53.into()), Some(49.into())));
+        assert_eq!(params.qinv, Some(38.into()));
+    }
+
+    #[test]
+    fn from_raw_hex_dump() {
+        let raw = "n = 0c:a1\ne: 11:00@le\nd = 0x0a c1\nc = text:hi\n";
+        let params = Parameters::from_raw(raw);
+        assert_eq!(params.n, Some(3233.into()));
+        assert_eq!(params.e, 17);
+        assert_eq!(params.d, Some(2753.into()));
+        assert_eq!(params.c, Some(0x6869.into()));
     }
 }
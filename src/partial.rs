@@ -0,0 +1,221 @@
+use std::{fmt::Display, str::FromStr};
+
+use rug::Integer;
+
+use crate::IntegerArg;
+
+/// Integer of which only some bits are known
+///
+/// Parsed from hexadecimal or binary digits where unknown digits are `?`,
+/// e.g. `0xdead????` has its 16 low bits unknown and `0b1?0?` its bits 0 and 2.
+/// Values without `?` are parsed as any integer argument and fully known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialInteger {
+    value: Integer,
+    mask: Integer,
+    bits: u32,
+}
+
+impl PartialInteger {
+    /// Create a partial integer of `bits` bits, where `mask` has the known bits set
+    ///
+    /// Bits of `value` outside of the mask are ignored.
+    pub fn new(value: Integer, mask: Integer, bits: u32) -> Self {
+        let mask = mask.keep_bits(bits);
+        Self {
+            value: value & &mask,
+            mask,
+            bits,
+        }
+    }
+
+    /// Returns the known bits, unknown ones set to 0
+    pub fn value(&self) -> &Integer {
+        &self.value
+    }
+
+    /// Returns the mask of the known bits
+    pub fn mask(&self) -> &Integer {
+        &self.mask
+    }
+
+    /// Returns the bit length of the integer
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Returns the value if all bits are known
+    pub fn known(&self) -> Option<&Integer> {
+        (self.unknown_count() == 0).then_some(&self.value)
+    }
+
+    /// Returns the positions of the unknown bits, from the lowest
+    pub fn unknown_bits(&self) -> Vec<u32> {
+        (0..self.bits).filter(|&i| !self.mask.get_bit(i)).collect()
+    }
+
+    /// Returns the number of unknown bits
+    pub fn unknown_count(&self) -> u32 {
+        self.bits - self.mask.count_ones().unwrap_or(0)
+    }
+
+    /// Returns the number of unknown low bits if all the other bits are known (known MSBs)
+    pub fn unknown_lsb(&self) -> Option<u32> {
+        let low = self.mask.find_one(0).unwrap_or(self.bits);
+        (self.mask.count_ones() == Some(self.bits - low)).then_some(low)
+    }
+
+    /// Returns the number of known low bits if all the other bits are unknown (known LSBs)
+    pub fn known_lsb(&self) -> Option<u32> {
+        let low = self.mask.find_zero(0).unwrap_or(self.bits).min(self.bits);
+        (self.mask.count_ones() == Some(low)).then_some(low)
+    }
+
+    /// Returns the position of the lowest unknown bit and the number of unknown bits, if they
+    /// are all contiguous
+    pub fn unknown_window(&self) -> Option<(u32, u32)> {
+        let low = self.mask.find_zero(0).filter(|&low| low < self.bits)?;
+        let count = self.unknown_count();
+        (self.mask.find_one(low).unwrap_or(self.bits).min(self.bits) == low + count)
+            .then_some((low, count))
+    }
+
+    /// Returns true if the candidate matches all the known bits
+    pub fn matches(&self, candidate: &Integer) -> bool {
+        Integer::from(candidate & &self.mask) == self.value
+            && candidate.significant_bits() <= self.bits
+    }
+}
+
+/// Returns the partial factors p and q of n, a missing one without any known bit
+///
+/// A factor without any known bit has at most bits(n) - bits(other factor) + 1 bits.
+/// Returns `None` if both are missing.
+pub(crate) fn partial_factors(
+    n: &Integer,
+    p: &Option<PartialInteger>,
+    q: &Option<PartialInteger>,
+) -> Option<(PartialInteger, PartialInteger)> {
+    if p.is_none() && q.is_none() {
+        return None;
+    }
+    let n_bits = n.significant_bits();
+    let unknown = |other: &Option<PartialInteger>| {
+        let bits = n_bits.saturating_sub(other.as_ref().map_or(n_bits / 2, |p| p.bits())) + 1;
+        PartialInteger::new(Integer::new(), Integer::new(), bits)
+    };
+    Some((
+        p.clone().unwrap_or_else(|| unknown(q)),
+        q.clone().unwrap_or_else(|| unknown(p)),
+    ))
+}
+
+impl From<Integer> for PartialInteger {
+    fn from(value: Integer) -> Self {
+        let bits = value.significant_bits();
+        Self {
+            mask: (Integer::from(1) << bits) - 1,
+            value,
+            bits,
+        }
+    }
+}
+
+impl FromStr for PartialInteger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.contains('?') {
+            return Ok(IntegerArg::from_str(s)?.0.into());
+        }
+
+        let (digits, radix_bits) = if let Some(digits) = s.strip_prefix("0x") {
+            (digits, 4)
+        } else if let Some(digits) = s.strip_prefix("0b") {
+            (digits, 1)
+        } else {
+            return Err(
+                "Unknown digits are only supported in hex (0x) and binary (0b)".to_string(),
+            );
+        };
+
+        let mut value = Integer::new();
+        let mut mask = Integer::new();
+        for c in digits.chars() {
+            value <<= radix_bits;
+            mask <<= radix_bits;
+            if c != '?' {
+                value += c
+                    .to_digit(1 << radix_bits)
+                    .ok_or(format!("Invalid digit: {c}"))?;
+                mask += (1 << radix_bits) - 1;
+            }
+        }
+        Ok(Self::new(value, mask, digits.len() as u32 * radix_bits))
+    }
+}
+
+impl Display for PartialInteger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = self.known() {
+            return write!(f, "{value}");
+        }
+
+        // Hex digits if each of them is either known or unknown, binary otherwise
+        let nibble_aligned = self.bits.is_multiple_of(4)
+            && (0..self.bits / 4).all(|i| {
+                let nibble = Integer::from(&self.mask >> (i * 4)).keep_bits(4);
+                nibble == 0 || nibble == 0xf
+            });
+        let radix_bits = if nibble_aligned { 4 } else { 1 };
+        let digits = (0..self.bits / radix_bits)
+            .rev()
+            .map(|i| {
+                let shift = i * radix_bits;
+                if self.mask.get_bit(shift) {
+                    let digit = Integer::from(&self.value >> shift).keep_bits(radix_bits);
+                    char::from_digit(digit.to_u32().unwrap(), 1 << radix_bits).unwrap()
+                } else {
+                    '?'
+                }
+            })
+            .collect::<String>();
+        write!(f, "0{}{digits}", if nibble_aligned { 'x' } else { 'b' })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let partial = PartialInteger::from_str("0xdead????").unwrap();
+        assert_eq!(partial.value(), &Integer::from(0xdead0000u32));
+        assert_eq!(partial.bits(), 32);
+        assert_eq!(partial.unknown_lsb(), Some(16));
+        assert_eq!(partial.known_lsb(), None);
+        assert!(partial.matches(&Integer::from(0xdeadbeefu32)));
+        assert_eq!(partial.to_string(), "0xdead????");
+
+        let partial = PartialInteger::from_str("0b??1?0").unwrap();
+        assert_eq!(partial.unknown_bits(), [1, 3, 4]);
+        assert_eq!(partial.known_lsb(), None);
+        assert_eq!(partial.unknown_window(), None);
+        assert_eq!(partial.to_string(), "0b??1?0");
+        assert_eq!(
+            PartialInteger::from_str("0xde??ad")
+                .unwrap()
+                .unknown_window(),
+            Some((8, 8))
+        );
+        assert_eq!(
+            PartialInteger::from_str("0x??ff").unwrap().known_lsb(),
+            Some(8)
+        );
+
+        let partial = PartialInteger::from_str("0x10").unwrap();
+        assert_eq!(partial.known(), Some(&Integer::from(16)));
+        assert!(PartialInteger::from_str("12??").is_err());
+    }
+}
@@ -0,0 +1,694 @@
+//! Univariate polynomials over Z/nZ, or GF(p) when the modulus is prime.
+//!
+//! The building block of the attacks solving polynomial equations modulo n (Franklin-Reiter,
+//! short pad, Coppersmith). Divisions by a leading coefficient which is not invertible modulo n
+//! fail with the non-trivial factor of n it reveals.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use rug::{integer::Order, ops::RemRounding, Integer};
+
+/// Below this number of coefficients, polynomials are multiplied with the schoolbook method
+const KRONECKER_THRESHOLD: usize = 16;
+
+/// Below this degree, the half-GCD falls back to the Euclidean algorithm
+const HALF_GCD_THRESHOLD: usize = 32;
+
+/// Polynomial with coefficients modulo n
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// Coefficients from the lowest degree, reduced modulo n, without trailing zeros
+    coefficients: Vec<Integer>,
+    modulus: Integer,
+}
+
+/// 2x2 matrix of polynomials, as returned by `Polynomial::half_gcd`
+pub type PolynomialMatrix = [[Polynomial; 2]; 2];
+
+impl Polynomial {
+    /// Create a polynomial modulo n from its coefficients, from the lowest degree
+    pub fn new<T: Into<Integer>>(
+        coefficients: impl IntoIterator<Item = T>,
+        modulus: &Integer,
+    ) -> Self {
+        Self::from_reduced(
+            coefficients
+                .into_iter()
+                .map(|coef| coef.into().rem_euc(modulus))
+                .collect(),
+            modulus,
+        )
+    }
+
+    /// Create a polynomial from coefficients already in [0, n)
+    fn from_reduced(mut coefficients: Vec<Integer>, modulus: &Integer) -> Self {
+        while coefficients.last().is_some_and(|coef| *coef == 0) {
+            coefficients.pop();
+        }
+        Self {
+            coefficients,
+            modulus: modulus.clone(),
+        }
+    }
+
+    /// The zero polynomial modulo n
+    pub fn zero(modulus: &Integer) -> Self {
+        Self::from_reduced(vec![], modulus)
+    }
+
+    /// The constant polynomial c modulo n
+    pub fn constant(c: impl Into<Integer>, modulus: &Integer) -> Self {
+        Self::new([c], modulus)
+    }
+
+    /// The monomial x^degree modulo n
+    pub fn monomial(degree: usize, modulus: &Integer) -> Self {
+        let mut coefficients = vec![Integer::ZERO; degree];
+        coefficients.push(Integer::from(1));
+        Self::new(coefficients, modulus)
+    }
+
+    /// Coefficients from the lowest degree, without trailing zeros
+    pub fn coefficients(&self) -> &[Integer] {
+        &self.coefficients
+    }
+
+    /// Modulus of the coefficients
+    pub fn modulus(&self) -> &Integer {
+        &self.modulus
+    }
+
+    /// Degree of the polynomial, `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    /// Returns true if this is the zero polynomial
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// Leading coefficient, `None` for the zero polynomial
+    pub fn leading_coefficient(&self) -> Option<&Integer> {
+        self.coefficients.last()
+    }
+
+    /// Evaluate the polynomial at x, modulo n
+    pub fn eval(&self, x: &Integer) -> Integer {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Integer::ZERO, |acc, coef| {
+                (acc * x + coef).rem_euc(&self.modulus)
+            })
+    }
+
+    /// Inverse of the leading coefficient, or the factor of n it shares
+    fn leading_inverse(&self) -> Result<Integer, Integer> {
+        let lc = self.leading_coefficient().expect("zero polynomial");
+        lc.clone()
+            .invert(&self.modulus)
+            .map_err(|lc| lc.gcd(&self.modulus))
+    }
+
+    /// Returns this polynomial divided by its leading coefficient
+    ///
+    /// Fails with a factor of n if the leading coefficient is not invertible.
+    pub fn monic(&self) -> Result<Self, Integer> {
+        if self.is_zero() {
+            return Ok(self.clone());
+        }
+        Ok(self.scale(&self.leading_inverse()?))
+    }
+
+    /// Multiply all coefficients by c
+    fn scale(&self, c: &Integer) -> Self {
+        Self::new(self.coefficients.iter().map(|coef| coef * c), &self.modulus)
+    }
+
+    /// Returns the quotient and the remainder of the division by `divisor`
+    ///
+    /// Fails with a factor of n if the leading coefficient of `divisor` is not invertible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `divisor` is zero.
+    pub fn div_rem(&self, divisor: &Self) -> Result<(Self, Self), Integer> {
+        let inverse = divisor.leading_inverse()?;
+        let (len, divisor_len) = (self.coefficients.len(), divisor.coefficients.len());
+        if len < divisor_len {
+            return Ok((Self::zero(&self.modulus), self.clone()));
+        }
+
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![Integer::ZERO; len - divisor_len + 1];
+        for i in (0..quotient.len()).rev() {
+            let q = Integer::from(&remainder[i + divisor_len - 1] * &inverse) % &self.modulus;
+            if q != 0 {
+                for (r, d) in remainder[i..].iter_mut().zip(&divisor.coefficients) {
+                    *r = (std::mem::take(r) - Integer::from(&q * d)).rem_euc(&self.modulus);
+                }
+            }
+            quotient[i] = q;
+        }
+        remainder.truncate(divisor_len - 1);
+        Ok((
+            Self::from_reduced(quotient, &self.modulus),
+            Self::from_reduced(remainder, &self.modulus),
+        ))
+    }
+
+    /// Returns the remainder of the division by `divisor`, see `div_rem`
+    pub fn rem(&self, divisor: &Self) -> Result<Self, Integer> {
+        Ok(self.div_rem(divisor)?.1)
+    }
+
+    /// Returns self^exp modulo the polynomial `modulus`, by square and multiply
+    ///
+    /// Fails with a factor of n if the leading coefficient of `modulus` is not invertible.
+    pub fn pow_mod(&self, exp: &Integer, modulus: &Self) -> Result<Self, Integer> {
+        let base = self.rem(modulus)?;
+        let mut result = Self::constant(1, &self.modulus).rem(modulus)?;
+        for bit in (0..exp.significant_bits()).rev() {
+            result = (&result * &result).rem(modulus)?;
+            if exp.get_bit(bit) {
+                result = (&result * &base).rem(modulus)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the quotient of the division by x^k
+    fn shift_right(&self, k: usize) -> Self {
+        Self::from_reduced(
+            self.coefficients.get(k..).unwrap_or_default().to_vec(),
+            &self.modulus,
+        )
+    }
+
+    /// Half-GCD of self and `other`, with deg self >= deg other
+    ///
+    /// Returns the matrix M of the Euclidean steps such that M * (self, other) = (c, d) are two
+    /// consecutive remainders of the Euclidean algorithm, with deg c >= ceil(deg self / 2) > deg d.
+    /// Only the high half of the coefficients is used at each step, so the GCD of polynomials of
+    /// degree d takes O(M(d) log d) operations instead of O(d^2).
+    ///
+    /// Fails with a factor of n if a leading coefficient is not invertible.
+    pub fn half_gcd(&self, other: &Self) -> Result<PolynomialMatrix, Integer> {
+        let n = &self.modulus;
+        let degree = self.degree().unwrap_or(0);
+        let m = degree.div_ceil(2);
+        let below_m = |poly: &Self| poly.degree().is_none_or(|d| d < m);
+        if below_m(other) {
+            return Ok(identity(n));
+        }
+
+        if degree < HALF_GCD_THRESHOLD {
+            let (mut steps, mut c, mut d) = (identity(n), self.clone(), other.clone());
+            while !below_m(&d) {
+                let (q, r) = c.div_rem(&d)?;
+                steps = matrix_mul(&euclidean_step(q), &steps);
+                (c, d) = (d, r);
+            }
+            return Ok(steps);
+        }
+
+        // Euclidean steps of the high halves, which are also steps of the full polynomials
+        let steps = self.shift_right(m).half_gcd(&other.shift_right(m))?;
+        let (c, d) = matrix_apply(&steps, self, other);
+        if below_m(&d) {
+            return Ok(steps);
+        }
+
+        let (q, r) = c.div_rem(&d)?;
+        let steps = matrix_mul(&euclidean_step(q), &steps);
+        let (c, d) = (d, r);
+        if below_m(&d) {
+            return Ok(steps);
+        }
+
+        let k = 2 * m - c.degree().unwrap_or(0);
+        let next_steps = c.shift_right(k).half_gcd(&d.shift_right(k))?;
+        Ok(matrix_mul(&next_steps, &steps))
+    }
+
+    /// Monic GCD of self and `other`, with the half-GCD
+    ///
+    /// Fails with a factor of n if a leading coefficient met along the way is not invertible.
+    pub fn gcd(&self, other: &Self) -> Result<Self, Integer> {
+        let (mut a, mut b) = if self.degree() >= other.degree() {
+            (self.clone(), other.clone())
+        } else {
+            (other.clone(), self.clone())
+        };
+        loop {
+            if b.is_zero() {
+                return a.monic();
+            }
+            let steps = a.half_gcd(&b)?;
+            (a, b) = matrix_apply(&steps, &a, &b);
+            if b.is_zero() {
+                return a.monic();
+            }
+            let r = a.rem(&b)?;
+            (a, b) = (b, r);
+        }
+    }
+
+    /// Resultant of self and `other`, with the Euclidean algorithm
+    ///
+    /// Res(a, b) = (-1)^(deg a * deg b) * lc(b)^(deg a - deg r) * Res(b, r), with r = a mod b.
+    /// Fails with a factor of n if a leading coefficient is not invertible.
+    pub fn resultant(&self, other: &Self) -> Result<Integer, Integer> {
+        if self.is_zero() || other.is_zero() {
+            return Ok(Integer::ZERO);
+        }
+
+        let n = &self.modulus;
+        let (mut a, mut b) = (self.clone(), other.clone());
+        let mut result = Integer::from(1);
+        loop {
+            let (deg_a, deg_b) = (a.degree().unwrap(), b.degree().unwrap());
+            let lc = b.leading_coefficient().unwrap().clone();
+            if deg_b == 0 {
+                let power = lc.pow_mod(&deg_a.into(), n).unwrap();
+                return Ok(result * power % n);
+            }
+            let r = a.rem(&b)?;
+            let Some(deg_r) = r.degree() else {
+                return Ok(Integer::ZERO);
+            };
+            result = result * lc.pow_mod(&(deg_a - deg_r).into(), n).unwrap() % n;
+            if deg_a * deg_b % 2 == 1 {
+                result = Integer::from(n - &result) % n;
+            }
+            (a, b) = (b, r);
+        }
+    }
+}
+
+/// Polynomial in x and y with coefficients modulo n
+///
+/// Stored as a polynomial in x, whose coefficients are polynomials in y.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BivariatePolynomial {
+    /// Coefficients of the powers of x, from the lowest degree, without trailing zeros
+    coefficients: Vec<Polynomial>,
+    modulus: Integer,
+}
+
+impl BivariatePolynomial {
+    /// Create a bivariate polynomial from the coefficients of the powers of x, from the lowest degree
+    pub fn new(mut coefficients: Vec<Polynomial>, modulus: &Integer) -> Self {
+        while coefficients.last().is_some_and(Polynomial::is_zero) {
+            coefficients.pop();
+        }
+        Self {
+            coefficients,
+            modulus: modulus.clone(),
+        }
+    }
+
+    /// Coefficients of the powers of x, from the lowest degree
+    pub fn coefficients(&self) -> &[Polynomial] {
+        &self.coefficients
+    }
+
+    /// Degree in x, `None` for the zero polynomial
+    pub fn degree_x(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    /// Degree in y, `None` for the zero polynomial
+    pub fn degree_y(&self) -> Option<usize> {
+        self.coefficients
+            .iter()
+            .filter_map(Polynomial::degree)
+            .max()
+    }
+
+    /// Returns the polynomial in x obtained by substituting y
+    pub fn eval_y(&self, y: &Integer) -> Polynomial {
+        Polynomial::new(
+            self.coefficients.iter().map(|coef| coef.eval(y)),
+            &self.modulus,
+        )
+    }
+
+    /// Resultant of self and `other` with respect to x, a polynomial in y
+    ///
+    /// Its degree is at most deg_x(a) * deg_y(b) + deg_x(b) * deg_y(a), it is interpolated from
+    /// the resultants of a(x, t) and b(x, t) for as many points t = 0, 1, 2...
+    /// Fails with a factor of n if a leading coefficient is not invertible, or if n has a factor
+    /// smaller than the number of points.
+    pub fn resultant_x(&self, other: &Self) -> Result<Polynomial, Integer> {
+        let n = &self.modulus;
+        let (Some(deg_a), Some(deg_b)) = (self.degree_x(), other.degree_x()) else {
+            return Ok(Polynomial::zero(n));
+        };
+        let degree = deg_a * other.degree_y().unwrap_or(0) + deg_b * self.degree_y().unwrap_or(0);
+
+        let values = (0..=degree)
+            .map(|t| {
+                let t = Integer::from(t);
+                resultant_with_degrees(&self.eval_y(&t), &other.eval_y(&t), deg_a, deg_b)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        interpolate(values, n)
+    }
+}
+
+/// Resultant of a and b seen as polynomials of degrees deg_a and deg_b, which may be larger than
+/// their actual degrees (leading coefficients cancelled by the substitution of y)
+fn resultant_with_degrees(
+    a: &Polynomial,
+    b: &Polynomial,
+    deg_a: usize,
+    deg_b: usize,
+) -> Result<Integer, Integer> {
+    let n = &a.modulus;
+    let (Some(actual_a), Some(actual_b)) = (a.degree(), b.degree()) else {
+        return Ok(Integer::ZERO);
+    };
+    if actual_a < deg_a && actual_b < deg_b {
+        // The first column of the Sylvester matrix is zero
+        return Ok(Integer::ZERO);
+    }
+
+    let mut result = a.resultant(b)?;
+    if actual_a < deg_a {
+        let lc = b.leading_coefficient().unwrap();
+        result *= Integer::from(lc.pow_mod_ref(&(deg_a - actual_a).into(), n).unwrap());
+        if (deg_a - actual_a) * deg_b % 2 == 1 {
+            result = -result;
+        }
+    } else if actual_b < deg_b {
+        let lc = a.leading_coefficient().unwrap();
+        result *= Integer::from(lc.pow_mod_ref(&(deg_b - actual_b).into(), n).unwrap());
+    }
+    Ok(result.rem_euc(n))
+}
+
+/// Returns the polynomial of degree less than values.len() taking the given values at 0, 1, 2...
+///
+/// Newton's divided differences, the differences between points being small integers.
+fn interpolate(mut values: Vec<Integer>, modulus: &Integer) -> Result<Polynomial, Integer> {
+    for j in 1..values.len() {
+        let inverse = Integer::from(j)
+            .invert(modulus)
+            .map_err(|j| j.gcd(modulus))?;
+        for i in (j..values.len()).rev() {
+            let difference = Integer::from(&values[i] - &values[i - 1]);
+            values[i] = (difference * &inverse).rem_euc(modulus);
+        }
+    }
+
+    let mut result = Polynomial::zero(modulus);
+    for (i, value) in values.iter().enumerate().rev() {
+        result = &(&result * &Polynomial::new([-Integer::from(i), Integer::from(1)], modulus))
+            + &Polynomial::constant(value.clone(), modulus);
+    }
+    Ok(result)
+}
+
+/// Identity matrix
+fn identity(modulus: &Integer) -> PolynomialMatrix {
+    let (zero, one) = (Polynomial::zero(modulus), Polynomial::constant(1, modulus));
+    [[one.clone(), zero.clone()], [zero, one]]
+}
+
+/// Matrix of the Euclidean step (a, b) -> (b, a - q * b)
+fn euclidean_step(q: Polynomial) -> PolynomialMatrix {
+    let (zero, one) = (
+        Polynomial::zero(&q.modulus),
+        Polynomial::constant(1, &q.modulus),
+    );
+    [[zero, one.clone()], [one, -&q]]
+}
+
+fn matrix_mul(a: &PolynomialMatrix, b: &PolynomialMatrix) -> PolynomialMatrix {
+    let entry = |i: usize, j: usize| &(&a[i][0] * &b[0][j]) + &(&a[i][1] * &b[1][j]);
+    [[entry(0, 0), entry(0, 1)], [entry(1, 0), entry(1, 1)]]
+}
+
+fn matrix_apply(m: &PolynomialMatrix, a: &Polynomial, b: &Polynomial) -> (Polynomial, Polynomial) {
+    (
+        &(&m[0][0] * a) + &(&m[0][1] * b),
+        &(&m[1][0] * a) + &(&m[1][1] * b),
+    )
+}
+
+/// Multiply two lists of coefficients in [0, n), without reducing the result
+fn mul_coefficients(a: &[Integer], b: &[Integer], modulus: &Integer) -> Vec<Integer> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    if a.len().min(b.len()) < KRONECKER_THRESHOLD {
+        let mut product = vec![Integer::ZERO; a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (p, y) in product[i..].iter_mut().zip(b) {
+                *p += x * y;
+            }
+        }
+        return product;
+    }
+
+    // Kronecker substitution: evaluate both polynomials at a power of 2 large enough for the
+    // coefficients of the product not to overlap, that is len * n^2, then multiply the integers
+    let bits = 2 * modulus.significant_bits() as usize + usize::BITS as usize
+        - a.len().min(b.len()).leading_zeros() as usize;
+    let limbs = bits.div_ceil(64);
+    let pack = |poly: &[Integer]| {
+        let mut digits = vec![0u64; poly.len() * limbs];
+        for (chunk, coef) in digits.chunks_mut(limbs).zip(poly) {
+            coef.write_digits(chunk, Order::Lsf);
+        }
+        Integer::from_digits(&digits, Order::Lsf)
+    };
+    let mut digits = (pack(a) * pack(b)).to_digits::<u64>(Order::Lsf);
+    digits.resize((a.len() + b.len() - 1) * limbs, 0);
+    digits
+        .chunks(limbs)
+        .map(|chunk| Integer::from_digits(chunk, Order::Lsf))
+        .collect()
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, rhs: &Polynomial) -> Polynomial {
+        debug_assert_eq!(self.modulus, rhs.modulus);
+        let (long, short) = if self.coefficients.len() >= rhs.coefficients.len() {
+            (self, rhs)
+        } else {
+            (rhs, self)
+        };
+        let mut coefficients = long.coefficients.clone();
+        for (coef, other) in coefficients.iter_mut().zip(&short.coefficients) {
+            *coef += other;
+            if *coef >= self.modulus {
+                *coef -= &self.modulus;
+            }
+        }
+        Polynomial::from_reduced(coefficients, &self.modulus)
+    }
+}
+
+impl Neg for &Polynomial {
+    type Output = Polynomial;
+
+    fn neg(self) -> Polynomial {
+        Polynomial::from_reduced(
+            self.coefficients
+                .iter()
+                .map(|coef| Integer::from(&self.modulus - coef) % &self.modulus)
+                .collect(),
+            &self.modulus,
+        )
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, rhs: &Polynomial) -> Polynomial {
+        self + &-rhs
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: &Polynomial) -> Polynomial {
+        debug_assert_eq!(self.modulus, rhs.modulus);
+        Polynomial::new(
+            mul_coefficients(&self.coefficients, &rhs.coefficients, &self.modulus),
+            &self.modulus,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poly(coefficients: &[i64], modulus: u32) -> Polynomial {
+        Polynomial::new(coefficients.iter().copied(), &modulus.into())
+    }
+
+    /// Pseudo-random polynomial of the given degree
+    fn random_poly(degree: usize, seed: u64, modulus: &Integer) -> Polynomial {
+        let mut state = seed;
+        let coefficients = (0..degree).map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            state >> 33
+        });
+        Polynomial::new(coefficients.chain([1]), modulus)
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = poly(&[1, 2, 3], 7);
+        let b = poly(&[6, 5], 7);
+        assert_eq!(&a + &b, poly(&[0, 0, 3], 7));
+        assert_eq!(&a - &a, Polynomial::zero(&7.into()));
+        assert_eq!(&a * &b, poly(&[6, 17, 28, 15], 7));
+        assert_eq!((&a + &b).degree(), Some(2));
+        assert_eq!(Polynomial::zero(&7.into()).degree(), None);
+        assert_eq!(a.eval(&2.into()), 3);
+        assert_eq!(Polynomial::monomial(3, &7.into()), poly(&[0, 0, 0, 1], 7));
+        assert_eq!(poly(&[-1, 8], 7).coefficients(), [6, 1]);
+    }
+
+    #[test]
+    fn kronecker_multiplication() {
+        let n = Integer::from(u64::MAX) * 3;
+        let a = random_poly(40, 1, &n);
+        let b = random_poly(30, 2, &n);
+        let product = &a * &b;
+        assert_eq!(product.degree(), Some(70));
+        for x in [0, 1, 12345] {
+            let x = Integer::from(x);
+            assert_eq!(product.eval(&x), a.eval(&x) * b.eval(&x) % &n);
+        }
+    }
+
+    #[test]
+    fn div_rem() {
+        let a = poly(&[1, 2, 3, 4], 7);
+        let b = poly(&[1, 2], 7);
+        let (q, r) = a.div_rem(&b).unwrap();
+        assert_eq!(&(&q * &b) + &r, a);
+        // The remainder is a(-1/2) = a(3)
+        assert_eq!(r, Polynomial::constant(a.eval(&3.into()), &7.into()));
+        assert_eq!(b.monic().unwrap(), poly(&[4, 1], 7));
+
+        // The leading coefficient 3 is not invertible modulo 15
+        let b = poly(&[1, 3], 15);
+        assert_eq!(b.monic(), Err(3.into()));
+        assert_eq!(poly(&[1, 2, 3, 4], 15).div_rem(&b), Err(3.into()));
+    }
+
+    #[test]
+    fn pow_mod() {
+        let x = poly(&[0, 1], 7);
+        let f = poly(&[1, 1, 0, 1], 7);
+        let x50 = x.pow_mod(&50.into(), &f).unwrap();
+        assert_eq!(
+            x.pow_mod(&100.into(), &f).unwrap(),
+            (&x50 * &x50).rem(&f).unwrap()
+        );
+        assert_eq!(x.pow_mod(&0.into(), &f).unwrap(), poly(&[1], 7));
+        assert_eq!(
+            x.pow_mod(&5.into(), &f).unwrap(),
+            Polynomial::monomial(5, &7.into()).rem(&f).unwrap()
+        );
+    }
+
+    #[test]
+    fn half_gcd() {
+        let p = Integer::from(1_000_000_007);
+        let g = random_poly(5, 3, &p);
+        let a = &g * &random_poly(120, 4, &p);
+        let b = &g * &random_poly(100, 5, &p);
+
+        let m = a.degree().unwrap().div_ceil(2);
+        let steps = a.half_gcd(&b).unwrap();
+        let (c, d) = matrix_apply(&steps, &a, &b);
+        assert!(c.degree().unwrap() >= m);
+        assert!(d.degree().is_none_or(|d| d < m));
+
+        // Both remainders are still multiples of the common factor
+        assert!(c.rem(&g).unwrap().is_zero());
+        assert!(d.rem(&g).unwrap().is_zero());
+    }
+
+    #[test]
+    fn gcd() {
+        let p = Integer::from(1_000_000_007);
+        let g = random_poly(5, 6, &p);
+        let a = &g * &random_poly(90, 7, &p);
+        let b = &g * &random_poly(70, 8, &p);
+        assert_eq!(a.gcd(&b).unwrap(), g.monic().unwrap());
+        assert_eq!(b.gcd(&a).unwrap(), g.monic().unwrap());
+        assert_eq!(a.gcd(&Polynomial::zero(&p)).unwrap(), a.monic().unwrap());
+
+        // The leading coefficient 11 of the first remainder reveals a factor of 143
+        let a = poly(&[1, 0, 1], 143);
+        let b = poly(&[1, 11], 143);
+        assert_eq!(a.gcd(&b), Err(11.into()));
+    }
+
+    #[test]
+    fn resultant() {
+        // Res(a, b) is the product of b(r) for the roots r of the monic polynomial a
+        let a = &poly(&[-2, 1], 101) * &poly(&[-3, 1], 101);
+        let b = poly(&[1, 0, 1], 101);
+        assert_eq!(a.resultant(&b).unwrap(), 5 * 10);
+        assert_eq!(b.resultant(&a).unwrap(), 50);
+        assert_eq!(a.resultant(&poly(&[-2, 1], 101)).unwrap(), 0);
+        assert_eq!(poly(&[3], 101).resultant(&a).unwrap(), 9);
+    }
+
+    #[test]
+    fn bivariate_resultant() {
+        // Short pad: m^3 = c1 and (m + r)^3 = c2, the resultant in x vanishes at y = r
+        let p = Integer::from(1_000_000_007);
+        let (m, r) = (Integer::from(123456), Integer::from(789));
+        let c1 = m.clone().pow_mod(&3.into(), &p).unwrap();
+        let c2 = (m + &r).pow_mod(&3.into(), &p).unwrap();
+
+        let constant = |c: i64| Polynomial::constant(c, &p);
+        let f = BivariatePolynomial::new(
+            vec![
+                Polynomial::constant(-c1, &p),
+                constant(0),
+                constant(0),
+                constant(1),
+            ],
+            &p,
+        );
+        // (x + y)^3 - c2 = x^3 + 3y * x^2 + 3y^2 * x + y^3 - c2
+        let g = BivariatePolynomial::new(
+            vec![
+                Polynomial::new([-c2, 0.into(), 0.into(), 1.into()], &p),
+                Polynomial::new([0, 0, 3], &p),
+                Polynomial::new([0, 3], &p),
+                constant(1),
+            ],
+            &p,
+        );
+        assert_eq!((g.degree_x(), g.degree_y()), (Some(3), Some(3)));
+
+        let resultant = f.resultant_x(&g).unwrap();
+        assert_eq!(resultant.degree(), Some(9));
+        assert_eq!(resultant.eval(&r), 0);
+        let t = Integer::from(424242);
+        assert_eq!(
+            resultant.eval(&t),
+            f.eval_y(&t).resultant(&g.eval_y(&t)).unwrap()
+        );
+    }
+}
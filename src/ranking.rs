@@ -0,0 +1,91 @@
+use regex::Regex;
+use rug::Integer;
+
+use crate::{integer_to_bytes, integer_to_bytes_padded};
+
+/// Default pattern of the flags searched in the candidate plaintexts, e.g. `CTF{...}`
+pub const DEFAULT_FLAG_PATTERN: &str = r"[A-Za-z0-9_]+\{[ -|~]+\}";
+
+/// Score of a plaintext matching the flag pattern
+const FLAG_SCORE: u32 = 200;
+
+/// Score of a plaintext with a PKCS#1 v1.5 padding
+const PKCS1_SCORE: u32 = 100;
+
+/// Returns true if the bytes, padded to the length of n, have a PKCS#1 v1.5 structure
+///
+/// Encryption blocks are 00 02 PS 00 M with PS at least 8 non zero bytes,
+/// signature blocks are 00 01 FF..FF 00 M.
+fn is_pkcs1(bytes: &[u8]) -> bool {
+    let (block_type, padding) = match bytes {
+        [0, block_type @ (1 | 2), padding @ ..] => (*block_type, padding),
+        _ => return false,
+    };
+    padding.iter().position(|&b| b == 0).is_some_and(|end| {
+        end >= 8 && (block_type == 2 || padding[..end].iter().all(|&b| b == 0xff))
+    })
+}
+
+/// Score a candidate plaintext, the higher the more likely to be the message
+///
+/// The score adds the percentage of printable ASCII bytes, a bonus for a PKCS#1 v1.5 padding
+/// (checked against the length of n, if known), and a larger one for a match of the flag pattern.
+pub fn plaintext_score(m: &Integer, n: Option<&Integer>, flag: &Regex) -> u32 {
+    let bytes = integer_to_bytes(m);
+    if bytes.is_empty() {
+        return 0;
+    }
+
+    let printable = bytes
+        .iter()
+        .filter(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+        .count();
+    let mut score = (printable * 100 / bytes.len()) as u32;
+
+    if let Some(n) = n {
+        let len = n.significant_bits().div_ceil(8) as usize;
+        if is_pkcs1(&integer_to_bytes_padded(m, len)) {
+            score += PKCS1_SCORE;
+        }
+    }
+    if flag.is_match(&String::from_utf8_lossy(&bytes)) {
+        score += FLAG_SCORE;
+    }
+    score
+}
+
+/// Sort candidate plaintexts from the most to the least likely, see `plaintext_score`
+///
+/// Candidates with the same score keep their order.
+pub fn rank_plaintexts(ms: &mut [Integer], n: Option<&Integer>, flag: &Regex) {
+    ms.sort_by_cached_key(|m| std::cmp::Reverse(plaintext_score(m, n, flag)));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bytes_to_integer, string_to_integer};
+
+    use super::*;
+
+    #[test]
+    fn rank() {
+        let flag = Regex::new(DEFAULT_FLAG_PATTERN).unwrap();
+        let n = Integer::from(1) << 199u32;
+        let mut pkcs1 = vec![0, 2];
+        pkcs1.extend([0x42; 20]);
+        pkcs1.push(0);
+        pkcs1.extend(b"hi");
+
+        let mut ms = vec![
+            bytes_to_integer(&[0x13, 0xff, 0x80, 0x01]),
+            string_to_integer("just text"),
+            bytes_to_integer(&pkcs1),
+            string_to_integer("flag{r4nk3d}"),
+        ];
+        rank_plaintexts(&mut ms, Some(&n), &flag);
+        assert_eq!(ms[0], string_to_integer("flag{r4nk3d}"));
+        assert_eq!(ms[1], bytes_to_integer(&pkcs1));
+        assert_eq!(ms[2], string_to_integer("just text"));
+        assert_eq!(plaintext_score(&ms[3], Some(&n), &flag), 0);
+    }
+}
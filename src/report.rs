@@ -0,0 +1,260 @@
+use std::path::Path;
+
+use rug::Integer;
+
+use crate::{integer_to_bytes, integer_to_string, Parameters, RunStats, Solution};
+
+/// Report document format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Markdown
+    #[default]
+    Markdown,
+    /// Standalone HTML page
+    Html,
+}
+
+impl ReportFormat {
+    /// Format of a report file from its extension, Markdown unless it is `.html` or `.htm`
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => {
+                Self::Html
+            }
+            _ => Self::Markdown,
+        }
+    }
+}
+
+/// Part of a report, rendered in Markdown or HTML
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    Code(String),
+    Table(Vec<String>, Vec<Vec<String>>),
+}
+
+/// Escape the HTML special characters of a text
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Block {
+    fn to_markdown(&self) -> String {
+        let row = |cells: &[String]| {
+            let cells = cells
+                .iter()
+                .map(|cell| cell.replace('|', "\\|"))
+                .collect::<Vec<_>>();
+            format!("| {} |\n", cells.join(" | "))
+        };
+        match self {
+            Self::Heading(level, text) => format!("{} {text}\n", "#".repeat(*level)),
+            Self::Paragraph(text) => format!("{text}\n"),
+            Self::Code(text) => format!("```text\n{}\n```\n", text.trim_end()),
+            Self::Table(header, rows) => {
+                let mut table = row(header);
+                table.push_str(&row(&vec!["---".to_string(); header.len()]));
+                table.extend(rows.iter().map(|cells| row(cells)));
+                table
+            }
+        }
+    }
+
+    fn to_html(&self) -> String {
+        let row = |tag: &str, cells: &[String]| {
+            let cells = cells
+                .iter()
+                .map(|cell| format!("<{tag}>{}</{tag}>", escape_html(cell)))
+                .collect::<String>();
+            format!("<tr>{cells}</tr>\n")
+        };
+        match self {
+            Self::Heading(level, text) => format!("<h{level}>{}</h{level}>\n", escape_html(text)),
+            Self::Paragraph(text) => format!("<p>{}</p>\n", escape_html(text)),
+            Self::Code(text) => format!("<pre>{}</pre>\n", escape_html(text.trim_end())),
+            Self::Table(header, rows) => {
+                let mut table = format!("<table>\n{}", row("th", header));
+                table.extend(rows.iter().map(|cells| row("td", cells)));
+                table.push_str("</table>\n");
+                table
+            }
+        }
+    }
+}
+
+/// Integer, hexadecimal and text forms of a plaintext
+fn plaintext(m: &Integer) -> String {
+    let hex = integer_to_bytes(m)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let mut text = format!("int = {m}\nhex = {hex}");
+    if let Some(string) = integer_to_string(m).filter(|s| !s.chars().any(char::is_control)) {
+        text.push_str(&format!("\nstr = {string}"));
+    }
+    text
+}
+
+/// Blocks describing a solution: its attack, key material and plaintexts
+fn solution_blocks(solution: &Solution, title: String) -> Vec<Block> {
+    let mut blocks = vec![
+        Block::Heading(2, title),
+        Block::Paragraph(format!("Succeeded with attack: {}", solution.attack)),
+    ];
+    if let Some(vulnerability) = &solution.vulnerability {
+        blocks.push(Block::Paragraph(format!("Vulnerability: {vulnerability}")));
+    }
+    if !solution.verified {
+        blocks.push(Block::Paragraph(
+            "The solution could not be verified against the inputs.".to_string(),
+        ));
+    }
+
+    if let Some(pk) = &solution.pk {
+        let mut key = format!("n = {}\ne = {}\n", pk.n, pk.e);
+        for (i, p) in pk.factors.as_vec().into_iter().enumerate() {
+            key.push_str(&format!("p{} = {p}\n", i + 1));
+        }
+        key.push_str(&format!("d = {}\n", pk.d));
+        blocks.push(Block::Heading(3, "Private key".to_string()));
+        blocks.push(Block::Code(key));
+        if pk.factors.len() == 2 {
+            if let Some(pem) = pk.to_pem(&None) {
+                blocks.push(Block::Code(pem));
+            }
+        }
+    }
+
+    if let Some(m) = &solution.m {
+        blocks.push(Block::Heading(3, "Plaintext".to_string()));
+        blocks.push(Block::Code(plaintext(m)));
+    }
+    if !solution.ms.is_empty() {
+        blocks.push(Block::Heading(3, "Possible plaintexts".to_string()));
+        blocks.extend(solution.ms.iter().map(|m| Block::Code(plaintext(m))));
+    }
+    blocks
+}
+
+/// Render a report of a run: the parameters, the solutions found and the statistics of the attacks
+///
+/// Meant as a writeup of the run, in Markdown or as a standalone HTML page.
+pub fn render_report(
+    params: &Parameters,
+    solutions: &[Solution],
+    stats: Option<&RunStats>,
+    format: ReportFormat,
+) -> String {
+    let title = "RsaCracker report";
+    let mut blocks = vec![
+        Block::Heading(1, title.to_string()),
+        Block::Heading(2, "Parameters".to_string()),
+        Block::Code(params.to_string()),
+    ];
+
+    match solutions {
+        [] => {
+            blocks.push(Block::Heading(2, "Solution".to_string()));
+            blocks.push(Block::Paragraph("No attack succeeded.".to_string()));
+        }
+        [solution] => blocks.extend(solution_blocks(solution, "Solution".to_string())),
+        solutions => {
+            for (solution, i) in solutions.iter().zip(1..) {
+                blocks.extend(solution_blocks(solution, format!("Solution {i}")));
+            }
+        }
+    }
+
+    if let Some(stats) = stats {
+        let rows = stats
+            .attacks()
+            .iter()
+            .map(|stats| {
+                vec![
+                    stats.attack.to_string(),
+                    stats.outcome.to_string(),
+                    stats.time_text(),
+                    stats.iterations_text(),
+                ]
+            })
+            .collect();
+        let header = ["Attack", "Outcome", "Time", "Iterations"].map(String::from);
+        blocks.push(Block::Heading(2, "Attacks".to_string()));
+        blocks.push(Block::Table(header.to_vec(), rows));
+    }
+
+    match format {
+        ReportFormat::Markdown => blocks
+            .iter()
+            .map(Block::to_markdown)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ReportFormat::Html => format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+            blocks.iter().map(Block::to_html).collect::<String>()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PrivateKey;
+
+    use super::*;
+
+    fn solution() -> Solution {
+        let pk = PrivateKey::from_p_q(61, 53, 17).unwrap();
+        let mut solution = Solution::new("small_prime", pk, Integer::from(0x41));
+        solution.verified = true;
+        solution
+    }
+
+    #[test]
+    fn markdown() {
+        let params = Parameters {
+            n: Some(3233.into()),
+            e: 17.into(),
+            ..Default::default()
+        };
+        let report = render_report(&params, &[solution()], None, ReportFormat::Markdown);
+        assert!(report.starts_with("# RsaCracker report\n\n## Parameters\n\n```text\n"));
+        assert!(report.contains("Succeeded with attack: small_prime\n"));
+        assert!(report.contains("p1 = 53\np2 = 61\nd = 2753\n"));
+        assert!(report.contains("### Plaintext\n\n```text\nint = 65\nhex = 41\nstr = A\n```\n"));
+        assert!(!report.contains("## Attacks"));
+    }
+
+    #[test]
+    fn html() {
+        let stats = RunStats::new();
+        let report = render_report(
+            &Parameters::default(),
+            &[],
+            Some(&stats),
+            ReportFormat::Html,
+        );
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.contains("<p>No attack succeeded.</p>"));
+        assert!(report.contains(
+            "<table>\n<tr><th>Attack</th><th>Outcome</th><th>Time</th><th>Iterations</th></tr>\n</table>"
+        ));
+        assert_eq!(escape_html("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("writeup.HTML")),
+            ReportFormat::Html
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("writeup.md")),
+            ReportFormat::Markdown
+        );
+    }
+}
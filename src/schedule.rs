@@ -0,0 +1,210 @@
+use std::sync::Arc;
+
+use itertools::Itertools;
+
+use crate::{
+    audit::{fermat_factor, small_factor},
+    diagnose, Attack, Parameters,
+};
+
+/// Attacks using each extra information, by name of the parameter
+const EXTRA_INFORMATION_ATTACKS: [(&str, &[&str]); 23] = [
+    ("d", &["known_d", "partial_d", "leaked_crt_coefficient"]),
+    (
+        "phi",
+        &["known_phi", "non_coprime_exp", "leaked_crt_coefficient"],
+    ),
+    ("dp", &["leaked_crt_exponent", "leaked_crt_exponents"]),
+    ("dq", &["leaked_crt_exponent", "leaked_crt_exponents"]),
+    ("qinv", &["leaked_crt_coefficient", "leaked_crt_exponents"]),
+    ("pinv", &["leaked_crt_coefficient", "leaked_crt_exponents"]),
+    ("p", &["leaked_pq"]),
+    ("q", &["leaked_pq"]),
+    ("sum_pq", &["sum_pq", "symmetric_pq"]),
+    ("sum_squares_pq", &["symmetric_pq"]),
+    ("diff_pq", &["symmetric_pq"]),
+    ("xor_pq", &["xor_pq"]),
+    ("sum_pq_mod", &["symmetric_pq"]),
+    ("pq_mod", &["symmetric_pq"]),
+    ("p_mod", &["p_mod"]),
+    ("signed_m", &["signature_dlog"]),
+    ("signature", &["signature_dlog"]),
+    (
+        "partial_p",
+        &["partial_pq", "partial_prime", "branch_and_prune"],
+    ),
+    (
+        "partial_q",
+        &["partial_pq", "partial_prime", "branch_and_prune"],
+    ),
+    ("partial_dp", &["partial_crt_exponent"]),
+    ("partial_dq", &["partial_crt_exponent"]),
+    ("partial_m", &["partial_m"]),
+    ("keys", &["common_modulus", "hastad"]),
+];
+
+/// Returns the names of the given parameters, besides n, e and c
+fn extra_information(params: &Parameters) -> Vec<&'static str> {
+    let mut names = params
+        .fields()
+        .into_iter()
+        .map(|(name, _)| name)
+        .filter(|name| !["n", "e", "c"].contains(name))
+        .collect::<Vec<_>>();
+    let others = [
+        ("sum_pq_mod", params.sum_pq_mod.is_some()),
+        ("pq_mod", params.pq_mod.is_some()),
+        ("p_mod", params.p_mod.is_some()),
+        ("partial_p", params.partial_p.is_some()),
+        ("partial_q", params.partial_q.is_some()),
+        ("partial_dp", params.partial_dp.is_some()),
+        ("partial_dq", params.partial_dq.is_some()),
+        ("partial_m", params.partial_m.is_some()),
+        ("keys", !params.keys.is_empty()),
+    ];
+    names.extend(
+        others
+            .into_iter()
+            .filter(|(_, given)| *given)
+            .map(|(name, _)| name),
+    );
+    names
+}
+
+/// Returns the attacks likely to succeed on the parameters, the most promising first
+///
+/// The attacks using the given extra information come first, then the ones matching a quick
+/// probe of n (a small factor, or close primes found with a few Fermat iterations), then the ones
+/// enabled by the diagnostics (e.g. a small or a large e).
+pub fn promising_attacks(params: &Parameters) -> Vec<&'static str> {
+    let mut attacks = vec![];
+    for name in extra_information(params) {
+        if let Some((_, used_by)) = EXTRA_INFORMATION_ATTACKS.iter().find(|(n, _)| *n == name) {
+            attacks.extend(*used_by);
+        }
+    }
+    if let Some(n) = &params.n {
+        if n.is_even() || small_factor(n).is_some() {
+            attacks.push("small_prime");
+        }
+        if fermat_factor(n).is_some() {
+            attacks.push("fermat");
+        }
+    }
+    for diagnostic in diagnose(params) {
+        attacks.extend(diagnostic.attacks());
+    }
+    attacks.into_iter().unique().collect()
+}
+
+/// Move the most promising attacks on the parameters first, see `promising_attacks`
+///
+/// The other attacks keep their order.
+pub fn schedule_attacks(params: &Parameters, attacks: &mut [Arc<dyn Attack + Sync + Send>]) {
+    let promising = promising_attacks(params);
+    attacks.sort_by_key(|attack| {
+        promising
+            .iter()
+            .position(|&name| name == attack.name())
+            .unwrap_or(promising.len())
+    });
+}
+
+/// Returns the attacks using the parameters an attack discovered, which it gives or changes
+///
+/// They are run again on the parameters completed with the discovered ones, e.g. `known_d` once
+/// `partial_d` has recovered the whole of d.
+pub fn dependent_attacks(params: &Parameters, discovered: &Parameters) -> Vec<&'static str> {
+    let known = params.fields();
+    discovered
+        .fields()
+        .into_iter()
+        .filter(|field| !known.contains(field))
+        .filter_map(|(name, _)| EXTRA_INFORMATION_ATTACKS.iter().find(|(n, _)| *n == name))
+        .flat_map(|(_, used_by)| used_by.iter().copied())
+        .unique()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::Integer;
+
+    use crate::{Parameters, ATTACKS};
+
+    #[test]
+    fn promising_attacks() {
+        // Close primes, and a sum of p and q
+        let p = Integer::from(1779681653u64);
+        let params = Parameters {
+            n: Some(p.clone() * p.clone().next_prime()),
+            sum_pq: Some(1.into()),
+            e: 3.into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            super::promising_attacks(&params),
+            [
+                "sum_pq",
+                "symmetric_pq",
+                "fermat",
+                "small_prime",
+                "pollard_rho",
+                "squfof",
+                "ecm",
+                "cube_root",
+                "small_e",
+                "eth_root"
+            ]
+        );
+
+        // A small factor
+        let params = Parameters {
+            n: Some(Integer::from(65521) * (Integer::from(1) << 300u32).next_prime()),
+            ..Default::default()
+        };
+        assert_eq!(super::promising_attacks(&params), ["small_prime"]);
+        assert!(super::promising_attacks(&Parameters::default()).is_empty());
+    }
+
+    #[test]
+    fn dependent_attacks() {
+        let params = Parameters {
+            n: Some(143.into()),
+            d: Some(7.into()),
+            ..Default::default()
+        };
+        assert!(super::dependent_attacks(&params, &params).is_empty());
+        assert_eq!(
+            super::dependent_attacks(
+                &params,
+                &Parameters {
+                    d: Some(103.into()),
+                    sum_pq: Some(24.into()),
+                    ..Default::default()
+                }
+            ),
+            [
+                "known_d",
+                "partial_d",
+                "leaked_crt_coefficient",
+                "sum_pq",
+                "symmetric_pq"
+            ]
+        );
+    }
+
+    #[test]
+    fn schedule_attacks() {
+        let params = Parameters {
+            n: Some(Integer::from(3) * (Integer::from(1) << 600u32).next_prime()),
+            p_mod: Some((1.into(), 10.into())),
+            ..Default::default()
+        };
+        let mut attacks = ATTACKS.to_vec();
+        super::schedule_attacks(&params, &mut attacks);
+        assert_eq!(attacks[0].name(), "p_mod");
+        assert_eq!(attacks[1].name(), "small_prime");
+        assert_eq!(attacks.len(), ATTACKS.len());
+    }
+}
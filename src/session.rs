@@ -0,0 +1,539 @@
+use std::{
+    fs,
+    io::{self, Seek, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use rug::Integer;
+use serde_json::{json, Value};
+
+use crate::{AttackOutcome, AttackStats, KeyEntry, Parameters, ATTACKS};
+
+/// Command line arguments whose values are secrets: private key values, values revealing the
+/// factors, passphrases and tokens
+const SECRET_ARGS: [&str; 19] = [
+    "-p",
+    "-q",
+    "-d",
+    "--phi",
+    "--dp",
+    "--dmp1",
+    "--dq",
+    "--dmq1",
+    "--qinv",
+    "--iqmp",
+    "--pinv",
+    "--ipmq",
+    "--sum-pq",
+    "--sum-squares-pq",
+    "--diff-pq",
+    "--xor-pq",
+    "--password",
+    "--addpassword",
+    "--token",
+];
+
+/// Command line arguments followed by a secret value and its modulus, both redacted
+const SECRET_MOD_ARGS: [&str; 3] = ["--sum-pq-mod", "--pq-mod", "--p-mod"];
+
+/// Replacement of the redacted argument values
+const REDACTED: &str = "<redacted>";
+
+/// Returns the command line arguments with the values of the secret ones redacted, to be logged
+///
+/// Both `--password secret` and `--password=secret` forms are redacted, as well as `-psecret`.
+pub fn redact_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut redact_next = 0;
+    args.into_iter()
+        .map(|arg| {
+            if redact_next > 0 {
+                redact_next -= 1;
+                return REDACTED.to_string();
+            }
+            if SECRET_ARGS.contains(&arg.as_str()) {
+                redact_next = 1;
+                return arg;
+            }
+            if SECRET_MOD_ARGS.contains(&arg.as_str()) {
+                redact_next = 2;
+                return arg;
+            }
+            for name in SECRET_ARGS.iter().chain(&SECRET_MOD_ARGS) {
+                match arg.strip_prefix(name) {
+                    Some(value) if value.starts_with('=') => {
+                        // The modulus follows as a separate argument
+                        redact_next = usize::from(SECRET_MOD_ARGS.contains(name));
+                        return format!("{name}={REDACTED}");
+                    }
+                    Some(value) if !name.starts_with("--") && !value.is_empty() => {
+                        return format!("{name}{REDACTED}")
+                    }
+                    _ => {}
+                }
+            }
+            arg
+        })
+        .collect()
+}
+
+/// Returns the parameters without the values revealing the private key, even partially
+fn public_params(params: &Parameters) -> Parameters {
+    Parameters {
+        p: None,
+        q: None,
+        d: None,
+        phi: None,
+        dp: None,
+        dq: None,
+        qinv: None,
+        pinv: None,
+        sum_pq: None,
+        sum_squares_pq: None,
+        diff_pq: None,
+        xor_pq: None,
+        sum_pq_mod: None,
+        pq_mod: None,
+        p_mod: None,
+        partial_p: None,
+        partial_q: None,
+        partial_dp: None,
+        partial_dq: None,
+        keys: params
+            .keys
+            .iter()
+            .map(|key| KeyEntry {
+                known: public_params(&key.known),
+                ..key.clone()
+            })
+            .collect(),
+        ..params.clone()
+    }
+}
+
+/// Run recorded in the session log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionEntry {
+    /// Start of the run, in seconds since the Unix epoch
+    pub time: u64,
+    /// Command line arguments of the run, see `redact_args`
+    pub args: Vec<String>,
+    /// Attacked parameters, whose values revealing the private key are not logged
+    pub params: Parameters,
+    /// Names of the scheduled attacks, in order
+    pub attacks: Vec<String>,
+    /// Statistics of the attacks which ran, in the order they ended
+    pub outcomes: Vec<AttackStats>,
+    /// Name of the attack which found a solution, if any
+    pub solved_by: Option<String>,
+    /// Wall time of the run
+    pub elapsed: Duration,
+    /// Version of RsaCracker
+    pub version: String,
+    /// Operating system and architecture, e.g. linux-x86_64
+    pub platform: String,
+    /// Number of threads of the run
+    pub threads: usize,
+}
+
+impl SessionEntry {
+    /// Names of the scheduled attacks which did not run to completion: not run, timed out or
+    /// stopped, to continue the run
+    pub fn unfinished_attacks(&self) -> Vec<&str> {
+        self.attacks
+            .iter()
+            .map(String::as_str)
+            .filter(|name| {
+                !self.outcomes.iter().any(|stats| {
+                    stats.attack == *name
+                        && !matches!(
+                            stats.outcome,
+                            AttackOutcome::TimedOut | AttackOutcome::Stopped
+                        )
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the size in bits of the attacked modulus, 0 if it is unknown
+    pub fn modulus_bits(&self) -> u32 {
+        self.params.n.as_ref().map_or(0, Integer::significant_bits)
+    }
+
+    fn to_json(&self) -> Value {
+        let outcomes = self
+            .outcomes
+            .iter()
+            .map(|stats| {
+                json!({
+                    "attack": stats.attack,
+                    "outcome": stats.outcome.to_string(),
+                    "elapsed_ms": stats.elapsed.as_millis() as u64,
+                    "iterations": stats.iterations,
+                    "unit": stats.unit,
+                })
+            })
+            .collect::<Vec<_>>();
+        // Private key values, even partial, are never written: replays need them again
+        let params = public_params(&self.params);
+        json!({
+            "time": self.time,
+            "args": self.args,
+            "params": params.to_string(),
+            "attacks": self.attacks,
+            "outcomes": outcomes,
+            "solved_by": self.solved_by,
+            "elapsed_ms": self.elapsed.as_millis() as u64,
+            "version": self.version,
+            "platform": self.platform,
+            "threads": self.threads,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let strings = |value: &Value| {
+            value.as_array().map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(String::from))
+                    .collect::<Vec<_>>()
+            })
+        };
+        let outcomes = value["outcomes"]
+            .as_array()?
+            .iter()
+            .filter_map(|stats| {
+                // Attacks removed since the run are left out
+                let name = stats["attack"].as_str()?;
+                let attack = ATTACKS.iter().find(|attack| attack.name() == name)?;
+                Some(AttackStats {
+                    attack: attack.name(),
+                    outcome: AttackOutcome::from_str(stats["outcome"].as_str()?).ok()?,
+                    elapsed: Duration::from_millis(stats["elapsed_ms"].as_u64()?),
+                    iterations: stats["iterations"].as_u64()?,
+                    unit: stats["unit"].as_str()?.to_string(),
+                })
+            })
+            .collect();
+        Some(Self {
+            time: value["time"].as_u64()?,
+            args: strings(&value["args"])?,
+            params: Parameters::from_raw(value["params"].as_str()?),
+            attacks: strings(&value["attacks"])?,
+            outcomes,
+            solved_by: value["solved_by"].as_str().map(String::from),
+            elapsed: Duration::from_millis(value["elapsed_ms"].as_u64()?),
+            version: value["version"].as_str()?.to_string(),
+            platform: value["platform"].as_str()?.to_string(),
+            threads: value["threads"].as_u64()? as usize,
+        })
+    }
+}
+
+/// Append-only log of the runs, one JSON entry per line.
+///
+/// Entries are numbered from 1, by line. The file is only readable by its owner, and holds no
+/// values revealing the private key nor passphrases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionLog {
+    path: PathBuf,
+}
+
+impl SessionLog {
+    /// Create a session log stored in the given file.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Create a session log stored in the default file (`~/.local/state/rsacracker/sessions.jsonl`).
+    ///
+    /// The file can be overridden with `RSACRACKER_SESSION_LOG`.
+    /// Returns `None` if no state directory can be found.
+    pub fn from_env() -> Option<Self> {
+        if let Some(path) = std::env::var_os("RSACRACKER_SESSION_LOG").filter(|p| !p.is_empty()) {
+            return Some(Self::new(path));
+        }
+        std::env::var_os("XDG_STATE_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .filter(|d| !d.is_empty())
+                    .map(|home| Path::new(&home).join(".local").join("state"))
+            })
+            .map(|dir| Self::new(dir.join("rsacracker").join("sessions.jsonl")))
+    }
+
+    /// Returns the session log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn lines(&self) -> Vec<String> {
+        fs::read_to_string(&self.path)
+            .map(|content| content.lines().map(String::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// Append an entry to the log, and return its number.
+    ///
+    /// The entry is written with a single append, so the entries of concurrent runs are neither
+    /// interleaved nor given the same number.
+    pub fn append(&self, entry: &SessionEntry) -> io::Result<usize> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&self.path)?;
+        file.write_all(format!("{}\n", entry.to_json()).as_bytes())?;
+
+        // The file offset is at the end of the entry, even if other runs appended since
+        let end = file.stream_position()? as usize;
+        let content = fs::read(&self.path)?;
+        Ok(content[..end].iter().filter(|&&byte| byte == b'\n').count())
+    }
+
+    /// Returns the entry of the given number, if it exists and is valid.
+    pub fn entry(&self, number: usize) -> Option<SessionEntry> {
+        let line = self.lines().into_iter().nth(number.checked_sub(1)?)?;
+        SessionEntry::from_json(&serde_json::from_str(&line).ok()?)
+    }
+
+    /// Returns the valid entries of the log, with their number.
+    pub fn entries(&self) -> Vec<(usize, SessionEntry)> {
+        self.lines()
+            .into_iter()
+            .zip(1..)
+            .filter_map(|(line, number)| {
+                let entry = SessionEntry::from_json(&serde_json::from_str(&line).ok()?)?;
+                Some((number, entry))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(outcomes: Vec<AttackStats>) -> SessionEntry {
+        SessionEntry {
+            time: 1_700_000_000,
+            args: vec![
+                "rsacracker".to_string(),
+                "-n".to_string(),
+                "143".to_string(),
+            ],
+            params: Parameters {
+                n: Some(143.into()),
+                c: Some(42.into()),
+                ..Default::default()
+            },
+            attacks: vec![
+                "wiener".to_string(),
+                "ecm".to_string(),
+                "fermat".to_string(),
+            ],
+            outcomes,
+            solved_by: None,
+            elapsed: Duration::from_millis(1500),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            platform: "linux-x86_64".to_string(),
+            threads: 4,
+        }
+    }
+
+    fn stats(attack: &'static str, outcome: AttackOutcome) -> AttackStats {
+        AttackStats {
+            attack,
+            outcome,
+            elapsed: Duration::from_millis(500),
+            iterations: 42,
+            unit: "curves".to_string(),
+        }
+    }
+
+    #[test]
+    fn append_and_read() {
+        let path =
+            std::env::temp_dir().join(format!("rsacracker-sessions-{}.jsonl", std::process::id()));
+        fs::remove_file(&path).ok();
+        let log = SessionLog::new(&path);
+
+        let first = entry(vec![stats("wiener", AttackOutcome::Failed)]);
+        let second = entry(vec![stats("ecm", AttackOutcome::TimedOut)]);
+        assert_eq!(log.append(&first).unwrap(), 1);
+        assert_eq!(log.append(&second).unwrap(), 2);
+        assert_eq!(log.entry(1), Some(first));
+        assert_eq!(log.entry(2), Some(second));
+        assert_eq!(log.entry(0), None);
+        assert_eq!(log.entry(3), None);
+        assert_eq!(log.entries().len(), 2);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn concurrent_appends() {
+        let path = std::env::temp_dir().join(format!(
+            "rsacracker-sessions-concurrent-{}.jsonl",
+            std::process::id()
+        ));
+        fs::remove_file(&path).ok();
+        let log = SessionLog::new(&path);
+
+        let entry = entry(vec![stats("wiener", AttackOutcome::Failed)]);
+        let mut numbers = std::thread::scope(|s| {
+            let handles = (0..8)
+                .map(|_| s.spawn(|| log.append(&entry).unwrap()))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        numbers.sort();
+        assert_eq!(numbers, (1..=8).collect::<Vec<_>>());
+        assert_eq!(log.entries().len(), 8);
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn private_values_are_not_logged() {
+        let path = std::env::temp_dir().join(format!(
+            "rsacracker-sessions-private-{}.jsonl",
+            std::process::id()
+        ));
+        fs::remove_file(&path).ok();
+        let log = SessionLog::new(&path);
+
+        let mut entry = entry(vec![]);
+        entry.params.p = Some(11.into());
+        entry.params.d = Some(103.into());
+        entry.params.partial_q = Some("0x1?".parse().unwrap());
+        entry.params.sum_pq = Some(24.into());
+        entry.params.diff_pq = Some(2.into());
+        entry.params.xor_pq = Some(6.into());
+        entry.params.sum_squares_pq = Some(290.into());
+        entry.params.p_mod = Some((1.into(), 10.into()));
+        entry.params.sum_pq_mod = Some((4.into(), 10.into()));
+        entry.params.pq_mod = Some((3.into(), 10.into()));
+        entry.params.keys = vec![KeyEntry {
+            n: 143.into(),
+            e: 65537.into(),
+            c: None,
+            known: Parameters {
+                q: Some(13.into()),
+                ..Default::default()
+            },
+        }];
+        log.append(&entry).unwrap();
+        let logged = log.entry(1).unwrap();
+        assert_eq!(logged.params.p, None);
+        assert_eq!(logged.params.d, None);
+        assert_eq!(logged.params.partial_q, None);
+        assert_eq!(logged.params.sum_pq, None);
+        assert_eq!(logged.params.diff_pq, None);
+        assert_eq!(logged.params.xor_pq, None);
+        assert_eq!(logged.params.sum_squares_pq, None);
+        assert_eq!(logged.params.p_mod, None);
+        assert_eq!(logged.params.sum_pq_mod, None);
+        assert_eq!(logged.params.pq_mod, None);
+        assert!(!fs::read_to_string(&path).unwrap().contains("290"));
+        assert_eq!(logged.params.n, Some(143.into()));
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn redact_args() {
+        let args = [
+            "rsacracker",
+            "-n",
+            "143",
+            "-p",
+            "11",
+            "-d103",
+            "--password",
+            "secret",
+            "--addpassword=secret",
+            "--password-wordlist",
+            "words.txt",
+            "--phi",
+            "120",
+            "--sum-pq",
+            "24",
+            "--xor-pq=6",
+            "--diff-pq",
+            "2",
+            "--sum-squares-pq",
+            "290",
+            "--p-mod",
+            "1",
+            "10",
+            "--sum-pq-mod=4",
+            "10",
+            "--pq-mod",
+            "3",
+            "10",
+            "-e",
+            "3",
+        ];
+        assert_eq!(
+            super::redact_args(args.map(String::from)),
+            [
+                "rsacracker",
+                "-n",
+                "143",
+                "-p",
+                "<redacted>",
+                "-d<redacted>",
+                "--password",
+                "<redacted>",
+                "--addpassword=<redacted>",
+                "--password-wordlist",
+                "words.txt",
+                "--phi",
+                "<redacted>",
+                "--sum-pq",
+                "<redacted>",
+                "--xor-pq=<redacted>",
+                "--diff-pq",
+                "<redacted>",
+                "--sum-squares-pq",
+                "<redacted>",
+                "--p-mod",
+                "<redacted>",
+                "<redacted>",
+                "--sum-pq-mod=<redacted>",
+                "<redacted>",
+                "--pq-mod",
+                "<redacted>",
+                "<redacted>",
+                "-e",
+                "3",
+            ]
+        );
+    }
+
+    #[test]
+    fn unfinished_attacks() {
+        let entry = entry(vec![
+            stats("wiener", AttackOutcome::Failed),
+            stats("ecm", AttackOutcome::TimedOut),
+        ]);
+        assert_eq!(entry.unfinished_attacks(), ["ecm", "fermat"]);
+        assert_eq!(entry.modulus_bits(), 8);
+    }
+}
@@ -1,6 +1,6 @@
 use rug::Integer;
 
-use crate::key::PrivateKey;
+use crate::{key::PrivateKey, Parameters, Vulnerability};
 
 /// Attack's result
 #[derive(Debug, Clone)]
@@ -13,6 +13,12 @@ pub struct Solution {
     pub m: Option<Integer>,
     /// Possible decrypted messages
     pub ms: Vec<Integer>,
+    /// True if the solution was checked against the parameters
+    pub verified: bool,
+    /// Weakness of the key exploited by the attack
+    pub vulnerability: Option<Vulnerability>,
+    /// Parameters derived by the attack, passed on to the attacks using them
+    pub discovered: Option<Box<Parameters>>,
 }
 
 impl Solution {
@@ -23,6 +29,9 @@ impl Solution {
             pk: Some(pk),
             m: Some(m),
             ms: vec![],
+            verified: false,
+            vulnerability: None,
+            discovered: None,
         }
     }
 
@@ -33,6 +42,9 @@ impl Solution {
             pk: Some(pk),
             m: None,
             ms: vec![],
+            verified: false,
+            vulnerability: None,
+            discovered: None,
         }
     }
 
@@ -43,6 +55,9 @@ impl Solution {
             pk: None,
             m: Some(m),
             ms: vec![],
+            verified: false,
+            vulnerability: None,
+            discovered: None,
         }
     }
 
@@ -53,6 +68,163 @@ impl Solution {
             pk: None,
             m: None,
             ms,
+            verified: false,
+            vulnerability: None,
+            discovered: None,
         }
     }
+
+    /// Tag the solution with the weakness of the key exploited by the attack
+    pub fn with_vulnerability(mut self, vulnerability: Vulnerability) -> Self {
+        self.vulnerability = Some(vulnerability);
+        self
+    }
+
+    /// Attach the parameters derived by the attack, besides its solution
+    pub fn with_discovered(mut self, discovered: Parameters) -> Self {
+        self.discovered = Some(Box::new(discovered));
+        self
+    }
+
+    /// Keep the candidate plaintexts accepted by `accepts`, returns false if none is left
+    ///
+    /// A solution without plaintext, e.g. a private key without cipher, is kept as is.
+    pub fn keep_candidates(&mut self, accepts: impl Fn(&Integer) -> bool) -> bool {
+        if let Some(m) = &self.m {
+            return accepts(m);
+        }
+        if !self.ms.is_empty() {
+            self.ms.retain(&accepts);
+            return !self.ms.is_empty();
+        }
+        true
+    }
+
+    /// Check the solution against the parameters, and mark it as verified if it matches them
+    ///
+    /// The private key must match n and e, or the known signature which pins down e, and the
+    /// messages must encrypt back to the cipher. Possible messages which do not are dropped.
+    /// Returns false if the solution contradicts the parameters.
+    pub fn verify(&mut self, params: &Parameters) -> bool {
+        let mut verified = true;
+        if let Some(pk) = &self.pk {
+            // Signing is decrypting the message
+            let signs = match (&params.signed_m, &params.signature) {
+                (Some(m), Some(s)) => Some(pk.decrypt(m) == Integer::from(s % &pk.n)),
+                _ => None,
+            };
+            if !pk.is_valid()
+                || signs == Some(false)
+                || (pk.e != params.e && signs.is_none())
+                || params.n.as_ref().is_some_and(|n| n != &pk.n)
+            {
+                return false;
+            }
+            // The known cipher must decrypt to the known plaintext
+            if let (Some(m), Some(c)) = (&params.known_m, &params.known_c) {
+                if pk.decrypt(c) != Integer::from(m % &pk.n) {
+                    return false;
+                }
+            }
+        }
+
+        let n = params.n.as_ref().or(self.pk.as_ref().map(|pk| &pk.n));
+        let e = self.pk.as_ref().map_or(&params.e, |pk| &pk.e);
+        if let (Some(n), Some(c)) = (n, &params.c) {
+            let encrypts_to_c = |m: &Integer| m.clone().pow_mod(e, n).is_ok_and(|m_e| &m_e == c);
+            if self.m.as_ref().is_some_and(|m| !encrypts_to_c(m)) {
+                return false;
+            }
+            if !self.ms.is_empty() {
+                self.ms.retain(encrypts_to_c);
+                if self.ms.is_empty() {
+                    return false;
+                }
+            }
+        } else if self.m.is_some() || !self.ms.is_empty() {
+            verified = false;
+        }
+
+        self.verified = verified && (self.pk.is_some() || self.m.is_some() || !self.ms.is_empty());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify() {
+        let pk = PrivateKey::from_p_q(1779681653u64, 1903643191u64, 65537).unwrap();
+        let params = Parameters {
+            n: Some(pk.n.clone()),
+            c: Some(Integer::from(42).pow_mod(&pk.e, &pk.n).unwrap()),
+            ..Default::default()
+        };
+
+        let mut solution = Solution::new("test", pk.clone(), 42.into());
+        assert!(solution.verify(&params));
+        assert!(solution.verified);
+
+        let mut solution = Solution::new("test", pk, 43.into());
+        assert!(!solution.verify(&params));
+
+        let mut solution = Solution::new_ms("test", vec![41.into(), 42.into()]);
+        assert!(solution.verify(&params));
+        assert_eq!(solution.ms, vec![Integer::from(42)]);
+
+        // Nothing to check the message against
+        let mut solution = Solution::new_m("test", 42.into());
+        assert!(solution.verify(&Parameters::default()));
+        assert!(!solution.verified);
+    }
+
+    #[test]
+    fn keep_candidates() {
+        let mut solution = Solution::new_m("test", 42.into());
+        assert!(solution.keep_candidates(|m| *m == 42));
+        assert!(!solution.keep_candidates(|m| *m == 43));
+
+        let mut solution = Solution::new_ms("test", vec![41.into(), 42.into()]);
+        assert!(solution.keep_candidates(|m| *m == 42));
+        assert_eq!(solution.ms, vec![Integer::from(42)]);
+        assert!(!solution.keep_candidates(|m| *m == 41));
+
+        let pk = PrivateKey::from_p_q(1779681653u64, 1903643191u64, 65537).unwrap();
+        assert!(Solution::new_pk("test", pk).keep_candidates(|_| false));
+    }
+
+    #[test]
+    fn verify_known_pair() {
+        let pk = PrivateKey::from_p_q(1779681653u64, 1903643191u64, 65537).unwrap();
+        let mut params = Parameters {
+            n: Some(pk.n.clone()),
+            known_m: Some(1337.into()),
+            known_c: Some(Integer::from(1337).pow_mod(&pk.e, &pk.n).unwrap()),
+            ..Default::default()
+        };
+        assert!(Solution::new_pk("test", pk.clone()).verify(&params));
+
+        // The known cipher is not the one of the known plaintext under this key
+        params.known_c = Some(1338.into());
+        assert!(!Solution::new_pk("test", pk).verify(&params));
+    }
+
+    #[test]
+    fn verify_signature() {
+        let pk = PrivateKey::from_p_q(1779681653u64, 1903643191u64, 65537).unwrap();
+        let mut params = Parameters {
+            n: Some(pk.n.clone()),
+            e: 3.into(),
+            signed_m: Some(1337.into()),
+            signature: Some(Integer::from(1337).pow_mod(&pk.d, &pk.n).unwrap()),
+            ..Default::default()
+        };
+        // The signature is made with the key, whose e is not the given one
+        assert!(Solution::new_pk("test", pk.clone()).verify(&params));
+
+        params.signature = Some(1338.into());
+        assert!(!Solution::new_pk("test", pk).verify(&params));
+    }
 }
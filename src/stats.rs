@@ -0,0 +1,265 @@
+use std::{fmt::Display, sync::Mutex, time::Duration};
+
+use indicatif::ProgressBar;
+
+use crate::{Error, Solution};
+
+/// Outcome of an attack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackOutcome {
+    /// The attack found a solution
+    Solved,
+    /// The attack found some factors of n
+    PartialFactors,
+    /// The attack derived parameters, passed on to the attacks using them
+    Discovered,
+    /// The attack ran to completion without a solution
+    Failed,
+    /// The attack is not applicable, its parameters are missing
+    NotApplicable,
+    /// The attack ran out of time
+    TimedOut,
+    /// The attack was stopped, after another attack succeeded or at the end of the run
+    Stopped,
+}
+
+impl AttackOutcome {
+    /// Outcome of an attack from its result, `stopped` telling if the whole run was stopped
+    pub(crate) fn new(result: &Result<Solution, Error>, stopped: bool) -> Self {
+        match result {
+            Ok(_) => Self::Solved,
+            Err(Error::PartialFactorization(_)) => Self::PartialFactors,
+            Err(Error::Discovered(_)) => Self::Discovered,
+            Err(Error::MissingParameters) => Self::NotApplicable,
+            Err(Error::Stopped) if stopped => Self::Stopped,
+            Err(Error::Stopped) => Self::TimedOut,
+            Err(_) => Self::Failed,
+        }
+    }
+}
+
+impl Display for AttackOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Padded, to be aligned in the statistics table
+        f.pad(match self {
+            Self::Solved => "solved",
+            Self::PartialFactors => "partial factors",
+            Self::Discovered => "discovered",
+            Self::Failed => "failed",
+            Self::NotApplicable => "not applicable",
+            Self::TimedOut => "timed out",
+            Self::Stopped => "stopped",
+        })
+    }
+}
+
+impl std::str::FromStr for AttackOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::Solved,
+            Self::PartialFactors,
+            Self::Discovered,
+            Self::Failed,
+            Self::NotApplicable,
+            Self::TimedOut,
+            Self::Stopped,
+        ]
+        .into_iter()
+        .find(|outcome| outcome.to_string() == s)
+        .ok_or_else(|| format!("Unknown outcome: {s}"))
+    }
+}
+
+/// Statistics of an attack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttackStats {
+    /// Attack name
+    pub attack: &'static str,
+    /// Outcome of the attack
+    pub outcome: AttackOutcome,
+    /// Wall time of the attack, over all its time slices
+    pub elapsed: Duration,
+    /// Steps done, or things tried, by the attack
+    pub iterations: u64,
+    /// Unit of the iterations of an unbounded attack (curves, candidates, etc.), empty otherwise
+    pub unit: String,
+}
+
+impl AttackStats {
+    /// Statistics of an attack, its iterations being read from its progress bar
+    pub(crate) fn new(
+        attack: &'static str,
+        outcome: AttackOutcome,
+        elapsed: Duration,
+        pb: &ProgressBar,
+    ) -> Self {
+        Self {
+            attack,
+            outcome,
+            elapsed,
+            iterations: pb.position(),
+            unit: pb.message(),
+        }
+    }
+
+    /// Wall time of the attack, in seconds
+    pub(crate) fn time_text(&self) -> String {
+        format!("{:.3}s", self.elapsed.as_secs_f64())
+    }
+
+    /// Iterations of the attack and their unit, `-` if it reported none
+    pub(crate) fn iterations_text(&self) -> String {
+        match (self.iterations, self.unit.as_str()) {
+            (0, _) => "-".to_string(),
+            (iterations, "") => iterations.to_string(),
+            (iterations, unit) => format!("{iterations} {unit}"),
+        }
+    }
+}
+
+/// Event of an attack run by `run_one`, sent to the caller as the attack goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttackEvent {
+    /// The attack started
+    Started {
+        /// Name of the attack
+        attack: &'static str,
+    },
+    /// The attack progressed
+    Progress {
+        /// Name of the attack
+        attack: &'static str,
+        /// Steps done
+        position: u64,
+        /// Steps to do, if known
+        length: Option<u64>,
+    },
+    /// The attack ended, its solution being returned by `run_one`
+    Finished {
+        /// Name of the attack
+        attack: &'static str,
+        /// Outcome of the attack
+        outcome: AttackOutcome,
+        /// Time spent by the attack
+        elapsed: Duration,
+    },
+}
+
+/// Statistics of all the attacks of a run, in the order they ended
+#[derive(Debug, Default)]
+pub struct RunStats(Mutex<Vec<AttackStats>>);
+
+impl RunStats {
+    /// Create empty statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the statistics of an attack
+    pub(crate) fn record(&self, stats: AttackStats) {
+        if let Ok(mut attacks) = self.0.lock() {
+            attacks.push(stats);
+        }
+    }
+
+    /// Returns the statistics of the attacks
+    pub fn attacks(&self) -> Vec<AttackStats> {
+        self.0
+            .lock()
+            .map(|attacks| attacks.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Display for RunStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let attacks = self.attacks();
+        let width = attacks
+            .iter()
+            .map(|stats| stats.attack.len())
+            .chain(["Attack".len()])
+            .max()
+            .unwrap_or_default();
+
+        writeln!(
+            f,
+            "{:width$}  {:15}  {:>10}  Iterations",
+            "Attack", "Outcome", "Time"
+        )?;
+        for stats in attacks {
+            writeln!(
+                f,
+                "{:width$}  {:15}  {:>10}  {}",
+                stats.attack,
+                stats.outcome,
+                stats.time_text(),
+                stats.iterations_text()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Factors;
+
+    use super::*;
+
+    #[test]
+    fn outcomes() {
+        let outcome =
+            |result: Result<Solution, Error>, stopped| AttackOutcome::new(&result, stopped);
+        assert_eq!(
+            outcome(Ok(Solution::new_m("test", 42.into())), false),
+            AttackOutcome::Solved
+        );
+        assert_eq!(
+            outcome(
+                Err(Error::PartialFactorization(Factors::from([3, 5]))),
+                false
+            ),
+            AttackOutcome::PartialFactors
+        );
+        assert_eq!(
+            outcome(Err(Error::Discovered(Box::default())), false),
+            AttackOutcome::Discovered
+        );
+        assert_eq!(outcome(Err(Error::NotFound), false), AttackOutcome::Failed);
+        assert_eq!(
+            outcome(Err(Error::MissingParameters), false),
+            AttackOutcome::NotApplicable
+        );
+        assert_eq!(outcome(Err(Error::Stopped), false), AttackOutcome::TimedOut);
+        assert_eq!(outcome(Err(Error::Stopped), true), AttackOutcome::Stopped);
+    }
+
+    #[test]
+    fn table() {
+        let pb = ProgressBar::hidden();
+        let stats = RunStats::new();
+        stats.record(AttackStats::new(
+            "wiener",
+            AttackOutcome::Failed,
+            Duration::from_millis(1500),
+            &pb,
+        ));
+        pb.inc(42);
+        pb.set_message("curves");
+        stats.record(AttackStats::new(
+            "ecm",
+            AttackOutcome::Solved,
+            Duration::from_millis(20),
+            &pb,
+        ));
+
+        assert_eq!(
+            stats.to_string(),
+            "Attack  Outcome                Time  Iterations\n\
+             wiener  failed               1.500s  -\n\
+             ecm     solved               0.020s  42 curves\n"
+        );
+    }
+}
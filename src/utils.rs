@@ -1,20 +1,119 @@
-use rug::{ops::Pow, Integer};
+use base64::{engine::general_purpose, Engine};
+use rug::{integer::Order, ops::Pow, Integer};
 
 /// Convert a `rug::Integer` to a byte vector.
 pub fn integer_to_bytes(i: &Integer) -> Vec<u8> {
     base_x::decode("0123456789", &i.to_string()).unwrap()
 }
 
+/// Convert a `rug::Integer` to a byte vector, left-padded with zeros to at least `len` bytes.
+pub fn integer_to_bytes_padded(i: &Integer, len: usize) -> Vec<u8> {
+    let bytes = integer_to_bytes(i);
+    let mut padded = vec![0; len.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded
+}
+
 /// Convert a `rug::Integer` to a string.
 pub fn integer_to_string(i: &Integer) -> Option<String> {
     String::from_utf8(integer_to_bytes(i)).ok()
 }
 
+/// Text encoding of the plaintexts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextEncoding {
+    /// Strict UTF-8
+    #[default]
+    Utf8,
+    /// UTF-8, invalid sequences replaced by U+FFFD
+    Utf8Lossy,
+    /// ISO-8859-1, each byte is a character
+    Latin1,
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-16, big endian
+    Utf16Be,
+}
+
+impl std::str::FromStr for TextEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', '_'], "").as_str() {
+            "utf8" => Ok(Self::Utf8),
+            "lossy" | "utf8lossy" => Ok(Self::Utf8Lossy),
+            "latin1" | "iso88591" => Ok(Self::Latin1),
+            "utf16le" => Ok(Self::Utf16Le),
+            "utf16be" | "utf16" => Ok(Self::Utf16Be),
+            _ => Err(format!("Unknown encoding: {s}")),
+        }
+    }
+}
+
+/// Convert a `rug::Integer` to a string in the given encoding.
+///
+/// Returns `None` if the bytes are not valid in this encoding.
+pub fn integer_to_string_with(i: &Integer, encoding: TextEncoding) -> Option<String> {
+    let bytes = integer_to_bytes(i);
+    match encoding {
+        TextEncoding::Utf8 => String::from_utf8(bytes).ok(),
+        TextEncoding::Utf8Lossy => Some(String::from_utf8_lossy(&bytes).into_owned()),
+        TextEncoding::Latin1 => Some(bytes.iter().map(|&b| b as char).collect()),
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            // The leading zero byte of a big endian first character is lost in the integer
+            let mut bytes = bytes;
+            if bytes.len() % 2 == 1 && encoding == TextEncoding::Utf16Be {
+                bytes.insert(0, 0);
+            }
+            let units = bytes
+                .chunks(2)
+                .map(|unit| match (unit, encoding) {
+                    ([low, high], TextEncoding::Utf16Le) => Some(u16::from_le_bytes([*low, *high])),
+                    ([high, low], _) => Some(u16::from_be_bytes([*high, *low])),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            char::decode_utf16(units).collect::<Result<_, _>>().ok()
+        }
+    }
+}
+
 /// Convert a byte vector to a `rug::Integer`.
 pub fn bytes_to_integer(bytes: &[u8]) -> Integer {
     Integer::from_str_radix(&base_x::encode("0123456789", bytes), 10).unwrap()
 }
 
+/// Reverse the bytes of a `rug::Integer`, to read a little-endian value stored as big-endian.
+pub fn reverse_bytes(i: &Integer) -> Integer {
+    Integer::from_digits(&i.to_digits::<u8>(Order::Msf), Order::Lsf)
+}
+
+/// Convert the content of a cipher file to a `rug::Integer`.
+///
+/// PEM blocks (e.g. `-----BEGIN MESSAGE-----`) are base64 decoded, other contents, such as the
+/// output of `openssl rsautl -encrypt`, are read as big-endian bytes.
+pub fn cipher_file_to_integer(bytes: &[u8]) -> Integer {
+    match pem_contents(bytes) {
+        Some(contents) => Integer::from_digits(&contents, Order::Msf),
+        None => Integer::from_digits(bytes, Order::Msf),
+    }
+}
+
+/// Base64 decoded content of the first PEM block, without its headers.
+fn pem_contents(bytes: &[u8]) -> Option<Vec<u8>> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let (_, block) = text.split_once("-----BEGIN ")?;
+    let (_, block) = block.split_once("-----")?;
+    let (body, _) = block.split_once("-----END ")?;
+    // Skip the RFC 1421 headers, e.g. Proc-Type: 4,ENCRYPTED
+    let base64 = body
+        .lines()
+        .filter(|line| !line.contains(':'))
+        .flat_map(str::split_whitespace)
+        .collect::<String>();
+    general_purpose::STANDARD.decode(base64).ok()
+}
+
 /// Convert a string to a `rug::Integer`.
 pub fn string_to_integer(s: &str) -> Integer {
     bytes_to_integer(s.as_bytes())
@@ -66,6 +165,53 @@ mod tests {
         assert_eq!(bytes, integer_to_bytes(&bytes_to_integer(bytes)).as_slice());
     }
 
+    #[test]
+    fn reverse_bytes() {
+        assert_eq!(super::reverse_bytes(&0x0102_0300.into()), 0x0003_0201);
+        assert_eq!(super::reverse_bytes(&0.into()), 0);
+    }
+
+    #[test]
+    fn integer_to_padded_bytes() {
+        let i = Integer::from(0x0102);
+        assert_eq!(integer_to_bytes_padded(&i, 4), [0, 0, 1, 2]);
+        assert_eq!(integer_to_bytes_padded(&i, 1), [1, 2]);
+    }
+
+    #[test]
+    fn integer_to_string_encodings() {
+        let latin1 = bytes_to_integer(b"caf\xe9");
+        assert_eq!(integer_to_string_with(&latin1, TextEncoding::Utf8), None);
+        assert_eq!(
+            integer_to_string_with(&latin1, TextEncoding::Latin1).unwrap(),
+            "café"
+        );
+        assert_eq!(
+            integer_to_string_with(&latin1, TextEncoding::Utf8Lossy).unwrap(),
+            "caf\u{fffd}"
+        );
+
+        let utf16 = bytes_to_integer(b"\0h\0i");
+        assert_eq!(
+            integer_to_string_with(&utf16, TextEncoding::Utf16Be).unwrap(),
+            "hi"
+        );
+        let utf16 = bytes_to_integer(b"h\0i\0");
+        assert_eq!(
+            integer_to_string_with(&utf16, TextEncoding::Utf16Le).unwrap(),
+            "hi"
+        );
+    }
+
+    #[test]
+    fn cipher_files() {
+        assert_eq!(cipher_file_to_integer(b"\x01\x02"), 0x0102);
+        let pem = b"-----BEGIN MESSAGE-----\nAQID\nBA==\n-----END MESSAGE-----\n";
+        assert_eq!(cipher_file_to_integer(pem), 0x01020304);
+        let pem = b"-----BEGIN MESSAGE-----\nComment: test\n\nAQIDBA==\n-----END MESSAGE-----";
+        assert_eq!(cipher_file_to_integer(pem), 0x01020304);
+    }
+
     #[test]
     fn string_to_integer_to_string() {
         let str = "RsaCracker!";
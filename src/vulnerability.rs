@@ -0,0 +1,101 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+/// Weakness of a key, exploited by an attack or found by an audit
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Vulnerability {
+    /// p and q are too close, or differ by only a few bits
+    ClosePrimes,
+    /// p - 1 is smooth
+    SmoothPMinus1,
+    /// n has a small factor
+    SmallFactor,
+    /// n is small enough to be factored directly
+    SmallModulus,
+    /// n is a prime
+    PrimeModulus,
+    /// n is a power of a prime
+    PrimePower,
+    /// A factor of n has a special form (Mersenne, factorial, Fibonacci, Cunningham chain, etc.)
+    SpecialFormPrime,
+    /// A factor of n is a prime reused across public CTF challenges
+    KnownWeakPrime,
+    /// The factorization of n is already known (FactorDB, cache, given factors)
+    KnownFactorization,
+    /// A factor of n is shared with another key (by index), or with the cipher
+    SharedFactor(Option<usize>),
+    /// The modulus has the fingerprint of a ROCA key (CVE-2017-15361)
+    RocaFingerprint,
+    /// The private exponent is small
+    SmallPrivateExponent,
+    /// The public exponent is low
+    LowExponent,
+    /// The same message was encrypted with a low public exponent under several keys (by index)
+    BroadcastMessage(Vec<usize>),
+    /// The same message was encrypted under the same modulus with other exponents (by index)
+    CommonModulus(Vec<usize>),
+    /// Most of the message was known, and it was encrypted with a low public exponent
+    StereotypedMessage,
+    /// The message was short and low entropy (e.g. a PIN or a word), and encrypted without padding
+    GuessableMessage,
+    /// The message was small, the product of two small numbers, and encrypted without padding
+    SmallMessage,
+    /// The public exponent is not coprime with phi
+    NonCoprimeExponent,
+    /// Secret values (d, phi, CRT parameters, bits of p and q, etc.) were leaked
+    LeakedSecret,
+}
+
+impl Vulnerability {
+    /// Returns the class of the vulnerability, to aggregate statistics regardless of the details
+    pub fn class(&self) -> &'static str {
+        match self {
+            Self::ClosePrimes => "close primes",
+            Self::SmoothPMinus1 => "smooth p-1",
+            Self::SmallFactor => "small factor",
+            Self::SmallModulus => "small modulus",
+            Self::PrimeModulus => "prime modulus",
+            Self::PrimePower => "prime power modulus",
+            Self::SpecialFormPrime => "special form prime",
+            Self::KnownWeakPrime => "known weak prime",
+            Self::KnownFactorization => "known factorization",
+            Self::SharedFactor(_) => "shared factor",
+            Self::RocaFingerprint => "ROCA fingerprint",
+            Self::SmallPrivateExponent => "small d",
+            Self::LowExponent | Self::BroadcastMessage(_) | Self::StereotypedMessage => "low e",
+            Self::CommonModulus(_) => "common modulus",
+            Self::GuessableMessage => "guessable message",
+            Self::SmallMessage => "small message",
+            Self::NonCoprimeExponent => "e not coprime with phi",
+            Self::LeakedSecret => "leaked secret",
+        }
+    }
+}
+
+impl Display for Vulnerability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SharedFactor(Some(key)) => write!(f, "shared factor with key {key}"),
+            Self::SharedFactor(None) => write!(f, "shared factor with the cipher"),
+            Self::BroadcastMessage(keys) => {
+                write!(
+                    f,
+                    "same message sent with a low e to keys {}",
+                    keys.iter().join(", ")
+                )
+            }
+            Self::CommonModulus(keys) if !keys.is_empty() => {
+                write!(
+                    f,
+                    "same message sent with several e under the modulus of keys {}",
+                    keys.iter().join(", ")
+                )
+            }
+            Self::StereotypedMessage => write!(f, "mostly known message sent with a low e"),
+            Self::GuessableMessage => write!(f, "low entropy message sent without padding"),
+            Self::SmallMessage => write!(f, "small message sent without padding"),
+            _ => write!(f, "{}", self.class()),
+        }
+    }
+}
@@ -21,8 +21,8 @@ fn crypton_intro_challenge_0() {
     let solution = run_attacks(&params).unwrap();
     let pk = solution.pk.unwrap();
 
-    assert_eq!(pk.p(), p);
-    assert_eq!(pk.q(), q);
+    assert_eq!(pk.p(), Some(p));
+    assert_eq!(pk.q(), Some(q));
     assert_eq!(
         integer_to_string(&solution.m.unwrap()).unwrap().trim(),
         "crypton{w0w_n1ce_w4y_t0_beg1n}"
@@ -21,8 +21,8 @@ fn tjctf_2022_rsa_apprentice() {
     let pk = solution_1.pk.unwrap();
 
     let params_2 = Parameters {
-        p: Some(pk.p()),
-        q: Some(pk.q()),
+        p: pk.p(),
+        q: pk.q(),
         c: Some(Integer::from_str("843105902970788695411197846605744081831851").unwrap()),
         ..Default::default()
     };
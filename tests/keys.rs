@@ -97,3 +97,28 @@ fn to_pem() {
         include_str!("keys/private_rsa.pem")
     );
 }
+
+#[test]
+fn check_key() {
+    let private = Parameters::from_private_key(
+        include_bytes!("keys/private_openssl.pem"),
+        Some(KEY_PASSPHRASE),
+    )
+    .unwrap();
+    for public in [
+        &include_bytes!("keys/public_openssl.pem")[..],
+        include_bytes!("keys/x509_certificate.der"),
+    ] {
+        let public = Parameters::from_public_key(public).unwrap();
+        assert!(rsacracker::check_key(&private, Some(&public)).is_empty());
+    }
+
+    let tampered = Parameters {
+        dp: private.dp.clone().map(|dp| dp + 1),
+        ..private
+    };
+    assert_eq!(
+        rsacracker::check_key(&tampered, None),
+        [rsacracker::KeyMismatch::WrongCrtValue("dp")]
+    );
+}
@@ -3,6 +3,7 @@ use std::{fs, str::FromStr};
 use rug::{integer::Order, Integer};
 
 const KEY_PASSPHRASE: &[u8] = b"Skyf0l";
+const PLAINTEXT: &[u8] = b"RsaCracker!";
 const OUT_PATH: &str = "../../tests/keys";
 
 lazy_static::lazy_static!(
@@ -185,6 +186,30 @@ fn openssh_keys() {
     .unwrap();
 }
 
+fn ciphers(rsa: &openssl::rsa::Rsa<openssl::pkey::Private>) {
+    let size = rsa.size() as usize;
+
+    // Textbook RSA, the plaintext zero-padded to the size of the modulus
+    let mut plaintext = vec![0; size - PLAINTEXT.len()];
+    plaintext.extend_from_slice(PLAINTEXT);
+    let mut cipher = vec![0; size];
+    rsa.public_encrypt(&plaintext, &mut cipher, openssl::rsa::Padding::NONE)
+        .unwrap();
+    fs::write(format!("{OUT_PATH}/cipher_textbook.bin"), &cipher).unwrap();
+
+    // PKCS#1 v1.5 padding
+    let mut cipher = vec![0; size];
+    rsa.public_encrypt(PLAINTEXT, &mut cipher, openssl::rsa::Padding::PKCS1)
+        .unwrap();
+    fs::write(format!("{OUT_PATH}/cipher_pkcs1.bin"), &cipher).unwrap();
+
+    // OAEP padding, with SHA-1 and MGF1
+    let mut cipher = vec![0; size];
+    rsa.public_encrypt(PLAINTEXT, &mut cipher, openssl::rsa::Padding::PKCS1_OAEP)
+        .unwrap();
+    fs::write(format!("{OUT_PATH}/cipher_oaep.bin"), &cipher).unwrap();
+}
+
 fn x509_cert(_pkey: openssl::pkey::PKey<openssl::pkey::Private>) {
     // openssl req -new -x509 -key tests/keys/private_openssl.pem -out tests/keys/x509_certificate.cer -days 365
     // sed -e '1d' -e '$d' tests/keys/x509_certificate.cer | base64 -d > tests/keys/x509_certificate.der
@@ -192,6 +217,7 @@ fn x509_cert(_pkey: openssl::pkey::PKey<openssl::pkey::Private>) {
 
 fn main() {
     let rsa = rsa_keys();
+    ciphers(&rsa);
     let pkey = openssl_keys(rsa);
 
     openssh_keys();